@@ -0,0 +1,8 @@
+//! Feeds arbitrary sequences of display events and commands to a manager, see
+//! `leftwm_core::utils::fuzzing`.
+#![no_main]
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    leftwm_core::utils::fuzzing::run(data);
+});
@@ -1,8 +1,9 @@
 use crate::{
     layouts::Layout,
-    models::{TagId, WindowHandle},
+    models::{Direction, Size, TagId, WindowHandle},
 };
 use serde::{Deserialize, Serialize};
+use thiserror::Error;
 
 #[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
 pub enum Command {
@@ -11,14 +12,29 @@ pub enum Command {
     SwapScreens,
     SoftReload,
     HardReload,
+    Exit,
     ToggleScratchPad(String),
     ToggleFullScreen,
     ToggleSticky,
+    ToggleZoom,
+    ToggleDock,
     GoToTag {
         tag: TagId,
         swap: bool,
     },
     ReturnToLastTag,
+    /// Show the tag with this label, or number.
+    GoToLabelledTag(String),
+    /// Go back to the tag the focused workspace showed before.
+    FocusLastTag,
+    /// Go back through the tags the focused workspace showed, as a browser goes back.
+    TagHistoryBack,
+    /// Return to the tags `TagHistoryBack` left.
+    TagHistoryForward,
+    /// Lay out the windows of all the tags on the focused workspace, or go back from there.
+    ToggleOverview,
+    /// Leave the overview for a window, the focused one when none is given.
+    PickOverviewWindow(Option<WindowHandle>),
     FloatingToTile,
     TileToFloating,
     ToggleFloating,
@@ -27,32 +43,157 @@ pub enum Command {
     MoveWindowTop {
         swap: bool,
     },
-    FocusNextTag,
-    FocusPreviousTag,
+    /// Show the next tag, going from the last to the first with `wrap`, and skipping the tags
+    /// without windows with `skip_empty`.
+    FocusNextTag {
+        wrap: bool,
+        skip_empty: bool,
+    },
+    /// Show the previous tag, the way `FocusNextTag` shows the next.
+    FocusPreviousTag {
+        wrap: bool,
+        skip_empty: bool,
+    },
     FocusWindow(String),
+    /// Show the tag of the window which most recently asked for attention, and focus it.
+    FocusUrgent,
     FocusWindowUp,
     FocusWindowDown,
     FocusWindowTop {
         swap: bool,
     },
+    FocusWindowInDirection(Direction),
+    MoveWindowInDirection(Direction),
+    SwapWindowWithMaster,
+    RotateStackForward,
+    RotateStackBackward,
     FocusWorkspaceNext,
     FocusWorkspacePrevious,
     SendWindowToTag {
         window: Option<WindowHandle>,
         tag: TagId,
     },
+    /// Add the focused window to a tag, or take it off the tag when it has others.
+    ToggleWindowTag(TagId),
+    /// `ToggleWindowTag` for the tag with this label, or number.
+    ToggleWindowLabelledTag(String),
+    /// Show a tag on the focused workspace beside the tags it shows, or stop showing it.
+    ToggleTagView(TagId),
+    /// `ToggleTagView` for the tag with this label, or number.
+    ToggleLabelledTagView(String),
     MoveWindowToLastWorkspace,
     MoveWindowToNextWorkspace,
     MoveWindowToPreviousWorkspace,
     MouseMoveWindow,
     NextLayout,
     PreviousLayout,
+    ToggleLastLayout,
     SetLayout(Layout),
     RotateTag,
-    IncreaseMainWidth(i8),
-    DecreaseMainWidth(i8),
+    IncreaseMainWidth(f32),
+    DecreaseMainWidth(f32),
+    IncreaseStackSplit(f32),
+    DecreaseStackSplit(f32),
+    IncreaseMainCount,
+    DecreaseMainCount,
+    IncreaseWindowWeight(f32),
+    DecreaseWindowWeight(f32),
+    RotateSubtree,
+    FlipSubtree,
     SetMarginMultiplier(f32),
+    /// Widen the gaps of the focused workspace by some pixels.
+    IncreaseGaps(u32),
+    /// Narrow the gaps of the focused workspace by some pixels.
+    DecreaseGaps(u32),
+    /// Take the gaps of the focused workspace away, or give them back.
+    ToggleGaps,
+    /// Put the focused workspace in game mode, or take it out.
+    ToggleGameMode,
+    SetMaxWindowWidth(Option<Size>),
     SendWorkspaceToTag(usize, usize),
+    /// Move the focused tag with its windows to the workspace at this index.
+    MoveTagToWorkspace(usize),
     CloseAllOtherWindows,
+    CloseAllOnTag,
+    /// A digit of the number of times to run the next command, if it is one which can be
+    /// repeated, see `is_repeatable`.
+    CountPrefix(usize),
+    /// Answer which managed window is at a point, in `window_at.json` beside the pipe.
+    WindowAt(i32, i32),
+    /// Answer the managed windows, with their X window ids, and the outputs, in `windows.json`.
+    ListWindows,
+    /// Switch to the workspaces of the profile with this name.
+    LoadProfile(String),
+    /// Add a tag with this label after the others.
+    AddTag(String),
+    /// Remove the focused tag, moving its windows to the tag focused before it.
+    RemoveTag,
+    /// Label a tag, given by its number or label, or the focused tag.
+    RenameTag {
+        tag: Option<String>,
+        label: String,
+    },
     Other(String),
 }
+
+impl Command {
+    /// Whether a `CountPrefix` runs the command more than once. Only the commands focusing,
+    /// moving or resizing something by a step are, running any other twice would at best do
+    /// nothing more.
+    #[must_use]
+    pub const fn is_repeatable(&self) -> bool {
+        matches!(
+            self,
+            Self::FocusWindowUp
+                | Self::FocusWindowDown
+                | Self::FocusWindowInDirection(_)
+                | Self::FocusNextTag { .. }
+                | Self::FocusPreviousTag { .. }
+                | Self::FocusWorkspaceNext
+                | Self::FocusWorkspacePrevious
+                | Self::TagHistoryBack
+                | Self::TagHistoryForward
+                | Self::MoveWindowUp
+                | Self::MoveWindowDown
+                | Self::MoveWindowInDirection(_)
+                | Self::MoveWindowToNextWorkspace
+                | Self::MoveWindowToPreviousWorkspace
+                | Self::RotateStackForward
+                | Self::RotateStackBackward
+                | Self::NextLayout
+                | Self::PreviousLayout
+                | Self::IncreaseMainWidth(_)
+                | Self::DecreaseMainWidth(_)
+                | Self::IncreaseStackSplit(_)
+                | Self::DecreaseStackSplit(_)
+                | Self::IncreaseMainCount
+                | Self::DecreaseMainCount
+                | Self::IncreaseWindowWeight(_)
+                | Self::DecreaseWindowWeight(_)
+                | Self::IncreaseGaps(_)
+                | Self::DecreaseGaps(_)
+        )
+    }
+
+    /// Whether `FocusNextTag` and `FocusPreviousTag` wrap around and skip the empty tags, from
+    /// their `NoWrap` and `SkipEmpty` flags.
+    ///
+    /// # Errors
+    ///
+    /// Errors on a word which isn't one of the flags.
+    pub fn parse_tag_cycle(flags: &str) -> Result<(bool, bool), ParseTagCycleError> {
+        let (mut wrap, mut skip_empty) = (true, false);
+        for flag in flags.split_whitespace() {
+            match flag {
+                "NoWrap" => wrap = false,
+                "SkipEmpty" => skip_empty = true,
+                _ => return Err(ParseTagCycleError(flag.to_string())),
+            }
+        }
+        Ok((wrap, skip_empty))
+    }
+}
+
+#[derive(Debug, Error)]
+#[error("unknown flag {0}")]
+pub struct ParseTagCycleError(String);
@@ -0,0 +1,13 @@
+use serde::{Deserialize, Serialize};
+
+/// Where the focused workspace goes once the last window of the tag it shows closes.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum EmptyTagBehavior {
+    /// Stay on the empty tag.
+    #[default]
+    Stay,
+    /// Go to the tag most recently focused which still has windows.
+    LastNonEmpty,
+    /// Go to the tag focused before, as `ReturnToLastTag` does.
+    Previous,
+}
@@ -1,16 +1,24 @@
+mod empty_tag_behavior;
 mod insert_behavior;
 mod keybind;
+mod new_window_placement;
+mod profile;
 mod scratchpad;
+mod spawn_target;
 mod workspace_config;
 
 use crate::display_servers::DisplayServer;
-use crate::layouts::Layout;
+use crate::layouts::{CustomLayout, Layout};
 pub use crate::models::{FocusBehaviour, Gutter, Margins, Size};
-use crate::models::{LayoutMode, Manager, Window, WindowType};
+use crate::models::{LayoutMode, Manager, TagId, Window, WindowType};
 use crate::state::State;
+pub use empty_tag_behavior::EmptyTagBehavior;
 pub use insert_behavior::InsertBehavior;
 pub use keybind::Keybind;
+pub use new_window_placement::NewWindowPlacement;
+pub use profile::Profile;
 pub use scratchpad::ScratchPad;
+pub use spawn_target::SpawnTarget;
 pub use workspace_config::Workspace;
 
 pub trait Config {
@@ -21,6 +29,9 @@ pub trait Config {
 
     fn workspaces(&self) -> Option<Vec<Workspace>>;
 
+    /// The named sets of workspaces `LoadProfile` switches between.
+    fn profiles(&self) -> Vec<Profile>;
+
     fn focus_behaviour(&self) -> FocusBehaviour;
 
     fn mousekey(&self) -> Vec<String>;
@@ -29,13 +40,37 @@ pub trait Config {
 
     fn layouts(&self) -> Vec<Layout>;
 
+    /// The layout tags start with, instead of the first of the `layouts`.
+    fn default_layout(&self) -> Option<Layout>;
+
+    /// The layouts defined in the config, used through `Layout::Custom` by their names.
+    fn custom_layouts(&self) -> Vec<CustomLayout>;
+
     fn layout_mode(&self) -> LayoutMode;
 
     fn insert_behavior(&self) -> InsertBehavior;
 
+    /// Where the focused workspace goes once the last window of its tag closes.
+    fn empty_tag_behavior(&self) -> EmptyTagBehavior;
+
+    /// The workspace new windows are put on.
+    fn new_window_placement(&self) -> NewWindowPlacement;
+
     fn focus_new_windows(&self) -> bool;
 
-    fn command_handler<SERVER>(command: &str, manager: &mut Manager<Self, SERVER>) -> bool
+    /// With `Sloppy` focus, whether moving the mouse onto a workspace without windows focuses it.
+    fn sloppy_focus_empty_workspaces(&self) -> bool;
+
+    /// With `ClickTo` focus, whether the click focusing a window is also delivered to it.
+    fn click_passthrough(&self) -> bool;
+
+    /// Whether the cursor is moved to the center of windows focused from the keyboard. `Sloppy`
+    /// focus always does this.
+    fn warp_cursor(&self) -> bool;
+
+    /// Handle a command leftwm doesn't know itself. Returns whether the windows need an update,
+    /// `None` when the command isn't known either.
+    fn command_handler<SERVER>(command: &str, manager: &mut Manager<Self, SERVER>) -> Option<bool>
     where
         SERVER: DisplayServer,
         Self: Sized;
@@ -46,14 +81,75 @@ pub trait Config {
     fn border_width(&self) -> i32;
     fn margin(&self) -> Margins;
     fn workspace_margin(&self) -> Option<Margins>;
+    /// The gap between tiled windows, replacing the margin of each of them.
+    fn inner_gap(&self) -> Option<u32>;
+    /// The gap between the windows and the edges of the workspaces, replacing the
+    /// `workspace_margin`.
+    fn outer_gap(&self) -> Option<u32>;
+    /// Whether a window tiled alone on a workspace goes without the gaps around it.
+    fn smart_gaps(&self) -> bool;
     fn gutter(&self) -> Option<Vec<Gutter>>;
     fn default_border_color(&self) -> String;
     fn floating_border_color(&self) -> String;
     fn focused_border_color(&self) -> String;
+    /// Border color of the windows asking for attention.
+    fn urgent_border_color(&self) -> String;
+    /// Border width of the focused window, when it differs from the one of the others.
+    fn focused_border_width(&self) -> Option<i32>;
+    /// Whether a window tiled alone on a tag goes without its border. Fullscreen windows never
+    /// have one.
+    fn smart_borders(&self) -> bool;
+    /// Smallest pixel size of the text leftwm draws itself, such as the tabs of `Tabbed`.
+    fn min_font_size(&self) -> Option<u32>;
     fn on_new_window_cmd(&self) -> Option<String>;
+    /// Shell command run, and waited for, when leftwm exits through the `Exit` command.
+    fn on_exit_cmd(&self) -> Option<String>;
+    /// Shell commands run once leftwm is back up after a reload, and when the config is reloaded.
+    fn on_reload_cmds(&self) -> Vec<String>;
+    /// Shell command run when another window, or none, gets the focus.
+    fn on_focus_change_cmd(&self) -> Option<String>;
+    /// Shell command run when the title of the focused window changes.
+    fn on_title_change_cmd(&self) -> Option<String>;
     fn get_list_of_gutters(&self) -> Vec<Gutter>;
     fn max_window_width(&self) -> Option<Size>;
+    /// The `max_window_width` of a tag, taking precedence over the one of the workspace
+    /// displaying it, which in turn takes precedence over the global one.
+    fn tag_max_window_width(&self, tag_id: TagId) -> Option<Size>;
+    /// Whether a tag is left out when cycling through the tags and from the state given to
+    /// bars.
+    fn tag_hidden(&self, tag_id: TagId) -> bool;
+    /// The layout a tag starts with, taking precedence over the default layout of the workspace
+    /// displaying it.
+    fn tag_default_layout(&self, tag_id: TagId) -> Option<Layout>;
+    /// The layouts cycled through on a tag instead of those of the workspace displaying it, none
+    /// keeping to the workspace's.
+    fn tag_layouts(&self, tag_id: TagId) -> Vec<Layout>;
+    /// The tags in the order they are cycled through and shown in the bars, those left out
+    /// coming after them in the order they are configured.
+    fn tag_order(&self) -> Vec<TagId>;
     fn disable_tile_drag(&self) -> bool;
+    /// Whether each workspace gets its own set of the configured tags, rather than all of them
+    /// sharing one.
+    fn per_screen_tags(&self) -> bool;
+    /// Whether going to a tag shown on another workspace brings it over to the focused one,
+    /// rather than focusing the workspace showing it.
+    fn greedy_view(&self) -> bool;
+    /// How many of the tags it showed before each workspace remembers for `TagHistoryBack`.
+    fn tag_history_depth(&self) -> usize;
+    /// Whether tiled windows are shrunk to a whole number of their resize increments, such as the
+    /// character cells of a terminal, with the pixels left over added to their gap.
+    fn honor_size_increments(&self) -> bool;
+    /// Milliseconds the windows take to slide in and out when switching tags, `0` to switch at
+    /// once.
+    fn tag_slide_duration(&self) -> u64;
+    /// Whether windows are moved at once rather than animated, whatever else is configured.
+    fn reduce_motion(&self) -> bool;
+    /// Distance in pixels at which a dragged floating window snaps to screen edges and other
+    /// windows. `0` disables snapping.
+    fn snap_distance(&self) -> i32;
+
+    /// Urgency requests a window may make per minute before it stays urgent until focused.
+    fn urgency_limit(&self) -> usize;
 
     /// Attempt to write current state to a file.
     ///
@@ -65,8 +161,13 @@ pub trait Config {
     /// Load saved state if it exists.
     fn load_state(&self, state: &mut State);
 
-    /// Handle window placement based on `WM_CLASS`
-    fn setup_predefined_window(&self, window: &mut Window) -> bool;
+    /// Read the configuration again from where it was loaded, keeping the current one when it
+    /// can't be read. Returns whether it was.
+    fn reload(&mut self) -> bool;
+
+    /// Handle window placement based on `WM_CLASS`, giving where to spawn the window when a
+    /// hook matched it.
+    fn setup_predefined_window(&self, window: &mut Window) -> Option<SpawnTarget>;
 
     fn load_window(&self, window: &mut Window) {
         if window.r#type == WindowType::Normal {
@@ -80,17 +181,29 @@ pub trait Config {
     }
 }
 
-#[cfg(test)]
+#[cfg(any(test, feature = "fuzzing"))]
 #[allow(clippy::module_name_repetitions)]
 #[derive(Default)]
 pub struct TestConfig {
     pub tags: Vec<String>,
     pub layouts: Vec<Layout>,
+    pub default_layout: Option<Layout>,
     pub workspaces: Option<Vec<Workspace>>,
+    pub profiles: Vec<Profile>,
     pub insert_behavior: InsertBehavior,
+    pub empty_tag_behavior: EmptyTagBehavior,
+    pub new_window_placement: NewWindowPlacement,
+    pub inner_gap: Option<u32>,
+    pub outer_gap: Option<u32>,
+    pub smart_gaps: bool,
+    pub smart_borders: bool,
+    pub hidden_tags: Vec<TagId>,
+    pub tag_order: Vec<TagId>,
+    pub tag_default_layouts: Vec<(TagId, Layout)>,
+    pub tag_layouts: Vec<(TagId, Vec<Layout>)>,
 }
 
-#[cfg(test)]
+#[cfg(any(test, feature = "fuzzing"))]
 impl Config for TestConfig {
     fn mapped_bindings(&self) -> Vec<Keybind> {
         unimplemented!()
@@ -101,6 +214,9 @@ impl Config for TestConfig {
     fn workspaces(&self) -> Option<Vec<Workspace>> {
         self.workspaces.clone()
     }
+    fn profiles(&self) -> Vec<Profile> {
+        self.profiles.clone()
+    }
     fn focus_behaviour(&self) -> FocusBehaviour {
         FocusBehaviour::ClickTo
     }
@@ -113,6 +229,12 @@ impl Config for TestConfig {
     fn layouts(&self) -> Vec<Layout> {
         self.layouts.clone()
     }
+    fn default_layout(&self) -> Option<Layout> {
+        self.default_layout.clone()
+    }
+    fn custom_layouts(&self) -> Vec<CustomLayout> {
+        vec![]
+    }
     fn layout_mode(&self) -> LayoutMode {
         LayoutMode::Workspace
     }
@@ -120,19 +242,34 @@ impl Config for TestConfig {
     fn insert_behavior(&self) -> InsertBehavior {
         self.insert_behavior
     }
+    fn empty_tag_behavior(&self) -> EmptyTagBehavior {
+        self.empty_tag_behavior
+    }
+    fn new_window_placement(&self) -> NewWindowPlacement {
+        self.new_window_placement
+    }
 
     fn focus_new_windows(&self) -> bool {
         false
     }
-    fn command_handler<SERVER>(command: &str, manager: &mut Manager<Self, SERVER>) -> bool
+    fn sloppy_focus_empty_workspaces(&self) -> bool {
+        true
+    }
+    fn click_passthrough(&self) -> bool {
+        true
+    }
+    fn warp_cursor(&self) -> bool {
+        false
+    }
+    fn command_handler<SERVER>(command: &str, manager: &mut Manager<Self, SERVER>) -> Option<bool>
     where
         SERVER: DisplayServer,
     {
         match command {
-            "GoToTag2" => manager.command_handler(&crate::Command::GoToTag {
+            "GoToTag2" => Some(manager.command_handler(&crate::Command::GoToTag {
                 tag: 2,
                 swap: false,
-            }),
+            })),
             _ => unimplemented!("custom command handler: {:?}", command),
         }
     }
@@ -154,6 +291,15 @@ impl Config for TestConfig {
     fn workspace_margin(&self) -> Option<Margins> {
         None
     }
+    fn inner_gap(&self) -> Option<u32> {
+        self.inner_gap
+    }
+    fn outer_gap(&self) -> Option<u32> {
+        self.outer_gap
+    }
+    fn smart_gaps(&self) -> bool {
+        self.smart_gaps
+    }
     fn gutter(&self) -> Option<Vec<Gutter>> {
         unimplemented!()
     }
@@ -166,30 +312,120 @@ impl Config for TestConfig {
     fn focused_border_color(&self) -> String {
         unimplemented!()
     }
+    fn urgent_border_color(&self) -> String {
+        unimplemented!()
+    }
+    fn focused_border_width(&self) -> Option<i32> {
+        None
+    }
+    fn smart_borders(&self) -> bool {
+        self.smart_borders
+    }
+    fn min_font_size(&self) -> Option<u32> {
+        None
+    }
     fn on_new_window_cmd(&self) -> Option<String> {
         None
     }
+    fn on_exit_cmd(&self) -> Option<String> {
+        None
+    }
+    fn on_reload_cmds(&self) -> Vec<String> {
+        vec![]
+    }
+    fn on_focus_change_cmd(&self) -> Option<String> {
+        None
+    }
+    fn on_title_change_cmd(&self) -> Option<String> {
+        None
+    }
     fn get_list_of_gutters(&self) -> Vec<Gutter> {
         Default::default()
     }
     fn max_window_width(&self) -> Option<Size> {
         None
     }
+    fn tag_max_window_width(&self, _tag_id: TagId) -> Option<Size> {
+        None
+    }
+    fn tag_hidden(&self, tag_id: TagId) -> bool {
+        self.hidden_tags.contains(&tag_id)
+    }
+    fn tag_default_layout(&self, tag_id: TagId) -> Option<Layout> {
+        self.tag_default_layouts
+            .iter()
+            .find(|(id, _)| *id == tag_id)
+            .map(|(_, layout)| layout.clone())
+    }
+    fn tag_layouts(&self, tag_id: TagId) -> Vec<Layout> {
+        self.tag_layouts
+            .iter()
+            .find(|(id, _)| *id == tag_id)
+            .map(|(_, layouts)| layouts.clone())
+            .unwrap_or_default()
+    }
+    fn tag_order(&self) -> Vec<TagId> {
+        self.tag_order.clone()
+    }
     fn disable_tile_drag(&self) -> bool {
         false
     }
+    fn per_screen_tags(&self) -> bool {
+        false
+    }
+    fn greedy_view(&self) -> bool {
+        true
+    }
+    fn tag_history_depth(&self) -> usize {
+        10
+    }
+    fn honor_size_increments(&self) -> bool {
+        false
+    }
+    fn tag_slide_duration(&self) -> u64 {
+        0
+    }
+    fn reduce_motion(&self) -> bool {
+        false
+    }
+    fn urgency_limit(&self) -> usize {
+        3
+    }
+    fn snap_distance(&self) -> i32 {
+        10
+    }
     fn save_state(&self, _state: &State) {
         unimplemented!()
     }
     fn load_state(&self, _state: &mut State) {
         unimplemented!()
     }
-    fn setup_predefined_window(&self, window: &mut Window) -> bool {
+    fn reload(&mut self) -> bool {
+        true
+    }
+    fn setup_predefined_window(&self, window: &mut Window) -> Option<SpawnTarget> {
         if window.res_class == Some("ShouldGoToTag2".to_string()) {
             window.tags = vec![2];
-            true
+            Some(SpawnTarget::default())
+        } else if window.res_class == Some("ShouldBeFullscreen".to_string()) {
+            window.set_states(vec![crate::models::WindowState::Fullscreen]);
+            window.borderless = true;
+            Some(SpawnTarget::default())
+        } else if window.res_class == Some("ShouldFollowToTag2OfDP-2".to_string()) {
+            Some(SpawnTarget {
+                tag: Some("2".to_string()),
+                output: Some("DP-2".to_string()),
+                follow: Some(true),
+                ..SpawnTarget::default()
+            })
+        } else if window.res_class == Some("ShouldFloatCentered".to_string()) {
+            Some(SpawnTarget {
+                width: Some(Size::Pixel(800)),
+                height: Some(Size::Ratio(0.5)),
+                ..SpawnTarget::default()
+            })
         } else {
-            false
+            None
         }
     }
 }
@@ -197,15 +433,21 @@ impl Config for TestConfig {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::models::BBox;
     use crate::models::Screen;
     use crate::models::Window;
     use crate::models::WindowHandle;
+    use crate::models::WindowState;
+    use crate::DisplayAction;
 
     #[test]
     fn ensure_command_handler_trait_boundary() {
         let mut manager = Manager::new_test(vec!["1".to_string(), "2".to_string()]);
         manager.screen_create_handler(Screen::default());
-        assert!(TestConfig::command_handler("GoToTag2", &mut manager));
+        assert_eq!(
+            TestConfig::command_handler("GoToTag2", &mut manager),
+            Some(true)
+        );
         assert_eq!(manager.state.focus_manager.tag_history, &[2, 1]);
     }
 
@@ -218,4 +460,77 @@ mod tests {
         manager.window_created_handler(subject, 0, 0);
         assert!(manager.state.windows.iter().all(|w| w.has_tag(&2)));
     }
+
+    #[test]
+    fn predefined_windows_can_spawn_on_a_tag_of_an_output_and_follow() {
+        let mut manager =
+            Manager::new_test(vec!["1".to_string(), "2".to_string(), "3".to_string()]);
+        for (x, output) in [(0, "DP-1"), (1000, "DP-2")] {
+            let mut screen = Screen::new(BBox {
+                x,
+                y: 0,
+                width: 1000,
+                height: 800,
+            });
+            screen.output = Some(output.to_string());
+            manager.screen_create_handler(screen);
+        }
+        let second = manager.state.workspaces[1].clone();
+        manager.state.focus_workspace(&second);
+        manager.state.goto_tag_handler(3);
+        let first = manager.state.workspaces[0].clone();
+        manager.state.focus_workspace(&first);
+
+        let mut subject = Window::new(WindowHandle::MockHandle(1), None, None);
+        subject.res_class = Some("ShouldFollowToTag2OfDP-2".to_string());
+        manager.window_created_handler(subject, 0, 0);
+        assert_eq!(manager.state.windows[0].tags, vec![2]);
+        assert_eq!(manager.state.workspaces[1].tags, vec![2]);
+        assert_eq!(
+            manager
+                .state
+                .focus_manager
+                .window(&manager.state.windows)
+                .map(|w| w.handle),
+            Some(WindowHandle::MockHandle(1))
+        );
+    }
+
+    #[test]
+    fn predefined_windows_can_float_centered_at_a_size() {
+        let mut manager = Manager::new_test(vec!["1".to_string()]);
+        manager.screen_create_handler(Screen::new(BBox {
+            x: 0,
+            y: 0,
+            width: 2000,
+            height: 1000,
+        }));
+        let mut subject = Window::new(WindowHandle::MockHandle(1), None, None);
+        subject.res_class = Some("ShouldFloatCentered".to_string());
+        manager.window_created_handler(subject, 0, 0);
+        manager.update_windows();
+        let window = &manager.state.windows[0];
+        assert!(window.floating());
+        let xyhw = window.calculated_xyhw();
+        assert_eq!(
+            (xyhw.x(), xyhw.y(), xyhw.w(), xyhw.h()),
+            (600, 250, 800, 500)
+        );
+    }
+
+    #[test]
+    fn states_set_by_predefined_windows_are_sent_to_the_display_server() {
+        let mut manager = Manager::new_test(vec!["1".to_string()]);
+        manager.screen_create_handler(Screen::default());
+        let mut subject = Window::new(WindowHandle::MockHandle(1), None, None);
+        subject.res_class = Some("ShouldBeFullscreen".to_string());
+        manager.window_created_handler(subject, 0, 0);
+        assert!(manager.state.actions.iter().any(|action| matches!(
+            action,
+            DisplayAction::SetState(WindowHandle::MockHandle(1), true, WindowState::Fullscreen)
+        )));
+        manager.update_windows();
+        assert_eq!(manager.state.windows[0].border(), 0);
+        assert!(manager.state.windows[0].is_fullscreen());
+    }
 }
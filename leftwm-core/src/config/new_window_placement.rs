@@ -0,0 +1,14 @@
+use serde::{Deserialize, Serialize};
+
+/// The workspace new windows are put on, unless a window rule says otherwise.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum NewWindowPlacement {
+    /// The workspace containing the pointer.
+    Pointer,
+    /// The focused workspace.
+    Focused,
+    /// The tag of the terminal the window was started from. Other windows go to the workspace
+    /// containing the pointer with `Sloppy` focus, and to the focused one otherwise.
+    #[default]
+    Terminal,
+}
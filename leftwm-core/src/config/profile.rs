@@ -0,0 +1,15 @@
+use super::Workspace;
+use serde::{Deserialize, Serialize};
+
+/// A named set of workspaces, which `LoadProfile` switches to, e.g. for docking a laptop.
+#[derive(Serialize, Deserialize, Default, Debug, Clone, PartialEq)]
+pub struct Profile {
+    pub name: String,
+    /// The outputs which, once they are the ones with a monitor connected, have the profile
+    /// loaded by itself. In any order, none leaving the profile to `LoadProfile`.
+    #[serde(default)]
+    pub outputs: Vec<String>,
+    /// The workspaces of the profile, one for each screen when there are none.
+    #[serde(default)]
+    pub workspaces: Vec<Workspace>,
+}
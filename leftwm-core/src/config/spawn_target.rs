@@ -0,0 +1,27 @@
+use crate::models::Size;
+
+/// Where a window rule spawns a window: on a tag given by its label, on the workspace of an
+/// output, and whether the focus follows it there.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SpawnTarget {
+    pub tag: Option<String>,
+    pub output: Option<String>,
+    /// Focus the window, showing its tag, or leave the focus where it is. Unset, the window is
+    /// focused as any new window.
+    pub follow: Option<bool>,
+    /// The place of the window in its workspace, which then floats. Without a position the
+    /// window is centered.
+    pub x: Option<Size>,
+    pub y: Option<Size>,
+    /// The size of the floating window, the one it asked for when left out.
+    pub width: Option<Size>,
+    pub height: Option<Size>,
+}
+
+impl SpawnTarget {
+    /// Whether the window floats at a size or position of its own.
+    #[must_use]
+    pub const fn has_geometry(&self) -> bool {
+        self.x.is_some() || self.y.is_some() || self.width.is_some() || self.height.is_some()
+    }
+}
@@ -12,4 +12,12 @@ pub struct Workspace {
     pub id: Option<i32>,
     pub max_window_width: Option<Size>,
     pub layouts: Option<Vec<Layout>>,
+    /// The layout its tags start with, instead of the first of its `layouts`.
+    pub default_layout: Option<Layout>,
+    /// The gap between its tiled windows, instead of the global `inner_gap`.
+    pub inner_gap: Option<u32>,
+    /// The gap between its windows and its edges, instead of the global `outer_gap`.
+    pub outer_gap: Option<u32>,
+    /// A name for bars to label the workspace by, e.g. `left monitor`.
+    pub name: Option<String>,
 }
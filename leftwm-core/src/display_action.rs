@@ -1,8 +1,11 @@
 use crate::config::Keybind;
+use crate::display_event::Timestamp;
+use crate::models::TabBar;
 use crate::models::TagId;
 use crate::models::Window;
 use crate::models::WindowHandle;
 use crate::models::WindowState;
+use crate::models::Xyhw;
 use crate::utils::xkeysym_lookup::Button;
 use serde::{Deserialize, Serialize};
 
@@ -14,6 +17,10 @@ pub enum DisplayAction {
     /// Nicely ask a window if it would please close at its convenience.
     KillWindow(WindowHandle),
 
+    /// Ask a window to close through `WM_DELETE_WINDOW`, without killing its client when it
+    /// doesn't support that.
+    DeleteWindow(WindowHandle),
+
     /// Get triggered after a new window is discovered and WE are
     /// managing it.
     AddedWindow(WindowHandle, bool, bool),
@@ -27,6 +34,9 @@ pub enum DisplayAction {
     /// Change a windows state.
     SetState(WindowHandle, bool, WindowState),
 
+    /// Mark a window as being on a workspace in game mode, or clear the mark.
+    SetGameMode(WindowHandle, bool),
+
     /// Sets the "z-index" order of the windows
     /// first in the array is top most
     SetWindowOrder(Vec<Window>),
@@ -38,10 +48,12 @@ pub enum DisplayAction {
     /// cleanup.
     DestroyedWindow(WindowHandle),
 
-    /// Tell a window that it is to become focused.
+    /// Tell a window that it is to become focused, at the time of the event focusing it.
     WindowTakeFocus {
         window: Window,
         previous_window: Option<Window>,
+        #[serde(default)]
+        time: Timestamp,
     },
 
     /// Remove focus on any visible window by focusing the root window.
@@ -52,6 +64,9 @@ pub enum DisplayAction {
 
     ReplayClick(WindowHandle, Button),
 
+    /// Release the pointer frozen by a click which doesn't focus a window, passing it on.
+    ReleasePointer,
+
     /// Tell the DM we are ready to resize this window.
     ReadyToResizeWindow(WindowHandle),
 
@@ -64,13 +79,35 @@ pub enum DisplayAction {
     /// Used to let the WM know of the tag for a given window.
     SetWindowTags(WindowHandle, Vec<TagId>),
 
+    /// Used to let the WM know of the labels of the tags, after tags were added or removed.
+    SetTagLabels(Vec<String>),
+
     /// Tell the DM to return to normal mode if it is not (ie resize a
     /// window or moving a window).
     NormalMode,
 
+    /// Tell the DM the next click picks the window under the cursor, for the overview.
+    PickWindow,
+
     /// SoftReload keygrabs, needed when keyboard changes.
     ReloadKeyGrabs(Vec<Keybind>),
 
     /// Configure a xlib window.
     ConfigureXlibWindow(Window),
+
+    /// Outline the slot a tiling operation would use, or hide the outline.
+    HighlightArea(Option<Xyhw>),
+
+    /// Draw these tab bars, replacing the ones drawn before.
+    UpdateTabBars(Vec<TabBar>),
+
+    /// Slide the windows of a tag switch `distance` pixels to the left, over `duration`
+    /// milliseconds. The entering windows end up where they were placed, the leaving ones are
+    /// hidden again once out of the way.
+    SlideWindows {
+        entering: Vec<WindowHandle>,
+        leaving: Vec<WindowHandle>,
+        distance: i32,
+        duration: u64,
+    },
 }
@@ -2,24 +2,48 @@ use super::{models::Screen, models::Window, models::WindowHandle, Button, ModMas
 use crate::models::WindowChange;
 use crate::Command;
 
+/// The time of an input event in milliseconds, as the display server counts them. Focus changes
+/// carry the time of the event causing them, so that one caused earlier can't override one caused
+/// later. 0 stands for the current time.
+pub type Timestamp = u64;
+
 #[allow(clippy::large_enum_variant)]
 #[derive(Debug)]
 pub enum DisplayEvent {
-    Movement(WindowHandle, i32, i32),
-    KeyCombo(ModMask, XKeysym),
+    Movement(WindowHandle, i32, i32, Timestamp),
+    KeyCombo(ModMask, XKeysym, Timestamp),
     KeyGrabReload, // Reloads keys for when keyboard changes.
-    MouseCombo(ModMask, Button, WindowHandle, i32, i32),
+    MouseCombo(ModMask, Button, WindowHandle, i32, i32, Timestamp),
     WindowCreate(Window, i32, i32),
     WindowChange(WindowChange),
     WindowDestroy(WindowHandle),
-    WindowTakeFocus(WindowHandle),
-    HandleWindowFocus(WindowHandle),
-    VerifyFocusedAt(WindowHandle), // Request focus validation for this window.
-    MoveFocusTo(i32, i32),         // Focus the nearest window to this point.
+    WindowTakeFocus(WindowHandle, Timestamp),
+    HandleWindowFocus(WindowHandle, Timestamp),
+    FocusTab(WindowHandle, Timestamp), // The tab of this window was clicked.
+    VerifyFocusedAt(WindowHandle),     // Request focus validation for this window.
+    MoveFocusTo(i32, i32),             // Focus the nearest window to this point.
     MoveWindow(WindowHandle, i32, i32),
     ResizeWindow(WindowHandle, i32, i32),
     ScreenCreate(Screen),
+    /// The outputs with a monitor connected changed, to these.
+    OutputsChange(Vec<String>),
     SendCommand(Command),
     ConfigureXlibWindow(WindowHandle),
     ChangeToNormalMode,
 }
+
+impl DisplayEvent {
+    /// The time of the input event this event comes from, if it comes from one.
+    #[must_use]
+    pub const fn time(&self) -> Option<Timestamp> {
+        match self {
+            Self::Movement(.., time)
+            | Self::KeyCombo(.., time)
+            | Self::MouseCombo(.., time)
+            | Self::WindowTakeFocus(_, time)
+            | Self::HandleWindowFocus(_, time)
+            | Self::FocusTab(_, time) => Some(*time),
+            _ => None,
+        }
+    }
+}
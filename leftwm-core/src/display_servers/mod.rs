@@ -1,19 +1,69 @@
-use crate::config::Config;
+use crate::config::{self, Config};
 use crate::display_action::DisplayAction;
+use crate::models::Screen;
 use crate::models::Window;
 use crate::models::WindowHandle;
 use crate::models::Workspace;
 use crate::DisplayEvent;
-#[cfg(test)]
+#[cfg(any(test, feature = "fuzzing"))]
 mod mock_display_server;
 pub mod xlib_display_server;
 use futures::prelude::*;
+use std::ffi::OsStr;
 use std::pin::Pin;
+use std::str::FromStr;
+use std::time::Duration;
+use thiserror::Error;
 
-#[cfg(test)]
+#[cfg(any(test, feature = "fuzzing"))]
 pub use self::mock_display_server::MockDisplayServer;
 pub use self::xlib_display_server::XlibDisplayServer;
 
+/// The kind of session a display server is made for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Backend {
+    X11,
+    Wayland,
+}
+
+impl Backend {
+    /// The backend for the session leftwm was started in.
+    #[must_use]
+    pub fn detect() -> Self {
+        Self::for_session(
+            std::env::var_os("WAYLAND_DISPLAY").as_deref(),
+            std::env::var_os("DISPLAY").as_deref(),
+        )
+    }
+
+    /// X11 whenever `DISPLAY` names a server, as there is no Wayland backend yet and Xwayland
+    /// sets it in Wayland sessions too. Wayland only if `WAYLAND_DISPLAY` is the sole one set.
+    fn for_session(wayland_display: Option<&OsStr>, display: Option<&OsStr>) -> Self {
+        let is_set = |var: Option<&OsStr>| var.is_some_and(|var| !var.is_empty());
+        if is_set(wayland_display) && !is_set(display) {
+            Self::Wayland
+        } else {
+            Self::X11
+        }
+    }
+}
+
+#[derive(Debug, Error)]
+#[error("Could not parse backend: {0}, expected x11 or wayland")]
+pub struct ParseBackendError(String);
+
+impl FromStr for Backend {
+    type Err = ParseBackendError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "x11" | "xlib" => Ok(Self::X11),
+            "wayland" => Ok(Self::Wayland),
+            _ => Err(ParseBackendError(s.to_string())),
+        }
+    }
+}
+
 pub trait DisplayServer {
     fn new(config: &impl Config) -> Self;
 
@@ -31,6 +81,12 @@ pub trait DisplayServer {
 
     fn update_workspaces(&self, _focused: Option<&Workspace>) {}
 
+    /// The screens showing the configured workspaces, or every screen of the display when none
+    /// are configured. Names the workspaces for bars.
+    fn workspace_screens(&self, workspaces: &[config::Workspace]) -> Vec<Screen> {
+        workspaces.iter().map(Screen::from).collect()
+    }
+
     fn execute_action(&mut self, _act: DisplayAction) -> Option<DisplayEvent> {
         None
     }
@@ -40,4 +96,45 @@ pub trait DisplayServer {
     fn flush(&self);
 
     fn generate_verify_focus_event(&self) -> Option<DisplayEvent>;
+
+    /// Take the next step of an animation going on, returning how long until the one after it,
+    /// `None` when there is none.
+    fn animate(&mut self) -> Option<Duration> {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn wayland_is_detected_from_wayland_display() {
+        let wayland = Some(OsStr::new("wayland-0"));
+        assert_eq!(Backend::for_session(wayland, None), Backend::Wayland);
+        assert_eq!(
+            Backend::for_session(Some(OsStr::new("")), None),
+            Backend::X11
+        );
+        assert_eq!(Backend::for_session(None, None), Backend::X11);
+    }
+
+    #[test]
+    fn x11_is_used_whenever_display_is_set() {
+        let display = Some(OsStr::new(":0"));
+        let wayland = Some(OsStr::new("wayland-0"));
+        assert_eq!(Backend::for_session(wayland, display), Backend::X11);
+        assert_eq!(Backend::for_session(None, display), Backend::X11);
+        assert_eq!(
+            Backend::for_session(wayland, Some(OsStr::new(""))),
+            Backend::Wayland
+        );
+    }
+
+    #[test]
+    fn should_be_able_to_parse_backends() {
+        assert_eq!(Backend::from_str("x11").unwrap(), Backend::X11);
+        assert_eq!(Backend::from_str("wayland").unwrap(), Backend::Wayland);
+        assert!(Backend::from_str("mir").is_err());
+    }
 }
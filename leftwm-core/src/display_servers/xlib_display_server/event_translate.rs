@@ -3,6 +3,7 @@ use super::{
     DisplayEvent, XWrap,
 };
 use crate::models::{Mode, WindowChange, WindowType, XyhwChange};
+use crate::Command;
 use std::os::raw::c_ulong;
 use x11_dl::xlib;
 
@@ -11,7 +12,8 @@ pub struct XEvent<'a>(pub &'a mut XWrap, pub xlib::XEvent);
 impl<'a> From<XEvent<'a>> for Option<DisplayEvent> {
     fn from(x_event: XEvent) -> Self {
         let raw_event = x_event.1;
-        let normal_mode = x_event.0.mode == Mode::Normal;
+        // Picking a window in the overview doesn't hold any window up as dragging one does.
+        let normal_mode = matches!(x_event.0.mode, Mode::Normal | Mode::PickingWindow);
         let sloppy_behaviour = x_event.0.focus_behaviour.is_sloppy();
 
         match raw_event.get_type() {
@@ -32,13 +34,19 @@ impl<'a> From<XEvent<'a>> for Option<DisplayEvent> {
             // Mouse motion notify.
             xlib::MotionNotify => from_motion_notify(x_event),
             // Mouse button pressed.
-            xlib::ButtonPress => Some(from_button_press(raw_event)),
+            xlib::ButtonPress => Some(from_button_press(x_event)),
+            // Tab bar exposed.
+            xlib::Expose => from_expose(x_event),
             // Mouse button released.
             xlib::ButtonRelease if !normal_mode => Some(from_button_release(x_event)),
             // Keyboard key pressed.
             xlib::KeyPress => Some(from_key_press(x_event)),
             // Listen for keyboard changes.
             xlib::MappingNotify => from_mapping_notify(x_event),
+            // Another window manager replacing leftwm.
+            xlib::SelectionClear => from_selection_clear(&x_event),
+            // Monitors plugged in or out.
+            other if x_event.0.is_output_change(other) => Some(from_output_change(&x_event)),
             _other => None,
         }
     }
@@ -50,6 +58,21 @@ fn from_map_request(x_event: XEvent) -> Option<DisplayEvent> {
     xw.setup_window(event.window)
 }
 
+fn from_output_change(x_event: &XEvent) -> DisplayEvent {
+    let mut event = x_event.1;
+    x_event.0.update_screen_configuration(&mut event);
+    DisplayEvent::OutputsChange(x_event.0.get_connected_outputs())
+}
+
+fn from_selection_clear(x_event: &XEvent) -> Option<DisplayEvent> {
+    let event = xlib::XSelectionClearEvent::from(x_event.1);
+    if event.selection != x_event.0.wm_selection {
+        return None;
+    }
+    log::info!("Another window manager took over, exiting");
+    Some(DisplayEvent::SendCommand(Command::Exit))
+}
+
 fn from_unmap_event(x_event: XEvent) -> Option<DisplayEvent> {
     let xw = x_event.0;
     let event = xlib::XUnmapEvent::from(x_event.1);
@@ -153,7 +176,7 @@ fn from_enter_notify(x_event: &XEvent) -> Option<DisplayEvent> {
     }
 
     let h = event.window.into();
-    Some(DisplayEvent::WindowTakeFocus(h))
+    Some(DisplayEvent::WindowTakeFocus(h, event.time))
 }
 
 fn from_motion_notify(x_event: XEvent) -> Option<DisplayEvent> {
@@ -180,9 +203,9 @@ fn from_motion_notify(x_event: XEvent) -> Option<DisplayEvent> {
             }
             Mode::ResizingWindow(h) => DisplayEvent::ResizeWindow(h, offset_x, offset_y),
             Mode::Normal if xw.focus_behaviour.is_sloppy() => {
-                DisplayEvent::Movement(event_h, event.x_root, event.y_root)
+                DisplayEvent::Movement(event_h, event.x_root, event.y_root, event.time)
             }
-            Mode::Normal => return None,
+            Mode::Normal | Mode::PickingWindow => return None,
         };
         return Some(display_event);
     }
@@ -190,12 +213,34 @@ fn from_motion_notify(x_event: XEvent) -> Option<DisplayEvent> {
     None
 }
 
-fn from_button_press(raw_event: xlib::XEvent) -> DisplayEvent {
-    let event = xlib::XButtonPressedEvent::from(raw_event);
+fn from_button_press(x_event: XEvent) -> DisplayEvent {
+    let xw = x_event.0;
+    let event = xlib::XButtonPressedEvent::from(x_event.1);
+    // The pointer is grabbed on the root, the window clicked is the child under it.
+    if xw.mode == Mode::PickingWindow {
+        xw.set_mode(Mode::Normal);
+        return DisplayEvent::SendCommand(match event.subwindow {
+            0 => Command::ToggleOverview,
+            window => Command::PickOverviewWindow(Some(window.into())),
+        });
+    }
+    if let Some(tab) = xw.tab_at(event.window, event.x) {
+        return DisplayEvent::FocusTab(tab, event.time);
+    }
     let h = event.window.into();
     let mut mod_mask = event.state;
     mod_mask &= !(xlib::Mod2Mask | xlib::LockMask);
-    DisplayEvent::MouseCombo(mod_mask, event.button, h, event.x, event.y)
+    DisplayEvent::MouseCombo(mod_mask, event.button, h, event.x, event.y, event.time)
+}
+
+fn from_expose(x_event: XEvent) -> Option<DisplayEvent> {
+    let xw = x_event.0;
+    let event = xlib::XExposeEvent::from(x_event.1);
+    // Only the last of a series of exposures redraws.
+    if event.count == 0 {
+        xw.redraw_tab_bar(event.window);
+    }
+    None
 }
 
 fn from_button_release(x_event: XEvent) -> DisplayEvent {
@@ -208,7 +253,7 @@ fn from_key_press(x_event: XEvent) -> DisplayEvent {
     let xw = x_event.0;
     let event = xlib::XKeyEvent::from(x_event.1);
     let sym = xw.keycode_to_keysym(event.keycode);
-    DisplayEvent::KeyCombo(event.state, sym)
+    DisplayEvent::KeyCombo(event.state, sym, event.time)
 }
 
 fn from_mapping_notify(x_event: XEvent) -> Option<DisplayEvent> {
@@ -1,5 +1,7 @@
 use super::{DisplayEvent, XWrap};
-use crate::{models::WindowChange, Command};
+use crate::display_event::Timestamp;
+use crate::models::{UrgencySource, WindowChange, WindowState};
+use crate::Command;
 use std::convert::TryFrom;
 use std::os::raw::c_long;
 use x11_dl::xlib;
@@ -52,32 +54,21 @@ pub fn from_event(xw: &XWrap, event: xlib::XClientMessageEvent) -> Option<Displa
         }
     }
     if event.message_type == xw.atoms.NetActiveWindow {
-        return Some(DisplayEvent::HandleWindowFocus(event.window.into()));
+        // The time of the user action the request comes from, 0 if it doesn't come from one.
+        let time = event.data.get_long(1) as Timestamp;
+        return Some(DisplayEvent::HandleWindowFocus(event.window.into(), time));
     }
 
-    //if the client is trying to toggle fullscreen without changing the window state, change it too
-    if event.message_type == xw.atoms.NetWMState
-        && (event.data.get_long(1) == xw.atoms.NetWMStateFullscreen as c_long
-            || event.data.get_long(2) == xw.atoms.NetWMStateFullscreen as c_long)
-    {
-        let set_fullscreen = event.data.get_long(0) == 1;
-        let toggle_fullscreen = event.data.get_long(0) == 2;
+    // Clients ask to change their states rather than changing them, so carry the request out.
+    if event.message_type == xw.atoms.NetWMState {
+        let known = xw.atoms.window_states();
+        let requested: Vec<xlib::Atom> = [event.data.get_long(1), event.data.get_long(2)]
+            .iter()
+            .map(|&atom| atom as xlib::Atom)
+            .filter(|atom| known.contains(atom))
+            .collect();
         let mut states = xw.get_window_states_atoms(event.window);
-        //determine what to change the state to
-        let fullscreen = if toggle_fullscreen {
-            !states.contains(&xw.atoms.NetWMStateFullscreen)
-        } else {
-            set_fullscreen
-        };
-        //update the list of states
-        if fullscreen {
-            states.push(xw.atoms.NetWMStateFullscreen);
-        } else {
-            states.retain(|x| x != &xw.atoms.NetWMStateFullscreen);
-        }
-        states.sort_unstable();
-        states.dedup();
-        //set the windows state
+        apply_state_request(&mut states, event.data.get_long(0), &requested);
         xw.set_window_states_atoms(event.window, &states);
     }
 
@@ -86,9 +77,45 @@ pub fn from_event(xw: &XWrap, event: xlib::XClientMessageEvent) -> Option<Displa
         let handle = event.window.into();
         let mut change = WindowChange::new(handle);
         let states = xw.get_window_states(event.window);
+        let attention = states.contains(&WindowState::DemandsAttention);
+        change.urgent = Some((UrgencySource::State, attention));
         change.states = Some(states);
         return Some(DisplayEvent::WindowChange(change));
     }
 
     None
 }
+
+/// Remove (`0`), add (`1`) or toggle (`2`) the requested states, as `_NET_WM_STATE` messages ask.
+fn apply_state_request(states: &mut Vec<xlib::Atom>, action: c_long, requested: &[xlib::Atom]) {
+    for atom in requested {
+        let present = states.contains(atom);
+        match action {
+            0 => states.retain(|state| state != atom),
+            1 if !present => states.push(*atom),
+            2 if present => states.retain(|state| state != atom),
+            2 => states.push(*atom),
+            _ => {}
+        }
+    }
+    states.sort_unstable();
+    states.dedup();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn state_requests_should_remove_add_and_toggle() {
+        let mut states = vec![3, 5];
+        apply_state_request(&mut states, 0, &[5]);
+        assert_eq!(states, vec![3]);
+        apply_state_request(&mut states, 1, &[7, 3]);
+        assert_eq!(states, vec![3, 7]);
+        apply_state_request(&mut states, 2, &[7, 9]);
+        assert_eq!(states, vec![3, 9]);
+        apply_state_request(&mut states, 4, &[3]);
+        assert_eq!(states, vec![3, 9]);
+    }
+}
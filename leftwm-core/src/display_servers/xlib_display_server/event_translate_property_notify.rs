@@ -1,5 +1,5 @@
 use super::{DisplayEvent, XWrap};
-use crate::models::{WindowChange, WindowType, Xyhw};
+use crate::models::{UrgencySource, WindowChange, WindowState, WindowType, Xyhw};
 use x11_dl::xlib;
 
 pub fn from_event(xw: &XWrap, event: xlib::XPropertyEvent) -> Option<DisplayEvent> {
@@ -30,16 +30,15 @@ pub fn from_event(xw: &XWrap, event: xlib::XPropertyEvent) -> Option<DisplayEven
         xlib::XA_WM_NORMAL_HINTS => {
             build_change_for_size_hints(xw, event.window).map(DisplayEvent::WindowChange)
         }
-        xlib::XA_WM_HINTS => match xw.get_wmhints(event.window) {
-            Some(hints) if hints.flags & xlib::InputHint != 0 => {
-                let handle = event.window.into();
-                let mut change = WindowChange::new(handle);
+        xlib::XA_WM_HINTS => xw.get_wmhints(event.window).map(|hints| {
+            let handle = event.window.into();
+            let mut change = WindowChange::new(handle);
+            if hints.flags & xlib::InputHint != 0 {
                 change.never_focus = Some(hints.input == 0);
-                Some(DisplayEvent::WindowChange(change))
             }
-            Some(_hints) => None,
-            None => None,
-        },
+            change.urgent = Some((UrgencySource::Hint, hints.flags & xlib::XUrgencyHint != 0));
+            DisplayEvent::WindowChange(change)
+        }),
         xlib::XA_WM_NAME => Some(update_title(xw, event.window)),
         _ => {
             if event.atom == xw.atoms.NetWMName {
@@ -59,6 +58,8 @@ pub fn from_event(xw: &XWrap, event: xlib::XPropertyEvent) -> Option<DisplayEven
                 let handle = event.window.into();
                 let mut change = WindowChange::new(handle);
                 let states = xw.get_window_states(event.window);
+                let attention = states.contains(&WindowState::DemandsAttention);
+                change.urgent = Some((UrgencySource::State, attention));
                 change.states = Some(states);
                 return Some(DisplayEvent::WindowChange(change));
             }
@@ -75,21 +76,14 @@ fn build_change_for_size_strut_partial(xw: &XWrap, window: xlib::Window) -> Opti
 
     if let Some(dock_area) = xw.get_window_strut_array(window) {
         let dems = xw.get_screens_area_dimensions();
-        let screen = xw
-            .get_screens()
-            .iter()
-            .find(|s| s.contains_dock_area(dock_area, dems))?
-            .clone();
-
-        if let Some(xyhw) = dock_area.as_xyhw(dems.0, dems.1, &screen) {
-            change.floating = Some(xyhw.into());
-            change.r#type = Some(r#type);
-            return Some(change);
-        }
+        let xyhw = dock_area.resolve(&xw.get_screens(), dems)?;
+        change.strut = Some(xyhw.into());
+        change.r#type = Some(r#type);
+        return Some(change);
     } else if let Ok(geo) = xw.get_window_geometry(window) {
         let mut xyhw = Xyhw::default();
         geo.update(&mut xyhw);
-        change.floating = Some(xyhw.into());
+        change.strut = Some(xyhw.into());
         change.r#type = Some(r#type);
         return Some(change);
     }
@@ -107,6 +101,7 @@ fn build_change_for_size_hints(xw: &XWrap, window: xlib::Window) -> Option<Windo
     let mut xyhw = Xyhw::default();
     hint.update(&mut xyhw);
     change.requested = Some(xyhw);
+    change.size_increments = xw.get_size_increments(window);
     Some(change)
 }
 
@@ -1,19 +1,22 @@
-use crate::config::Config;
+use crate::config::{self, Config};
 use crate::display_action::DisplayAction;
+use crate::display_event::Timestamp;
 use crate::models::Mode;
 use crate::models::Screen;
+use crate::models::TabBar;
 use crate::models::TagId;
 use crate::models::Window;
 use crate::models::WindowHandle;
 use crate::models::WindowState;
 use crate::models::Workspace;
+use crate::models::Xyhw;
 use crate::utils;
 use crate::DisplayEvent;
 use crate::DisplayServer;
 use crate::Keybind;
 use futures::prelude::*;
-use std::os::raw::c_uint;
 use std::pin::Pin;
+use std::time::Duration;
 use x11_dl::xlib;
 
 mod event_translate;
@@ -109,29 +112,43 @@ impl DisplayServer for XlibDisplayServer {
         let xw = &mut self.xw;
         let event: Option<DisplayEvent> = match act {
             DisplayAction::KillWindow(h) => from_kill_window(xw, h),
+            DisplayAction::DeleteWindow(h) => from_delete_window(xw, h),
             DisplayAction::AddedWindow(h, f, fm) => from_added_window(xw, h, f, fm),
             DisplayAction::MoveMouseOver(h, f) => from_move_mouse_over(xw, h, f),
             DisplayAction::MoveMouseOverPoint(p) => from_move_mouse_over_point(xw, p),
             DisplayAction::DestroyedWindow(h) => from_destroyed_window(xw, h),
             DisplayAction::Unfocus(h, f) => from_unfocus(xw, h, f),
-            DisplayAction::ReplayClick(h, b) => from_replay_click(xw, h, b),
+            DisplayAction::ReplayClick(..) => from_replay_click(xw),
+            DisplayAction::ReleasePointer => from_release_pointer(xw),
             DisplayAction::SetState(h, t, s) => from_set_state(xw, h, t, s),
+            DisplayAction::SetGameMode(h, g) => from_set_game_mode(xw, h, g),
             DisplayAction::SetWindowOrder(ws) => from_set_window_order(xw, &ws),
             DisplayAction::MoveToTop(h) => from_move_to_top(xw, h),
             DisplayAction::ReadyToMoveWindow(h) => from_ready_to_move_window(xw, h),
             DisplayAction::ReadyToResizeWindow(h) => from_ready_to_resize_window(xw, h),
             DisplayAction::SetCurrentTags(ts) => from_set_current_tags(xw, &ts),
             DisplayAction::SetWindowTags(h, ts) => from_set_window_tags(xw, h, &ts),
+            DisplayAction::SetTagLabels(ls) => from_set_tag_labels(xw, ls),
             DisplayAction::ReloadKeyGrabs(ks) => from_reload_key_grabs(xw, &ks),
             DisplayAction::ConfigureXlibWindow(w) => from_configure_xlib_window(xw, &w),
+            DisplayAction::HighlightArea(area) => from_highlight_area(xw, area),
+            DisplayAction::UpdateTabBars(bars) => from_update_tab_bars(xw, bars),
+            DisplayAction::SlideWindows {
+                entering,
+                leaving,
+                distance,
+                duration,
+            } => from_slide_windows(xw, &entering, &leaving, distance, duration),
 
             DisplayAction::WindowTakeFocus {
                 window,
                 previous_window,
-            } => from_window_take_focus(xw, &window, &previous_window),
+                time,
+            } => from_window_take_focus(xw, &window, &previous_window, time),
 
             DisplayAction::FocusWindowUnderCursor => from_focus_window_under_cursor(xw),
             DisplayAction::NormalMode => from_normal_mode(xw),
+            DisplayAction::PickWindow => from_pick_window(xw),
         };
         if event.is_some() {
             log::trace!("DisplayEvent: {:?}", event);
@@ -139,6 +156,27 @@ impl DisplayServer for XlibDisplayServer {
         event
     }
 
+    fn workspace_screens(&self, workspaces: &[config::Workspace]) -> Vec<Screen> {
+        if workspaces.is_empty() {
+            // Tell manager about existing screens.
+            return self.xw.get_screens();
+        }
+        let names: Vec<String> = workspaces
+            .iter()
+            .map(|wsc| wsc.name.clone().unwrap_or_default())
+            .collect();
+        self.xw.set_workspace_names(&names);
+        workspaces
+            .iter()
+            .map(|wsc| {
+                let mut screen = Screen::from(wsc);
+                screen.root = self.root.into();
+                screen.output = self.xw.get_output_name(&screen.bbox);
+                screen
+            })
+            .collect()
+    }
+
     fn wait_readable(&self) -> Pin<Box<dyn Future<Output = ()>>> {
         let task_notify = self.xw.task_notify.clone();
         Box::pin(async move {
@@ -155,6 +193,10 @@ impl DisplayServer for XlibDisplayServer {
         let handle = self.xw.get_cursor_window().ok()?;
         Some(DisplayEvent::VerifyFocusedAt(handle))
     }
+
+    fn animate(&mut self) -> Option<Duration> {
+        self.xw.step_slide()
+    }
 }
 
 impl XlibDisplayServer {
@@ -162,21 +204,14 @@ impl XlibDisplayServer {
     fn initial_events(&self, config: &impl Config) -> Vec<DisplayEvent> {
         let mut events = vec![];
         if let Some(workspaces) = config.workspaces() {
-            if workspaces.is_empty() {
-                // tell manager about existing screens
-                self.xw.get_screens().into_iter().for_each(|screen| {
-                    let e = DisplayEvent::ScreenCreate(screen);
-                    events.push(e);
-                });
-            } else {
-                for wsc in &workspaces {
-                    let mut screen = Screen::from(wsc);
-                    screen.root = self.root.into();
-                    let e = DisplayEvent::ScreenCreate(screen);
-                    events.push(e);
-                }
-            }
+            events.extend(
+                self.workspace_screens(&workspaces)
+                    .into_iter()
+                    .map(DisplayEvent::ScreenCreate),
+            );
         }
+        // A profile may have been made for the monitors connected.
+        events.push(DisplayEvent::OutputsChange(self.xw.get_connected_outputs()));
 
         // Tell manager about existing windows.
         events.append(&mut self.find_all_windows());
@@ -192,11 +227,10 @@ impl XlibDisplayServer {
                     Ok(x) => x,
                     Err(_) => return,
                 };
-                let state = match self.xw.get_wm_state(handle) {
-                    Some(state) => state,
-                    None => return,
-                };
-                if attrs.map_state == xlib::IsViewable || state == ICONIC_STATE {
+                // Windows mapped while no window manager was running, e.g. during a reload, have
+                // no WM_STATE yet but still need to be adopted.
+                let state = self.xw.get_wm_state(handle);
+                if attrs.map_state == xlib::IsViewable || state == Some(ICONIC_STATE) {
                     if let Some(event) = self.xw.setup_window(handle) {
                         all.push(event);
                     }
@@ -216,6 +250,11 @@ fn from_kill_window(xw: &mut XWrap, handle: WindowHandle) -> Option<DisplayEvent
     None
 }
 
+fn from_delete_window(xw: &mut XWrap, handle: WindowHandle) -> Option<DisplayEvent> {
+    xw.delete_window(&handle);
+    None
+}
+
 fn from_added_window(
     xw: &mut XWrap,
     handle: WindowHandle,
@@ -255,10 +294,13 @@ fn from_unfocus(
     None
 }
 
-fn from_replay_click(xw: &mut XWrap, handle: WindowHandle, button: c_uint) -> Option<DisplayEvent> {
-    if let WindowHandle::XlibHandle(handle) = handle {
-        xw.replay_click(handle, button);
-    }
+fn from_replay_click(xw: &mut XWrap) -> Option<DisplayEvent> {
+    xw.replay_click();
+    None
+}
+
+fn from_release_pointer(xw: &mut XWrap) -> Option<DisplayEvent> {
+    xw.allow_pointer_events();
     None
 }
 
@@ -281,11 +323,22 @@ fn from_set_state(
         WindowState::Fullscreen => xw.atoms.NetWMStateFullscreen,
         WindowState::Above => xw.atoms.NetWMStateAbove,
         WindowState::Below => xw.atoms.NetWMStateBelow,
+        WindowState::DemandsAttention => xw.atoms.NetWMStateDemandsAttention,
     };
     xw.set_state(handle, toggle_to, state);
     None
 }
 
+fn from_set_game_mode(
+    xw: &mut XWrap,
+    handle: WindowHandle,
+    game_mode: bool,
+) -> Option<DisplayEvent> {
+    let window = handle.xlib_handle()?;
+    xw.set_window_game_mode(window, game_mode);
+    None
+}
+
 fn from_set_window_order(xw: &mut XWrap, windows: &[Window]) -> Option<DisplayEvent> {
     // The windows we are managing should be behind unmanaged windows. Unless they are
     // fullscreen, or their children.
@@ -350,6 +403,11 @@ fn from_set_window_tags(
     None
 }
 
+fn from_set_tag_labels(xw: &mut XWrap, labels: Vec<String>) -> Option<DisplayEvent> {
+    xw.set_desktop_names(labels);
+    None
+}
+
 fn from_reload_key_grabs(xw: &mut XWrap, keybinds: &[Keybind]) -> Option<DisplayEvent> {
     xw.reset_grabs(keybinds);
     None
@@ -360,12 +418,34 @@ fn from_configure_xlib_window(xw: &mut XWrap, window: &Window) -> Option<Display
     None
 }
 
+fn from_update_tab_bars(xw: &mut XWrap, bars: Vec<TabBar>) -> Option<DisplayEvent> {
+    xw.update_tab_bars(bars);
+    None
+}
+
+fn from_highlight_area(xw: &mut XWrap, area: Option<Xyhw>) -> Option<DisplayEvent> {
+    xw.highlight_area(area);
+    None
+}
+
+fn from_slide_windows(
+    xw: &mut XWrap,
+    entering: &[WindowHandle],
+    leaving: &[WindowHandle],
+    distance: i32,
+    duration: u64,
+) -> Option<DisplayEvent> {
+    xw.slide_windows(entering, leaving, distance, Duration::from_millis(duration));
+    None
+}
+
 fn from_window_take_focus(
     xw: &mut XWrap,
     window: &Window,
     previous_window: &Option<Window>,
+    time: Timestamp,
 ) -> Option<DisplayEvent> {
-    xw.window_take_focus(window, previous_window.as_ref());
+    xw.window_take_focus(window, previous_window.as_ref(), time);
     None
 }
 
@@ -379,3 +459,8 @@ fn from_normal_mode(xw: &mut XWrap) -> Option<DisplayEvent> {
     xw.set_mode(Mode::Normal);
     None
 }
+
+fn from_pick_window(xw: &mut XWrap) -> Option<DisplayEvent> {
+    xw.set_mode(Mode::PickingWindow);
+    None
+}
@@ -58,6 +58,9 @@ pub struct XAtom {
     pub NetNumberOfDesktops: xlib::Atom,
     pub NetCurrentDesktop: xlib::Atom,
     pub NetDesktopNames: xlib::Atom,
+    pub LeftwmWorkspaceNames: xlib::Atom,
+    pub LeftwmGameMode: xlib::Atom,
+    pub Manager: xlib::Atom,
     pub NetWMDesktop: xlib::Atom,
     pub NetWMStrutPartial: xlib::Atom, //net version - Reserve Screen Space
     pub NetWMStrut: xlib::Atom,        //old version
@@ -66,6 +69,24 @@ pub struct XAtom {
 }
 
 impl XAtom {
+    /// The `_NET_WM_STATE` atoms leftwm knows.
+    pub const fn window_states(&self) -> [xlib::Atom; 12] {
+        [
+            self.NetWMStateModal,
+            self.NetWMStateSticky,
+            self.NetWMStateMaximizedVert,
+            self.NetWMStateMaximizedHorz,
+            self.NetWMStateShaded,
+            self.NetWMStateSkipTaskbar,
+            self.NetWMStateSkipPager,
+            self.NetWMStateHidden,
+            self.NetWMStateFullscreen,
+            self.NetWMStateAbove,
+            self.NetWMStateBelow,
+            self.NetWMStateDemandsAttention,
+        ]
+    }
+
     pub fn net_supported(&self) -> Vec<xlib::Atom> {
         vec![
             self.NetActiveWindow,
@@ -161,6 +182,9 @@ impl XAtom {
             a if a == self.NetNumberOfDesktops => "_NET_NUMBER_OF_DESKTOPS",
             a if a == self.NetCurrentDesktop => "_NET_CURRENT_DESKTOP",
             a if a == self.NetDesktopNames => "_NET_DESKTOP_NAMES",
+            a if a == self.LeftwmWorkspaceNames => "_LEFTWM_WORKSPACE_NAMES",
+            a if a == self.LeftwmGameMode => "_LEFTWM_GAME_MODE",
+            a if a == self.Manager => "MANAGER",
             a if a == self.NetWMDesktop => "_NET_WM_DESKTOP",
             a if a == self.NetWMStrutPartial => "_NET_WM_STRUT_PARTIAL",
             a if a == self.NetWMStrut => "_NET_WM_STRUT",
@@ -223,6 +247,9 @@ impl XAtom {
             NetNumberOfDesktops: from(xlib, dpy, "_NET_NUMBER_OF_DESKTOPS"),
             NetCurrentDesktop: from(xlib, dpy, "_NET_CURRENT_DESKTOP"),
             NetDesktopNames: from(xlib, dpy, "_NET_DESKTOP_NAMES"),
+            LeftwmWorkspaceNames: from(xlib, dpy, "_LEFTWM_WORKSPACE_NAMES"),
+            LeftwmGameMode: from(xlib, dpy, "_LEFTWM_GAME_MODE"),
+            Manager: from(xlib, dpy, "MANAGER"),
             NetWMDesktop: from(xlib, dpy, "_NET_WM_DESKTOP"),
             NetWMStrutPartial: from(xlib, dpy, "_NET_WM_STRUT_PARTIAL"),
             NetWMStrut: from(xlib, dpy, "_NET_WM_STRUT"),
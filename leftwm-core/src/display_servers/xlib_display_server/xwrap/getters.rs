@@ -1,11 +1,13 @@
 //! `XWrap` getters.
 use super::{Screen, WindowHandle, XlibError, MAX_PROPERTY_VALUE_LEN, MOUSEMASK};
-use crate::models::{DockArea, WindowState, WindowType, XyhwChange};
+use crate::models::BBox;
+use crate::models::{DockArea, SizeIncrements, WindowState, WindowType, XyhwChange};
 use crate::XWrap;
 use std::ffi::CString;
 use std::os::raw::{c_char, c_int, c_long, c_uchar, c_uint, c_ulong};
 use std::slice;
 use x11_dl::xlib;
+use x11_dl::xrandr::{self, Xrandr};
 
 impl XWrap {
     // Public functions.
@@ -127,6 +129,29 @@ impl XWrap {
         self.root
     }
 
+    /// Returns the resize increments of a window from its `WM_NORMAL_HINTS`. As in ICCCM, the
+    /// minimum size stands in for a missing base size.
+    #[must_use]
+    pub fn get_size_increments(&self, window: xlib::Window) -> Option<SizeIncrements> {
+        let size = self.get_hint_sizing(window)?;
+        if (size.flags & xlib::PResizeInc) == 0 {
+            return None;
+        }
+        let (base_w, base_h) = if (size.flags & xlib::PBaseSize) != 0 {
+            (size.base_width, size.base_height)
+        } else if (size.flags & xlib::PMinSize) != 0 {
+            (size.min_width, size.min_height)
+        } else {
+            (0, 0)
+        };
+        Some(SizeIncrements {
+            base_w,
+            base_h,
+            inc_w: size.width_inc,
+            inc_h: size.height_inc,
+        })
+    }
+
     /// Returns the `WM_SIZE_HINTS`/`WM_NORMAL_HINTS` of a window as a `XyhwChange`.
     #[must_use]
     pub fn get_hint_sizing_as_xyhw(&self, window: xlib::Window) -> Option<XyhwChange> {
@@ -224,14 +249,16 @@ impl XWrap {
             // Take ownership of the array.
             let xinerama_infos: &[XineramaScreenInfo] =
                 unsafe { slice::from_raw_parts(info_array_raw, screen_count as usize) };
-            xinerama_infos
+            let screens = xinerama_infos
                 .iter()
                 .map(|i| {
                     let mut s = Screen::from(i);
                     s.root = root;
+                    s.output = self.get_output_name(&s.bbox);
                     s
                 })
-                .collect()
+                .collect();
+            Screen::merge_mirrored(screens)
         } else {
             // NON-XINERAMA
             let roots: Result<Vec<xlib::XWindowAttributes>, _> = self
@@ -244,6 +271,93 @@ impl XWrap {
         }
     }
 
+    /// Returns the names of the active outputs with their geometry, as xrandr reports them.
+    // `XRRGetScreenResources`: https://www.x.org/releases/current/doc/man/man3/Xrandr.3.xhtml
+    #[must_use]
+    pub fn get_outputs(&self) -> Vec<(String, BBox)> {
+        Xrandr::open().map_or_else(|_| vec![], |xrandr| self.read_outputs(&xrandr))
+    }
+
+    /// Returns the names of the outputs a monitor is connected to, active or not.
+    #[must_use]
+    pub fn get_connected_outputs(&self) -> Vec<String> {
+        Xrandr::open().map_or_else(|_| vec![], |xrandr| self.read_connected_outputs(&xrandr))
+    }
+
+    fn read_connected_outputs(&self, xrandr: &Xrandr) -> Vec<String> {
+        let mut outputs = vec![];
+        unsafe {
+            let resources = (xrandr.XRRGetScreenResources)(self.display, self.root);
+            if resources.is_null() {
+                return outputs;
+            }
+            let ids = slice::from_raw_parts((*resources).outputs, (*resources).noutput as usize);
+            for &id in ids {
+                let info = (xrandr.XRRGetOutputInfo)(self.display, resources, id);
+                if info.is_null() {
+                    continue;
+                }
+                if c_int::from((*info).connection) == xrandr::RR_Connected {
+                    let name =
+                        slice::from_raw_parts((*info).name.cast::<u8>(), (*info).nameLen as usize);
+                    outputs.push(String::from_utf8_lossy(name).into_owned());
+                }
+                (xrandr.XRRFreeOutputInfo)(info);
+            }
+            (xrandr.XRRFreeScreenResources)(resources);
+        }
+        outputs
+    }
+
+    fn read_outputs(&self, xrandr: &Xrandr) -> Vec<(String, BBox)> {
+        let mut outputs = vec![];
+        unsafe {
+            let resources = (xrandr.XRRGetScreenResources)(self.display, self.root);
+            if resources.is_null() {
+                return outputs;
+            }
+            let ids = slice::from_raw_parts((*resources).outputs, (*resources).noutput as usize);
+            for &id in ids {
+                let info = (xrandr.XRRGetOutputInfo)(self.display, resources, id);
+                if info.is_null() {
+                    continue;
+                }
+                if (*info).crtc != 0 {
+                    let crtc = (xrandr.XRRGetCrtcInfo)(self.display, resources, (*info).crtc);
+                    if !crtc.is_null() {
+                        let name = slice::from_raw_parts(
+                            (*info).name.cast::<u8>(),
+                            (*info).nameLen as usize,
+                        );
+                        let bbox = BBox {
+                            x: (*crtc).x,
+                            y: (*crtc).y,
+                            width: (*crtc).width as i32,
+                            height: (*crtc).height as i32,
+                        };
+                        outputs.push((String::from_utf8_lossy(name).into_owned(), bbox));
+                        (xrandr.XRRFreeCrtcInfo)(crtc);
+                    }
+                }
+                (xrandr.XRRFreeOutputInfo)(info);
+            }
+            (xrandr.XRRFreeScreenResources)(resources);
+        }
+        outputs
+    }
+
+    /// Returns the name of the output the top left corner of an area is on.
+    #[must_use]
+    pub fn get_output_name(&self, bbox: &BBox) -> Option<String> {
+        self.get_outputs()
+            .into_iter()
+            .find(|(_, output)| {
+                (output.x..output.x + output.width).contains(&bbox.x)
+                    && (output.y..output.y + output.height).contains(&bbox.y)
+            })
+            .map(|(name, _)| name)
+    }
+
     /// Returns the dimensions of the screens.
     #[must_use]
     pub fn get_screens_area_dimensions(&self) -> (i32, i32) {
@@ -424,19 +538,22 @@ impl XWrap {
     pub fn get_window_states(&self, window: xlib::Window) -> Vec<WindowState> {
         self.get_window_states_atoms(window)
             .iter()
-            .map(|a| match a {
-                x if x == &self.atoms.NetWMStateModal => WindowState::Modal,
-                x if x == &self.atoms.NetWMStateSticky => WindowState::Sticky,
-                x if x == &self.atoms.NetWMStateMaximizedVert => WindowState::MaximizedVert,
-                x if x == &self.atoms.NetWMStateMaximizedHorz => WindowState::MaximizedHorz,
-                x if x == &self.atoms.NetWMStateShaded => WindowState::Shaded,
-                x if x == &self.atoms.NetWMStateSkipTaskbar => WindowState::SkipTaskbar,
-                x if x == &self.atoms.NetWMStateSkipPager => WindowState::SkipPager,
-                x if x == &self.atoms.NetWMStateHidden => WindowState::Hidden,
-                x if x == &self.atoms.NetWMStateFullscreen => WindowState::Fullscreen,
-                x if x == &self.atoms.NetWMStateAbove => WindowState::Above,
-                x if x == &self.atoms.NetWMStateBelow => WindowState::Below,
-                _ => WindowState::Modal,
+            .filter_map(|a| match a {
+                x if x == &self.atoms.NetWMStateModal => Some(WindowState::Modal),
+                x if x == &self.atoms.NetWMStateSticky => Some(WindowState::Sticky),
+                x if x == &self.atoms.NetWMStateMaximizedVert => Some(WindowState::MaximizedVert),
+                x if x == &self.atoms.NetWMStateMaximizedHorz => Some(WindowState::MaximizedHorz),
+                x if x == &self.atoms.NetWMStateShaded => Some(WindowState::Shaded),
+                x if x == &self.atoms.NetWMStateSkipTaskbar => Some(WindowState::SkipTaskbar),
+                x if x == &self.atoms.NetWMStateSkipPager => Some(WindowState::SkipPager),
+                x if x == &self.atoms.NetWMStateHidden => Some(WindowState::Hidden),
+                x if x == &self.atoms.NetWMStateFullscreen => Some(WindowState::Fullscreen),
+                x if x == &self.atoms.NetWMStateAbove => Some(WindowState::Above),
+                x if x == &self.atoms.NetWMStateBelow => Some(WindowState::Below),
+                x if x == &self.atoms.NetWMStateDemandsAttention => {
+                    Some(WindowState::DemandsAttention)
+                }
+                _ => None,
             })
             .collect()
     }
@@ -677,8 +794,19 @@ impl XWrap {
             #[allow(clippy::cast_ptr_alignment)]
             let array_ptr = prop_return.cast::<c_long>();
             let slice = slice::from_raw_parts(array_ptr, nitems_return as usize);
-            if slice.len() == 12 {
-                return Some(DockArea::from(slice));
+            // `_NET_WM_STRUT` only holds the widths, the strut spans the whole edge.
+            if slice.len() == 4 {
+                return Some(DockArea {
+                    left: slice[0] as i32,
+                    right: slice[1] as i32,
+                    top: slice[2] as i32,
+                    bottom: slice[3] as i32,
+                    left_end_y: i32::MAX,
+                    right_end_y: i32::MAX,
+                    top_end_x: i32::MAX,
+                    bottom_end_x: i32::MAX,
+                    ..DockArea::default()
+                });
             }
             None
         }
@@ -10,21 +10,22 @@ use super::xatom::XAtom;
 use super::xcursor::XCursor;
 use super::{utils, Screen, Window, WindowHandle};
 use crate::config::Config;
-use crate::models::{FocusBehaviour, Mode};
+use crate::models::{FocusBehaviour, Mode, TabBar};
 use crate::utils::xkeysym_lookup::ModMask;
-use std::ffi::CString;
-use std::os::raw::{c_char, c_double, c_int, c_long, c_short, c_ulong};
+use std::os::raw::{c_double, c_int, c_long, c_short, c_ulong};
 use std::sync::Arc;
+use std::time::Instant;
 use std::{ptr, slice};
 use tokio::sync::{oneshot, Notify};
 use tokio::time::Duration;
 use x11_dl::xlib;
-use x11_dl::xrandr::Xrandr;
+use x11_dl::xrandr::{self, Xrandr};
 
 mod getters;
 mod keyboard;
 mod mouse;
 mod setters;
+mod tab_bar;
 mod window;
 
 type WindowStateConst = c_long;
@@ -46,6 +47,17 @@ pub struct Colors {
     normal: c_ulong,
     floating: c_ulong,
     active: c_ulong,
+    urgent: c_ulong,
+}
+
+/// The slide of the windows of a tag switch, stepped by `step_slide`.
+struct Slide {
+    /// The windows with the places they slide into and out of.
+    entering: Vec<(xlib::Window, i32, i32)>,
+    leaving: Vec<(xlib::Window, i32, i32)>,
+    distance: i32,
+    start: Instant,
+    duration: Duration,
 }
 
 #[derive(Debug, Clone)]
@@ -67,12 +79,32 @@ pub struct XWrap {
     pub tag_labels: Vec<String>,
     pub mode: Mode,
     pub focus_behaviour: FocusBehaviour,
+    pub click_passthrough: bool,
     pub mouse_key_mask: ModMask,
     pub mode_origin: (i32, i32),
     _task_guard: oneshot::Receiver<()>,
     pub task_notify: Arc<Notify>,
     pub motion_event_limiter: c_ulong,
+    /// The time of the last focus change, see `window_take_focus`.
+    last_focus_time: xlib::Time,
+    /// The `WM_S<screen>` selection, which leftwm gives up by exiting.
+    pub wm_selection: xlib::Atom,
+    /// The number of the first `RandR` event, when the server has the extension.
+    pub randr_event_base: Option<c_int>,
+    /// The window with the focused border color, set in `window_take_focus`.
+    focused: Option<xlib::Window>,
     pub refresh_rate: c_short,
+    /// Edges of the outline drawn by `highlight_area`, created when first needed.
+    highlight: Vec<xlib::Window>,
+    /// Windows drawing the tab bars of the `Tabbed` layout, with the bars they draw.
+    tab_bars: Vec<(xlib::Window, TabBar)>,
+    min_font_size: Option<u32>,
+    /// The fonts of the tab bars, null if none could be loaded.
+    tab_font: xlib::XFontSet,
+    /// The graphics context the tab bars are drawn with, created in `init`.
+    tab_gc: xlib::GC,
+    /// The slide of a tag switch going on.
+    slide: Option<Slide>,
 }
 
 impl Default for XWrap {
@@ -81,6 +113,12 @@ impl Default for XWrap {
     }
 }
 
+impl Drop for XWrap {
+    fn drop(&mut self) {
+        self.free_tab_drawing();
+    }
+}
+
 impl XWrap {
     /// # Panics
     ///
@@ -132,6 +170,7 @@ impl XWrap {
         });
 
         let atoms = XAtom::new(&xlib, display);
+        let wm_selection = utils::instance::wm_selection(&xlib, display);
         let cursors = XCursor::new(&xlib, display);
         let root = unsafe { (xlib.XDefaultRootWindow)(display) };
 
@@ -139,6 +178,7 @@ impl XWrap {
             normal: 0,
             floating: 0,
             active: 0,
+            urgent: 0,
         };
 
         let refresh_rate = match Xrandr::open() {
@@ -186,12 +226,23 @@ impl XWrap {
             tag_labels: vec![],
             mode: Mode::Normal,
             focus_behaviour: FocusBehaviour::Sloppy,
+            click_passthrough: true,
             mouse_key_mask: 0,
             mode_origin: (0, 0),
             _task_guard,
             task_notify,
             motion_event_limiter: 0,
+            last_focus_time: xlib::CurrentTime,
+            wm_selection,
+            randr_event_base: None,
+            focused: None,
             refresh_rate,
+            highlight: vec![],
+            tab_bars: vec![],
+            min_font_size: None,
+            tab_font: ptr::null_mut(),
+            tab_gc: ptr::null_mut(),
+            slide: None,
         };
 
         // Check that another WM is not running.
@@ -241,8 +292,13 @@ impl XWrap {
         windows: &[Window],
     ) {
         self.focus_behaviour = config.focus_behaviour();
+        self.click_passthrough = config.click_passthrough();
         self.mouse_key_mask = utils::xkeysym_lookup::into_modmask(&config.mousekey());
         self.load_colors(config, focused, Some(windows));
+        if self.min_font_size != config.min_font_size() {
+            self.min_font_size = config.min_font_size();
+            self.load_tab_drawing();
+        }
         self.tag_labels = config.create_list_of_tag_labels();
         self.reset_grabs(&config.mapped_bindings());
     }
@@ -253,10 +309,15 @@ impl XWrap {
     // TODO: split into smaller functions
     pub fn init(&mut self, config: &impl Config) {
         self.focus_behaviour = config.focus_behaviour();
+        self.click_passthrough = config.click_passthrough();
         self.mouse_key_mask = utils::xkeysym_lookup::into_modmask(&config.mousekey());
 
         let root = self.root;
         self.load_colors(config, None, None);
+        self.min_font_size = config.min_font_size();
+        // Font sets decode the titles following the locale, which Rust doesn't set up.
+        unsafe { nix::libc::setlocale(nix::libc::LC_CTYPE, c"".as_ptr()) };
+        self.load_tab_drawing();
 
         let mut attrs: xlib::XSetWindowAttributes = unsafe { std::mem::zeroed() };
         attrs.cursor = self.cursors.normal;
@@ -273,6 +334,11 @@ impl XWrap {
 
         self.subscribe_to_event(root, ROOT_EVENT_MASK);
 
+        self.own_wm_selection();
+
+        // Hear of monitors being plugged in or out, to load the profile of the outputs connected.
+        self.randr_event_base = self.select_output_changes();
+
         // EWMH compliance.
         unsafe {
             let supported: Vec<c_long> = self
@@ -296,12 +362,81 @@ impl XWrap {
         self.sync();
     }
 
+    /// Own the selection of window managers, so others can tell leftwm runs and replace it, and
+    /// tell the clients with the `MANAGER` message ICCCM has window managers send.
+    fn own_wm_selection(&self) {
+        let window = unsafe {
+            (self.xlib.XCreateSimpleWindow)(self.display, self.root, -1, -1, 1, 1, 0, 0, 0)
+        };
+        let owned = unsafe {
+            utils::instance::own_wm_selection(&self.xlib, self.display, self.wm_selection, window)
+        };
+        let Some(time) = owned else {
+            log::error!("Couldn't take the selection of window managers");
+            return;
+        };
+        let mut msg: xlib::XClientMessageEvent = unsafe { std::mem::zeroed() };
+        msg.type_ = xlib::ClientMessage;
+        msg.window = self.root;
+        msg.message_type = self.atoms.Manager;
+        msg.format = 32;
+        msg.data.set_long(0, time as c_long);
+        msg.data.set_long(1, self.wm_selection as c_long);
+        msg.data.set_long(2, window as c_long);
+        let mut ev: xlib::XEvent = msg.into();
+        self.send_xevent(self.root, 0, xlib::StructureNotifyMask, &mut ev);
+    }
+
+    /// Listen for the outputs and the screen changing, returning the number of the first `RandR`
+    /// event. `None` if the server doesn't have `RandR`.
+    // `XRRSelectInput`: https://www.x.org/releases/current/doc/man/man3/Xrandr.3.xhtml
+    fn select_output_changes(&self) -> Option<c_int> {
+        let xrandr = Xrandr::open().ok()?;
+        let (mut event_base, mut error_base) = (0, 0);
+        unsafe {
+            let found = (xrandr.XRRQueryExtension)(
+                self.display,
+                ptr::addr_of_mut!(event_base),
+                ptr::addr_of_mut!(error_base),
+            );
+            if found == 0 {
+                return None;
+            }
+            (xrandr.XRRSelectInput)(
+                self.display,
+                self.root,
+                xrandr::RRScreenChangeNotifyMask | xrandr::RROutputChangeNotifyMask,
+            );
+        }
+        Some(event_base)
+    }
+
+    /// Whether an event is `RandR` telling of the outputs or the screen changing.
+    #[must_use]
+    pub fn is_output_change(&self, event_type: c_int) -> bool {
+        self.randr_event_base.is_some_and(|base| {
+            event_type == base + xrandr::RRScreenChangeNotify
+                || event_type == base + xrandr::RRNotify
+        })
+    }
+
+    /// Let Xlib know of the new size of the screen a `RRScreenChangeNotify` tells of.
+    pub fn update_screen_configuration(&self, event: &mut xlib::XEvent) {
+        if let Ok(xrandr) = Xrandr::open() {
+            unsafe { (xrandr.XRRUpdateConfiguration)(event) };
+        }
+    }
+
+    /// Name the workspaces in `_LEFTWM_WORKSPACE_NAMES` on the root, in the order of the
+    /// workspaces of the config, for bars to label themselves by. Unnamed ones are empty.
+    pub fn set_workspace_names(&self, names: &[String]) {
+        self.set_desktop_prop_string_list(names, self.atoms.LeftwmWorkspaceNames);
+    }
+
     /// EWMH support used for bars such as polybar.
     ///  # Panics
     ///
     ///  Panics if a new Cstring cannot be formed
-    // `Xutf8TextListToTextProperty`: https://linux.die.net/man/3/xutf8textlisttotextproperty
-    // `XSetTextProperty`: https://tronche.com/gui/x/xlib/ICC/client-to-window-manager/XSetTextProperty.html
     pub fn init_desktops_hints(&self) {
         let tag_labels = &self.tag_labels;
         let tag_length = tag_labels.len();
@@ -312,28 +447,7 @@ impl XWrap {
         let data = vec![0_u32, xlib::CurrentTime as u32];
         self.set_desktop_prop(&data, self.atoms.NetCurrentDesktop);
         // Set desktop names.
-        let mut text: xlib::XTextProperty = unsafe { std::mem::zeroed() };
-        unsafe {
-            let mut clist_tags: Vec<*mut c_char> = tag_labels
-                .iter()
-                .map(|x| CString::new(x.clone()).unwrap_or_default().into_raw())
-                .collect();
-            let ptr = clist_tags.as_mut_ptr();
-            (self.xlib.Xutf8TextListToTextProperty)(
-                self.display,
-                ptr,
-                clist_tags.len() as i32,
-                xlib::XUTF8StringStyle,
-                &mut text,
-            );
-            std::mem::forget(clist_tags);
-            (self.xlib.XSetTextProperty)(
-                self.display,
-                self.root,
-                &mut text,
-                self.atoms.NetDesktopNames,
-            );
-        }
+        self.set_desktop_prop_string_list(tag_labels, self.atoms.NetDesktopNames);
 
         // Set the WM NAME.
         self.set_desktop_prop_string("LeftWM", self.atoms.NetWMName, self.atoms.UTF8String);
@@ -354,6 +468,16 @@ impl XWrap {
     /// Send a xevent atom for a window to X.
     // `XSendEvent`: https://tronche.com/gui/x/xlib/event-handling/XSendEvent.html
     fn send_xevent_atom(&self, window: xlib::Window, atom: xlib::Atom) -> bool {
+        self.send_xevent_atom_at(window, atom, xlib::CurrentTime)
+    }
+
+    /// Send a xevent atom for a window to X, with the time of the event causing it.
+    fn send_xevent_atom_at(
+        &self,
+        window: xlib::Window,
+        atom: xlib::Atom,
+        time: xlib::Time,
+    ) -> bool {
         if self.can_send_xevent_atom(window, atom) {
             let mut msg: xlib::XClientMessageEvent = unsafe { std::mem::zeroed() };
             msg.type_ = xlib::ClientMessage;
@@ -361,7 +485,7 @@ impl XWrap {
             msg.message_type = self.atoms.WMProtocols;
             msg.format = 32;
             msg.data.set_long(0, atom as c_long);
-            msg.data.set_long(1, xlib::CurrentTime as c_long);
+            msg.data.set_long(1, time as c_long);
             let mut ev: xlib::XEvent = msg.into();
             self.send_xevent(window, 0, xlib::NoEventMask, &mut ev);
             return true;
@@ -406,6 +530,7 @@ impl XWrap {
             normal: self.get_color(config.default_border_color()),
             floating: self.get_color(config.floating_border_color()),
             active: self.get_color(config.focused_border_color()),
+            urgent: self.get_color(config.urgent_border_color()),
         };
         // Update all the windows with the new colors.
         if let Some(windows) = windows {
@@ -413,14 +538,7 @@ impl XWrap {
                 if let WindowHandle::XlibHandle(handle) = window.handle {
                     let is_focused =
                         matches!(focused, Some(&Some(focused)) if focused == window.handle);
-                    let color: c_ulong = if is_focused {
-                        self.colors.active
-                    } else if window.floating() {
-                        self.colors.floating
-                    } else {
-                        self.colors.normal
-                    };
-                    self.set_window_border_color(handle, color);
+                    self.set_window_border_color(handle, self.border_color(window, is_focused));
                 }
             }
         }
@@ -443,7 +561,7 @@ impl XWrap {
                 let cursor = match mode {
                     Mode::ReadyToResize(_) | Mode::ResizingWindow(_) => self.cursors.resize,
                     Mode::ReadyToMove(_) | Mode::MovingWindow(_) => self.cursors.move_,
-                    Mode::Normal => self.cursors.normal,
+                    Mode::Normal | Mode::PickingWindow => self.cursors.normal,
                 };
                 self.grab_pointer(cursor);
             }
@@ -455,10 +573,14 @@ impl XWrap {
                 let cursor = match mode {
                     Mode::ReadyToResize(_) | Mode::ResizingWindow(_) => self.cursors.resize,
                     Mode::ReadyToMove(_) | Mode::MovingWindow(_) => self.cursors.move_,
-                    Mode::Normal => self.cursors.normal,
+                    Mode::Normal | Mode::PickingWindow => self.cursors.normal,
                 };
                 self.grab_pointer(cursor);
             }
+            Mode::PickingWindow if self.mode == Mode::Normal => {
+                self.mode = mode;
+                self.grab_pointer(self.cursors.normal);
+            }
             Mode::Normal => {
                 self.ungrab_pointer();
                 self.mode = mode;
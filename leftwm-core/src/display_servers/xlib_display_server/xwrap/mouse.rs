@@ -1,8 +1,9 @@
 //! Xlib calls related to a mouse.
 use super::{XlibError, MOUSEMASK};
 use crate::display_servers::xlib_display_server::xwrap::BUTTONMASK;
+use crate::models::FocusBehaviour;
 use crate::XWrap;
-use std::os::raw::{c_int, c_uint, c_ulong};
+use std::os::raw::{c_int, c_ulong};
 use x11_dl::xlib;
 
 impl XWrap {
@@ -10,8 +11,9 @@ impl XWrap {
     pub fn grab_mouse_clicks(&self, handle: xlib::Window, is_focused: bool) {
         self.ungrab_buttons(handle);
         if !is_focused {
-            self.grab_buttons(handle, xlib::Button1, xlib::AnyModifier);
-            self.grab_buttons(handle, xlib::Button3, xlib::AnyModifier);
+            let mode = unfocused_pointer_mode(self.focus_behaviour);
+            self.grab_buttons_with_mode(handle, xlib::Button1, xlib::AnyModifier, mode);
+            self.grab_buttons_with_mode(handle, xlib::Button3, xlib::AnyModifier, mode);
         }
         self.grab_buttons(handle, xlib::Button1, self.mouse_key_mask);
         self.grab_buttons(handle, xlib::Button1, self.mouse_key_mask | xlib::ShiftMask);
@@ -22,6 +24,18 @@ impl XWrap {
     /// Grabs the button with the modifier for a window.
     // `XGrabButton`: https://tronche.com/gui/x/xlib/input/XGrabButton.html
     pub fn grab_buttons(&self, window: xlib::Window, button: u32, modifiers: u32) {
+        self.grab_buttons_with_mode(window, button, modifiers, xlib::GrabModeAsync);
+    }
+
+    /// Grabs the button with the modifier for a window, using `pointer_mode` for the pointer.
+    // `XGrabButton`: https://tronche.com/gui/x/xlib/input/XGrabButton.html
+    pub fn grab_buttons_with_mode(
+        &self,
+        window: xlib::Window,
+        button: u32,
+        modifiers: u32,
+        pointer_mode: c_int,
+    ) {
         // Grab the buttons with and without numlock (Mod2).
         let mods: Vec<u32> = vec![
             modifiers,
@@ -37,7 +51,7 @@ impl XWrap {
                     window,
                     0,
                     BUTTONMASK as u32,
-                    xlib::GrabModeAsync,
+                    pointer_mode,
                     xlib::GrabModeAsync,
                     0,
                     0,
@@ -124,45 +138,52 @@ impl XWrap {
         Ok(())
     }
 
-    /// Replay a click on a window.
-    // `XQueryPointer`: https://tronche.com/gui/x/xlib/window-information/XQueryPointer.html
-    pub fn replay_click(&self, focused_window: xlib::Window, button: c_uint) {
-        unsafe {
-            let mut event: xlib::XButtonEvent = std::mem::zeroed();
-            event.button = button;
-            event.same_screen = xlib::True;
-            event.subwindow = self.get_default_root();
-
-            while event.subwindow != 0 {
-                event.window = event.subwindow;
-                (self.xlib.XQueryPointer)(
-                    self.display,
-                    event.window,
-                    &mut event.root,
-                    &mut event.subwindow,
-                    &mut event.x_root,
-                    &mut event.y_root,
-                    &mut event.x,
-                    &mut event.y,
-                    &mut event.state,
-                );
-            }
-
-            // Make sure we are clicking on the focused window. This also prevents clicks when
-            // focus is changed by a keybind.
-            if event.window == focused_window {
-                event.type_ = xlib::ButtonPress;
-                self.send_xevent(event.window, 0, xlib::ButtonPressMask, &mut event.into());
-
-                event.type_ = xlib::ButtonRelease;
-                self.send_xevent(event.window, 0, xlib::ButtonReleaseMask, &mut event.into());
-            }
-        }
+    /// Release the pointer frozen by a click on an unfocused window. The click is replayed to
+    /// the window if clicks pass through, otherwise it is swallowed.
+    // `XAllowEvents`: https://linux.die.net/man/3/xallowevents
+    pub fn replay_click(&self) {
+        let mode = if self.click_passthrough {
+            xlib::ReplayPointer
+        } else {
+            xlib::AsyncPointer
+        };
+        unsafe { (self.xlib.XAllowEvents)(self.display, mode, xlib::CurrentTime) };
     }
 
-    /// Release the pointer if it is frozen.
+    /// Release the pointer if it is frozen, passing the click on to the window under it.
     // `XAllowEvents`: https://linux.die.net/man/3/xallowevents
     pub fn allow_pointer_events(&self) {
-        unsafe { (self.xlib.XAllowEvents)(self.display, xlib::SyncPointer, xlib::CurrentTime) };
+        unsafe { (self.xlib.XAllowEvents)(self.display, xlib::ReplayPointer, xlib::CurrentTime) };
+    }
+}
+
+/// How the clicks on unfocused windows grab the pointer. `ClickTo` freezes it so the focusing
+/// click can be replayed or swallowed once focus has changed, the other modes leave it moving.
+fn unfocused_pointer_mode(behaviour: FocusBehaviour) -> c_int {
+    if behaviour.is_clickto() {
+        xlib::GrabModeSync
+    } else {
+        xlib::GrabModeAsync
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn only_click_to_focus_should_freeze_the_pointer() {
+        assert_eq!(
+            unfocused_pointer_mode(FocusBehaviour::ClickTo),
+            xlib::GrabModeSync
+        );
+        assert_eq!(
+            unfocused_pointer_mode(FocusBehaviour::Sloppy),
+            xlib::GrabModeAsync
+        );
+        assert_eq!(
+            unfocused_pointer_mode(FocusBehaviour::Driven),
+            xlib::GrabModeAsync
+        );
     }
 }
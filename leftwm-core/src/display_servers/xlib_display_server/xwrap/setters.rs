@@ -3,7 +3,7 @@ use super::WindowHandle;
 use crate::models::TagId;
 use crate::XWrap;
 use std::ffi::CString;
-use std::os::raw::{c_long, c_ulong};
+use std::os::raw::{c_char, c_long, c_ulong};
 use x11_dl::xlib;
 
 impl XWrap {
@@ -79,6 +79,14 @@ impl XWrap {
         self.set_desktop_prop(&indexes, self.atoms.NetCurrentDesktop);
     }
 
+    /// Sets the number and names of the desktops, to the tags labelled `labels`.
+    pub fn set_desktop_names(&mut self, labels: Vec<String>) {
+        let data = vec![labels.len() as u32];
+        self.set_desktop_prop(&data, self.atoms.NetNumberOfDesktops);
+        self.set_desktop_prop_string_list(&labels, self.atoms.NetDesktopNames);
+        self.tag_labels = labels;
+    }
+
     // /// Sets the current viewport.
     // fn set_current_viewport(&self, tags: Vec<&String>) {
     //     let mut indexes: Vec<u32> = vec![];
@@ -127,6 +135,34 @@ impl XWrap {
         }
     }
 
+    /// Sets a desktop property with a list of UTF-8 strings.
+    // `Xutf8TextListToTextProperty`: https://linux.die.net/man/3/xutf8textlisttotextproperty
+    // `XSetTextProperty`: https://tronche.com/gui/x/xlib/ICC/client-to-window-manager/XSetTextProperty.html
+    pub fn set_desktop_prop_string_list(&self, values: &[String], atom: c_ulong) {
+        let mut text: xlib::XTextProperty = unsafe { std::mem::zeroed() };
+        unsafe {
+            let mut clist: Vec<*mut c_char> = values
+                .iter()
+                .map(|x| CString::new(x.clone()).unwrap_or_default().into_raw())
+                .collect();
+            let ptr = clist.as_mut_ptr();
+            (self.xlib.Xutf8TextListToTextProperty)(
+                self.display,
+                ptr,
+                clist.len() as i32,
+                xlib::XUTF8StringStyle,
+                std::ptr::addr_of_mut!(text),
+            );
+            std::mem::forget(clist);
+            (self.xlib.XSetTextProperty)(
+                self.display,
+                self.root,
+                std::ptr::addr_of_mut!(text),
+                atom,
+            );
+        }
+    }
+
     /// Sets a windows state.
     pub fn set_state(&self, handle: WindowHandle, toggle_to: bool, atom: xlib::Atom) {
         if let WindowHandle::XlibHandle(h) = handle {
@@ -179,6 +215,18 @@ impl XWrap {
         self.replace_property_long(window, self.atoms.NetWMDesktop, xlib::XA_CARDINAL, &indexes);
     }
 
+    /// Sets `_LEFTWM_GAME_MODE` to 1 on a window of a workspace in game mode, which compositor
+    /// rules can unredirect, or removes it.
+    // `XDeleteProperty`: https://tronche.com/gui/x/xlib/window-information/XDeleteProperty.html
+    pub fn set_window_game_mode(&self, window: xlib::Window, game_mode: bool) {
+        if game_mode {
+            let data = [1];
+            self.replace_property_long(window, self.atoms.LeftwmGameMode, xlib::XA_CARDINAL, &data);
+        } else {
+            unsafe { (self.xlib.XDeleteProperty)(self.display, window, self.atoms.LeftwmGameMode) };
+        }
+    }
+
     /// Sets the atom states of a window.
     pub fn set_window_states_atoms(&self, window: xlib::Window, states: &[xlib::Atom]) {
         let data: Vec<c_long> = states.iter().map(|x| *x as c_long).collect();
@@ -0,0 +1,197 @@
+//! Drawing of the tab bars of the `Tabbed` layout.
+use crate::models::{TabBar, WindowHandle};
+use crate::XWrap;
+use std::ffi::CString;
+use std::os::raw::{c_char, c_int, c_uint};
+use std::ptr;
+use x11_dl::xlib;
+
+/// Space in pixels left of the title of a tab.
+const TITLE_PADDING: i32 = 6;
+
+impl XWrap {
+    /// Draw the tab bars, reusing the windows of the ones drawn before and destroying those
+    /// left over.
+    // `XDestroyWindow`: https://tronche.com/gui/x/xlib/window/XDestroyWindow.html
+    // `XMapRaised`: https://tronche.com/gui/x/xlib/window/XMapRaised.html
+    pub fn update_tab_bars(&mut self, bars: Vec<TabBar>) {
+        let missing = bars.len().saturating_sub(self.tab_bars.len());
+        let windows: Vec<xlib::Window> = self
+            .tab_bars
+            .iter()
+            .map(|(window, _)| *window)
+            .chain((0..missing).map(|_| self.create_tab_bar_window()))
+            .collect();
+        for &window in &windows[bars.len()..] {
+            unsafe { (self.xlib.XDestroyWindow)(self.display, window) };
+        }
+        self.tab_bars = windows.into_iter().zip(bars).collect();
+        for (window, bar) in &self.tab_bars {
+            let (w, h) = (bar.area.w().max(1), bar.area.h().max(1));
+            self.move_resize_window(*window, bar.area.x(), bar.area.y(), w as u32, h as u32);
+            unsafe { (self.xlib.XMapRaised)(self.display, *window) };
+            self.draw_tab_bar(*window, bar);
+        }
+    }
+
+    /// Draw a tab bar again after it was exposed, if the window is one.
+    pub fn redraw_tab_bar(&self, window: xlib::Window) {
+        if let Some((window, bar)) = self.tab_bars.iter().find(|(w, _)| *w == window) {
+            self.draw_tab_bar(*window, bar);
+        }
+    }
+
+    /// The window of the tab clicked at `x` in a tab bar, `None` if the window isn't a tab bar.
+    #[must_use]
+    pub fn tab_at(&self, window: xlib::Window, x: i32) -> Option<WindowHandle> {
+        let (_, bar) = self.tab_bars.iter().find(|(w, _)| *w == window)?;
+        bar.tab_at(x)
+    }
+
+    /// An override-redirect window, so it isn't managed or restacked with the managed windows.
+    fn create_tab_bar_window(&self) -> xlib::Window {
+        let mut attrs: xlib::XSetWindowAttributes = unsafe { std::mem::zeroed() };
+        attrs.override_redirect = xlib::True;
+        attrs.background_pixel = self.colors.normal;
+        attrs.event_mask = xlib::ButtonPressMask | xlib::ExposureMask;
+        unsafe {
+            (self.xlib.XCreateWindow)(
+                self.display,
+                self.root,
+                0,
+                0,
+                1,
+                1,
+                0,
+                xlib::CopyFromParent,
+                xlib::InputOutput as c_uint,
+                ptr::null_mut(),
+                xlib::CWOverrideRedirect | xlib::CWBackPixel | xlib::CWEventMask,
+                ptr::addr_of_mut!(attrs),
+            )
+        }
+    }
+
+    /// Load the fonts of the tabs again, as after `min_font_size` changed, and create the
+    /// graphics context they are drawn with if it doesn't exist yet.
+    // `XCreateGC`: https://tronche.com/gui/x/xlib/GC/XCreateGC.html
+    pub(super) fn load_tab_drawing(&mut self) {
+        self.free_tab_font();
+        if self.tab_gc.is_null() {
+            self.tab_gc =
+                unsafe { (self.xlib.XCreateGC)(self.display, self.root, 0, ptr::null_mut()) };
+        }
+        self.tab_font = self.load_tab_font();
+    }
+
+    /// Free the font and the graphics context of the tabs.
+    // `XFreeGC`: https://tronche.com/gui/x/xlib/GC/XFreeGC.html
+    pub(super) fn free_tab_drawing(&mut self) {
+        self.free_tab_font();
+        if !self.tab_gc.is_null() {
+            unsafe { (self.xlib.XFreeGC)(self.display, self.tab_gc) };
+            self.tab_gc = ptr::null_mut();
+        }
+    }
+
+    // `XFreeFontSet`: https://tronche.com/gui/x/xlib/locales/output-methods/XFreeFontSet.html
+    fn free_tab_font(&mut self) {
+        if !self.tab_font.is_null() {
+            unsafe { (self.xlib.XFreeFontSet)(self.display, self.tab_font) };
+            self.tab_font = ptr::null_mut();
+        }
+    }
+
+    /// The fonts of the tabs, the first set of fixed fonts at least `min_font_size` pixels high
+    /// if it is set, or null if none can be loaded. A font set draws the characters of the
+    /// titles any of its fonts has, so titles aren't limited to one charset.
+    // `XCreateFontSet`: https://tronche.com/gui/x/xlib/locales/output-methods/XCreateFontSet.html
+    // `XFreeStringList`: https://tronche.com/gui/x/xlib/ICC/client-to-window-manager/XFreeStringList.html
+    fn load_tab_font(&self) -> xlib::XFontSet {
+        let names = self.min_font_size.map_or_else(Vec::new, |size| {
+            (size..=size.saturating_mul(2))
+                .map(|pixels| format!("-*-fixed-medium-r-*-*-{pixels}-*-*-*-*-*-*-*"))
+                .collect()
+        });
+        names
+            .into_iter()
+            .chain(std::iter::once("fixed".to_owned()))
+            .filter_map(|name| CString::new(name).ok())
+            .map(|name| unsafe {
+                let mut missing: *mut *mut c_char = ptr::null_mut();
+                let mut missing_count: c_int = 0;
+                let mut default: *mut c_char = ptr::null_mut();
+                let font = (self.xlib.XCreateFontSet)(
+                    self.display,
+                    name.as_ptr(),
+                    ptr::addr_of_mut!(missing),
+                    ptr::addr_of_mut!(missing_count),
+                    ptr::addr_of_mut!(default),
+                );
+                if !missing.is_null() {
+                    (self.xlib.XFreeStringList)(missing);
+                }
+                font
+            })
+            .find(|font| !font.is_null())
+            .unwrap_or(ptr::null_mut())
+    }
+
+    /// Fill each tab with the active or the normal border color, and write its title over it.
+    /// Titles too long for their tab are cut off by the next tab.
+    // `XFillRectangle`: https://tronche.com/gui/x/xlib/graphics/filling-areas/XFillRectangle.html
+    // `XExtentsOfFontSet`: https://tronche.com/gui/x/xlib/locales/output-methods/XExtentsOfFontSet.html
+    // `Xutf8DrawString`: https://www.x.org/releases/current/doc/man/man3/Xutf8DrawString.3.xhtml
+    fn draw_tab_bar(&self, window: xlib::Window, bar: &TabBar) {
+        let count = bar.tabs.len() as i32;
+        if count == 0 || self.tab_gc.is_null() {
+            return;
+        }
+        let (width, height) = (bar.area.w(), bar.area.h());
+        let (font, gc) = (self.tab_font, self.tab_gc);
+        unsafe {
+            let baseline = if font.is_null() {
+                height * 3 / 4
+            } else {
+                // The logical extent starts above the baseline, at minus the ascent.
+                let extent = (*(self.xlib.XExtentsOfFontSet)(font)).max_logical_extent;
+                (height - i32::from(extent.height)) / 2 - i32::from(extent.y)
+            };
+            let screen = (self.xlib.XDefaultScreen)(self.display);
+            let text_color = (self.xlib.XWhitePixel)(self.display, screen);
+            for (i, tab) in (0..).zip(&bar.tabs) {
+                let x = width * i / count;
+                let next = width * (i + 1) / count;
+                let background = if tab.active {
+                    self.colors.active
+                } else {
+                    self.colors.normal
+                };
+                (self.xlib.XSetForeground)(self.display, gc, background);
+                (self.xlib.XFillRectangle)(
+                    self.display,
+                    window,
+                    gc,
+                    x,
+                    0,
+                    (next - x) as c_uint,
+                    height as c_uint,
+                );
+                if font.is_null() {
+                    continue;
+                }
+                (self.xlib.XSetForeground)(self.display, gc, text_color);
+                (self.xlib.Xutf8DrawString)(
+                    self.display,
+                    window,
+                    font,
+                    gc,
+                    x + TITLE_PADDING,
+                    baseline,
+                    tab.title.as_ptr().cast::<c_char>(),
+                    tab.title.len() as c_int,
+                );
+            }
+        }
+    }
+}
@@ -1,8 +1,11 @@
 //! Xlib calls related to a window.
-use super::{Window, WindowHandle, ICONIC_STATE, NORMAL_STATE, ROOT_EVENT_MASK};
+use super::{Slide, Window, WindowHandle, ICONIC_STATE, NORMAL_STATE, ROOT_EVENT_MASK};
+use crate::display_event::Timestamp;
 use crate::models::{WindowChange, WindowType, Xyhw, XyhwChange};
 use crate::{DisplayEvent, XWrap};
-use std::os::raw::{c_long, c_ulong};
+use std::os::raw::{c_long, c_uint, c_ulong};
+use std::ptr;
+use std::time::{Duration, Instant};
 use x11_dl::xlib;
 
 impl XWrap {
@@ -72,6 +75,7 @@ impl XWrap {
             hint.update(&mut requested);
         }
         w.requested = Some(requested);
+        w.size_increments = self.get_size_increments(window);
         w.can_resize = can_resize;
         if let Some(hint) = wm_hint {
             w.never_focus = hint.flags & xlib::InputHint != 0 && hint.input == 0;
@@ -114,18 +118,11 @@ impl XWrap {
         if r#type == WindowType::Dock || r#type == WindowType::Desktop {
             if let Some(dock_area) = self.get_window_strut_array(handle) {
                 let dems = self.get_screens_area_dimensions();
-                let screen = self
-                    .get_screens()
-                    .iter()
-                    .find(|s| s.contains_dock_area(dock_area, dems))?
-                    .clone();
-
-                if let Some(xyhw) = dock_area.as_xyhw(dems.0, dems.1, &screen) {
-                    let mut change = WindowChange::new(h);
-                    change.strut = Some(xyhw.into());
-                    change.r#type = Some(r#type);
-                    return Some(DisplayEvent::WindowChange(change));
-                }
+                let xyhw = dock_area.resolve(&self.get_screens(), dems)?;
+                let mut change = WindowChange::new(h);
+                change.strut = Some(xyhw.into());
+                change.r#type = Some(r#type);
+                return Some(DisplayEvent::WindowChange(change));
             } else if let Ok(geo) = self.get_window_geometry(handle) {
                 let mut xyhw = Xyhw::default();
                 geo.update(&mut xyhw);
@@ -184,6 +181,8 @@ impl XWrap {
                 let unlock =
                     xlib::CWX | xlib::CWY | xlib::CWWidth | xlib::CWHeight | xlib::CWBorderWidth;
                 self.set_window_config(handle, changes, u32::from(unlock));
+                let focused = self.focused == Some(handle);
+                self.set_window_border_color(handle, self.border_color(window, focused));
                 self.configure_window(window);
             }
             let state = match self.get_wm_state(handle) {
@@ -199,6 +198,20 @@ impl XWrap {
         }
     }
 
+    /// The border color of a window: the focused one, else the urgent one while it asks for
+    /// attention, else the floating or the default one.
+    pub fn border_color(&self, window: &Window, focused: bool) -> c_ulong {
+        if focused {
+            self.colors.active
+        } else if window.urgent {
+            self.colors.urgent
+        } else if window.floating() {
+            self.colors.floating
+        } else {
+            self.colors.normal
+        }
+    }
+
     /// Maps and unmaps a window depending on it is visible.
     pub fn toggle_window_visibility(&self, window: xlib::Window, visible: bool) {
         // We don't want to receive this map or unmap event.
@@ -227,9 +240,22 @@ impl XWrap {
         self.change_window_attributes(self.root, xlib::CWEventMask, attrs);
     }
 
-    /// Makes a window take focus.
+    /// Makes a window take focus, at the time of the event focusing it. Requests older than the
+    /// last focus change are dropped, as X would ignore the `XSetInputFocus` of them anyway.
     // `XSetInputFocus`: https://tronche.com/gui/x/xlib/input/XSetInputFocus.html
-    pub fn window_take_focus(&mut self, window: &Window, previous: Option<&Window>) {
+    pub fn window_take_focus(
+        &mut self,
+        window: &Window,
+        previous: Option<&Window>,
+        time: Timestamp,
+    ) {
+        if is_older(time, self.last_focus_time) {
+            log::debug!("Dropping a focus request older than the last focus change");
+            return;
+        }
+        if time != xlib::CurrentTime {
+            self.last_focus_time = time;
+        }
         if let WindowHandle::XlibHandle(handle) = window.handle {
             // Play a click when in ClickToFocus.
 
@@ -241,12 +267,10 @@ impl XWrap {
             // Update previous window.
             if let Some(previous) = previous {
                 if let WindowHandle::XlibHandle(previous_handle) = previous.handle {
-                    let color = if previous.floating() {
-                        self.colors.floating
-                    } else {
-                        self.colors.normal
-                    };
-                    self.set_window_border_color(previous_handle, color);
+                    self.set_window_border_color(
+                        previous_handle,
+                        self.border_color(previous, false),
+                    );
                     // Open up button1 clicking on the previously focused window.
                     if self.focus_behaviour.is_clickto() {
                         self.grab_mouse_clicks(previous_handle, false);
@@ -256,6 +280,7 @@ impl XWrap {
             self.grab_mouse_clicks(handle, true);
 
             if !window.never_focus {
+                self.focused = Some(handle);
                 self.set_window_border_color(handle, self.colors.active);
                 // Mark this window as the `_NET_ACTIVE_WINDOW`
                 unsafe {
@@ -263,7 +288,7 @@ impl XWrap {
                         self.display,
                         handle,
                         xlib::RevertToPointerRoot,
-                        xlib::CurrentTime,
+                        time,
                     );
                     let list = vec![handle as c_long];
                     self.replace_property_long(
@@ -279,7 +304,7 @@ impl XWrap {
             // See: https://github.com/leftwm/leftwm/pull/563
             if !self.focus_behaviour.is_sloppy() {
                 // Tell the window to take focus
-                self.send_xevent_atom(handle, self.atoms.WMTakeFocus);
+                self.send_xevent_atom_at(handle, self.atoms.WMTakeFocus, time);
             }
             self.sync();
         }
@@ -287,7 +312,8 @@ impl XWrap {
 
     /// Unfocuses all windows.
     // `XSetInputFocus`: https://tronche.com/gui/x/xlib/input/XSetInputFocus.html
-    pub fn unfocus(&self, handle: Option<WindowHandle>, floating: bool) {
+    pub fn unfocus(&mut self, handle: Option<WindowHandle>, floating: bool) {
+        self.focused = None;
         if let Some(WindowHandle::XlibHandle(handle)) = handle {
             let color = if floating {
                 self.colors.floating
@@ -326,7 +352,7 @@ impl XWrap {
             configure_event.y = window.y();
             configure_event.width = window.width();
             configure_event.height = window.height();
-            configure_event.border_width = window.border;
+            configure_event.border_width = window.border();
             configure_event.above = 0;
             configure_event.override_redirect = 0;
             self.send_xevent(
@@ -373,6 +399,145 @@ impl XWrap {
         }
     }
 
+    // `XMoveWindow`: https://tronche.com/gui/x/xlib/window/XMoveWindow.html
+    pub fn move_window(&self, window: xlib::Window, x: i32, y: i32) {
+        unsafe {
+            (self.xlib.XMoveWindow)(self.display, window, x, y);
+        }
+    }
+
+    /// Start sliding windows `distance` pixels to the left, the entering ones from the right into
+    /// their place and the leaving ones out of it, ending a slide going on. The leaving windows,
+    /// already unmapped, are shown for the slide and hidden again afterwards. The windows are
+    /// moved by `step_slide`.
+    pub fn slide_windows(
+        &mut self,
+        entering: &[WindowHandle],
+        leaving: &[WindowHandle],
+        distance: i32,
+        duration: Duration,
+    ) {
+        if let Some(slide) = self.slide.as_mut() {
+            slide.duration = Duration::ZERO;
+            self.step_slide();
+        }
+        let positions = |handles: &[WindowHandle]| -> Vec<(xlib::Window, i32, i32)> {
+            handles
+                .iter()
+                .filter_map(|handle| match handle {
+                    WindowHandle::XlibHandle(window) => Some(*window),
+                    WindowHandle::MockHandle(_) => None,
+                })
+                .filter_map(|window| {
+                    let attrs = self.get_window_attrs(window).ok()?;
+                    Some((window, attrs.x, attrs.y))
+                })
+                .collect()
+        };
+        let entering = positions(entering);
+        let leaving = positions(leaving);
+        for &(window, _, _) in &leaving {
+            self.toggle_window_visibility(window, true);
+        }
+        self.slide = Some(Slide {
+            entering,
+            leaving,
+            distance,
+            start: Instant::now(),
+            duration,
+        });
+        self.step_slide();
+    }
+
+    /// Move the sliding windows to where they are by now, returning how long until the next
+    /// step, `None` once the slide is over.
+    pub fn step_slide(&mut self) -> Option<Duration> {
+        const FRAME: Duration = Duration::from_millis(16);
+        let slide = self.slide.as_ref()?;
+        let left = slide.duration.saturating_sub(slide.start.elapsed());
+        // How far the entering windows are still off their place.
+        let offset = if slide.duration.is_zero() {
+            0
+        } else {
+            (f64::from(slide.distance) * left.as_secs_f64() / slide.duration.as_secs_f64()) as i32
+        };
+        for &(window, x, y) in &slide.entering {
+            self.move_window(window, x + offset, y);
+        }
+        for &(window, x, y) in &slide.leaving {
+            self.move_window(window, x + offset - slide.distance, y);
+        }
+        if !left.is_zero() {
+            self.flush();
+            return Some(FRAME.min(left));
+        }
+        for &(window, x, y) in &slide.leaving {
+            self.toggle_window_visibility(window, false);
+            self.move_window(window, x, y);
+        }
+        self.flush();
+        self.slide = None;
+        None
+    }
+
+    /// Outline an area with a window along each of its edges, or hide the outline.
+    // `XCreateWindow`: https://tronche.com/gui/x/xlib/window/XCreateWindow.html
+    // `XMapRaised`: https://tronche.com/gui/x/xlib/window/XMapRaised.html
+    pub fn highlight_area(&mut self, area: Option<Xyhw>) {
+        const THICKNESS: i32 = 4;
+        let area = match area {
+            Some(area) if area.w() > 0 && area.h() > 0 => area,
+            _ => {
+                for &edge in &self.highlight {
+                    unsafe { (self.xlib.XUnmapWindow)(self.display, edge) };
+                }
+                return;
+            }
+        };
+        if self.highlight.is_empty() {
+            self.highlight = (0..4).map(|_| self.create_highlight_edge()).collect();
+        }
+        let (x, y, w, h) = (area.x(), area.y(), area.w(), area.h());
+        let thickness = THICKNESS.min(w).min(h);
+        let edges = [
+            (x, y, w, thickness),
+            (x, y + h - thickness, w, thickness),
+            (x, y, thickness, h),
+            (x + w - thickness, y, thickness, h),
+        ];
+        for (&edge, (x, y, w, h)) in self.highlight.iter().zip(edges) {
+            self.move_resize_window(edge, x, y, w as u32, h as u32);
+            unsafe {
+                (self.xlib.XSetWindowBackground)(self.display, edge, self.colors.active);
+                (self.xlib.XClearWindow)(self.display, edge);
+                (self.xlib.XMapRaised)(self.display, edge);
+            }
+        }
+    }
+
+    /// An override-redirect window, so it isn't managed or restacked with the managed windows.
+    fn create_highlight_edge(&self) -> xlib::Window {
+        let mut attrs: xlib::XSetWindowAttributes = unsafe { std::mem::zeroed() };
+        attrs.override_redirect = xlib::True;
+        attrs.background_pixel = self.colors.active;
+        unsafe {
+            (self.xlib.XCreateWindow)(
+                self.display,
+                self.root,
+                0,
+                0,
+                1,
+                1,
+                0,
+                xlib::CopyFromParent,
+                xlib::InputOutput as c_uint,
+                ptr::null_mut(),
+                xlib::CWOverrideRedirect | xlib::CWBackPixel,
+                ptr::addr_of_mut!(attrs),
+            )
+        }
+    }
+
     /// Raise a window.
     // `XRaiseWindow`: https://tronche.com/gui/x/xlib/window/XRaiseWindow.html
     pub fn move_to_top(&self, handle: &WindowHandle) {
@@ -404,6 +569,15 @@ impl XWrap {
         }
     }
 
+    /// Ask a window to close if it supports `WM_DELETE_WINDOW`, leaving it alone otherwise.
+    pub fn delete_window(&self, h: &WindowHandle) {
+        if let WindowHandle::XlibHandle(handle) = h {
+            if !self.send_xevent_atom(*handle, self.atoms.WMDelete) {
+                log::debug!("Window {handle} doesn't support WM_DELETE_WINDOW, not closing it");
+            }
+        }
+    }
+
     /// Forcibly unmap a window.
     pub fn force_unmapped(&mut self, window: xlib::Window) {
         let managed = self.managed_windows.contains(&window);
@@ -425,3 +599,13 @@ impl XWrap {
         self.subscribe_to_event(window, mask);
     }
 }
+
+/// Whether the server time `time` is before `than`. Server times are 32 bits of milliseconds
+/// which wrap around, so as X does the later one is the one less than half the range ahead.
+fn is_older(time: Timestamp, than: Timestamp) -> bool {
+    if time == xlib::CurrentTime || than == xlib::CurrentTime {
+        return false;
+    }
+    let behind = (than as u32).wrapping_sub(time as u32);
+    behind != 0 && behind < 1 << 31
+}
@@ -1,32 +1,43 @@
+use crate::models::dto::FullState;
+use crate::utils::child_process::session_env;
+use crate::utils::command_socket::Event;
 use crate::{child_process::Nanny, config::Config};
-use crate::{CommandPipe, DisplayServer, Manager, Mode, StateSocket, Window};
+use crate::{
+    CommandPipe, CommandSocket, DisplayServer, I3Socket, Manager, Mode, StateSocket, Window,
+};
 use std::path::{Path, PathBuf};
+use std::process::Command;
 use std::sync::{atomic::Ordering, Once};
+use tokio::signal::unix::{signal, SignalKind};
+use tokio::time::{sleep, sleep_until, Duration, Instant};
 
 impl<C: Config, SERVER: DisplayServer> Manager<C, SERVER> {
+    /// Runs until a reload or exit is requested. Returns `true` if the worker should exit rather
+    /// than be restarted.
+    ///
     /// # Panics
     /// This function panics if it can't create or write to the command file.
-    pub async fn event_loop(mut self) {
-        let socket_file = place_runtime_file("current_state.sock")
-            .expect("ERROR: couldn't create current_state.sock");
-        let mut state_socket = StateSocket::default();
-        state_socket
-            .listen(socket_file)
-            .await
-            .expect("ERROR: couldn't connect to current_state.sock");
-
-        let file_name = CommandPipe::pipe_name();
-        let pipe_file = place_runtime_file(&file_name)
-            .unwrap_or_else(|_| panic!("ERROR: couldn't create {}", file_name.display()));
-        let mut command_pipe = CommandPipe::new(pipe_file)
-            .await
-            .unwrap_or_else(|_| panic!("ERROR: couldn't connect to {}", file_name.display()));
+    pub async fn event_loop(mut self) -> bool {
+        let mut state_socket = listen_on_state_socket().await;
+        let mut command_pipe = open_command_pipe().await;
+        let mut command_socket = listen_on_command_socket().await;
+        let mut i3_socket = listen_on_i3_socket(&command_socket).await;
+
+        // `SIGUSR1` applies the changes made to the config without a restart.
+        let mut reload_signal =
+            signal(SignalKind::user_defined1()).expect("ERROR: couldn't listen for SIGUSR1");
 
         //start the current theme
         let after_first_loop: Once = Once::new();
+        let restore_state: Once = Once::new();
 
         //main event loop
-        let mut event_buffer = vec![];
+        // The first events adopt the windows already on screen.
+        let mut event_buffer = self.display_server.get_next_events();
+        // What the subscribers of the command socket last saw.
+        let mut published: Option<FullState> = None;
+        // When the next step of an animation, such as the slide of a tag switch, is due.
+        let mut next_frame: Option<Instant> = None;
         loop {
             if self.state.mode == Mode::Normal {
                 state_socket.write_manager_state(&self.state).await.ok();
@@ -49,9 +60,22 @@ impl<C: Config, SERVER: DisplayServer> Manager<C, SERVER> {
                     }
                     continue;
                 }
+                () = sleep_until(next_frame.unwrap_or_else(Instant::now)), if event_buffer.is_empty()
+                    && next_frame.is_some() => {
+                    next_frame = self.display_server.animate().map(|step| Instant::now() + step);
+                    continue;
+                }
                 Some(cmd) = command_pipe.read_command(), if event_buffer.is_empty() => {
                     needs_update = self.command_handler(&cmd) || needs_update;
                 }
+                Some(pending) = command_socket.read_request(), if event_buffer.is_empty() => {
+                    let (response, changed) = self.request_handler(&pending.request);
+                    pending.reply(response);
+                    needs_update = changed || needs_update;
+                }
+                Some(()) = reload_signal.recv(), if event_buffer.is_empty() => {
+                    needs_update = self.reload_config_file() || needs_update;
+                }
                 else => {
                     event_buffer
                         .drain(..)
@@ -59,26 +83,14 @@ impl<C: Config, SERVER: DisplayServer> Manager<C, SERVER> {
                 }
             }
 
+            // Restore the state saved by a reload before the adopted windows are first drawn, so
+            // windows on hidden tags are never mapped and nothing is moved around twice.
+            restore_state.call_once(|| self.config.load_state(&mut self.state));
+
             // If we need to update the displayed state.
             if needs_update {
                 self.update_windows();
-
-                match self.state.mode {
-                    // When (resizing / moving) only deal with the single window.
-                    Mode::ResizingWindow(h) | Mode::MovingWindow(h) => {
-                        let windows: Vec<&Window> = self
-                            .state
-                            .windows
-                            .iter()
-                            .filter(|w| w.handle == h)
-                            .collect();
-                        self.display_server.update_windows(windows);
-                    }
-                    _ => {
-                        let windows: Vec<&Window> = self.state.windows.iter().collect();
-                        self.display_server.update_windows(windows);
-                    }
-                }
+                self.display_windows();
             }
 
             //preform any actions requested by the handler
@@ -90,35 +102,161 @@ impl<C: Config, SERVER: DisplayServer> Manager<C, SERVER> {
                 }
             }
 
-            //after the very first loop run the 'up' scripts (global and theme). we need the unix
-            //socket to already exist.
-            after_first_loop.call_once(|| {
-                match Nanny::run_global_up_script() {
-                    Ok(child) => {
-                        child.map(|child| self.children.insert(child));
-                    }
-                    Err(err) => log::error!("Global up script faild: {}", err),
-                }
-                match Nanny::boot_current_theme() {
-                    Ok(child) => {
-                        child.map(|child| self.children.insert(child));
-                    }
-                    Err(err) => log::error!("Theme loading failed: {}", err),
+            if next_frame.is_none() {
+                next_frame = self
+                    .display_server
+                    .animate()
+                    .map(|step| Instant::now() + step);
+            }
+
+            self.run_focus_hooks();
+
+            if command_socket.has_subscribers() {
+                let state = FullState::from(&self.state);
+                if let Some(previous) = &published {
+                    command_socket.publish(&Event::between(previous, &state));
                 }
+                published = Some(state);
+            } else {
+                published = None;
+            }
 
-                self.config.load_state(&mut self.state);
-            });
+            //after the very first loop run the 'up' scripts (global and theme). we need the unix
+            //socket to already exist.
+            after_first_loop.call_once(|| self.run_up_scripts());
 
             if self.reap_requested.swap(false, Ordering::SeqCst) {
                 self.children.reap();
             }
 
-            if self.reload_requested {
+            if self.reload_requested || self.exit_requested {
                 state_socket.shutdown().await;
+                i3_socket.shutdown().await;
+                command_socket.shutdown().await;
                 break;
             }
         }
+
+        if self.exit_requested {
+            self.run_exit_scripts();
+        }
+        self.exit_requested
     }
+
+    /// Send the windows to the display server, only the one moved or resized while that goes on.
+    fn display_windows(&mut self) {
+        match self.state.mode {
+            // When (resizing / moving) only deal with the single window.
+            Mode::ResizingWindow(h) | Mode::MovingWindow(h) => {
+                let windows: Vec<&Window> = self
+                    .state
+                    .windows
+                    .iter()
+                    .filter(|w| w.handle == h)
+                    .collect();
+                self.display_server.update_windows(windows);
+            }
+            _ => {
+                let windows = self.state.windows_to_display();
+                self.display_server.update_windows(windows);
+            }
+        }
+    }
+
+    /// Run the global and theme 'up' scripts, and the reload commands after a restart.
+    fn run_up_scripts(&mut self) {
+        match Nanny::run_global_up_script() {
+            Ok(child) => {
+                child.map(|child| self.children.insert(child));
+            }
+            Err(err) => log::error!("Global up script faild: {}", err),
+        }
+        match Nanny::boot_current_theme() {
+            Ok(child) => {
+                child.map(|child| self.children.insert(child));
+            }
+            Err(err) => log::error!("Theme loading failed: {}", err),
+        }
+        if self.restarted {
+            self.run_reload_cmds();
+        }
+    }
+
+    /// Run the exit hook, then the theme and global 'down' scripts, waiting for each of them so
+    /// they are done before the session goes away.
+    fn run_exit_scripts(&self) {
+        if let Some(cmd) = self.config.on_exit_cmd() {
+            let status = Command::new("sh")
+                .arg("-c")
+                .arg(&cmd)
+                .envs(session_env())
+                .status();
+            if let Err(err) = status {
+                log::error!("Exit hook failed: {}", err);
+            }
+        }
+        for script in [
+            Nanny::shutdown_current_theme(),
+            Nanny::run_global_down_script(),
+        ] {
+            match script {
+                Ok(Some(mut child)) => {
+                    child.wait().ok();
+                }
+                Ok(None) => {}
+                Err(err) => log::error!("Down script failed: {}", err),
+            }
+        }
+    }
+}
+
+async fn listen_on_state_socket() -> StateSocket {
+    let socket_file = place_runtime_file("current_state.sock")
+        .expect("ERROR: couldn't create current_state.sock");
+    let mut state_socket = StateSocket::default();
+    state_socket
+        .listen(socket_file)
+        .await
+        .expect("ERROR: couldn't connect to current_state.sock");
+    state_socket
+}
+
+async fn open_command_pipe() -> CommandPipe {
+    let file_name = CommandPipe::pipe_name();
+    let pipe_file = place_runtime_file(&file_name)
+        .unwrap_or_else(|_| panic!("ERROR: couldn't create {}", file_name.display()));
+    CommandPipe::new(pipe_file)
+        .await
+        .unwrap_or_else(|_| panic!("ERROR: couldn't connect to {}", file_name.display()))
+}
+
+/// Listen on the command socket, and serve it on the session bus too with the `dbus` feature.
+async fn listen_on_command_socket() -> CommandSocket {
+    let file_name = CommandSocket::socket_name();
+    let command_socket_file = place_runtime_file(&file_name)
+        .unwrap_or_else(|_| panic!("ERROR: couldn't create {}", file_name.display()));
+    let command_socket = CommandSocket::listen(command_socket_file)
+        .await
+        .unwrap_or_else(|_| panic!("ERROR: couldn't listen on {}", file_name.display()));
+    #[cfg(feature = "dbus")]
+    if let Err(err) = crate::utils::dbus::serve(&command_socket).await {
+        log::error!(
+            "Couldn't serve {} on the session bus: {}",
+            crate::utils::dbus::NAME,
+            err
+        );
+    }
+    command_socket
+}
+
+/// Listen on the i3 socket, which passes what it is asked on to the command socket.
+async fn listen_on_i3_socket(command_socket: &CommandSocket) -> I3Socket {
+    let file_name = I3Socket::socket_name();
+    let i3_socket_file = I3Socket::socket_file()
+        .unwrap_or_else(|_| panic!("ERROR: couldn't create {}", file_name.display()));
+    I3Socket::listen(i3_socket_file, command_socket.client())
+        .await
+        .unwrap_or_else(|_| panic!("ERROR: couldn't listen on {}", file_name.display()))
 }
 
 fn place_runtime_file<P>(path: P) -> std::io::Result<PathBuf>
@@ -129,6 +267,5 @@ where
 }
 
 async fn timeout(mills: u64) {
-    use tokio::time::{sleep, Duration};
     sleep(Duration::from_millis(mills)).await;
 }
@@ -10,12 +10,17 @@ use crate::child_process::Children;
 use crate::display_action::DisplayAction;
 use crate::display_servers::DisplayServer;
 use crate::layouts::Layout;
-use crate::models::{TagId, WindowState};
+use crate::models::dto::{WindowAt, WindowList};
+use crate::models::{BspTree, Direction, Size, TagId, WindowState, Zoom};
 use crate::state::State;
+use crate::utils::command_pipe;
 use crate::utils::helpers::relative_find;
 use crate::utils::{child_process::exec_shell, helpers};
 use crate::{config::Config, models::FocusBehaviour};
 
+/// Largest count a `CountPrefix` builds up, so a stray key can't hang the manager.
+const MAX_COUNT: usize = 100;
+
 impl<C: Config, SERVER: DisplayServer> Manager<C, SERVER> {
     /* Please also update src/bin/leftwm-check if any of the following apply after your update:
      * - a command now requires a value
@@ -24,7 +29,30 @@ impl<C: Config, SERVER: DisplayServer> Manager<C, SERVER> {
      *  */
     /// Processes a command and invokes the associated function.
     pub fn command_handler(&mut self, command: &Command) -> bool {
-        process_internal(self, command).unwrap_or(false)
+        self.checked_command_handler(command).unwrap_or(false)
+    }
+
+    /// Like `command_handler`, but `None` when the command couldn't be carried out, say for want
+    /// of a focused window or for an unknown tag.
+    pub fn checked_command_handler(&mut self, command: &Command) -> Option<bool> {
+        if let Command::CountPrefix(digit) = command {
+            let count = self.state.pending_count.unwrap_or(0) * 10 + digit;
+            self.state.pending_count = Some(count.min(MAX_COUNT));
+            return Some(false);
+        }
+        // Any other command cancels a pending `CloseAllOnTag`.
+        if command != &Command::CloseAllOnTag {
+            self.state.close_all_pending = None;
+        }
+        let count = self.state.pending_count.take().unwrap_or(1).max(1);
+        let count = if command.is_repeatable() { count } else { 1 };
+        let mut result = None;
+        for _ in 0..count {
+            if let Some(changed) = process_internal(self, command) {
+                result = Some(changed || result == Some(true));
+            }
+        }
+        result
     }
 }
 
@@ -33,7 +61,7 @@ macro_rules! move_focus_common_vars {
         let handle = $state.focus_manager.window(&$state.windows)?.handle;
         let tag_id = $state.focus_manager.tag(0)?;
         let tag = $state.tags.get(tag_id)?;
-        let (tags, layout) = (vec![tag_id], Some(tag.layout));
+        let (tags, layout) = (vec![tag_id], Some(tag.layout.clone()));
 
         let for_active_workspace =
             |x: &Window| -> bool { helpers::intersect(&tags, &x.tags) && !x.is_unmanaged() };
@@ -55,79 +83,140 @@ fn process_internal<C: Config, SERVER: DisplayServer>(
 
         Command::ToggleFullScreen => toggle_state(state, WindowState::Fullscreen),
         Command::ToggleSticky => toggle_state(state, WindowState::Sticky),
+        Command::ToggleZoom => toggle_zoom(state),
+        Command::ToggleDock => Some(toggle_dock(manager)),
 
         Command::SendWindowToTag { window, tag } => move_to_tag(*window, *tag, manager),
+        Command::ToggleWindowTag(tag) => {
+            toggle_window_tag(state.focused_workspace_tag(*tag)?, manager)
+        }
+        Command::ToggleWindowLabelledTag(label) => {
+            toggle_window_tag(state.find_tag(label)?, manager)
+        }
+        Command::ToggleTagView(tag) => state.toggle_tag_view(state.focused_workspace_tag(*tag)?),
+        Command::ToggleLabelledTagView(label) => state.toggle_tag_view(state.find_tag(label)?),
         Command::MoveWindowToNextWorkspace => move_window_to_workspace_change(manager, 1),
         Command::MoveWindowToPreviousWorkspace => move_window_to_workspace_change(manager, -1),
         Command::MoveWindowUp => move_focus_common_vars!(move_window_change(state, -1)),
         Command::MoveWindowDown => move_focus_common_vars!(move_window_change(state, 1)),
         Command::MoveWindowTop { swap } => move_focus_common_vars!(move_window_top(state, *swap)),
+        Command::SwapWindowWithMaster => move_focus_common_vars!(swap_window_with_master(state)),
+        Command::RotateStackForward => move_focus_common_vars!(rotate_stack(state, 1)),
+        Command::RotateStackBackward => move_focus_common_vars!(rotate_stack(state, -1)),
 
         Command::GoToTag { tag, swap } => goto_tag(state, *tag, *swap),
         Command::ReturnToLastTag => return_to_last_tag(state),
+        Command::FocusLastTag => focus_last_tag(state),
+        Command::TagHistoryBack => tag_history_step(state, false),
+        Command::TagHistoryForward => tag_history_step(state, true),
+        Command::ToggleOverview => state.toggle_overview(),
+        Command::PickOverviewWindow(handle) => pick_overview_window(manager, *handle),
+        Command::GoToLabelledTag(label) => state.goto_tag_handler(state.find_tag(label)?),
 
         Command::CloseWindow => close_window(state),
         Command::SwapScreens => swap_tags(state),
         Command::MoveWindowToLastWorkspace => move_to_last_workspace(state),
         Command::NextLayout => next_layout(state),
         Command::PreviousLayout => previous_layout(state),
+        Command::ToggleLastLayout => toggle_last_layout(state),
 
-        Command::SetLayout(layout) => set_layout(*layout, state),
+        Command::SetLayout(layout) => set_layout(layout.clone(), state),
 
         Command::FloatingToTile => floating_to_tile(state),
         Command::TileToFloating => tile_to_floating(state),
         Command::ToggleFloating => toggle_floating(state),
 
-        Command::FocusNextTag => focus_tag_change(state, 1),
-        Command::FocusPreviousTag => focus_tag_change(state, -1),
+        Command::FocusNextTag { wrap, skip_empty } => {
+            focus_tag_change(state, 1, *wrap, *skip_empty)
+        }
+        Command::FocusPreviousTag { wrap, skip_empty } => {
+            focus_tag_change(state, -1, *wrap, *skip_empty)
+        }
         Command::FocusWindow(param) => focus_window(state, param),
+        Command::FocusUrgent => focus_urgent(state),
         Command::FocusWindowUp => move_focus_common_vars!(focus_window_change(state, -1)),
         Command::FocusWindowDown => move_focus_common_vars!(focus_window_change(state, 1)),
         Command::FocusWindowTop { swap } => focus_window_top(state, *swap),
+        Command::FocusWindowInDirection(direction) => focus_window_in_direction(state, *direction),
+        Command::MoveWindowInDirection(direction) => move_window_in_direction(manager, *direction),
         Command::FocusWorkspaceNext => focus_workspace_change(state, 1),
         Command::FocusWorkspacePrevious => focus_workspace_change(state, -1),
 
-        Command::MouseMoveWindow => None,
-
-        Command::SoftReload => {
-            // Make sure the currently focused window is saved for the tag.
-            if let Some((handle, tag)) = state
-                .focus_manager
-                .window(&state.windows)
-                .map(|w| (w.handle, w.tags[0]))
-            {
-                let old_handle = state
-                    .focus_manager
-                    .tags_last_window
-                    .entry(tag)
-                    .or_insert(handle);
-                *old_handle = handle;
-            }
-            manager.config.save_state(&manager.state);
-            manager.hard_reload();
-            None
-        }
+        Command::MouseMoveWindow => Some(false),
+
+        Command::SoftReload => Some(soft_reload(manager)),
         Command::HardReload => {
             manager.hard_reload();
-            None
+            Some(false)
+        }
+        Command::Exit => {
+            manager.exit();
+            Some(false)
         }
 
         Command::RotateTag => rotate_tag(state),
 
-        Command::IncreaseMainWidth(delta) => change_main_width(state, *delta, 1),
-        Command::DecreaseMainWidth(delta) => change_main_width(state, *delta, -1),
+        Command::IncreaseMainWidth(delta) => change_main_width(state, *delta),
+        Command::DecreaseMainWidth(delta) => change_main_width(state, -*delta),
+        Command::IncreaseStackSplit(delta) => change_stack_split(state, *delta),
+        Command::DecreaseStackSplit(delta) => change_stack_split(state, -*delta),
+        Command::IncreaseMainCount => change_main_count(state, 1),
+        Command::DecreaseMainCount => change_main_count(state, -1),
+        Command::IncreaseWindowWeight(delta) => change_window_weight(state, *delta),
+        Command::DecreaseWindowWeight(delta) => change_window_weight(state, -*delta),
+        Command::RotateSubtree => change_bsp_subtree(state, BspTree::rotate),
+        Command::FlipSubtree => change_bsp_subtree(state, BspTree::flip),
         Command::SetMarginMultiplier(multiplier) => set_margin_multiplier(state, *multiplier),
-        Command::SendWorkspaceToTag(ws_index, tag_index) => {
-            Some(send_workspace_to_tag(state, *ws_index, *tag_index))
-        }
+        Command::IncreaseGaps(step) => change_gaps(state, |ws| ws.grow_gaps(*step)),
+        Command::DecreaseGaps(step) => change_gaps(state, |ws| ws.shrink_gaps(*step)),
+        Command::ToggleGaps => change_gaps(state, Workspace::toggle_gaps),
+        Command::ToggleGameMode => change_gaps(state, Workspace::toggle_game_mode),
+        Command::SetMaxWindowWidth(size) => set_max_window_width(state, *size),
+        Command::SendWorkspaceToTag(ws, tag) => Some(send_workspace_to_tag(state, *ws, *tag)),
+        Command::MoveTagToWorkspace(index) => move_tag_to_workspace(state, *index),
         Command::CloseAllOtherWindows => close_all_other_windows(state),
-        Command::Other(cmd) => Some(C::command_handler(cmd, manager)),
+        Command::CloseAllOnTag => close_all_on_tag(state),
+        // Handled by `command_handler`, before it gets here.
+        Command::CountPrefix(_) => None,
+        Command::WindowAt(x, y) => Some(window_at(state, *x, *y)),
+        Command::ListWindows => Some(list_windows(state)),
+        Command::LoadProfile(name) => Some(manager.load_profile(name)),
+        Command::AddTag(label) => Some(add_tag(state, label)),
+        Command::RemoveTag => state.remove_tag(state.focus_manager.tag(0)?),
+        Command::RenameTag { tag, label } => rename_tag(state, tag.as_deref(), label),
+        Command::Other(cmd) => C::command_handler(cmd, manager),
     }
 }
 
+fn toggle_dock<C: Config, SERVER: DisplayServer>(manager: &mut Manager<C, SERVER>) -> bool {
+    manager.state.docks_hidden = !manager.state.docks_hidden;
+    manager.update_workspace_avoid_list();
+    true
+}
+
+/// Save the state and restart, so windows keep their tags and layouts across the reload.
+fn soft_reload<C: Config, SERVER: DisplayServer>(manager: &mut Manager<C, SERVER>) -> bool {
+    let state = &mut manager.state;
+    // Make sure the currently focused window is saved for the tag.
+    if let Some((handle, tag)) = state
+        .focus_manager
+        .window(&state.windows)
+        .map(|w| (w.handle, w.tags[0]))
+    {
+        let old_handle = state
+            .focus_manager
+            .tags_last_window
+            .entry(tag)
+            .or_insert(handle);
+        *old_handle = handle;
+    }
+    manager.config.save_state(&manager.state);
+    manager.hard_reload();
+    false
+}
+
 fn execute(children: &mut Children, shell_command: &str) -> Option<bool> {
-    let _ = exec_shell(shell_command, children);
-    None
+    exec_shell(shell_command, children).map(|_| false)
 }
 
 fn toggle_scratchpad<C: Config, SERVER: DisplayServer>(
@@ -208,7 +297,7 @@ fn toggle_scratchpad<C: Config, SERVER: DisplayServer>(
         let name = scratchpad.name.clone();
         let pid = exec_shell(&scratchpad.value, &mut manager.children);
         manager.state.active_scratchpads.insert(name, pid);
-        return None;
+        return Some(false);
     }
     log::warn!("unable to find NSP tag");
     None
@@ -232,6 +321,7 @@ fn move_to_tag<C: Config, SERVER: DisplayServer>(
     tag_num: TagId,
     manager: &mut Manager<C, SERVER>,
 ) -> Option<bool> {
+    let tag_num = manager.state.focused_workspace_tag(tag_num)?;
     let tag = manager.state.tags.get(tag_num)?.clone();
 
     // In order to apply the correct margin multiplier we want to copy this value
@@ -276,6 +366,55 @@ fn move_to_tag<C: Config, SERVER: DisplayServer>(
     Some(true)
 }
 
+fn toggle_window_tag<C: Config, SERVER: DisplayServer>(
+    tag: TagId,
+    manager: &mut Manager<C, SERVER>,
+) -> Option<bool> {
+    if tag < 1 || tag > manager.state.tags.len_normal() {
+        return None;
+    }
+    let handle = manager
+        .state
+        .focus_manager
+        .window(&manager.state.windows)?
+        .handle;
+    let new_handle = manager.get_next_or_previous(&handle);
+
+    let window = manager
+        .state
+        .windows
+        .iter_mut()
+        .find(|w| w.handle == handle)?;
+    if !window.has_tag(&tag) {
+        window.tag(&tag);
+    } else if window.tags.len() > 1 {
+        window.untag(&tag);
+    } else {
+        // A window keeps at least one tag.
+        return None;
+    }
+    let act = DisplayAction::SetWindowTags(window.handle, window.tags.clone());
+    manager.state.actions.push_back(act);
+
+    // Taken off the tag shown, the window leaves the workspace.
+    let window = manager.state.windows.iter().find(|w| w.handle == handle)?;
+    let shown = manager
+        .state
+        .focus_manager
+        .workspace(&manager.state.workspaces)
+        .is_some_and(|ws| ws.is_displaying(window));
+    if !shown {
+        if let Some(new_handle) = new_handle {
+            manager.state.focus_window(&new_handle);
+        } else {
+            let act = DisplayAction::Unfocus(Some(handle), false);
+            manager.state.actions.push_back(act);
+            manager.state.focus_manager.window_history.push_front(None);
+        }
+    }
+    Some(true)
+}
+
 fn move_window_to_workspace_change<C: Config, SERVER: DisplayServer>(
     manager: &mut Manager<C, SERVER>,
     delta: i32,
@@ -292,14 +431,12 @@ fn move_window_to_workspace_change<C: Config, SERVER: DisplayServer>(
 }
 
 fn goto_tag(state: &mut State, input_tag: TagId, current_tag_swap: bool) -> Option<bool> {
-    let current_tag = state.focus_manager.tag(0).unwrap_or_default();
-    let previous_tag = state.focus_manager.tag(1).unwrap_or_default();
-    let destination_tag = if current_tag_swap && current_tag == input_tag {
-        previous_tag
-    } else {
-        input_tag
-    };
-    state.goto_tag_handler(destination_tag)
+    let input_tag = state.focused_workspace_tag(input_tag)?;
+    let current_tags = &state.focus_manager.workspace(&state.workspaces)?.tags;
+    if current_tag_swap && current_tags == &[input_tag] {
+        return focus_last_tag(state);
+    }
+    state.goto_tag_handler(input_tag)
 }
 
 fn return_to_last_tag(state: &mut State) -> Option<bool> {
@@ -307,6 +444,46 @@ fn return_to_last_tag(state: &mut State) -> Option<bool> {
     state.goto_tag_handler(previous_tag)
 }
 
+/// Go back to the tags the focused workspace showed before.
+fn focus_last_tag(state: &mut State) -> Option<bool> {
+    let previous = state
+        .focus_manager
+        .workspace(&state.workspaces)?
+        .previous_tags
+        .clone();
+    let (&first, rest) = previous.split_first()?;
+    let result = state.goto_tag_handler(first);
+    for &tag in rest {
+        state.toggle_tag_view(tag);
+    }
+    result
+}
+
+/// Go back through the tags the focused workspace showed, or forward again.
+fn tag_history_step(state: &mut State, forward: bool) -> Option<bool> {
+    let index = *state.focus_manager.workspace_history.front()?;
+    let ws = state.workspaces.get(index)?;
+    let mut back = ws.tag_history_back.clone();
+    let mut ahead = ws.tag_history_forward.clone();
+    let (from, to) = if forward {
+        (&mut ahead, &mut back)
+    } else {
+        (&mut back, &mut ahead)
+    };
+    let target = from.pop()?;
+    to.push(ws.tags.clone());
+    let (&first, rest) = target.split_first()?;
+    let result = state.goto_tag_handler(first);
+    for &tag in rest {
+        state.toggle_tag_view(tag);
+    }
+    // Going through the history isn't a visit of its own.
+    let ws = state.workspaces.get_mut(index)?;
+    ws.tag_history_back = back;
+    ws.tag_history_forward = ahead;
+    result
+}
+
 fn focus_window(state: &mut State, param: &str) -> Option<bool> {
     match param.parse::<usize>() {
         Ok(index) if index > 0 => {
@@ -319,7 +496,7 @@ fn focus_window(state: &mut State, param: &str) -> Option<bool> {
                 .handle;
 
             state.handle_window_focus(&handle);
-            None
+            Some(false)
         }
         Err(_) => focus_window_by_class(state, param),
         Ok(_) => None,
@@ -347,12 +524,48 @@ fn focus_window_by_class(state: &mut State, window_class: &str) -> Option<bool>
     } else {
         state.windows.iter().find(|w| is_target(*w)).cloned()
     }?;
+    show_and_focus(state, &target_window)
+}
 
+fn focus_urgent(state: &mut State) -> Option<bool> {
+    // Windows urgent since before a restart come last.
+    let urgent = state
+        .windows
+        .iter()
+        .filter(|w| w.urgent)
+        .max_by_key(|w| w.urgent_since())?
+        .clone();
+    show_and_focus(state, &urgent)
+}
+
+/// Leave the overview of the focused workspace for a window, the focused one by default.
+fn pick_overview_window<C: Config, SERVER: DisplayServer>(
+    manager: &mut Manager<C, SERVER>,
+    handle: Option<WindowHandle>,
+) -> Option<bool> {
+    let state = &mut manager.state;
+    let index = *state.focus_manager.workspace_history.front()?;
+    let handle = handle.or_else(|| state.focus_manager.window(&state.windows).map(|w| w.handle));
+    if !state.leave_overview(index) {
+        return None;
+    }
+    // The windows shown outside the overview are needed to tell whether to change tags.
+    manager.update_windows();
+    let state = &mut manager.state;
+    let window = handle.and_then(|h| state.windows.iter().find(|w| w.handle == h));
+    match window.cloned() {
+        Some(window) => show_and_focus(state, &window).or(Some(true)),
+        None => Some(true),
+    }
+}
+
+/// Focus a window, going to its tag first if it isn't shown.
+fn show_and_focus(state: &mut State, target_window: &Window) -> Option<bool> {
     let handle = target_window.handle;
 
     if target_window.visible() {
         state.handle_window_focus(&handle);
-        return None;
+        return Some(false);
     }
 
     let tag_id = target_window.tags.first()?;
@@ -361,9 +574,9 @@ fn focus_window_by_class(state: &mut State, window_class: &str) -> Option<bool>
     match state
         .focus_manager
         .workspace(&state.workspaces)
-        .map(|ws| ws.layout)
+        .map(|ws| ws.layout.clone())
     {
-        Some(layout) if layout == Layout::Monocle || layout == Layout::MainAndDeck => {
+        Some(layout) if layout.is_monocle() || layout == Layout::MainAndDeck => {
             let mut windows = helpers::vec_extract(&mut state.windows, |w| {
                 w.has_tag(tag_id) && !w.is_unmanaged() && !w.floating()
             });
@@ -374,7 +587,7 @@ fn focus_window_by_class(state: &mut State, window_class: &str) -> Option<bool>
                 s.windows.append(wins);
             };
 
-            if layout == Layout::Monocle && windows.len() > 1 {
+            if layout.is_monocle() && windows.len() > 1 {
                 cycle(&mut windows, state);
             } else if layout == Layout::MainAndDeck && windows.len() > 2 {
                 let main_window = windows.remove(0);
@@ -397,23 +610,59 @@ fn focus_window_by_class(state: &mut State, window_class: &str) -> Option<bool>
 
 /// Focus the adjacent tags, depending on the delta.
 /// A delta of 1 means "next tag", a delta of -1 means "previous tag".
-fn focus_tag_change(state: &mut State, delta: i8) -> Option<bool> {
+fn focus_tag_change(state: &mut State, delta: i8, wrap: bool, skip_empty: bool) -> Option<bool> {
     let current_tag = state.focus_manager.tag(0)?;
-    let tags = state.tags.normal();
-    let relative_tag_id = relative_find(tags, |tag| tag.id == current_tag, i32::from(delta), true)
-        .map(|tag| tag.id)?;
+    let workspace = state.focus_manager.workspace(&state.workspaces)?;
+    let mut tags = state.listed_tags(&state.workspace_tags(workspace));
+    if skip_empty {
+        // The current tag stays to count from.
+        tags.retain(|tag| {
+            *tag == current_tag
+                || state
+                    .windows
+                    .iter()
+                    .any(|w| w.has_tag(tag) && !w.is_unmanaged())
+        });
+    }
+    // From a hidden tag, cycling starts over at the first of the others.
+    let relative_tag_id = match tags.iter().position(|&id| id == current_tag) {
+        Some(_) => relative_find(&tags, |&id| id == current_tag, i32::from(delta), wrap),
+        None => tags.first(),
+    }
+    .copied()?;
     state.goto_tag_handler(relative_tag_id)
 }
 
+/// Swap the tags of the focused and the last focused workspace. The focus stays on the focused
+/// workspace, with the tags it now shows.
 fn swap_tags(state: &mut State) -> Option<bool> {
     if state.workspaces.len() >= 2 && state.focus_manager.workspace_history.len() >= 2 {
         let hist_a = *state.focus_manager.workspace_history.get(0)?;
         let hist_b = *state.focus_manager.workspace_history.get(1)?;
+        if hist_a == hist_b {
+            return Some(false);
+        }
         //Update workspace tags
-        let mut temp = vec![];
-        std::mem::swap(&mut state.workspaces.get_mut(hist_a)?.tags, &mut temp);
-        std::mem::swap(&mut state.workspaces.get_mut(hist_b)?.tags, &mut temp);
-        std::mem::swap(&mut state.workspaces.get_mut(hist_a)?.tags, &mut temp);
+        let tags_a = state.workspaces.get(hist_a)?.tags.clone();
+        let tags_b = state.workspaces.get(hist_b)?.tags.clone();
+        let depth = state.tag_history_depth;
+        state.workspaces.get_mut(hist_a)?.show_tags(tags_b, depth);
+        state.workspaces.get_mut(hist_b)?.show_tags(tags_a, depth);
+        // Workspaces with their own tags swap the whole sets.
+        if state.per_screen_tags {
+            let id_a = state.workspaces.get(hist_a)?.id;
+            let id_b = state.workspaces.get(hist_b)?.id;
+            for tag in state.tags.all_mut() {
+                if tag.workspace_id == id_a {
+                    tag.workspace_id = id_b;
+                } else if tag.workspace_id == id_b {
+                    tag.workspace_id = id_a;
+                }
+            }
+        }
+        if let Some(&tag) = state.workspaces.get(hist_a)?.tags.first() {
+            state.focus_tag(&tag);
+        }
         // Update dock tags and layouts.
         state.update_static();
         state
@@ -423,19 +672,21 @@ fn swap_tags(state: &mut State) -> Option<bool> {
         return Some(true);
     }
     if state.workspaces.len() == 1 {
-        let last = *state.focus_manager.tag_history.get(1).unwrap();
+        let last = *state.focus_manager.tag_history.get(1)?;
         return state.goto_tag_handler(last);
     }
-    None
+    // Without another workspace focused before there is nothing to swap with.
+    Some(false)
 }
 
 fn close_window(state: &mut State) -> Option<bool> {
     let window = state.focus_manager.window(&state.windows)?;
-    if !window.is_unmanaged() {
-        let act = DisplayAction::KillWindow(window.handle);
-        state.actions.push_back(act);
+    if window.is_unmanaged() {
+        return None;
     }
-    None
+    let act = DisplayAction::KillWindow(window.handle);
+    state.actions.push_back(act);
+    Some(false)
 }
 
 fn move_to_last_workspace(state: &mut State) -> Option<bool> {
@@ -446,22 +697,40 @@ fn move_to_last_workspace(state: &mut State) -> Option<bool> {
         window.tags = vec![*wp_tags.get(0)?];
         return Some(true);
     }
-    None
+    // With a single workspace the window is on the last one already.
+    Some(false)
 }
 
 fn next_layout(state: &mut State) -> Option<bool> {
-    let workspace = state.focus_manager.workspace_mut(&mut state.workspaces)?;
-    let layout = state.layout_manager.next_layout(workspace);
+    let workspace = state.focus_manager.workspace(&state.workspaces)?;
+    let tag = workspace.tags.first().and_then(|id| state.tags.get(*id));
+    let layout = state.layout_manager.next_layout(workspace, tag);
     set_layout(layout, state)
 }
 
 fn previous_layout(state: &mut State) -> Option<bool> {
-    let workspace = state.focus_manager.workspace_mut(&mut state.workspaces)?;
-    let layout = state.layout_manager.previous_layout(workspace);
+    let workspace = state.focus_manager.workspace(&state.workspaces)?;
+    let tag = workspace.tags.first().and_then(|id| state.tags.get(*id));
+    let layout = state.layout_manager.previous_layout(workspace, tag);
+    set_layout(layout, state)
+}
+
+/// Switch the focused tag back to the layout it had before its current one.
+fn toggle_last_layout(state: &mut State) -> Option<bool> {
+    let tag_id = state.focus_manager.tag(0)?;
+    let layout = state.tags.get(tag_id)?.last_layout.clone()?;
     set_layout(layout, state)
 }
 
 fn set_layout(layout: Layout, state: &mut State) -> Option<bool> {
+    // Any name parses as a custom layout, so a misspelt one would silently be tiled as the
+    // default layout.
+    if let Layout::Custom(name) = &layout {
+        if !state.custom_layouts.iter().any(|l| l.name == name.as_str()) {
+            log::warn!("There is no layout named {}", name.as_str());
+            return None;
+        }
+    }
     let tag_id = state.focus_manager.tag(0)?;
     // When switching to Monocle or MainAndDeck layout while in Driven
     // or ClickTo focus mode, we check if the focus is given to a visible window.
@@ -479,7 +748,7 @@ fn set_layout(layout: Layout, state: &mut State) -> Option<bool> {
         if !is_focused_floating {
             let mut to_focus: Option<Window> = None;
 
-            if layout == Layout::Monocle {
+            if layout.is_monocle() {
                 to_focus = state
                     .windows
                     .iter()
@@ -510,17 +779,60 @@ fn set_layout(layout: Layout, state: &mut State) -> Option<bool> {
         }
     }
     let workspace = state.focus_manager.workspace_mut(&mut state.workspaces)?;
-    workspace.layout = layout;
+    workspace.layout = layout.clone();
     let tag = state.tags.get_mut(tag_id)?;
     match layout {
         Layout::RightWiderLeftStack | Layout::LeftWiderRightStack => {
-            tag.set_layout(layout, layout.main_width());
+            let main_width = layout.main_width();
+            tag.set_layout(layout, main_width);
         }
         _ => tag.set_layout(layout, workspace.main_width_percentage),
     }
     Some(true)
 }
 
+fn toggle_zoom(state: &mut State) -> Option<bool> {
+    let workspace = state.focus_manager.workspace(&state.workspaces)?;
+    let is_managed = |w: &Window| workspace.is_managed(w);
+    let mut to_reorder;
+    let (layout, zoom) = if let Some(zoom) = &workspace.zoom {
+        to_reorder = helpers::vec_extract(&mut state.windows, is_managed);
+        // Windows opened while zoomed in end up after the others.
+        to_reorder.sort_by_key(|w| {
+            zoom.order
+                .iter()
+                .position(|h| *h == w.handle)
+                .unwrap_or(usize::MAX)
+        });
+        (zoom.layout.clone(), None)
+    } else {
+        let handle = state
+            .focus_manager
+            .window(&state.windows)
+            .filter(|w| is_managed(w) && !w.floating())?
+            .handle;
+        to_reorder = helpers::vec_extract(&mut state.windows, is_managed);
+        let order = to_reorder.iter().map(|w| w.handle).collect();
+        // Monocle only shows the first window, so bring the focused one to the front.
+        if let Some(index) = to_reorder.iter().position(|w| w.handle == handle) {
+            let window = to_reorder.remove(index);
+            to_reorder.insert(0, window);
+        }
+        let zoom = Zoom {
+            layout: workspace.layout.clone(),
+            order,
+        };
+        (Layout::Monocle, Some(zoom))
+    };
+    state.windows.append(&mut to_reorder);
+    set_layout(layout, state)?;
+    state
+        .focus_manager
+        .workspace_mut(&mut state.workspaces)?
+        .zoom = zoom;
+    Some(true)
+}
+
 fn floating_to_tile(state: &mut State) -> Option<bool> {
     let workspace = state.focus_manager.workspace(&state.workspaces)?;
     let window = state.focus_manager.window_mut(&mut state.windows)?;
@@ -530,7 +842,8 @@ fn floating_to_tile(state: &mut State) -> Option<bool> {
     //Not ideal as is_floating and must_float are connected so have to check
     //them separately
     if !window.floating() {
-        return None;
+        // Tiled already.
+        return Some(false);
     }
     let handle = window.handle;
     if window.snap_to_workspace(workspace) {
@@ -550,7 +863,8 @@ fn tile_to_floating(state: &mut State) -> Option<bool> {
     //Not ideal as is_floating and must_float are connected so have to check
     //them separately
     if window.floating() {
-        return None;
+        // Floating already.
+        return Some(false);
     }
 
     let mut normal = window.normal;
@@ -587,7 +901,7 @@ fn move_window_change(
     val: i32,
 ) -> Option<bool> {
     let is_handle = |x: &Window| -> bool { x.handle == handle };
-    if layout == Some(Layout::Monocle) {
+    if layout.as_ref().is_some_and(Layout::is_monocle) {
         handle = helpers::relative_find(&to_reorder, is_handle, -val, true)?.handle;
         let _ = helpers::cycle_vec(&mut to_reorder, val);
     } else if layout == Some(Layout::MainAndDeck) {
@@ -648,7 +962,7 @@ fn focus_window_change(
     val: i32,
 ) -> Option<bool> {
     let is_handle = |x: &Window| -> bool { x.handle == handle };
-    if layout == Some(Layout::Monocle) {
+    if layout.as_ref().is_some_and(Layout::is_monocle) {
         // For Monocle we want to also move windows up/down
         // Not the best solution but results
         // in desired behaviour
@@ -675,7 +989,57 @@ fn focus_window_change(
     }
     state.windows.append(&mut to_reorder);
     state.handle_window_focus(&handle);
-    Some(layout == Some(Layout::Monocle))
+    Some(layout.is_some_and(|l| l.is_monocle()))
+}
+
+/// Swap the focused window with the master window of the tag. The master window itself is
+/// swapped with the next tiled window.
+fn swap_window_with_master(
+    state: &mut State,
+    handle: WindowHandle,
+    _layout: Option<Layout>,
+    mut to_reorder: Vec<Window>,
+) -> Option<bool> {
+    let tiled: Vec<usize> = (0..to_reorder.len())
+        .filter(|&i| !to_reorder[i].floating())
+        .collect();
+    let index = tiled.iter().position(|&i| to_reorder[i].handle == handle);
+    let other = match index {
+        Some(0) => tiled.get(1).copied(),
+        Some(_) => tiled.first().copied(),
+        None => None,
+    };
+    let swap = index.zip(other).map(|(index, other)| (tiled[index], other));
+    if let Some((index, other)) = swap {
+        to_reorder.swap(index, other);
+    }
+    state.windows.append(&mut to_reorder);
+    swap?;
+    state.handle_window_focus(&handle);
+    Some(true)
+}
+
+/// Move every tiled window `shift` slots further through the layout. The focus stays in the slot
+/// it was in, on the window moving into it.
+fn rotate_stack(
+    state: &mut State,
+    handle: WindowHandle,
+    _layout: Option<Layout>,
+    to_reorder: Vec<Window>,
+    shift: i32,
+) -> Option<bool> {
+    let (mut tiled, mut floating): (Vec<Window>, Vec<Window>) =
+        to_reorder.into_iter().partition(|w| !w.floating());
+    let slot = tiled.iter().position(|w| w.handle == handle);
+    let rotated = helpers::cycle_vec(&mut tiled, shift);
+    let focus = slot.and_then(|slot| tiled.get(slot)).map(|w| w.handle);
+    state.windows.append(&mut tiled);
+    state.windows.append(&mut floating);
+    rotated?;
+    if let Some(focus) = focus {
+        state.handle_window_focus(&focus);
+    }
+    Some(true)
 }
 
 fn focus_window_top(state: &mut State, swap: bool) -> Option<bool> {
@@ -695,7 +1059,60 @@ fn focus_window_top(state: &mut State, swap: bool) -> Option<bool> {
         (Some(next), Some(cur), _) if next != cur => state.handle_window_focus(&next),
         _ => {}
     }
-    None
+    Some(false)
+}
+
+fn focus_window_in_direction(state: &mut State, direction: Direction) -> Option<bool> {
+    let current = state.focus_manager.window(&state.windows);
+    let from = match current {
+        Some(window) => window.calculated_xyhw(),
+        None => state.focus_manager.workspace(&state.workspaces)?.xyhw,
+    };
+    let current = current.map(|w| w.handle);
+    let candidates = state
+        .windows
+        .iter()
+        .filter(|w| w.can_focus() && Some(w.handle) != current)
+        .map(|w| (w.calculated_xyhw(), w.handle));
+    let handle = direction.nearest(&from, candidates)?;
+    state.handle_window_focus(&handle);
+    Some(false)
+}
+
+/// Swap the focused tiled window with its neighbour in `direction`. Without a neighbour on its
+/// workspace, the window is moved to the workspace in that direction instead.
+fn move_window_in_direction<C: Config, SERVER: DisplayServer>(
+    manager: &mut Manager<C, SERVER>,
+    direction: Direction,
+) -> Option<bool> {
+    let state = &mut manager.state;
+    let window = state.focus_manager.window(&state.windows)?;
+    if window.floating() {
+        return None;
+    }
+    let (handle, from) = (window.handle, window.calculated_xyhw());
+    let workspace = state.focus_manager.workspace(&state.workspaces)?;
+    let candidates = state
+        .windows
+        .iter()
+        .enumerate()
+        .filter(|(_, w)| w.handle != handle && !w.floating() && !w.is_unmanaged())
+        .filter(|(_, w)| workspace.is_displaying(w))
+        .map(|(index, w)| (w.calculated_xyhw(), index));
+    if let Some(neighbour) = direction.nearest(&from, candidates) {
+        let index = state.windows.iter().position(|w| w.handle == handle)?;
+        state.windows.swap(index, neighbour);
+        state.handle_window_focus(&handle);
+        return Some(true);
+    }
+
+    let candidates = state
+        .workspaces
+        .iter()
+        .filter(|ws| ws.id != workspace.id)
+        .map(|ws| (ws.xyhw, ws.tags.first().copied()));
+    let tag = direction.nearest(&workspace.xyhw, candidates)??;
+    move_to_tag(None, tag, manager)
 }
 
 fn close_all_other_windows(state: &mut State) -> Option<bool> {
@@ -715,11 +1132,30 @@ fn close_all_other_windows(state: &mut State) -> Option<bool> {
     Some(true)
 }
 
+/// Close every window on the focused tag. The first call only arms this, the windows are asked
+/// to close when it is repeated on the same tag without another command in between.
+fn close_all_on_tag(state: &mut State) -> Option<bool> {
+    let tag = state.focus_manager.tag(0)?;
+    if state.close_all_pending.take() != Some(tag) {
+        state.close_all_pending = Some(tag);
+        return Some(false);
+    }
+    // Unlike `CloseWindow` this doesn't kill the clients of windows which can't be asked to close,
+    // sparing the work open in the windows the user didn't single out.
+    for window in &state.windows {
+        if window.has_tag(&tag) && !window.is_unmanaged() {
+            let act = DisplayAction::DeleteWindow(window.handle);
+            state.actions.push_back(act);
+        }
+    }
+    Some(false)
+}
+
 fn focus_workspace_change(state: &mut State, val: i32) -> Option<bool> {
     let current = state.focus_manager.workspace(&state.workspaces)?;
     let workspace = helpers::relative_find(&state.workspaces, |w| w == current, val, true)?.clone();
 
-    if state.focus_manager.behaviour.is_sloppy() {
+    if state.focus_manager.behaviour.is_sloppy() || state.focus_manager.warp_cursor {
         let action = workspace
             .tags
             .first()
@@ -731,7 +1167,7 @@ fn focus_workspace_change(state: &mut State, val: i32) -> Option<bool> {
         state.actions.push_back(action);
     }
     state.focus_workspace(&workspace);
-    None
+    Some(false)
 }
 
 fn rotate_tag(state: &mut State) -> Option<bool> {
@@ -741,15 +1177,66 @@ fn rotate_tag(state: &mut State) -> Option<bool> {
     Some(true)
 }
 
-fn change_main_width(state: &mut State, delta: i8, factor: i8) -> Option<bool> {
+fn change_main_width(state: &mut State, delta: f32) -> Option<bool> {
     let workspace = state.focus_manager.workspace_mut(&mut state.workspaces)?;
-    workspace.change_main_width(delta * factor);
+    workspace.change_main_width(delta);
     let tag_id = state.focus_manager.tag(0)?;
     let tag = state.tags.get_mut(tag_id)?;
-    tag.change_main_width(delta * factor);
+    tag.change_main_width(delta);
     Some(true)
 }
 
+fn change_stack_split(state: &mut State, delta: f32) -> Option<bool> {
+    let workspace = state.focus_manager.workspace_mut(&mut state.workspaces)?;
+    workspace.change_stack_split(delta);
+    let tag_id = state.focus_manager.tag(0)?;
+    let tag = state.tags.get_mut(tag_id)?;
+    tag.change_stack_split(delta);
+    Some(true)
+}
+
+fn change_main_count(state: &mut State, delta: isize) -> Option<bool> {
+    let tag_id = state.focus_manager.tag(0)?;
+    let tag = state.tags.get_mut(tag_id)?;
+    tag.change_main_count(delta);
+    Some(true)
+}
+
+fn change_window_weight(state: &mut State, delta: f32) -> Option<bool> {
+    let handle = state.focus_manager.window(&state.windows)?.handle;
+    let tag_id = state.focus_manager.tag(0)?;
+    let tag = state.tags.get_mut(tag_id)?;
+    tag.change_window_weight(handle, delta);
+    Some(true)
+}
+
+fn change_bsp_subtree(state: &mut State, change: fn(&mut BspTree, WindowHandle)) -> Option<bool> {
+    let handle = state.focus_manager.window(&state.windows)?.handle;
+    let tag_id = state.focus_manager.tag(0)?;
+    let tag = state.tags.get_mut(tag_id)?;
+    if tag.layout != Layout::Bsp {
+        return None;
+    }
+    change(&mut tag.bsp, handle);
+    Some(true)
+}
+
+fn window_at(state: &State, x: i32, y: i32) -> bool {
+    let answer = WindowAt::new(state, x, y);
+    if let Err(err) = command_pipe::write_answer(command_pipe::WINDOW_AT_FILE, &answer) {
+        log::error!("Failed to answer WindowAt: {}", err);
+    }
+    false
+}
+
+fn list_windows(state: &State) -> bool {
+    let answer = WindowList::from(state);
+    if let Err(err) = command_pipe::write_answer(command_pipe::WINDOWS_FILE, &answer) {
+        log::error!("Failed to answer ListWindows: {}", err);
+    }
+    false
+}
+
 fn set_margin_multiplier(state: &mut State, margin_multiplier: f32) -> Option<bool> {
     let ws = state.focus_manager.workspace_mut(&mut state.workspaces)?;
     ws.set_margin_multiplier(margin_multiplier);
@@ -770,21 +1257,59 @@ fn set_margin_multiplier(state: &mut State, margin_multiplier: f32) -> Option<bo
     Some(true)
 }
 
+fn change_gaps(state: &mut State, change: impl FnOnce(&mut Workspace)) -> Option<bool> {
+    change(state.focus_manager.workspace_mut(&mut state.workspaces)?);
+    Some(true)
+}
+
+fn set_max_window_width(state: &mut State, size: Option<Size>) -> Option<bool> {
+    let tag_id = state.focus_manager.tag(0)?;
+    let tag = state.tags.get_mut(tag_id)?;
+    tag.max_window_width = size;
+    Some(true)
+}
+
 fn send_workspace_to_tag(state: &mut State, ws_index: usize, tag_index: usize) -> bool {
     // todo: address inconsistency of using the index instead of the id here
-    if ws_index < state.workspaces.len() && tag_index < state.tags.len_normal() {
+    if ws_index < state.workspaces.len() {
         let workspace = &state.workspaces[ws_index].clone();
-        state.focus_workspace(workspace);
-        state.goto_tag_handler(tag_index + 1);
-        return true;
+        if let Some(&tag_id) = state.workspace_tags(workspace).get(tag_index) {
+            state.focus_workspace(workspace);
+            state.goto_tag_handler(tag_id);
+            return true;
+        }
     }
     false
 }
 
+fn move_tag_to_workspace(state: &mut State, index: usize) -> Option<bool> {
+    let workspace = state.focus_manager.workspace(&state.workspaces)?;
+    let tag = *workspace.tags.first()?;
+    state.move_tag_to_workspace(tag, index)
+}
+
+fn add_tag(state: &mut State, label: &str) -> bool {
+    state.add_tag(label);
+    false
+}
+
+/// Rename the tag labelled `tag`, or else the focused one.
+fn rename_tag(state: &mut State, tag: Option<&str>, label: &str) -> Option<bool> {
+    let tag = match tag {
+        Some(tag) => state.find_tag(tag)?,
+        None => state.focus_manager.tag(0)?,
+    };
+    state.rename_tag(tag, label)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::models::Tags;
+    use crate::config::TestConfig;
+    use crate::display_servers::MockDisplayServer;
+    use crate::models::dto::ManagerState;
+    use crate::models::{BBox, Margins, Tags, UrgencySource, XyhwBuilder};
+    use std::str::FromStr;
 
     #[test]
     fn return_to_last_tag_should_go_back_to_last_tag() {
@@ -818,6 +1343,91 @@ mod tests {
         assert_eq!(current_tag, 1);
     }
 
+    #[test]
+    fn focus_last_tag_should_go_back_on_the_focused_workspace() {
+        let mut manager = Manager::new_test((1..=4).map(|tag| tag.to_string()).collect());
+        manager.screen_create_handler(Screen::default());
+        manager.screen_create_handler(Screen::default());
+        // Workspace 0 goes from tag 1 to 3, then workspace 1 from tag 2 to 4.
+        manager
+            .state
+            .focus_workspace(&manager.state.workspaces[0].clone());
+        manager.state.goto_tag_handler(3);
+        manager
+            .state
+            .focus_workspace(&manager.state.workspaces[1].clone());
+        manager.state.goto_tag_handler(4);
+
+        manager
+            .state
+            .focus_workspace(&manager.state.workspaces[0].clone());
+        assert!(manager.command_handler(&Command::FocusLastTag));
+        assert_eq!(manager.state.workspaces[0].tags, [1]);
+        assert_eq!(manager.state.workspaces[1].tags, [4]);
+        // And forth, as does going to the tag shown.
+        manager.command_handler(&Command::GoToTag { tag: 1, swap: true });
+        assert_eq!(manager.state.workspaces[0].tags, [3]);
+    }
+
+    #[test]
+    fn tag_history_should_go_back_and_forth_up_to_its_depth() {
+        let mut manager = Manager::new_test((1..=4).map(|tag| tag.to_string()).collect());
+        manager.screen_create_handler(Screen::default());
+        manager.state.tag_history_depth = 2;
+        for tag in 2..=4 {
+            manager.state.goto_tag_handler(tag);
+        }
+        let tags = |manager: &Manager<TestConfig, MockDisplayServer>| {
+            manager.state.workspaces[0].tags.clone()
+        };
+
+        assert!(manager.command_handler(&Command::TagHistoryBack));
+        assert_eq!(tags(&manager), [3]);
+        assert!(manager.command_handler(&Command::TagHistoryBack));
+        assert_eq!(tags(&manager), [2]);
+        // Tag 1 is past the depth.
+        assert!(!manager.command_handler(&Command::TagHistoryBack));
+        assert!(manager.command_handler(&Command::TagHistoryForward));
+        assert_eq!(tags(&manager), [3]);
+        // Going elsewhere loses what was ahead.
+        manager.state.goto_tag_handler(1);
+        assert!(!manager.command_handler(&Command::TagHistoryForward));
+        assert!(manager.command_handler(&Command::TagHistoryBack));
+        assert_eq!(tags(&manager), [3]);
+    }
+
+    #[test]
+    fn swap_screens_should_keep_the_focus_on_the_focused_workspace() {
+        let mut manager = Manager::new_test(vec!["1".to_string(), "2".to_string()]);
+        manager.screen_create_handler(Screen::default());
+        manager.screen_create_handler(Screen::default());
+        manager
+            .state
+            .focus_workspace(&manager.state.workspaces[1].clone());
+        manager
+            .state
+            .focus_workspace(&manager.state.workspaces[0].clone());
+
+        assert!(manager.command_handler(&Command::SwapScreens));
+        assert_eq!(manager.state.workspaces[0].tags, [2]);
+        assert_eq!(manager.state.workspaces[1].tags, [1]);
+        assert_eq!(
+            manager.state.focus_manager.workspace_history.front(),
+            Some(&0)
+        );
+        assert_eq!(manager.state.focus_manager.tag(0), Some(2));
+    }
+
+    #[test]
+    fn swap_screens_should_do_nothing_on_one_screen_without_a_previous_tag() {
+        let mut manager = Manager::new_test(vec!["1".to_string(), "2".to_string()]);
+        manager.screen_create_handler(Screen::default());
+        assert!(manager.state.focus_manager.tag_history.len() < 2);
+
+        assert!(!manager.command_handler(&Command::SwapScreens));
+        assert_eq!(manager.state.workspaces[0].tags, [1]);
+    }
+
     #[test]
     fn go_to_tag_should_return_false_if_no_screen_is_created() {
         let mut manager = Manager::new_test(vec![]);
@@ -941,25 +1551,159 @@ mod tests {
         state.focus_tag(&2);
         assert_eq!(state.focus_manager.tag(0).unwrap(), 2);
 
-        focus_tag_change(state, 1);
+        focus_tag_change(state, 1, true, false);
         assert_eq!(state.focus_manager.tag(0).unwrap(), 3);
 
-        focus_tag_change(state, -1);
+        focus_tag_change(state, -1, true, false);
         assert_eq!(state.focus_manager.tag(0).unwrap(), 2);
 
-        focus_tag_change(state, 2);
+        focus_tag_change(state, 2, true, false);
         assert_eq!(state.focus_manager.tag(0).unwrap(), 4);
 
-        focus_tag_change(state, -5);
+        focus_tag_change(state, -5, true, false);
         assert_eq!(state.focus_manager.tag(0).unwrap(), 5);
 
-        focus_tag_change(state, 3);
+        focus_tag_change(state, 3, true, false);
         assert_eq!(state.focus_manager.tag(0).unwrap(), 2);
 
-        focus_tag_change(state, 13);
+        focus_tag_change(state, 13, true, false);
         assert_eq!(state.focus_manager.tag(0).unwrap(), 3);
     }
 
+    #[test]
+    fn focus_tag_change_should_skip_hidden_tags_and_follow_the_tag_order() {
+        let mut manager: Manager<TestConfig, MockDisplayServer> = Manager::new(TestConfig {
+            tags: vec![
+                "1".to_string(),
+                "2".to_string(),
+                "3".to_string(),
+                "4".to_string(),
+            ],
+            hidden_tags: vec![2],
+            tag_order: vec![4],
+            ..TestConfig::default()
+        });
+        manager.screen_create_handler(Screen::default());
+        let state = &mut manager.state;
+
+        state.focus_tag(&1);
+        focus_tag_change(state, 1, true, false);
+        assert_eq!(state.focus_manager.tag(0), Some(3));
+        focus_tag_change(state, 1, true, false);
+        assert_eq!(state.focus_manager.tag(0), Some(4));
+        focus_tag_change(state, 1, true, false);
+        assert_eq!(state.focus_manager.tag(0), Some(1));
+
+        let bar_state = ManagerState::from(&*state);
+        assert_eq!(bar_state.desktop_names, vec!["4", "1", "3"]);
+        assert_eq!(bar_state.desktop_indexes, vec![3, 0, 2]);
+    }
+
+    #[test]
+    fn focus_tag_change_should_skip_empty_tags_and_stop_at_the_ends() {
+        let mut manager = Manager::new_test(vec![
+            "1".to_string(),
+            "2".to_string(),
+            "3".to_string(),
+            "web".to_string(),
+        ]);
+        manager.screen_create_handler(Screen::default());
+        manager.state.goto_tag_handler(3);
+        manager.window_created_handler(
+            Window::new(WindowHandle::MockHandle(1), None, None),
+            -1,
+            -1,
+        );
+        manager.state.goto_tag_handler(1);
+        manager.window_created_handler(
+            Window::new(WindowHandle::MockHandle(2), None, None),
+            -1,
+            -1,
+        );
+        let state = &mut manager.state;
+
+        focus_tag_change(state, 1, true, true);
+        assert_eq!(state.focus_manager.tag(0), Some(3));
+        focus_tag_change(state, 1, false, true);
+        assert_eq!(state.focus_manager.tag(0), Some(3));
+        focus_tag_change(state, 1, true, true);
+        assert_eq!(state.focus_manager.tag(0), Some(1));
+        focus_tag_change(state, -1, false, false);
+        assert_eq!(state.focus_manager.tag(0), Some(1));
+
+        assert!(manager.command_handler(&Command::GoToLabelledTag("web".to_string())));
+        assert_eq!(manager.state.focus_manager.tag(0), Some(4));
+    }
+
+    #[test]
+    fn focus_urgent_should_go_to_the_most_recently_urgent_window() {
+        let mut manager =
+            Manager::new_test(vec!["1".to_string(), "2".to_string(), "3".to_string()]);
+        manager.screen_create_handler(Screen::default());
+        for (handle, tag) in &[(1, 2), (2, 3)] {
+            manager.state.goto_tag_handler(*tag);
+            manager.window_created_handler(
+                Window::new(WindowHandle::MockHandle(*handle), None, None),
+                -1,
+                -1,
+            );
+        }
+        manager.state.goto_tag_handler(1);
+        let now = std::time::Instant::now();
+        manager.state.windows[1].request_urgency(UrgencySource::Hint, true, 0, now);
+        manager.state.windows[0].request_urgency(
+            UrgencySource::Hint,
+            true,
+            0,
+            now + std::time::Duration::from_secs(1),
+        );
+
+        assert!(manager.command_handler(&Command::FocusUrgent));
+        assert_eq!(manager.state.focus_manager.tag(0), Some(2));
+        assert_eq!(
+            manager.state.focus_manager.window_history.front(),
+            Some(&Some(WindowHandle::MockHandle(1)))
+        );
+        assert!(manager.command_handler(&Command::FocusUrgent));
+        assert_eq!(manager.state.focus_manager.tag(0), Some(3));
+        assert!(!manager.command_handler(&Command::FocusUrgent));
+    }
+
+    #[test]
+    fn game_mode_should_take_the_gaps_and_the_sloppy_focus_away() {
+        let mut manager = Manager::new_test(vec![]);
+        manager.state.focus_manager.behaviour = FocusBehaviour::Sloppy;
+        manager.screen_create_handler(Screen::default());
+        manager.window_created_handler(
+            Window::new(WindowHandle::MockHandle(1), None, None),
+            -1,
+            -1,
+        );
+        manager.state.actions.clear();
+        let margin = manager.state.workspaces[0].margin;
+
+        assert!(manager.command_handler(&Command::ToggleGameMode));
+        manager.update_windows();
+        let workspace = &manager.state.workspaces[0];
+        assert_eq!(workspace.inner_gap, Some(0));
+        assert_eq!(workspace.margin, Margins::new(0));
+        assert!(!manager.state.focus_follows_mouse());
+        assert!(matches!(
+            manager.state.actions.back(),
+            Some(DisplayAction::SetGameMode(
+                WindowHandle::MockHandle(1),
+                true
+            ))
+        ));
+        assert!(ManagerState::from(&manager.state).viewports[0].game_mode);
+
+        assert!(manager.command_handler(&Command::ToggleGameMode));
+        manager.update_windows();
+        assert_eq!(manager.state.workspaces[0].margin, margin);
+        assert!(manager.state.focus_follows_mouse());
+        assert!(!manager.state.windows[0].game_mode);
+    }
+
     #[test]
     fn focus_window_top() {
         let mut manager = Manager::new_test(vec![]);
@@ -1049,4 +1793,616 @@ mod tests {
         manager.command_handler(&Command::MoveWindowTop { swap: true });
         assert_eq!(manager.state.windows[0].handle, expected.handle);
     }
+
+    #[test]
+    fn change_layout_ratios_should_update_workspace_and_tag() {
+        let mut manager = Manager::new_test(vec!["1".to_string()]);
+        manager.screen_create_handler(Screen::default());
+
+        manager.command_handler(&Command::IncreaseMainWidth(2.5));
+        manager.command_handler(&Command::DecreaseStackSplit(12.5));
+
+        let workspace = &manager.state.workspaces[0];
+        assert!((workspace.main_width_percentage - 52.5).abs() < f32::EPSILON);
+        assert!((workspace.stack_split_percentage - 37.5).abs() < f32::EPSILON);
+        let tag = manager.state.tags.get(1).unwrap();
+        assert!((tag.main_width_percentage() - 52.5).abs() < f32::EPSILON);
+        assert!((tag.stack_split_percentage() - 37.5).abs() < f32::EPSILON);
+
+        manager.command_handler(&Command::IncreaseMainWidth(200.0));
+        assert!((manager.state.workspaces[0].main_width_percentage - 95.0).abs() < f32::EPSILON);
+        manager.command_handler(&Command::DecreaseMainWidth(200.0));
+        let tag = manager.state.tags.get(1).unwrap();
+        assert!((tag.main_width_percentage() - 5.0).abs() < f32::EPSILON);
+    }
+
+    #[test]
+    fn main_count_should_never_drop_below_one() {
+        let mut manager = Manager::new_test(vec!["1".to_string()]);
+        manager.screen_create_handler(Screen::default());
+
+        manager.command_handler(&Command::IncreaseMainCount);
+        manager.command_handler(&Command::IncreaseMainCount);
+        assert_eq!(manager.state.tags.get(1).unwrap().main_count(), 3);
+
+        for _ in 0..4 {
+            manager.command_handler(&Command::DecreaseMainCount);
+        }
+        assert_eq!(manager.state.tags.get(1).unwrap().main_count(), 1);
+    }
+
+    #[test]
+    fn max_window_width_of_tag_should_override_workspace() {
+        let mut manager = Manager::new_test(vec!["1".to_string()]);
+        manager.screen_create_handler(Screen::default());
+        manager.window_created_handler(
+            Window::new(WindowHandle::MockHandle(1), None, None),
+            -1,
+            -1,
+        );
+
+        manager.command_handler(&Command::SetMaxWindowWidth(Some(Size::Pixel(400))));
+        manager.update_windows();
+        let window = &manager.state.windows[0];
+        assert_eq!((window.x(), window.width()), (200, 400));
+
+        manager.command_handler(&Command::SetMaxWindowWidth(None));
+        manager.update_windows();
+        assert_eq!(manager.state.windows[0].width(), 800);
+    }
+
+    #[test]
+    fn window_weights_should_survive_new_windows() {
+        let mut manager = Manager::new_test(vec!["1".to_string()]);
+        manager.screen_create_handler(Screen::default());
+        for i in 1..=3 {
+            manager.window_created_handler(
+                Window::new(WindowHandle::MockHandle(i), None, None),
+                -1,
+                -1,
+            );
+        }
+        manager.state.focus_window(&WindowHandle::MockHandle(2));
+        manager.command_handler(&Command::IncreaseWindowWeight(1.0));
+        manager.update_windows();
+        let heights: Vec<i32> = manager.state.windows[1..]
+            .iter()
+            .map(Window::height)
+            .collect();
+        assert_eq!(heights, vec![400, 200]);
+
+        manager.window_created_handler(
+            Window::new(WindowHandle::MockHandle(4), None, None),
+            -1,
+            -1,
+        );
+        manager.update_windows();
+        let height_of = |handle| {
+            manager
+                .state
+                .windows
+                .iter()
+                .find(|w| w.handle == WindowHandle::MockHandle(handle))
+                .map(Window::height)
+        };
+        assert_eq!(height_of(2), Some(300));
+        assert_eq!(height_of(4), Some(150));
+    }
+
+    #[test]
+    fn bsp_windows_should_split_the_focused_region() {
+        let mut manager = Manager::new_test(vec!["1".to_string()]);
+        manager.screen_create_handler(Screen::default());
+        manager.command_handler(&Command::SetLayout(Layout::Bsp));
+        for i in 1..=2 {
+            manager.window_created_handler(
+                Window::new(WindowHandle::MockHandle(i), None, None),
+                -1,
+                -1,
+            );
+        }
+        manager.update_windows();
+        manager.state.focus_window(&WindowHandle::MockHandle(1));
+        manager.window_created_handler(
+            Window::new(WindowHandle::MockHandle(3), None, None),
+            -1,
+            -1,
+        );
+        manager.update_windows();
+        let area_of = |windows: &[Window], handle| {
+            windows
+                .iter()
+                .find(|w| w.handle == WindowHandle::MockHandle(handle))
+                .map(|w| (w.x(), w.y(), w.width(), w.height()))
+        };
+        assert_eq!(area_of(&manager.state.windows, 1), Some((0, 0, 400, 300)));
+        assert_eq!(area_of(&manager.state.windows, 3), Some((0, 300, 400, 300)));
+        assert_eq!(area_of(&manager.state.windows, 2), Some((400, 0, 400, 600)));
+
+        manager.command_handler(&Command::RotateSubtree);
+        manager.update_windows();
+        assert_eq!(area_of(&manager.state.windows, 1), Some((0, 0, 200, 600)));
+        assert_eq!(area_of(&manager.state.windows, 3), Some((200, 0, 200, 600)));
+    }
+
+    #[test]
+    fn count_prefix_should_repeat_the_next_command() {
+        let mut manager = Manager::new_test(vec!["1".to_string()]);
+        manager.screen_create_handler(Screen::default());
+        for i in 1..=4 {
+            manager.window_created_handler(
+                Window::new(WindowHandle::MockHandle(i), None, None),
+                -1,
+                -1,
+            );
+        }
+        manager.state.focus_window(&WindowHandle::MockHandle(1));
+
+        manager.command_handler(&Command::CountPrefix(2));
+        manager.command_handler(&Command::FocusWindowDown);
+        let focused = manager.state.focus_manager.window(&manager.state.windows);
+        assert_eq!(focused.map(|w| w.handle), Some(WindowHandle::MockHandle(3)));
+
+        // The count only applies to a single command.
+        manager.command_handler(&Command::FocusWindowDown);
+        let focused = manager.state.focus_manager.window(&manager.state.windows);
+        assert_eq!(focused.map(|w| w.handle), Some(WindowHandle::MockHandle(4)));
+    }
+
+    #[test]
+    fn count_prefix_should_not_repeat_commands_which_are_not_steps() {
+        let mut manager = Manager::new_test(vec!["1".to_string()]);
+        manager.screen_create_handler(Screen::default());
+        manager.command_handler(&Command::CountPrefix(3));
+        manager.command_handler(&Command::AddTag("web".to_string()));
+        assert_eq!(manager.state.tags.normal().len(), 2);
+        // Nor is the count kept for the next command.
+        manager.command_handler(&Command::AddTag("mail".to_string()));
+        assert_eq!(manager.state.tags.normal().len(), 3);
+    }
+
+    #[test]
+    fn monocle_numbers_the_focused_window_as_focus_cycles() {
+        let mut manager = Manager::new_test(vec!["1".to_string()]);
+        manager.screen_create_handler(Screen::default());
+        for i in 1..=3 {
+            manager.window_created_handler(
+                Window::new(WindowHandle::MockHandle(i), None, None),
+                -1,
+                -1,
+            );
+        }
+        manager.command_handler(&Command::SetLayout(Layout::Monocle));
+        manager
+            .state
+            .focus_window(&manager.state.windows[0].handle.clone());
+        manager.update_windows();
+        let numbered = |manager: &Manager<TestConfig, MockDisplayServer>| {
+            let viewport = &ManagerState::from(&manager.state).viewports[0];
+            (viewport.focused_window, viewport.window_count)
+        };
+        assert_eq!(numbered(&manager), (Some(1), 3));
+
+        manager.command_handler(&Command::FocusWindowDown);
+        assert_eq!(numbered(&manager), (Some(2), 3));
+        manager.command_handler(&Command::FocusWindowDown);
+        manager.command_handler(&Command::FocusWindowDown);
+        assert_eq!(numbered(&manager), (Some(1), 3));
+        manager.command_handler(&Command::FocusWindowUp);
+        assert_eq!(numbered(&manager), (Some(3), 3));
+    }
+
+    #[test]
+    fn layouts_should_be_set_by_name_and_cycled_backwards() {
+        let mut manager = Manager::new_test(vec!["1".to_string()]);
+        manager.state.layout_manager.layouts = vec![Layout::Monocle, Layout::Grid, Layout::Fair];
+        manager.screen_create_handler(Screen::default());
+        let fair = Layout::from_str("Fair").unwrap();
+        assert!(manager.command_handler(&Command::SetLayout(fair)));
+        assert_eq!(manager.state.workspaces[0].layout, Layout::Fair);
+        manager.command_handler(&Command::PreviousLayout);
+        assert_eq!(manager.state.workspaces[0].layout, Layout::Grid);
+
+        // Misspelt layouts are refused rather than tiled as the default layout.
+        let misspelt = Layout::from_str("Monocel").unwrap();
+        assert!(!manager.command_handler(&Command::SetLayout(misspelt)));
+        assert_eq!(manager.state.workspaces[0].layout, Layout::Grid);
+    }
+
+    #[test]
+    fn toggle_last_layout_should_flip_between_the_last_two_layouts() {
+        let mut manager = Manager::new_test(vec!["1".to_string()]);
+        manager.screen_create_handler(Screen::default());
+        let layout = |manager: &Manager<TestConfig, MockDisplayServer>| {
+            manager.state.workspaces[0].layout.clone()
+        };
+        assert!(!manager.command_handler(&Command::ToggleLastLayout));
+
+        manager.command_handler(&Command::SetLayout(Layout::Fibonacci));
+        manager.command_handler(&Command::SetLayout(Layout::Monocle));
+        manager.command_handler(&Command::ToggleLastLayout);
+        assert_eq!(layout(&manager), Layout::Fibonacci);
+        manager.command_handler(&Command::ToggleLastLayout);
+        assert_eq!(layout(&manager), Layout::Monocle);
+    }
+
+    #[test]
+    fn toggle_zoom_should_restore_layout_and_window_order() {
+        let mut manager = Manager::new_test(vec!["1".to_string()]);
+        manager.screen_create_handler(Screen::default());
+        for i in 1..=3 {
+            manager.window_created_handler(
+                Window::new(WindowHandle::MockHandle(i), None, None),
+                -1,
+                -1,
+            );
+        }
+        let layout = manager.state.workspaces[0].layout.clone();
+        let order: Vec<WindowHandle> = manager.state.windows.iter().map(|w| w.handle).collect();
+        let focused = order[2];
+        manager.state.focus_window(&focused);
+
+        manager.command_handler(&Command::ToggleZoom);
+        assert_eq!(manager.state.workspaces[0].layout, Layout::Monocle);
+        assert_eq!(manager.state.windows[0].handle, focused);
+        assert!(manager.state.workspaces[0].zoom.is_some());
+
+        manager.command_handler(&Command::ToggleZoom);
+        assert_eq!(manager.state.workspaces[0].layout, layout);
+        assert_eq!(manager.state.tags.get(1).unwrap().layout, layout);
+        let restored: Vec<WindowHandle> = manager.state.windows.iter().map(|w| w.handle).collect();
+        assert_eq!(restored, order);
+        assert!(manager.state.workspaces[0].zoom.is_none());
+    }
+
+    #[test]
+    fn toggle_dock_should_hide_docks_and_release_their_struts() {
+        let mut manager = Manager::new_test(vec!["1".to_string()]);
+        manager.screen_create_handler(Screen::default());
+        let mut dock = Window::new(WindowHandle::MockHandle(1), None, None);
+        dock.r#type = WindowType::Dock;
+        dock.strut = Some(
+            XyhwBuilder {
+                h: 20,
+                w: 800,
+                ..XyhwBuilder::default()
+            }
+            .into(),
+        );
+        manager.state.windows.push(dock);
+        manager.update_workspace_avoid_list();
+        let height = manager.state.workspaces[0].height();
+
+        manager.command_handler(&Command::ToggleDock);
+        manager.update_windows();
+        assert!(!manager.state.windows[0].visible());
+        assert_eq!(manager.state.workspaces[0].height(), height + 20);
+
+        manager.command_handler(&Command::ToggleDock);
+        manager.update_windows();
+        assert!(manager.state.windows[0].visible());
+        assert_eq!(manager.state.workspaces[0].height(), height);
+    }
+
+    #[test]
+    fn close_all_on_tag_should_only_close_windows_once_confirmed() {
+        let mut manager = Manager::new_test(vec!["1".to_string(), "2".to_string()]);
+        manager.screen_create_handler(Screen::default());
+        for i in 1..=2 {
+            manager.window_created_handler(
+                Window::new(WindowHandle::MockHandle(i), None, None),
+                -1,
+                -1,
+            );
+        }
+        let mut other = Window::new(WindowHandle::MockHandle(3), None, None);
+        other.tag(&2);
+        manager.state.windows.push(other);
+        let asked = |state: &State| -> Vec<WindowHandle> {
+            state
+                .actions
+                .iter()
+                .filter_map(|a| match a {
+                    DisplayAction::DeleteWindow(h) => Some(*h),
+                    _ => None,
+                })
+                .collect()
+        };
+        manager.state.actions.clear();
+
+        manager.command_handler(&Command::CloseAllOnTag);
+        assert!(asked(&manager.state).is_empty());
+        // Another command in between cancels it.
+        manager.command_handler(&Command::FocusWindowDown);
+        manager.command_handler(&Command::CloseAllOnTag);
+        assert!(asked(&manager.state).is_empty());
+
+        manager.command_handler(&Command::CloseAllOnTag);
+        let closed = asked(&manager.state);
+        assert_eq!(
+            closed,
+            vec![WindowHandle::MockHandle(1), WindowHandle::MockHandle(2)]
+        );
+    }
+
+    #[test]
+    fn commands_which_worked_should_not_be_reported_as_failures() {
+        let mut manager = Manager::new_test(vec!["1".to_string(), "2".to_string()]);
+        for x in [0, 400] {
+            manager.screen_create_handler(Screen::new(BBox {
+                x,
+                y: 0,
+                width: 400,
+                height: 600,
+            }));
+        }
+        for i in 1..=2 {
+            manager.window_created_handler(
+                Window::new(WindowHandle::MockHandle(i), None, None),
+                -1,
+                -1,
+            );
+        }
+        manager.update_windows();
+        manager
+            .state
+            .handle_window_focus(&WindowHandle::MockHandle(1));
+
+        let right = Command::FocusWindowInDirection(Direction::Right);
+        assert!(manager.checked_command_handler(&right).is_some());
+        let focused = manager.state.focus_manager.window(&manager.state.windows);
+        assert_eq!(focused.map(|w| w.handle), Some(WindowHandle::MockHandle(2)));
+        let top = Command::FocusWindowTop { swap: false };
+        assert!(manager.checked_command_handler(&top).is_some());
+        let moved = manager.checked_command_handler(&Command::MouseMoveWindow);
+        assert!(moved.is_some());
+
+        manager.state.actions.clear();
+        let closed = manager.checked_command_handler(&Command::CloseWindow);
+        assert_eq!(closed, Some(false));
+        assert!(manager
+            .state
+            .actions
+            .iter()
+            .any(|a| matches!(a, DisplayAction::KillWindow(WindowHandle::MockHandle(1)))));
+        for _ in 0..2 {
+            let closed = manager.checked_command_handler(&Command::CloseAllOnTag);
+            assert_eq!(closed, Some(false));
+        }
+
+        let focused = manager
+            .state
+            .focus_manager
+            .workspace_history
+            .front()
+            .copied();
+        let next = manager.checked_command_handler(&Command::FocusWorkspaceNext);
+        assert!(next.is_some());
+        let now = manager
+            .state
+            .focus_manager
+            .workspace_history
+            .front()
+            .copied();
+        assert_ne!(now, focused);
+    }
+
+    #[test]
+    fn toggle_window_tag_should_show_windows_on_several_tags() {
+        let mut manager = Manager::new_test(vec!["1".to_string(), "web".to_string()]);
+        manager.screen_create_handler(Screen::default());
+        manager.window_created_handler(
+            Window::new(WindowHandle::MockHandle(1), None, None),
+            -1,
+            -1,
+        );
+
+        assert!(manager.command_handler(&Command::ToggleWindowLabelledTag("web".to_string())));
+        assert_eq!(manager.state.windows[0].tags, [1, 2]);
+        manager.command_handler(&Command::GoToTag {
+            tag: 2,
+            swap: false,
+        });
+        manager.update_windows();
+        assert!(manager.state.windows[0].visible());
+
+        assert!(manager.command_handler(&Command::ToggleWindowTag(1)));
+        assert_eq!(manager.state.windows[0].tags, [2]);
+        // The last tag of a window stays.
+        assert!(!manager.command_handler(&Command::ToggleWindowTag(2)));
+        assert_eq!(manager.state.windows[0].tags, [2]);
+    }
+
+    #[test]
+    fn focus_window_in_direction_should_focus_the_neighbouring_window() {
+        let mut manager = Manager::new_test(vec![]);
+        manager.screen_create_handler(Screen::default());
+        for i in 1..=2 {
+            manager.window_created_handler(
+                Window::new(WindowHandle::MockHandle(i), None, None),
+                -1,
+                -1,
+            );
+        }
+        manager.update_windows();
+        let focused = |state: &State| state.focus_manager.window(&state.windows).map(|w| w.handle);
+        manager
+            .state
+            .handle_window_focus(&WindowHandle::MockHandle(1));
+
+        manager.command_handler(&Command::FocusWindowInDirection(Direction::Right));
+        assert_eq!(focused(&manager.state), Some(WindowHandle::MockHandle(2)));
+        // Nothing lies further right, so the focus stays.
+        manager.command_handler(&Command::FocusWindowInDirection(Direction::Right));
+        assert_eq!(focused(&manager.state), Some(WindowHandle::MockHandle(2)));
+        manager.command_handler(&Command::FocusWindowInDirection(Direction::Left));
+        assert_eq!(focused(&manager.state), Some(WindowHandle::MockHandle(1)));
+    }
+
+    #[test]
+    fn exit_should_only_be_requested_by_the_exit_command() {
+        let mut manager = Manager::new_test(vec![]);
+        manager.command_handler(&Command::HardReload);
+        assert!(!manager.exit_requested);
+        manager.command_handler(&Command::Exit);
+        assert!(manager.exit_requested);
+    }
+
+    #[test]
+    fn move_window_in_direction_should_swap_then_move_to_the_next_screen() {
+        let mut manager = Manager::new_test(vec!["1".to_string(), "2".to_string()]);
+        manager.screen_create_handler(Screen::default());
+        manager.screen_create_handler(Screen::new(BBox {
+            x: 800,
+            y: 0,
+            width: 800,
+            height: 600,
+        }));
+        for i in 1..=2 {
+            manager.window_created_handler(
+                Window::new(WindowHandle::MockHandle(i), None, None),
+                -1,
+                -1,
+            );
+        }
+        manager.update_windows();
+        let first = WindowHandle::MockHandle(1);
+        manager.state.handle_window_focus(&first);
+
+        manager.command_handler(&Command::MoveWindowInDirection(Direction::Right));
+        manager.update_windows();
+        assert_eq!(manager.state.windows[1].handle, first);
+        assert!(manager.state.windows[1].x() > manager.state.windows[0].x());
+
+        manager.command_handler(&Command::MoveWindowInDirection(Direction::Right));
+        let moved = manager.state.windows.iter().find(|w| w.handle == first);
+        assert_eq!(moved.map(|w| w.tags.clone()), Some(vec![2]));
+    }
+
+    #[test]
+    fn swap_window_with_master_should_swap_places() {
+        let mut manager = Manager::new_test(vec![]);
+        manager.screen_create_handler(Screen::default());
+        for i in 1..=3 {
+            manager.window_created_handler(
+                Window::new(WindowHandle::MockHandle(i), None, None),
+                -1,
+                -1,
+            );
+        }
+        let order = |state: &State| -> Vec<WindowHandle> {
+            state.windows.iter().map(|w| w.handle).collect()
+        };
+        manager
+            .state
+            .handle_window_focus(&WindowHandle::MockHandle(3));
+
+        manager.command_handler(&Command::SwapWindowWithMaster);
+        assert_eq!(
+            order(&manager.state),
+            vec![
+                WindowHandle::MockHandle(3),
+                WindowHandle::MockHandle(2),
+                WindowHandle::MockHandle(1)
+            ]
+        );
+        // The master window is swapped with the next one.
+        manager.command_handler(&Command::SwapWindowWithMaster);
+        assert_eq!(
+            order(&manager.state),
+            vec![
+                WindowHandle::MockHandle(2),
+                WindowHandle::MockHandle(3),
+                WindowHandle::MockHandle(1)
+            ]
+        );
+    }
+
+    #[test]
+    fn window_list_names_the_output_and_tags_of_windows() {
+        let mut manager = Manager::new_test(vec!["1".to_string(), "2".to_string()]);
+        manager.screen_create_handler(Screen {
+            output: Some("DP-1".to_string()),
+            ..Screen::default()
+        });
+        manager.window_created_handler(
+            Window::new(WindowHandle::MockHandle(1), None, None),
+            -1,
+            -1,
+        );
+        let list = WindowList::from(&manager.state);
+        assert_eq!(list.outputs.len(), 1);
+        assert_eq!(list.outputs[0].name.as_deref(), Some("DP-1"));
+        assert_eq!(list.windows.len(), 1);
+        assert_eq!(list.windows[0].window_id, None);
+        assert_eq!(list.windows[0].tags, vec!["1".to_string()]);
+        assert_eq!(list.windows[0].workspace, Some(0));
+        assert_eq!(list.windows[0].output.as_deref(), Some("DP-1"));
+    }
+
+    #[test]
+    fn window_list_should_be_ordered_by_tag_then_stack() {
+        let mut manager = Manager::new_test(vec!["1".to_string(), "2".to_string()]);
+        manager.screen_create_handler(Screen::default());
+        for i in 1..=4 {
+            manager.window_created_handler(
+                Window::new(WindowHandle::MockHandle(i), None, None),
+                -1,
+                -1,
+            );
+        }
+        for window in &mut manager.state.windows {
+            if matches!(window.handle, WindowHandle::MockHandle(1 | 3)) {
+                window.tags = vec![2];
+            }
+        }
+        let stack: Vec<WindowHandle> = manager.state.windows.iter().map(|w| w.handle).collect();
+        let list = WindowList::from(&manager.state);
+        let listed: Vec<(WindowHandle, usize)> = list
+            .windows
+            .iter()
+            .map(|w| (w.handle, w.stack_index))
+            .collect();
+        let on_tag = |tag: &[i32]| {
+            stack
+                .iter()
+                .filter(|h| matches!(h, WindowHandle::MockHandle(i) if tag.contains(i)))
+                .enumerate()
+                .map(|(index, &h)| (h, index))
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(listed, [on_tag(&[2, 4]), on_tag(&[1, 3])].concat());
+    }
+
+    #[test]
+    fn rotate_stack_should_keep_the_focused_slot() {
+        let mut manager = Manager::new_test(vec![]);
+        manager.screen_create_handler(Screen::default());
+        for i in 1..=3 {
+            manager.window_created_handler(
+                Window::new(WindowHandle::MockHandle(i), None, None),
+                -1,
+                -1,
+            );
+        }
+        manager
+            .state
+            .handle_window_focus(&WindowHandle::MockHandle(1));
+
+        manager.command_handler(&Command::RotateStackForward);
+        let order: Vec<WindowHandle> = manager.state.windows.iter().map(|w| w.handle).collect();
+        assert_eq!(
+            order,
+            vec![
+                WindowHandle::MockHandle(3),
+                WindowHandle::MockHandle(1),
+                WindowHandle::MockHandle(2)
+            ]
+        );
+        let focused = manager.state.focus_manager.window(&manager.state.windows);
+        assert_eq!(focused.map(|w| w.handle), Some(WindowHandle::MockHandle(3)));
+
+        manager.command_handler(&Command::RotateStackBackward);
+        assert_eq!(manager.state.windows[0].handle, WindowHandle::MockHandle(1));
+    }
 }
@@ -8,15 +8,23 @@ impl<C: Config, SERVER: DisplayServer> Manager<C, SERVER> {
     /// Process a collection of events, and apply them changes to a manager.
     /// Returns true if changes need to be rendered.
     pub fn display_event_handler(&mut self, event: DisplayEvent) -> bool {
+        if let Some(time) = event.time() {
+            self.state.event_time = time;
+        }
         match event {
             DisplayEvent::ScreenCreate(s) => self.screen_create_handler(s),
+            DisplayEvent::OutputsChange(outputs) => self.load_profile_of_outputs(&outputs),
             DisplayEvent::WindowCreate(w, x, y) => self.window_created_handler(w, x, y),
             DisplayEvent::WindowChange(w) => self.window_changed_handler(w),
-            DisplayEvent::WindowTakeFocus(handle) => {
-                self.state.focus_window(&handle);
+            DisplayEvent::WindowTakeFocus(handle, _) => {
+                // Sent as the mouse enters a window with `Sloppy` focus.
+                if !self.state.in_game_mode() {
+                    self.state.focus_window(&handle);
+                }
                 false
             }
-            DisplayEvent::HandleWindowFocus(handle) => {
+            DisplayEvent::FocusTab(handle, _) => self.state.focus_tab(&handle),
+            DisplayEvent::HandleWindowFocus(handle, _) => {
                 self.state.handle_window_focus(&handle);
                 false
             }
@@ -36,7 +44,7 @@ impl<C: Config, SERVER: DisplayServer> Manager<C, SERVER> {
             // This is a request to validate focus. Double check that we are focused on the correct
             // window.
             DisplayEvent::VerifyFocusedAt(handle) => {
-                if self.state.focus_manager.behaviour.is_sloppy() {
+                if self.state.focus_follows_mouse() {
                     self.state.validate_focus_at(&handle);
                 }
                 false
@@ -44,7 +52,7 @@ impl<C: Config, SERVER: DisplayServer> Manager<C, SERVER> {
 
             DisplayEvent::WindowDestroy(handle) => self.window_destroyed_handler(&handle),
 
-            DisplayEvent::KeyCombo(mod_mask, xkeysym) => {
+            DisplayEvent::KeyCombo(mod_mask, xkeysym, _) => {
                 //look through the config and build a command if its defined in the config
                 let build = CommandBuilder::<C>::new(&self.config);
                 let command = build.xkeyevent(mod_mask, xkeysym);
@@ -53,13 +61,17 @@ impl<C: Config, SERVER: DisplayServer> Manager<C, SERVER> {
 
             DisplayEvent::SendCommand(command) => self.command_handler(&command),
 
-            DisplayEvent::MouseCombo(mod_mask, button, handle, x, y) => self
+            DisplayEvent::MouseCombo(mod_mask, button, handle, x, y, _) => self
                 .state
                 .mouse_combo_handler(mod_mask, button, handle, x, y),
 
             DisplayEvent::ChangeToNormalMode => {
                 match self.state.mode {
-                    Mode::MovingWindow(h) | Mode::ResizingWindow(h) => {
+                    Mode::MovingWindow(h) => {
+                        self.state.finish_tile_drag(h);
+                        self.state.focus_window(&h);
+                    }
+                    Mode::ResizingWindow(h) => {
                         self.state.focus_window(&h);
                     }
                     _ => {}
@@ -68,8 +80,9 @@ impl<C: Config, SERVER: DisplayServer> Manager<C, SERVER> {
                 true
             }
 
-            DisplayEvent::Movement(handle, x, y) => {
-                if self.state.screens.iter().any(|s| s.root == handle) {
+            DisplayEvent::Movement(handle, x, y, _) => {
+                if self.state.screens.iter().any(|s| s.root == handle) && !self.state.in_game_mode()
+                {
                     self.state.focus_workspace_under_cursor(x, y);
                 }
                 false
@@ -79,6 +92,11 @@ impl<C: Config, SERVER: DisplayServer> Manager<C, SERVER> {
                 // Setup for when window first moves.
                 if let Mode::ReadyToMove(h) = self.state.mode {
                     self.state.mode = Mode::MovingWindow(h);
+                    self.state.dragging_tile = self
+                        .state
+                        .windows
+                        .iter()
+                        .any(|w| w.handle == h && !w.floating());
                     prepare_window(&mut self.state, h);
                 }
                 self.window_move_handler(&handle, x, y)
@@ -1,18 +1,23 @@
 #![allow(clippy::wildcard_imports)]
 
 use super::*;
+use crate::display_action::DisplayAction;
 use crate::models::TagId;
 use crate::state::State;
-use crate::{display_action::DisplayAction, models::FocusBehaviour};
+use crate::utils::helpers;
 
 impl State {
     pub fn handle_window_focus(&mut self, handle: &WindowHandle) {
-        match self.focus_manager.behaviour {
-            FocusBehaviour::Sloppy => {
+        if self.focus_manager.behaviour.is_sloppy() {
+            let act = DisplayAction::MoveMouseOver(*handle, false);
+            self.actions.push_back(act);
+        } else {
+            self.focus_window(handle);
+            // Keep scroll events going to the focused window.
+            if self.focus_manager.warp_cursor {
                 let act = DisplayAction::MoveMouseOver(*handle, false);
                 self.actions.push_back(act);
             }
-            _ => self.focus_window(handle),
         }
     }
 
@@ -43,6 +48,28 @@ impl State {
         }
     }
 
+    /// Brings the window of a clicked tab to the front of its tag, and focuses it. Returns
+    /// whether the window was found.
+    pub fn focus_tab(&mut self, handle: &WindowHandle) -> bool {
+        let tag_id = match self.windows.iter().find(|w| &w.handle == handle) {
+            Some(window) => window.tags.first().copied(),
+            None => return false,
+        };
+        if let Some(tag_id) = tag_id {
+            let mut windows = helpers::vec_extract(&mut self.windows, |w| {
+                w.has_tag(&tag_id) && !w.is_unmanaged() && !w.floating()
+            });
+            let index = windows
+                .iter()
+                .position(|w| &w.handle == handle)
+                .unwrap_or(0);
+            let _ = helpers::cycle_vec(&mut windows, -(index as i32));
+            self.windows.append(&mut windows);
+        }
+        self.focus_window(handle);
+        true
+    }
+
     /// Focuses the given workspace.
     // NOTE: Should only be called externally from this file.
     pub fn focus_workspace(&mut self, workspace: &Workspace) {
@@ -103,11 +130,14 @@ impl State {
 
     fn focus_window_work(&mut self, handle: &WindowHandle) -> Option<Window> {
         // Find the handle in our managed windows.
-        let found: &Window = self.windows.iter().find(|w| &w.handle == handle)?;
+        let found: &mut Window = self.windows.iter_mut().find(|w| &w.handle == handle)?;
         // Docks don't want to get focus. If they do weird things happen. They don't get events...
         if found.is_unmanaged() {
             return None;
         }
+        // Focusing a window answers its request for attention.
+        found.clear_urgency();
+        let found: &Window = self.windows.iter().find(|w| &w.handle == handle)?;
         let previous = self.focus_manager.window(&self.windows);
         // No new history if no change.
         if let Some(previous) = previous {
@@ -130,6 +160,7 @@ impl State {
         let act = DisplayAction::WindowTakeFocus {
             window: found.clone(),
             previous_window: previous.cloned(),
+            time: self.event_time,
         };
         self.actions.push_back(act);
 
@@ -174,10 +205,13 @@ impl State {
             Some(fws) => fws.id,
             None => None,
         };
+        let focus_empty = self.focus_manager.sloppy_focus_empty_workspaces;
+        let windows = &self.windows;
         if let Some(w) = self
             .workspaces
             .iter()
             .find(|ws| ws.contains_point(x, y) && ws.id != focused_id)
+            .filter(|ws| focus_empty || windows.iter().any(|w| ws.is_managed(w)))
             .cloned()
         {
             self.focus_workspace(&w);
@@ -261,8 +295,76 @@ fn distance(window: &Window, x: i32, y: i32) -> i32 {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::models::BBox;
     use crate::Manager;
 
+    #[test]
+    fn focus_should_carry_the_time_of_the_event_causing_it() {
+        let mut manager = Manager::new_test(vec!["1".to_string()]);
+        manager.screen_create_handler(Screen::default());
+        for i in 1..=2 {
+            manager.window_created_handler(
+                Window::new(WindowHandle::MockHandle(i), None, None),
+                -1,
+                -1,
+            );
+        }
+        manager.state.focus_window(&WindowHandle::MockHandle(1));
+        manager.state.actions.clear();
+        manager.display_event_handler(crate::DisplayEvent::WindowTakeFocus(
+            WindowHandle::MockHandle(2),
+            4242,
+        ));
+        assert!(manager.state.actions.iter().any(|action| matches!(
+            action,
+            DisplayAction::WindowTakeFocus { window, time: 4242, .. }
+                if window.handle == WindowHandle::MockHandle(2)
+        )));
+    }
+
+    #[test]
+    fn clicking_a_tab_should_bring_its_window_to_the_front() {
+        let mut manager = Manager::new_test(vec!["1".to_string()]);
+        manager.screen_create_handler(Screen::default());
+        for i in 1..=3 {
+            manager.window_created_handler(
+                Window::new(WindowHandle::MockHandle(i), None, None),
+                -1,
+                -1,
+            );
+        }
+        manager.command_handler(&crate::Command::SetLayout(crate::layouts::Layout::Tabbed));
+        manager.update_windows();
+        let order: Vec<WindowHandle> = manager.state.tab_bars[0]
+            .tabs
+            .iter()
+            .map(|tab| tab.handle)
+            .collect();
+        assert_eq!(order.len(), 3);
+
+        assert!(manager.state.focus_tab(&order[2]));
+        manager.update_windows();
+        let bar = &manager.state.tab_bars[0];
+        let tabs: Vec<(WindowHandle, bool)> = bar
+            .tabs
+            .iter()
+            .map(|tab| (tab.handle, tab.active))
+            .collect();
+        assert_eq!(
+            tabs,
+            vec![(order[0], false), (order[1], false), (order[2], true)]
+        );
+        assert!(manager
+            .state
+            .windows
+            .iter()
+            .any(|w| w.handle == order[2] && w.visible()));
+        assert!(matches!(
+            manager.state.actions.back(),
+            Some(DisplayAction::UpdateTabBars(_))
+        ));
+    }
+
     #[test]
     fn focusing_a_workspace_should_make_it_active() {
         let mut manager = Manager::new_test(vec![]);
@@ -468,4 +570,66 @@ mod tests {
         let focused = manager.state.focus_manager.window(&manager.state.windows);
         assert!(focused.is_none());
     }
+
+    #[test]
+    fn hovering_an_empty_workspace_can_be_kept_from_focusing_it() {
+        let mut manager = Manager::new_test(vec![]);
+        manager.screen_create_handler(Screen::default());
+        manager.screen_create_handler(Screen::new(BBox {
+            x: 800,
+            y: 0,
+            width: 800,
+            height: 600,
+        }));
+        manager
+            .state
+            .focus_workspace(&manager.state.workspaces[0].clone());
+
+        manager.state.focus_manager.sloppy_focus_empty_workspaces = false;
+        manager.state.focus_workspace_under_cursor(1000, 300);
+        let focused = |manager: &Manager<_, _>| {
+            manager
+                .state
+                .focus_manager
+                .workspace(&manager.state.workspaces)
+                .unwrap()
+                .id
+        };
+        assert_eq!(focused(&manager), Some(0));
+
+        manager.state.focus_manager.sloppy_focus_empty_workspaces = true;
+        manager.state.focus_workspace_under_cursor(1000, 300);
+        assert_eq!(focused(&manager), Some(1));
+    }
+
+    #[test]
+    fn keyboard_focus_warps_the_cursor_only_if_enabled() {
+        let mut manager = Manager::new_test(vec![]);
+        manager.screen_create_handler(Screen::default());
+        for i in 1..=2 {
+            manager.window_created_handler(
+                Window::new(WindowHandle::MockHandle(i), None, None),
+                -1,
+                -1,
+            );
+        }
+        let warps = |state: &State| {
+            state
+                .actions
+                .iter()
+                .any(|a| matches!(a, DisplayAction::MoveMouseOver(..)))
+        };
+
+        manager.state.actions.clear();
+        manager
+            .state
+            .handle_window_focus(&WindowHandle::MockHandle(1));
+        assert!(!warps(&manager.state));
+
+        manager.state.focus_manager.warp_cursor = true;
+        manager
+            .state
+            .handle_window_focus(&WindowHandle::MockHandle(2));
+        assert!(warps(&manager.state));
+    }
 }
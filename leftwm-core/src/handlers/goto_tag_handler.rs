@@ -1,9 +1,29 @@
-use crate::{models::TagId, state::State};
+use crate::display_action::DisplayAction;
+use crate::models::{TagId, WindowHandle};
+use crate::state::State;
 
 impl State {
     pub fn goto_tag_handler(&mut self, tag_num: TagId) -> Option<bool> {
         if tag_num > self.tags.len_normal() || tag_num < 1 {
-            return Some(false);
+            return None;
+        }
+        // The workspaces in the overview give the tags they borrowed back first.
+        for index in 0..self.workspaces.len() {
+            self.leave_overview(index);
+        }
+
+        // A tag of another workspace is shown where it belongs, and without greedy view a tag
+        // shown on another workspace stays there.
+        let home = if self.per_screen_tags {
+            let owner = self.tags.get(tag_num)?.workspace_id;
+            self.workspaces.iter().find(|ws| ws.id == owner)
+        } else if self.greedy_view {
+            None
+        } else {
+            self.workspaces.iter().find(|ws| ws.has_tag(&tag_num))
+        };
+        if let Some(workspace) = home.cloned() {
+            self.focus_workspace(&workspace);
         }
 
         //let tag_id = self.tags[tag_num - 1].label.clone();
@@ -18,23 +38,99 @@ impl State {
                 .or_insert(handle);
             *old_handle = handle;
         }
-        if let Some(ws) = self.workspaces.iter_mut().find(|ws| ws.tags == new_tags) {
-            ws.tags = old_tags;
+        // Tags swapped with another workspace don't slide, the windows stay on screen.
+        let slide = self.tag_slide_duration > 0
+            && !self.reduce_motion
+            && old_tags != new_tags
+            && !self.workspaces.iter().any(|ws| ws.has_tag(&tag_num));
+        // Another workspace showing the tag gets the old tags, or keeps the other tags it shows.
+        let focused = self.focus_manager.workspace_history.front().copied();
+        let given: Vec<TagId> = old_tags.iter().copied().filter(|&t| t != tag_num).collect();
+        let depth = self.tag_history_depth;
+        for (i, ws) in self.workspaces.iter_mut().enumerate() {
+            if Some(i) != focused && ws.has_tag(&tag_num) {
+                let mut tags: Vec<TagId> =
+                    ws.tags.iter().copied().filter(|&t| t != tag_num).collect();
+                if tags.is_empty() {
+                    tags.clone_from(&given);
+                }
+                ws.show_tags(tags, depth);
+            }
         }
 
-        self.focus_manager.workspace_mut(&mut self.workspaces)?.tags = new_tags;
+        let workspace = self.focus_manager.workspace_mut(&mut self.workspaces)?;
+        if workspace.tags != new_tags {
+            workspace.show_tags(new_tags, depth);
+        }
         self.focus_tag(&tag_num);
         self.update_static();
         self.layout_manager
             .update_layouts(&mut self.workspaces, self.tags.all_mut());
+        if slide {
+            self.slide_tags(&old_tags, tag_num);
+        }
         Some(true)
     }
+
+    /// Slide the windows of the old tags out of the focused workspace and those of the new tag
+    /// in, from the right when going to a later tag.
+    fn slide_tags(&mut self, old_tags: &[TagId], tag_num: TagId) {
+        let width = match self.focus_manager.workspace(&self.workspaces) {
+            Some(ws) => ws.xyhw.w(),
+            None => return,
+        };
+        let distance = if old_tags.iter().all(|&tag| tag < tag_num) {
+            width
+        } else {
+            -width
+        };
+        // Sticky windows and docks stay where they are.
+        let sliding = |tags: &[TagId], others: &[TagId]| -> Vec<WindowHandle> {
+            self.windows
+                .iter()
+                .filter(|w| !w.is_sticky() && w.strut.is_none())
+                .filter(|w| tags.iter().any(|tag| w.has_tag(tag)))
+                .filter(|w| !others.iter().any(|tag| w.has_tag(tag)))
+                .map(|w| w.handle)
+                .collect()
+        };
+        let action = DisplayAction::SlideWindows {
+            entering: sliding(&[tag_num], old_tags),
+            leaving: sliding(old_tags, &[tag_num]),
+            distance,
+            duration: self.tag_slide_duration,
+        };
+        self.actions.push_back(action);
+    }
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::models::Screen;
-    use crate::Manager;
+    use crate::display_action::DisplayAction;
+    use crate::layouts::Layout;
+    use crate::models::{LayoutMode, Screen, Window, WindowHandle};
+    use crate::{Command, Manager};
+
+    #[test]
+    fn tags_should_keep_their_layouts_in_tag_mode() {
+        let mut manager = Manager::new_test(vec!["1".to_string(), "2".to_string()]);
+        manager.state.layout_manager.mode = LayoutMode::Tag;
+        manager.screen_create_handler(Screen::default());
+        manager.command_handler(&Command::SetLayout(Layout::Monocle));
+        manager.command_handler(&Command::IncreaseMainWidth(10.0));
+        manager.command_handler(&Command::IncreaseMainCount);
+        let main_width = manager.state.workspaces[0].main_width_percentage;
+
+        manager.state.goto_tag_handler(2);
+        manager.command_handler(&Command::SetLayout(Layout::Fibonacci));
+        manager.state.goto_tag_handler(1);
+        let workspace = &manager.state.workspaces[0];
+        assert_eq!(workspace.layout, Layout::Monocle);
+        assert!((workspace.main_width_percentage - main_width).abs() < f32::EPSILON);
+        assert_eq!(manager.state.tags.get(1).unwrap().main_count(), 2);
+        manager.state.goto_tag_handler(2);
+        assert_eq!(manager.state.workspaces[0].layout, Layout::Fibonacci);
+    }
 
     #[test]
     fn going_to_a_workspace_that_is_already_visible_should_not_duplicate_the_workspace() {
@@ -45,4 +141,56 @@ mod tests {
         assert_eq!(manager.state.workspaces[0].tags, [2]);
         assert_eq!(manager.state.workspaces[1].tags, [1]);
     }
+
+    #[test]
+    fn going_to_a_visible_tag_without_greedy_view_should_focus_its_workspace() {
+        let mut manager = Manager::new_test(vec!["1".to_string(), "2".to_string()]);
+        manager.state.greedy_view = false;
+        manager.screen_create_handler(Screen::default());
+        manager.screen_create_handler(Screen::default());
+        manager.state.goto_tag_handler(1);
+        assert_eq!(manager.state.workspaces[0].tags, [1]);
+        assert_eq!(manager.state.workspaces[1].tags, [2]);
+        let focused = manager
+            .state
+            .focus_manager
+            .workspace(&manager.state.workspaces);
+        assert_eq!(focused.and_then(|ws| ws.id), manager.state.workspaces[0].id);
+    }
+
+    #[test]
+    fn switching_tags_should_slide_the_windows_when_enabled() {
+        let mut manager = Manager::new_test(vec!["1".to_string(), "2".to_string()]);
+        manager.state.tag_slide_duration = 150;
+        manager.screen_create_handler(Screen::default());
+        let handle = WindowHandle::MockHandle(1);
+        manager.window_created_handler(Window::new(handle, None, None), -1, -1);
+        manager.state.actions.clear();
+
+        manager.state.goto_tag_handler(2);
+        let slide = manager
+            .state
+            .actions
+            .iter()
+            .find_map(|action| match action {
+                DisplayAction::SlideWindows {
+                    entering,
+                    leaving,
+                    distance,
+                    ..
+                } => Some((entering.clone(), leaving.clone(), *distance)),
+                _ => None,
+            });
+        assert_eq!(slide, Some((vec![], vec![handle], 800)));
+
+        // Reducing motion turns the sliding off.
+        manager.state.reduce_motion = true;
+        manager.state.actions.clear();
+        manager.state.goto_tag_handler(1);
+        assert!(!manager
+            .state
+            .actions
+            .iter()
+            .any(|action| matches!(action, DisplayAction::SlideWindows { .. })));
+    }
 }
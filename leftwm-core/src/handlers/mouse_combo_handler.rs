@@ -15,16 +15,33 @@ impl State {
         handle: WindowHandle,
         x: i32,
         y: i32,
+    ) -> bool {
+        let needs_update = self.handle_mouse_combo(modmask, button, handle, x, y);
+        // In `ClickTo` the grab on unfocused windows froze the pointer, which is let go of
+        // whatever the click did, unless the focusing click is replayed.
+        let replayed = matches!(self.actions.back(), Some(DisplayAction::ReplayClick(..)));
+        if self.focus_manager.behaviour.is_clickto() && !replayed {
+            self.actions.push_back(DisplayAction::ReleasePointer);
+        }
+        needs_update
+    }
+
+    fn handle_mouse_combo(
+        &mut self,
+        modmask: ModMask,
+        button: Button,
+        handle: WindowHandle,
+        x: i32,
+        y: i32,
     ) -> bool {
         if let Some(window) = self.windows.iter().find(|w| w.handle == handle) {
-            if !self.disable_tile_drag || window.floating() {
-                let modifier = utils::xkeysym_lookup::into_modmask(&self.mousekey);
-                // Build the display to say whether we are ready to move/resize.
-                let act = self.build_action(modmask, button, handle, modifier);
-                if let Some(act) = act {
-                    self.actions.push_back(act);
-                    return false;
-                }
+            let can_drag = !self.disable_tile_drag || window.floating();
+            let modifier = utils::xkeysym_lookup::into_modmask(&self.mousekey);
+            // Build the display to say whether we are ready to move/resize.
+            let act = self.build_action(modmask, button, handle, modifier, can_drag);
+            if let Some(act) = act {
+                self.actions.push_back(act);
+                return false;
             }
         } else if self.focus_manager.behaviour.is_clickto() {
             if let xlib::Button1 | xlib::Button3 = button {
@@ -43,10 +60,12 @@ impl State {
         button: Button,
         window: WindowHandle,
         modifier: ModMask,
+        can_drag: bool,
     ) -> Option<DisplayAction> {
         let is_mouse_key = mod_mask == modifier || mod_mask == (modifier | xlib::ShiftMask);
+        let is_drag = is_mouse_key && can_drag;
         match button {
-            xlib::Button1 if is_mouse_key => {
+            xlib::Button1 if is_drag => {
                 let _ = self
                     .windows
                     .iter()
@@ -54,7 +73,7 @@ impl State {
                 self.mode = Mode::ReadyToMove(window);
                 Some(DisplayAction::ReadyToMoveWindow(window))
             }
-            xlib::Button3 if is_mouse_key => {
+            xlib::Button3 if is_drag => {
                 let _ = self
                     .windows
                     .iter()
@@ -62,6 +81,7 @@ impl State {
                 self.mode = Mode::ReadyToResize(window);
                 Some(DisplayAction::ReadyToResizeWindow(window))
             }
+            // The click is frozen by the grab on unfocused windows until it is replayed.
             xlib::Button1 | xlib::Button3 if self.focus_manager.behaviour.is_clickto() => {
                 self.focus_window(&window);
                 Some(DisplayAction::ReplayClick(window, button))
@@ -70,3 +90,63 @@ impl State {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{FocusBehaviour, Screen};
+    use crate::{Manager, Window};
+
+    fn clicked(behaviour: FocusBehaviour, handle: WindowHandle) -> Vec<DisplayAction> {
+        let mut manager = Manager::new_test(vec!["1".to_string()]);
+        manager.state.focus_manager.behaviour = behaviour;
+        manager.screen_create_handler(Screen::default());
+        for window in 1..=2 {
+            let window = Window::new(WindowHandle::MockHandle(window), None, None);
+            manager.window_created_handler(window, -1, -1);
+        }
+        manager.state.actions.clear();
+        manager
+            .state
+            .mouse_combo_handler(0, xlib::Button1, handle, 0, 0);
+        manager.state.actions.into_iter().collect()
+    }
+
+    fn releases_pointer(actions: &[DisplayAction]) -> bool {
+        actions.iter().any(|action| {
+            matches!(
+                action,
+                DisplayAction::ReplayClick(..) | DisplayAction::ReleasePointer
+            )
+        })
+    }
+
+    #[test]
+    fn focusing_clicks_should_be_replayed_in_click_to_focus() {
+        let actions = clicked(FocusBehaviour::ClickTo, WindowHandle::MockHandle(1));
+        assert!(matches!(
+            actions.last(),
+            Some(DisplayAction::ReplayClick(..))
+        ));
+        assert!(!actions
+            .iter()
+            .any(|action| matches!(action, DisplayAction::ReleasePointer)));
+    }
+
+    #[test]
+    fn other_clicks_should_let_go_of_the_pointer_in_click_to_focus() {
+        let actions = clicked(FocusBehaviour::ClickTo, WindowHandle::MockHandle(9));
+        assert!(matches!(
+            actions.last(),
+            Some(DisplayAction::ReleasePointer)
+        ));
+    }
+
+    #[test]
+    fn clicks_should_not_touch_the_pointer_in_other_modes() {
+        for behaviour in [FocusBehaviour::Sloppy, FocusBehaviour::Driven] {
+            let actions = clicked(behaviour, WindowHandle::MockHandle(1));
+            assert!(!releases_pointer(&actions), "{behaviour:?}");
+        }
+    }
+}
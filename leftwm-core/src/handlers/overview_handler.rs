@@ -0,0 +1,120 @@
+use crate::display_action::DisplayAction;
+use crate::models::TagId;
+use crate::state::State;
+
+impl State {
+    /// Show the windows of every tag the focused workspace can show, and no other workspace
+    /// does, in a grid until one is picked. In the overview already, go back to the tags shown
+    /// before it.
+    pub fn toggle_overview(&mut self) -> Option<bool> {
+        let index = *self.focus_manager.workspace_history.front()?;
+        if self.leave_overview(index) {
+            return Some(true);
+        }
+        let ws = self.workspaces.get(index)?;
+        let own = self.listed_tags(&self.workspace_tags(ws));
+        let mut tags = ws.tags.clone();
+        tags.extend(
+            own.into_iter()
+                .filter(|t| !self.workspaces.iter().any(|ws| ws.has_tag(t))),
+        );
+        let ws = self.workspaces.get_mut(index)?;
+        ws.overview = Some(std::mem::replace(&mut ws.tags, tags.clone()));
+        // Clicks pick the window under the cursor.
+        self.actions.push_back(DisplayAction::PickWindow);
+        self.actions.push_back(DisplayAction::SetCurrentTags(tags));
+        self.update_static();
+        Some(true)
+    }
+
+    /// Take the workspace at `index` out of the overview. Returns whether it was in it.
+    pub fn leave_overview(&mut self, index: usize) -> bool {
+        match self
+            .workspaces
+            .get_mut(index)
+            .and_then(|ws| ws.overview.take())
+        {
+            Some(saved) => {
+                self.restore_tags(index, saved);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Show the tags of `saved` on the workspace at `index` again, those no other workspace took
+    /// meanwhile.
+    fn restore_tags(&mut self, index: usize, saved: Vec<TagId>) {
+        let shown_elsewhere = |t: &TagId| {
+            self.workspaces
+                .iter()
+                .enumerate()
+                .any(|(i, ws)| i != index && ws.has_tag(t))
+        };
+        let mut tags: Vec<TagId> = saved
+            .into_iter()
+            .filter(|t| self.tags.get(*t).is_some() && !shown_elsewhere(t))
+            .collect();
+        let ws = &mut self.workspaces[index];
+        if tags.is_empty() {
+            tags.extend(ws.tags.first());
+        }
+        ws.tags = tags;
+        let first = ws.tags.first().copied();
+        if let Some(first) = first {
+            self.focus_tag(&first);
+        }
+        self.actions.push_back(DisplayAction::NormalMode);
+        let tags = self.workspaces[index].tags.clone();
+        self.actions.push_back(DisplayAction::SetCurrentTags(tags));
+        self.update_static();
+    }
+
+    /// Whether a workspace shows the overview.
+    #[must_use]
+    pub fn in_overview(&self) -> bool {
+        self.workspaces.iter().any(|ws| ws.overview.is_some())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::models::{Screen, WindowHandle};
+    use crate::{Command, Manager, Window};
+
+    #[test]
+    fn the_overview_should_lay_out_every_tag_and_jump_to_the_picked_window() {
+        let mut manager =
+            Manager::new_test(vec!["1".to_string(), "2".to_string(), "3".to_string()]);
+        manager.screen_create_handler(Screen::default());
+        for tag in 1..=3 {
+            manager.state.goto_tag_handler(tag);
+            manager.window_created_handler(
+                Window::new(WindowHandle::MockHandle(tag as i32), None, None),
+                -1,
+                -1,
+            );
+        }
+        manager.state.goto_tag_handler(1);
+
+        assert!(manager.command_handler(&Command::ToggleOverview));
+        assert_eq!(manager.state.workspaces[0].tags, [1, 2, 3]);
+        manager.update_windows();
+        let visible = manager.state.windows.iter().filter(|w| w.visible()).count();
+        assert_eq!(visible, 3);
+
+        let picked = WindowHandle::MockHandle(3);
+        assert!(manager.command_handler(&Command::PickOverviewWindow(Some(picked))));
+        assert_eq!(manager.state.workspaces[0].tags, [3]);
+        assert!(!manager.state.in_overview());
+        assert_eq!(
+            manager.state.focus_manager.window_history.front(),
+            Some(&Some(picked))
+        );
+
+        // Leaving without picking goes back.
+        manager.command_handler(&Command::ToggleOverview);
+        manager.command_handler(&Command::ToggleOverview);
+        assert_eq!(manager.state.workspaces[0].tags, [3]);
+    }
+}
@@ -0,0 +1,126 @@
+use crate::config::Config;
+use crate::display_servers::DisplayServer;
+use crate::models::dto::{FullState, WindowAt, WindowList};
+use crate::utils::command_socket::{Request, Response};
+use crate::{Command, Manager};
+
+impl<C: Config, SERVER: DisplayServer> Manager<C, SERVER> {
+    /// Answer a request read from the command socket. Returns the answer and whether the windows
+    /// need an update.
+    pub fn request_handler(&mut self, request: &Request) -> (Response, bool) {
+        match request {
+            Request::Command(Command::Execute(_)) => (
+                Response::Error("Execute isn't taken from the command socket".to_string()),
+                false,
+            ),
+            Request::Command(Command::WindowAt(x, y)) | Request::WindowAt { x, y } => (
+                Response::WindowAt(WindowAt::new(&self.state, *x, *y)),
+                false,
+            ),
+            Request::Command(Command::ListWindows) | Request::ListWindows => {
+                (Response::Windows(WindowList::from(&self.state)), false)
+            }
+            Request::Command(command) => match self.checked_command_handler(command) {
+                Some(changed) => (Response::Done, changed),
+                None => (
+                    Response::Error(format!("{command:?} couldn't be carried out")),
+                    false,
+                ),
+            },
+            Request::GetState => (Response::State(FullState::from(&self.state)), false),
+            // The socket takes the client on as a subscriber itself.
+            Request::Subscribe => (Response::Done, false),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{Screen, WindowHandle};
+    use crate::Window;
+
+    #[test]
+    fn requests_should_run_commands_and_answer_queries() {
+        let mut manager = Manager::new_test(vec!["1".to_string(), "2".to_string()]);
+        manager.screen_create_handler(Screen::default());
+        manager.window_created_handler(
+            Window::new(WindowHandle::MockHandle(1), None, None),
+            -1,
+            -1,
+        );
+        manager.update_windows();
+
+        let (response, changed) = manager.request_handler(&Request::Command(Command::GoToTag {
+            tag: 2,
+            swap: false,
+        }));
+        assert!(matches!(response, Response::Done));
+        assert!(changed);
+        manager.update_windows();
+        assert!(matches!(
+            manager.request_handler(&Request::Command(Command::Execute("true".to_string()))),
+            (Response::Error(_), false)
+        ));
+        assert!(matches!(
+            manager.request_handler(&Request::Command(Command::GoToTag {
+                tag: 9,
+                swap: false
+            })),
+            (Response::Error(_), false)
+        ));
+        assert!(matches!(
+            manager.request_handler(&Request::Command(Command::CloseWindow)),
+            (Response::Error(_), false)
+        ));
+        match manager.request_handler(&Request::ListWindows) {
+            (Response::Windows(list), false) => assert_eq!(list.windows.len(), 1),
+            other => panic!("unexpected answer {other:?}"),
+        }
+        match manager.request_handler(&Request::WindowAt { x: 10, y: 10 }) {
+            (Response::WindowAt(answer), false) => assert!(answer.window.is_none()),
+            other => panic!("unexpected answer {other:?}"),
+        }
+        manager.command_handler(&Command::GoToTag {
+            tag: 1,
+            swap: false,
+        });
+        manager.update_windows();
+        assert!(matches!(
+            manager.request_handler(&Request::Command(Command::CloseWindow)),
+            (Response::Done, false)
+        ));
+    }
+
+    #[test]
+    fn the_state_should_tell_what_is_shown_and_focused() {
+        let mut manager = Manager::new_test(vec!["1".to_string(), "2".to_string()]);
+        manager.screen_create_handler(Screen::default());
+        manager.window_created_handler(
+            Window::new(WindowHandle::MockHandle(1), None, None),
+            -1,
+            -1,
+        );
+        manager.command_handler(&Command::ToggleFloating);
+        manager.update_windows();
+
+        let state = match manager.request_handler(&Request::GetState) {
+            (Response::State(state), false) => state,
+            other => panic!("unexpected answer {other:?}"),
+        };
+        assert_eq!(state.screens.len(), 1);
+        assert_eq!(state.workspaces[0].tags, vec![1]);
+        assert_eq!(state.focused_workspace, Some(0));
+        assert_eq!(state.focused_tag, Some(1));
+        assert_eq!(state.focused_window, Some(WindowHandle::MockHandle(1)));
+        let tag = &state.tags[0];
+        assert_eq!((tag.workspace, tag.window_count), (Some(0), 1));
+        assert_eq!(
+            (state.tags[1].workspace, state.tags[1].window_count),
+            (None, 0)
+        );
+        let window = &state.windows[0];
+        assert!(window.visible && window.floating && !window.fullscreen);
+        assert_eq!(window.info.tags, vec!["1".to_string()]);
+    }
+}
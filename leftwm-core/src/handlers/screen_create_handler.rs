@@ -1,6 +1,7 @@
 use super::{Manager, Screen, Workspace};
 use crate::config::Config;
 use crate::display_servers::DisplayServer;
+use crate::models::{TagId, XyhwBuilder};
 
 impl<C: Config, SERVER: DisplayServer> Manager<C, SERVER> {
     /// Process a collection of events, and apply them changes to a manager.
@@ -28,10 +29,18 @@ impl<C: Config, SERVER: DisplayServer> Manager<C, SERVER> {
             dbg!("Workspace ID needs to be less than or equal to the number of tags available.");
         }
         new_workspace.load_config(&self.config);
+        new_workspace.output.clone_from(&screen.output);
+        new_workspace.name.clone_from(&screen.name);
 
         //make sure are enough tags for this new screen
-        let next_id = if tag_len > tag_index {
-            tag_index + 1
+        // The tag matching the new workspace may already be shown on another one.
+        let free_tag = (tag_index + 1..=tag_len)
+            .chain(1..=tag_index.min(tag_len))
+            .find(|tag| !self.state.workspaces.iter().any(|ws| ws.has_tag(tag)));
+        let next_id = if self.state.per_screen_tags {
+            self.create_workspace_tags(workspace_id)
+        } else if let Some(tag) = free_tag {
+            tag
         } else {
             // add a new tag for the workspace
             self.state
@@ -40,7 +49,8 @@ impl<C: Config, SERVER: DisplayServer> Manager<C, SERVER> {
         };
 
         if let Some(tag) = self.state.tags.get_mut(next_id) {
-            tag.layout = new_workspace.layout;
+            tag.layout = new_workspace.layout.clone();
+            tag.use_own_layout();
         }
 
         self.state.focus_workspace(&new_workspace);
@@ -52,11 +62,167 @@ impl<C: Config, SERVER: DisplayServer> Manager<C, SERVER> {
         self.state.focus_workspace(&new_workspace);
         false
     }
+
+    /// Give the workspace of an existing screen the screen's new geometry.
+    ///
+    /// Returns `true` if changes need to be rendered.
+    pub fn screen_update_handler(&mut self, screen: &Screen) -> bool {
+        let xyhw = XyhwBuilder {
+            h: screen.bbox.height,
+            w: screen.bbox.width,
+            x: screen.bbox.x,
+            y: screen.bbox.y,
+            ..XyhwBuilder::default()
+        }
+        .into();
+        let ws = self
+            .state
+            .workspaces
+            .iter_mut()
+            .find(|ws| ws.id.is_some() && ws.id == screen.wsid);
+        let old = match ws {
+            Some(ws) if ws.xyhw != xyhw => std::mem::replace(&mut ws.xyhw, xyhw),
+            _ => return false,
+        };
+        // The workspace was made from the screen, so the screen has its old geometry.
+        let bbox = self.state.screens.iter_mut().find(|s| {
+            (s.bbox.x, s.bbox.y, s.bbox.width, s.bbox.height)
+                == (old.x(), old.y(), old.w(), old.h())
+        });
+        if let Some(s) = bbox {
+            s.bbox = screen.bbox;
+        }
+        self.update_workspace_avoid_list();
+        true
+    }
+
+    /// Remove the workspace of a screen which went away. The windows stay on their tags, which
+    /// the other workspaces can show, and with per-screen tags its tags go to the next workspace
+    /// created.
+    ///
+    /// Returns `true` if changes need to be rendered.
+    pub fn screen_destroy_handler(&mut self, wsid: i32) -> bool {
+        let index = self
+            .state
+            .workspaces
+            .iter()
+            .position(|ws| ws.id == Some(wsid));
+        let (index, removed) = match index {
+            Some(index) => (index, self.state.workspaces.remove(index)),
+            None => return false,
+        };
+        self.state.screens.retain(|s| {
+            (s.bbox.x, s.bbox.y, s.bbox.width, s.bbox.height)
+                != (
+                    removed.xyhw.x(),
+                    removed.xyhw.y(),
+                    removed.xyhw.w(),
+                    removed.xyhw.h(),
+                )
+        });
+        for tag in self.state.tags.all_mut() {
+            if tag.workspace_id == Some(wsid) {
+                tag.workspace_id = None;
+            }
+        }
+        // The history holds indexes into the workspaces.
+        let history = &mut self.state.focus_manager.workspace_history;
+        let was_focused = history.front() == Some(&index);
+        history.retain(|&i| i != index);
+        for i in history.iter_mut().filter(|i| **i > index) {
+            *i -= 1;
+        }
+        if was_focused {
+            history.clear();
+            if let Some(ws) = self.state.workspaces.first().cloned() {
+                self.state.focus_workspace(&ws);
+            }
+        }
+        self.update_workspace_avoid_list();
+        true
+    }
+
+    /// Show the workspaces on the given screens, updating the geometry of the workspaces which
+    /// stay, creating those which are new and removing the others, as when monitors are plugged
+    /// in or out. Screens without a workspace id are numbered in order.
+    ///
+    /// Returns `true` if changes need to be rendered.
+    pub fn screens_change_handler(&mut self, screens: Vec<Screen>) -> bool {
+        let mut ids = vec![];
+        for (i, mut screen) in screens.into_iter().enumerate() {
+            let wsid = screen.wsid.unwrap_or(i as i32);
+            screen.wsid = Some(wsid);
+            ids.push(wsid);
+            let max_window_width = screen.max_window_width.or(self.state.max_window_width);
+            let ws = self
+                .state
+                .workspaces
+                .iter_mut()
+                .find(|ws| ws.id == Some(wsid));
+            if let Some(ws) = ws {
+                ws.name.clone_from(&screen.name);
+                ws.output.clone_from(&screen.output);
+                ws.max_window_width = max_window_width;
+                self.screen_update_handler(&screen);
+            } else {
+                self.screen_create_handler(screen);
+            }
+        }
+        let removed: Vec<i32> = self
+            .state
+            .workspaces
+            .iter()
+            .filter_map(|ws| ws.id)
+            .filter(|id| !ids.contains(id))
+            .collect();
+        for wsid in removed {
+            self.screen_destroy_handler(wsid);
+        }
+        self.update_workspace_avoid_list();
+        true
+    }
+
+    /// Give a new workspace its own set of the configured tags, returning the first of them. The
+    /// first workspace takes the tags created at startup.
+    fn create_workspace_tags(&mut self, workspace_id: i32) -> TagId {
+        let mut ids: Vec<TagId> = self
+            .state
+            .tags
+            .normal()
+            .iter()
+            .filter(|tag| tag.workspace_id.is_none())
+            .map(|tag| tag.id)
+            .collect();
+        if ids.is_empty() {
+            let layout = self.state.layout_manager.new_layout(Some(workspace_id));
+            for (index, label) in self.config.create_list_of_tag_labels().iter().enumerate() {
+                let id = self.state.tags.add_new(label, layout.clone());
+                if let Some(tag) = self.state.tags.get_mut(id) {
+                    tag.load_config(&self.config, index + 1);
+                    tag.use_own_layout();
+                }
+                ids.push(id);
+            }
+        }
+        if ids.is_empty() {
+            let layout = self.state.layout_manager.new_layout(Some(workspace_id));
+            ids.push(self.state.tags.add_new_unlabeled(layout));
+        }
+        for id in &ids {
+            if let Some(tag) = self.state.tags.get_mut(*id) {
+                tag.workspace_id = Some(workspace_id);
+            }
+        }
+        ids[0]
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::config::{Profile, TestConfig};
+    use crate::display_servers::MockDisplayServer;
+    use crate::layouts::Layout;
     use crate::Manager;
 
     #[test]
@@ -68,6 +234,174 @@ mod tests {
         assert!(manager.state.workspaces[1].has_tag(&2));
     }
 
+    #[test]
+    fn updated_screens_should_move_their_workspace() {
+        let mut manager = Manager::new_test(vec!["1".to_string(), "2".to_string()]);
+        let left = crate::config::Workspace {
+            width: 800,
+            height: 600,
+            ..Default::default()
+        };
+        let right = crate::config::Workspace {
+            x: 800,
+            ..left.clone()
+        };
+        manager.screen_create_handler(Screen::from(&left));
+        manager.screen_create_handler(Screen::from(&right));
+        let moved = crate::config::Workspace {
+            id: Some(1),
+            width: 1024,
+            height: 768,
+            ..right
+        };
+        assert!(manager.screen_update_handler(&Screen::from(&moved)));
+        assert!(!manager.screen_update_handler(&Screen::from(&moved)));
+        let ws = &manager.state.workspaces[1];
+        assert_eq!((ws.xyhw.x(), ws.xyhw.w(), ws.xyhw.h()), (800, 1024, 768));
+        assert_eq!(manager.state.screens[1].bbox.x, 800);
+        assert_eq!(manager.state.screens[0].bbox.width, 800);
+    }
+
+    #[test]
+    fn tags_should_start_with_their_own_layouts() {
+        let mut manager: Manager<TestConfig, MockDisplayServer> = Manager::new(TestConfig {
+            tags: vec!["1".to_string(), "video".to_string(), "3".to_string()],
+            layouts: vec![Layout::MainAndVertStack, Layout::Grid],
+            tag_default_layouts: vec![(2, Layout::Monocle)],
+            tag_layouts: vec![(3, vec![Layout::Fibonacci, Layout::Grid])],
+            ..TestConfig::default()
+        });
+        manager.screen_create_handler(Screen::default());
+        manager.screen_create_handler(Screen::default());
+        let layouts: Vec<Layout> = manager
+            .state
+            .tags
+            .normal()
+            .iter()
+            .map(|tag| tag.layout.clone())
+            .collect();
+        assert_eq!(
+            layouts,
+            vec![Layout::MainAndVertStack, Layout::Monocle, Layout::Fibonacci]
+        );
+    }
+
+    #[test]
+    fn loading_profiles_should_add_and_remove_workspaces() {
+        let monitor = |x: i32| crate::config::Workspace {
+            x,
+            width: 800,
+            height: 600,
+            ..Default::default()
+        };
+        let mut manager: Manager<TestConfig, MockDisplayServer> = Manager::new(TestConfig {
+            tags: vec!["1".to_string(), "2".to_string(), "3".to_string()],
+            profiles: vec![
+                Profile {
+                    name: "docked".to_string(),
+                    workspaces: vec![monitor(0), monitor(800), monitor(1600)],
+                    ..Profile::default()
+                },
+                Profile {
+                    name: "laptop".to_string(),
+                    workspaces: vec![crate::config::Workspace {
+                        width: 1280,
+                        ..monitor(0)
+                    }],
+                    ..Profile::default()
+                },
+            ],
+            ..TestConfig::default()
+        });
+        assert!(manager.load_profile("docked"));
+        assert_eq!(manager.state.workspaces.len(), 3);
+        manager
+            .state
+            .focus_workspace(&manager.state.workspaces[2].clone());
+
+        assert!(manager.load_profile("laptop"));
+        assert_eq!(manager.state.workspaces.len(), 1);
+        assert_eq!(manager.state.screens.len(), 1);
+        assert_eq!(manager.state.workspaces[0].xyhw.w(), 1280);
+        assert_eq!(
+            manager.state.focus_manager.workspace_history.front(),
+            Some(&0)
+        );
+
+        assert!(manager.load_profile("docked"));
+        let tags: Vec<TagId> = manager
+            .state
+            .workspaces
+            .iter()
+            .map(|ws| ws.tags[0])
+            .collect();
+        assert_eq!(tags, vec![1, 2, 3]);
+        assert!(!manager.load_profile("presentation"));
+    }
+
+    #[test]
+    fn profiles_should_load_once_their_outputs_are_connected() {
+        let monitor = |x: i32| crate::config::Workspace {
+            x,
+            width: 800,
+            height: 600,
+            ..Default::default()
+        };
+        let outputs = |names: &[&str]| -> Vec<String> {
+            names.iter().map(|name| (*name).to_string()).collect()
+        };
+        let mut manager: Manager<TestConfig, MockDisplayServer> = Manager::new(TestConfig {
+            tags: vec!["1".to_string(), "2".to_string()],
+            profiles: vec![
+                Profile {
+                    name: "docked".to_string(),
+                    outputs: outputs(&["eDP-1", "DP-1"]),
+                    workspaces: vec![monitor(0), monitor(800)],
+                },
+                Profile {
+                    name: "laptop".to_string(),
+                    outputs: outputs(&["eDP-1"]),
+                    workspaces: vec![monitor(0)],
+                },
+                Profile {
+                    name: "presentation".to_string(),
+                    workspaces: vec![monitor(0)],
+                    ..Profile::default()
+                },
+            ],
+            ..TestConfig::default()
+        });
+        assert!(manager.load_profile_of_outputs(&outputs(&["DP-1", "eDP-1"])));
+        assert_eq!(manager.state.workspaces.len(), 2);
+        assert!(manager.load_profile_of_outputs(&outputs(&["eDP-1"])));
+        assert_eq!(manager.state.workspaces.len(), 1);
+        assert!(!manager.load_profile_of_outputs(&outputs(&["eDP-1", "HDMI-1"])));
+        assert!(!manager.load_profile_of_outputs(&[]));
+        assert_eq!(manager.state.workspaces.len(), 1);
+    }
+
+    #[test]
+    fn workspace_names_should_show_in_the_state() {
+        let mut manager = Manager::new_test(vec!["1".to_string(), "2".to_string()]);
+        let named = crate::config::Workspace {
+            name: Some("left monitor".to_string()),
+            width: 800,
+            height: 600,
+            ..Default::default()
+        };
+        manager.screen_create_handler(Screen::from(&named));
+        manager.screen_create_handler(Screen::default());
+        let state = crate::models::dto::DisplayState::from(crate::models::dto::ManagerState::from(
+            &manager.state,
+        ));
+        let labels: Vec<Option<&str>> = state
+            .workspaces
+            .iter()
+            .map(|ws| ws.name.as_deref())
+            .collect();
+        assert_eq!(labels, vec![Some("left monitor"), None]);
+    }
+
     #[test]
     fn should_be_able_to_add_screens_with_preexisting_tags() {
         let mut manager = Manager::new_test(vec![
@@ -81,6 +415,53 @@ mod tests {
         assert!(manager.state.workspaces[1].has_tag(&2));
     }
 
+    #[test]
+    fn new_screens_should_get_a_tag_not_shown_on_another_screen() {
+        let mut manager =
+            Manager::new_test(vec!["1".to_string(), "2".to_string(), "3".to_string()]);
+        manager.screen_create_handler(Screen::default());
+        manager.screen_create_handler(Screen::default());
+        manager
+            .state
+            .focus_workspace(&manager.state.workspaces[0].clone());
+        manager.state.goto_tag_handler(3);
+        assert!(manager.state.workspaces[0].has_tag(&3));
+
+        manager.screen_create_handler(Screen::default());
+        assert!(manager.state.workspaces[2].has_tag(&1));
+    }
+
+    #[test]
+    fn screens_with_their_own_tags_should_switch_independently() {
+        let mut manager = Manager::new_test(vec!["web".to_string(), "code".to_string()]);
+        manager.state.per_screen_tags = true;
+        manager.screen_create_handler(Screen::default());
+        manager.screen_create_handler(Screen::default());
+        assert_eq!(manager.state.tags.len_normal(), 4);
+        assert!(manager.state.workspaces[0].has_tag(&1));
+        assert!(manager.state.workspaces[1].has_tag(&3));
+
+        // The second screen is focused, its second tag is tag 4.
+        manager.command_handler(&crate::Command::GoToTag {
+            tag: 2,
+            swap: false,
+        });
+        assert!(manager.state.workspaces[0].has_tag(&1));
+        assert!(manager.state.workspaces[1].has_tag(&4));
+
+        let state = crate::models::dto::DisplayState::from(crate::models::dto::ManagerState::from(
+            &manager.state,
+        ));
+        let names: Vec<&str> = state.workspaces[1]
+            .tags
+            .iter()
+            .map(|tag| tag.name.as_str())
+            .collect();
+        assert_eq!(names, vec!["web", "code"]);
+        assert!(state.workspaces[1].tags[1].focused);
+        assert!(!state.workspaces[0].tags[1].visible);
+    }
+
     #[test]
     fn creating_more_screens_than_tags_should_automatically_create_new_tags() {
         let mut manager = Manager::new_test(vec!["web".to_string(), "console".to_string()]);
@@ -0,0 +1,430 @@
+use crate::display_action::DisplayAction;
+use crate::models::TagId;
+use crate::state::State;
+
+impl State {
+    /// Add a tag labelled `label` after the others, which with `per_screen_tags` belongs to the
+    /// focused workspace. Returns its ID.
+    pub fn add_tag(&mut self, label: &str) -> TagId {
+        let workspace_id = self
+            .focus_manager
+            .workspace(&self.workspaces)
+            .and_then(|ws| ws.id);
+        let order = self.tags.normal().iter().map(|tag| tag.order + 1).max();
+        let id = self
+            .tags
+            .add_new(label, self.layout_manager.new_layout(workspace_id));
+        if let Some(tag) = self.tags.get_mut(id) {
+            tag.order = order.unwrap_or(id);
+            if self.per_screen_tags {
+                tag.workspace_id = workspace_id;
+            }
+        }
+        self.send_tag_labels();
+        id
+    }
+
+    /// Remove a normal tag, renumbering the tags after it. Its windows move to the fallback tag,
+    /// the last one focused which nothing else shows, and the workspace showing it shows the
+    /// fallback instead. A tag without a fallback, the last one a workspace can show, stays.
+    pub fn remove_tag(&mut self, id: TagId) -> Option<bool> {
+        let tag = self.tags.normal().get(id.checked_sub(1)?)?;
+        let owner = tag.workspace_id;
+        let is_free = |other: TagId| {
+            other != id
+                && self
+                    .tags
+                    .get(other)
+                    .is_some_and(|t| t.workspace_id == owner)
+                && !self
+                    .workspaces
+                    .iter()
+                    .any(|ws| ws.has_tag(&other) && !ws.has_tag(&id))
+        };
+        let fallback = self
+            .focus_manager
+            .tag_history
+            .iter()
+            .copied()
+            .find(|&other| other <= self.tags.len_normal() && is_free(other))
+            .or_else(|| {
+                self.tags
+                    .normal()
+                    .iter()
+                    .map(|t| t.id)
+                    .find(|&t| is_free(t))
+            })?;
+        let was_focused = self.focus_manager.tag(0) == Some(id);
+
+        // The tags after the removed one move one to the left.
+        let last = self.tags.len_normal();
+        let renumber = |t: TagId| if t > id && t <= last { t - 1 } else { t };
+        let moved = |tags: &[TagId]| -> Vec<TagId> {
+            let mut moved: Vec<TagId> = tags.iter().copied().filter(|&t| t != id).collect();
+            if moved.is_empty() && !tags.is_empty() {
+                moved.push(fallback);
+            }
+            moved.into_iter().map(renumber).collect()
+        };
+        self.tags.remove(id);
+        for window in &mut self.windows {
+            let tags = moved(&window.tags);
+            if tags != window.tags {
+                window.tags = tags;
+                let act = DisplayAction::SetWindowTags(window.handle, window.tags.clone());
+                self.actions.push_back(act);
+            }
+        }
+        let forget = |tags: &[TagId]| -> Vec<TagId> {
+            tags.iter()
+                .copied()
+                .filter(|&t| t != id)
+                .map(renumber)
+                .collect()
+        };
+        for ws in &mut self.workspaces {
+            ws.tags = moved(&ws.tags);
+            ws.previous_tags = forget(&ws.previous_tags);
+            ws.overview = ws.overview.as_deref().map(forget);
+            for history in [&mut ws.tag_history_back, &mut ws.tag_history_forward] {
+                *history = history.iter().map(|tags| forget(tags)).collect();
+                history.retain(|tags| !tags.is_empty());
+            }
+        }
+        self.focus_manager.tag_history.retain(|&t| t != id);
+        for t in &mut self.focus_manager.tag_history {
+            *t = renumber(*t);
+        }
+        self.focus_manager.tags_last_window = self
+            .focus_manager
+            .tags_last_window
+            .drain()
+            .filter(|&(t, _)| t != id)
+            .map(|(t, handle)| (renumber(t), handle))
+            .collect();
+        self.close_all_pending = self.close_all_pending.filter(|&t| t != id).map(renumber);
+
+        self.send_tag_labels();
+        let fallback = renumber(fallback);
+        if was_focused {
+            self.focus_tag(&fallback);
+        }
+        if let Some(&current) = self.focus_manager.tag_history.front() {
+            self.actions
+                .push_back(DisplayAction::SetCurrentTags(vec![current]));
+        }
+        self.update_static();
+        self.layout_manager
+            .update_layouts(&mut self.workspaces, self.tags.all_mut());
+        Some(true)
+    }
+
+    /// Show the windows of a tag on the focused workspace beside those of the tags it shows, the
+    /// layout of the first of them arranging all the windows, or stop showing the tag when it
+    /// isn't the only one. A tag shown on another workspace stays there.
+    pub fn toggle_tag_view(&mut self, tag: TagId) -> Option<bool> {
+        if tag < 1 || tag > self.tags.len_normal() {
+            return None;
+        }
+        let focused = *self.focus_manager.workspace_history.front()?;
+        if self
+            .workspaces
+            .iter()
+            .enumerate()
+            .any(|(i, ws)| i != focused && ws.has_tag(&tag))
+        {
+            return None;
+        }
+        let ws = self.workspaces.get_mut(focused)?;
+        if !ws.has_tag(&tag) {
+            ws.tags.push(tag);
+        } else if ws.tags.len() > 1 {
+            ws.tags.retain(|&t| t != tag);
+        } else {
+            return None;
+        }
+        let (first, tags) = (ws.tags[0], ws.tags.clone());
+        self.focus_tag(&first);
+        self.actions.push_back(DisplayAction::SetCurrentTags(tags));
+        self.update_static();
+        self.layout_manager
+            .update_layouts(&mut self.workspaces, self.tags.all_mut());
+        Some(true)
+    }
+
+    /// Move a tag with its windows to the workspace at `index`, which then shows it. The
+    /// workspace it leaves shows the tags it showed before, or another tag no workspace shows.
+    /// With `per_screen_tags` the tag joins the set of the workspace it moves to.
+    pub fn move_tag_to_workspace(&mut self, tag: TagId, index: usize) -> Option<bool> {
+        if tag < 1 || tag > self.tags.len_normal() {
+            return None;
+        }
+        let target = self.workspaces.get(index)?.clone();
+        let source = self.workspaces.iter().position(|ws| ws.has_tag(&tag));
+        if source == Some(index) {
+            return None;
+        }
+        let source_tags = match source {
+            Some(source) => Some((source, self.tags_left_behind(source, tag)?)),
+            None => None,
+        };
+
+        let depth = self.tag_history_depth;
+        if let Some((source, tags)) = source_tags {
+            self.workspaces[source].show_tags(tags, depth);
+        }
+        self.workspaces[index].show_tags(vec![tag], depth);
+        if self.per_screen_tags {
+            self.tags.get_mut(tag)?.workspace_id = target.id;
+        }
+
+        self.focus_workspace(&target);
+        self.focus_tag(&tag);
+        self.update_static();
+        self.layout_manager
+            .update_layouts(&mut self.workspaces, self.tags.all_mut());
+        Some(true)
+    }
+
+    /// The tags the workspace at `index` shows once `tag` moves away: the others it shows, or
+    /// else the tags it showed before or was focused before, or any tag it can show which no
+    /// workspace shows.
+    fn tags_left_behind(&self, index: usize, tag: TagId) -> Option<Vec<TagId>> {
+        let ws = self.workspaces.get(index)?;
+        let others: Vec<TagId> = ws.tags.iter().copied().filter(|&t| t != tag).collect();
+        if !others.is_empty() {
+            return Some(others);
+        }
+        let own = self.workspace_tags(ws);
+        let is_free = |t: &TagId| {
+            *t != tag && own.contains(t) && !self.workspaces.iter().any(|ws| ws.has_tag(t))
+        };
+        ws.previous_tags
+            .iter()
+            .chain(&self.focus_manager.tag_history)
+            .chain(&own)
+            .copied()
+            .find(is_free)
+            .map(|t| vec![t])
+    }
+
+    /// Label a normal tag `label`.
+    pub fn rename_tag(&mut self, id: TagId, label: &str) -> Option<bool> {
+        let tag = self.tags.get_mut(id).filter(|tag| !tag.hidden)?;
+        tag.label = label.to_string();
+        self.send_tag_labels();
+        Some(false)
+    }
+
+    /// The tag given by its number, counting the tags of the focused workspace as commands do,
+    /// or by its label, preferring the tags of the focused workspace.
+    pub fn find_tag(&self, tag: &str) -> Option<TagId> {
+        if let Ok(number) = tag.parse() {
+            return self
+                .focused_workspace_tag(number)
+                .filter(|&id| id >= 1 && id <= self.tags.len_normal());
+        }
+        let own = self
+            .focus_manager
+            .workspace(&self.workspaces)
+            .map(|ws| self.workspace_tags(ws))
+            .unwrap_or_default();
+        let labelled: Vec<TagId> = self
+            .tags
+            .normal()
+            .iter()
+            .filter(|t| t.label == tag)
+            .map(|t| t.id)
+            .collect();
+        labelled
+            .iter()
+            .find(|id| own.contains(id))
+            .or_else(|| labelled.first())
+            .copied()
+    }
+
+    /// Let the display server know of the labels of the normal tags, which it numbers the
+    /// desktops by.
+    fn send_tag_labels(&mut self) {
+        let labels = self.tags.normal().iter().map(|t| t.label.clone()).collect();
+        self.actions.push_back(DisplayAction::SetTagLabels(labels));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::config::TestConfig;
+    use crate::display_servers::MockDisplayServer;
+    use crate::models::{Screen, TagListing, WindowHandle};
+    use crate::{Command, Manager, Window};
+
+    #[test]
+    fn added_tags_should_come_after_the_others() {
+        let mut manager = Manager::new_test(vec!["1".to_string(), "2".to_string()]);
+        manager.screen_create_handler(Screen::default());
+
+        assert_eq!(manager.state.add_tag("mail"), 3);
+        assert_eq!(manager.state.tags.get(3).unwrap().label, "mail");
+        assert!(manager.state.goto_tag_handler(3).unwrap());
+    }
+
+    #[test]
+    fn reloads_should_keep_the_settings_of_tags_with_their_labels() {
+        let config = TestConfig {
+            tags: vec!["1".to_string(), "2".to_string(), "3".to_string()],
+            hidden_tags: vec![2],
+            ..TestConfig::default()
+        };
+        let mut manager: Manager<TestConfig, MockDisplayServer> = Manager::new(config);
+        manager.screen_create_handler(Screen::default());
+        manager.state.goto_tag_handler(2);
+        manager.command_handler(&Command::RemoveTag);
+        manager.state.add_tag("mail");
+        manager.state.tags.get_mut(3).unwrap().listing = TagListing::Unlisted;
+
+        manager.reload_config();
+        let listings: Vec<(&str, TagListing)> = manager
+            .state
+            .tags
+            .normal()
+            .iter()
+            .map(|tag| (tag.label.as_str(), tag.listing))
+            .collect();
+        assert_eq!(
+            listings,
+            [
+                ("1", TagListing::Listed),
+                ("3", TagListing::Listed),
+                ("mail", TagListing::Unlisted),
+            ]
+        );
+    }
+
+    #[test]
+    fn toggled_tags_should_be_laid_out_together() {
+        let mut manager =
+            Manager::new_test(vec!["1".to_string(), "2".to_string(), "3".to_string()]);
+        manager.screen_create_handler(Screen::default());
+        manager.window_created_handler(
+            Window::new(WindowHandle::MockHandle(1), None, None),
+            -1,
+            -1,
+        );
+        manager.state.goto_tag_handler(2);
+        manager.window_created_handler(
+            Window::new(WindowHandle::MockHandle(2), None, None),
+            -1,
+            -1,
+        );
+
+        assert!(manager.command_handler(&Command::ToggleTagView(1)));
+        manager.update_windows();
+        assert_eq!(manager.state.workspaces[0].tags, [2, 1]);
+        assert!(manager.state.windows.iter().all(Window::visible));
+        // The windows share the workspace rather than overlap.
+        let first = manager.state.windows[0].calculated_xyhw();
+        let second = manager.state.windows[1].calculated_xyhw();
+        assert_ne!(first, second);
+        assert_eq!(first.w() + second.w(), manager.state.workspaces[0].width());
+
+        // Going to a tag shows it alone again.
+        manager.state.goto_tag_handler(1);
+        assert_eq!(manager.state.workspaces[0].tags, [1]);
+        assert!(manager.command_handler(&Command::ToggleTagView(2)));
+        assert!(manager.command_handler(&Command::ToggleTagView(1)));
+        assert_eq!(manager.state.workspaces[0].tags, [2]);
+        // The only tag shown stays.
+        assert!(!manager.command_handler(&Command::ToggleTagView(2)));
+    }
+
+    #[test]
+    fn moved_tags_should_take_their_windows_along() {
+        let mut manager =
+            Manager::new_test(vec!["1".to_string(), "2".to_string(), "3".to_string()]);
+        manager.screen_create_handler(Screen::default());
+        manager.screen_create_handler(Screen::default());
+        manager
+            .state
+            .focus_workspace(&manager.state.workspaces[0].clone());
+        manager.window_created_handler(
+            Window::new(WindowHandle::MockHandle(1), None, None),
+            -1,
+            -1,
+        );
+
+        assert!(manager.command_handler(&Command::MoveTagToWorkspace(1)));
+        assert_eq!(manager.state.workspaces[1].tags, [1]);
+        assert_eq!(manager.state.workspaces[1].previous_tags, [2]);
+        // The workspace left behind shows a tag no other workspace shows.
+        assert_eq!(manager.state.workspaces[0].tags, [3]);
+        assert_eq!(manager.state.windows[0].tags, [1]);
+        assert_eq!(manager.state.focus_manager.tag(0), Some(1));
+        // The tag is on that workspace already.
+        assert!(!manager.command_handler(&Command::MoveTagToWorkspace(1)));
+    }
+
+    #[test]
+    fn tags_can_be_renamed_by_number_or_label() {
+        let mut manager = Manager::new_test(vec!["1".to_string(), "web".to_string()]);
+        manager.screen_create_handler(Screen::default());
+
+        manager.command_handler(&Command::RenameTag {
+            tag: Some("web".to_string()),
+            label: "mail".to_string(),
+        });
+        manager.command_handler(&Command::RenameTag {
+            tag: None,
+            label: "code".to_string(),
+        });
+        let labels: Vec<&str> = manager
+            .state
+            .tags
+            .normal()
+            .iter()
+            .map(|t| t.label.as_str())
+            .collect();
+        assert_eq!(labels, ["code", "mail"]);
+        assert_eq!(manager.state.find_tag("2"), Some(2));
+        assert_eq!(manager.state.find_tag("web"), None);
+    }
+
+    #[test]
+    fn removed_tags_should_hand_their_windows_to_a_fallback() {
+        let mut manager =
+            Manager::new_test(vec!["1".to_string(), "2".to_string(), "3".to_string()]);
+        manager.screen_create_handler(Screen::default());
+        manager.state.goto_tag_handler(2);
+        manager.window_created_handler(
+            Window::new(WindowHandle::MockHandle(1), None, None),
+            -1,
+            -1,
+        );
+        manager.state.goto_tag_handler(3);
+        manager.window_created_handler(
+            Window::new(WindowHandle::MockHandle(2), None, None),
+            -1,
+            -1,
+        );
+
+        assert!(manager.state.remove_tag(2).unwrap());
+        let labels: Vec<&str> = manager
+            .state
+            .tags
+            .normal()
+            .iter()
+            .map(|t| t.label.as_str())
+            .collect();
+        assert_eq!(labels, ["1", "3"]);
+        // Tag 3 is now tag 2, and the window of the removed tag went to tag 1, focused before it.
+        assert_eq!(manager.state.windows[0].tags, [1]);
+        assert_eq!(manager.state.windows[1].tags, [2]);
+        assert_eq!(manager.state.workspaces[0].tags, [2]);
+        assert_eq!(manager.state.focus_manager.tag(0), Some(2));
+
+        assert!(manager.state.remove_tag(2).unwrap());
+        assert_eq!(manager.state.windows[1].tags, [1]);
+        assert_eq!(manager.state.workspaces[0].tags, [1]);
+        // The last tag stays.
+        assert_eq!(manager.state.remove_tag(1), None);
+    }
+}
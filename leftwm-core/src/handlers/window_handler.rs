@@ -1,14 +1,17 @@
 use super::{Manager, Window, WindowChange, WindowType, Workspace};
 use crate::child_process::exec_shell;
-use crate::config::{Config, InsertBehavior, ScratchPad};
+use crate::config::{
+    Config, EmptyTagBehavior, InsertBehavior, NewWindowPlacement, ScratchPad, SpawnTarget,
+};
 use crate::display_action::DisplayAction;
 use crate::display_servers::DisplayServer;
 use crate::layouts::Layout;
-use crate::models::{Size, WindowHandle, WindowState, Xyhw, XyhwBuilder};
+use crate::models::{Size, TagId, WindowHandle, WindowState, Xyhw, XyhwBuilder};
 use crate::state::State;
 use crate::utils::helpers;
 use std::env;
 use std::str::FromStr;
+use std::time::Instant;
 
 impl<C: Config, SERVER: DisplayServer> Manager<C, SERVER> {
     /// Process a collection of events, and apply them changes to a manager.
@@ -20,7 +23,15 @@ impl<C: Config, SERVER: DisplayServer> Manager<C, SERVER> {
         }
 
         // Setup any predifined hooks.
-        self.config.setup_predefined_window(&mut window);
+        let states = window.states();
+        let target = self.config.setup_predefined_window(&mut window);
+        let follow = target.as_ref().and_then(|target| target.follow);
+        if let Some(target) = &target {
+            let workspace_id = spawn_on_target(&self.state, &mut window, target);
+            if follow == Some(true) {
+                show_spawned_window(&mut self.state, &window, workspace_id);
+            }
+        }
         let mut is_first = false;
         let mut on_same_tag = true;
         //Random value
@@ -34,6 +45,10 @@ impl<C: Config, SERVER: DisplayServer> Manager<C, SERVER> {
             &mut on_same_tag,
         );
         self.config.load_window(&mut window);
+        restore_floating_geometry(&self.state, &mut window);
+        if let Some(target) = target.as_ref().filter(|target| target.has_geometry()) {
+            float_on_target(&self.state, &mut window, target);
+        }
         insert_window(&mut self.state, &mut window, layout);
 
         let follow_mouse = self.state.focus_manager.focus_new_windows
@@ -43,6 +58,16 @@ impl<C: Config, SERVER: DisplayServer> Manager<C, SERVER> {
         let act = DisplayAction::AddedWindow(window.handle, window.floating(), follow_mouse);
         self.state.actions.push_back(act);
 
+        // Let the DS know about the states set by the hooks, such as fullscreen.
+        for state in window.states().iter().filter(|s| !states.contains(s)) {
+            let act = DisplayAction::SetState(window.handle, true, *state);
+            self.state.actions.push_back(act);
+        }
+        for state in states.iter().filter(|s| !window.has_state(s)) {
+            let act = DisplayAction::SetState(window.handle, false, *state);
+            self.state.actions.push_back(act);
+        }
+
         //let the DS know the correct desktop to find this window
         if !window.tags.is_empty() {
             let act = DisplayAction::SetWindowTags(window.handle, window.tags);
@@ -52,7 +77,8 @@ impl<C: Config, SERVER: DisplayServer> Manager<C, SERVER> {
         // Tell the WM the new display order of the windows.
         self.state.sort_windows();
 
-        if (self.state.focus_manager.focus_new_windows || is_first) && on_same_tag {
+        let focus = (self.state.focus_manager.focus_new_windows || is_first) && on_same_tag;
+        if follow.unwrap_or(focus) {
             self.state.focus_window(&window.handle);
         }
 
@@ -73,31 +99,47 @@ impl<C: Config, SERVER: DisplayServer> Manager<C, SERVER> {
             Some(window) => (window.transient, window.floating()),
             None => (None, false),
         };
+        let tags = self
+            .state
+            .windows
+            .iter()
+            .find(|w| &w.handle == handle)
+            .map(|w| w.tags.clone())
+            .unwrap_or_default();
+        remember_floating_geometry(&mut self.state, handle);
         self.state
             .focus_manager
             .tags_last_window
             .retain(|_, h| h != handle);
+        for tag in self.state.tags.all_mut() {
+            tag.window_weights.retain(|(h, _)| h != handle);
+        }
         self.state.windows.retain(|w| &w.handle != handle);
 
         //make sure the workspaces do not draw on the docks
         self.update_workspace_avoid_list();
 
+        leave_emptied_tag(&mut self.state, &tags);
+
         let focused = self.state.focus_manager.window_history.get(0);
         //make sure focus is recalculated if we closed the currently focused window
         if focused == Some(&Some(*handle)) {
             if self.state.focus_manager.behaviour.is_sloppy() {
                 let act = DisplayAction::FocusWindowUnderCursor;
                 self.state.actions.push_back(act);
-            } else if let Some(parent) =
-                find_transient_parent(&self.state.windows, transient).map(|p| p.handle)
-            {
-                self.state.focus_window(&parent);
-            } else if let Some(handle) = new_handle {
-                self.state.focus_window(&handle);
             } else {
-                let act = DisplayAction::Unfocus(Some(*handle), floating);
-                self.state.actions.push_back(act);
-                self.state.focus_manager.window_history.push_front(None);
+                let next = find_transient_parent(&self.state.windows, transient)
+                    .map(|p| p.handle)
+                    .or(new_handle);
+                if let Some(next) = next {
+                    self.state.focus_window(&next);
+                }
+                // The window found may not take the focus, such as a dock.
+                if self.state.focus_manager.window_history.front() == Some(&Some(*handle)) {
+                    let act = DisplayAction::Unfocus(Some(*handle), floating);
+                    self.state.actions.push_back(act);
+                    self.state.focus_manager.window_history.push_front(None);
+                }
             }
         }
 
@@ -107,6 +149,7 @@ impl<C: Config, SERVER: DisplayServer> Manager<C, SERVER> {
     pub fn window_changed_handler(&mut self, change: WindowChange) -> bool {
         let mut changed = false;
         let mut fullscreen_changed = false;
+        let mut layer_changed = false;
         let strut_changed = change.strut.is_some();
         let windows = self.state.windows.clone();
         if let Some(window) = self
@@ -131,14 +174,31 @@ impl<C: Config, SERVER: DisplayServer> Manager<C, SERVER> {
             };
 
             log::debug!("WINDOW CHANGED {:?} {:?}", &window, change);
+            let urgent = change.urgent;
+            let layer = |w: &Window| {
+                (
+                    w.has_state(&WindowState::Above),
+                    w.has_state(&WindowState::Below),
+                )
+            };
+            let old_layer = layer(window);
             changed = change.update(window, container);
+            layer_changed = layer(window) != old_layer;
+            if let Some((source, urgent)) = urgent {
+                let limit = self.state.urgency_limit;
+                let now = Instant::now();
+                changed = window.request_urgency(source, urgent, limit, now) || changed;
+            }
             if window.r#type == WindowType::Dock {
                 self.update_workspace_avoid_list();
                 // Don't let changes from docks re-render the worker. This will result in an
                 // infinite loop. Just be patient a rerender will occur.
             }
         }
-        if fullscreen_changed {
+        if layer_changed {
+            // Keep the windows above or below the others there.
+            self.state.sort_windows();
+        } else if fullscreen_changed {
             // Reorder windows.
             let act = DisplayAction::SetWindowOrder(self.state.windows.clone());
             self.state.actions.push_back(act);
@@ -151,10 +211,11 @@ impl<C: Config, SERVER: DisplayServer> Manager<C, SERVER> {
 
     pub fn update_workspace_avoid_list(&mut self) {
         let mut avoid = vec![];
+        let docks_hidden = self.state.docks_hidden;
         self.state
             .windows
             .iter()
-            .filter(|w| w.r#type == WindowType::Dock)
+            .filter(|w| w.r#type == WindowType::Dock && !docks_hidden)
             .filter_map(|w| w.strut.map(|strut| (w.handle, strut)))
             .for_each(|(handle, to_avoid)| {
                 log::debug!("AVOID STRUT:[{:?}] {:?}", handle, to_avoid);
@@ -164,10 +225,7 @@ impl<C: Config, SERVER: DisplayServer> Manager<C, SERVER> {
             let struts = avoid
                 .clone()
                 .into_iter()
-                .filter(|s| {
-                    let (x, y) = s.center();
-                    ws.contains_point(x, y)
-                })
+                .filter(|s| ws.xyhw.intersects(s))
                 .collect();
             ws.avoid = struts;
             ws.update_avoided_areas();
@@ -215,6 +273,130 @@ impl Window {
     }
 }
 
+/// Go to another tag, as `empty_tag_behavior` says, when the tag shown on the focused workspace
+/// lost its last window, which had the `tags`.
+fn leave_emptied_tag(state: &mut State, tags: &[TagId]) {
+    let current = match state.focus_manager.tag(0) {
+        Some(tag) if tags.contains(&tag) => tag,
+        _ => return,
+    };
+    let has_windows = |state: &State, tag: TagId| {
+        state
+            .windows
+            .iter()
+            .any(|w| w.has_tag(&tag) && !w.is_unmanaged())
+    };
+    if has_windows(state, current) {
+        return;
+    }
+    let next = match state.empty_tag_behavior {
+        EmptyTagBehavior::Stay => None,
+        EmptyTagBehavior::LastNonEmpty => state
+            .focus_manager
+            .tag_history
+            .iter()
+            .copied()
+            .find(|&tag| tag != current && has_windows(state, tag)),
+        EmptyTagBehavior::Previous => state.focus_manager.tag(1).filter(|&tag| tag != current),
+    };
+    if let Some(tag) = next {
+        state.goto_tag_handler(tag);
+    }
+}
+
+/// Put a window on the tag a window rule gives by its label, among the tags of the workspace of
+/// the output given, or of the focused one. Without a label the window goes to the tag shown on
+/// the workspace of the output. Returns the id of the workspace.
+fn spawn_on_target(state: &State, window: &mut Window, target: &SpawnTarget) -> Option<i32> {
+    let workspace = match &target.output {
+        Some(output) => {
+            let found = state
+                .workspaces
+                .iter()
+                .find(|ws| ws.output.as_ref() == Some(output));
+            if found.is_none() {
+                log::warn!("No workspace is on the output {output} of a window rule");
+            }
+            found
+        }
+        None => state.focus_manager.workspace(&state.workspaces),
+    }?;
+    let tag = match &target.tag {
+        Some(label) => state
+            .workspace_tags(workspace)
+            .into_iter()
+            .find(|id| state.tags.get(*id).is_some_and(|tag| tag.label == *label)),
+        None if target.output.is_some() => workspace.tags.first().copied(),
+        None => None,
+    };
+    if let Some(tag) = tag {
+        window.tags = vec![tag];
+    }
+    workspace.id
+}
+
+/// Float a window at the size and place a window rule gives, relative to the workspace showing
+/// its tag, or the focused one.
+fn float_on_target(state: &State, window: &mut Window, target: &SpawnTarget) {
+    let ws = state
+        .workspaces
+        .iter()
+        .find(|ws| window.tags.iter().any(|tag| ws.has_tag(tag)))
+        .or_else(|| state.focus_manager.workspace(&state.workspaces));
+    let area = match ws {
+        Some(ws) => ws.xyhw,
+        None => return,
+    };
+    let requested = window.requested.unwrap_or_else(|| area.center_halfed());
+    let w = target
+        .width
+        .map_or(requested.w(), |w| w.into_absolute(area.w()));
+    let h = target
+        .height
+        .map_or(requested.h(), |h| h.into_absolute(area.h()));
+    let x = target
+        .x
+        .map_or((area.w() - w) / 2, |x| x.into_absolute(area.w()));
+    let y = target
+        .y
+        .map_or((area.h() - h) / 2, |y| y.into_absolute(area.h()));
+    window.set_floating(true);
+    window.normal = area;
+    window.set_floating_exact(
+        XyhwBuilder {
+            x: area.x() + x,
+            y: area.y() + y,
+            w,
+            h,
+            ..XyhwBuilder::default()
+        }
+        .into(),
+    );
+}
+
+/// Make the tag of a window spawned by a window rule shown and focused, on the workspace of the
+/// rule when it isn't shown already.
+fn show_spawned_window(state: &mut State, window: &Window, workspace_id: Option<i32>) {
+    let tag = match window.tags.first() {
+        Some(tag) => *tag,
+        None => return,
+    };
+    let shown_on = state.workspaces.iter().find(|ws| ws.has_tag(&tag)).cloned();
+    if let Some(ws) = shown_on {
+        state.focus_workspace(&ws);
+        return;
+    }
+    let target = state
+        .workspaces
+        .iter()
+        .find(|ws| workspace_id.is_some() && ws.id == workspace_id)
+        .cloned();
+    if let Some(ws) = target {
+        state.focus_workspace(&ws);
+    }
+    state.goto_tag_handler(tag);
+}
+
 fn setup_window(
     state: &mut State,
     window: &mut Window,
@@ -223,13 +405,17 @@ fn setup_window(
     is_first: &mut bool,
     on_same_tag: &mut bool,
 ) {
-    // When adding a window we add to the workspace under the cursor, This isn't necessarily the
-    // focused workspace. If the workspace is empty, it might not have received focus. This is so
-    // the workspace that has windows on its is still active not the empty workspace.
+    // The workspace under the cursor isn't necessarily the focused one. If it is empty, it might
+    // not have received focus, so the workspace with windows on it is still active.
+    let under_cursor = match state.new_window_placement {
+        NewWindowPlacement::Pointer => true,
+        NewWindowPlacement::Focused => false,
+        NewWindowPlacement::Terminal => state.focus_manager.behaviour.is_sloppy(),
+    };
     let ws: Option<&Workspace> = state
         .workspaces
         .iter()
-        .find(|ws| ws.xyhw.contains_point(xy.0, xy.1) && state.focus_manager.behaviour.is_sloppy())
+        .find(|ws| under_cursor && ws.xyhw.contains_point(xy.0, xy.1))
         .or_else(|| state.focus_manager.workspace(&state.workspaces)); //backup plan
 
     if let Some(ws) = ws {
@@ -238,11 +424,14 @@ fn setup_window(
         *is_first = !state.windows.iter().any(|w| for_active_workspace(w));
         // May have been set by a predefined tag.
         if window.tags.is_empty() {
-            window.tags = find_terminal(state, window.pid)
-                .map_or_else(|| ws.tags.clone(), |terminal| terminal.tags.clone());
+            let terminal = (state.new_window_placement == NewWindowPlacement::Terminal)
+                .then(|| find_terminal(state, window.pid))
+                .flatten();
+            window.tags =
+                terminal.map_or_else(|| ws.tags.clone(), |terminal| terminal.tags.clone());
         }
         *on_same_tag = ws.tags == window.tags;
-        *layout = ws.layout;
+        *layout = ws.layout.clone();
 
         if is_scratchpad(state, window) {
             window.set_floating(true);
@@ -279,12 +468,13 @@ fn setup_window(
         if window.r#type == WindowType::Splash {
             set_relative_floating(window, ws, ws.xyhw);
         }
-        if let Some(parent) = find_transient_parent(&state.windows, window.transient) {
-            // This is currently for vlc, this probably will need to be more general if another
-            // case comes up where we don't want to move the window.
-            if window.r#type != WindowType::Utility {
-                set_relative_floating(window, ws, parent.exact_xyhw());
-            }
+        // This is currently for vlc, this probably will need to be more general if another
+        // case comes up where we don't want to move the window.
+        if window.transient.is_some() && window.r#type != WindowType::Utility {
+            // Center transients on their parent, or on the workspace if it isn't known.
+            let outer = find_transient_parent(&state.windows, window.transient)
+                .map_or(ws.xyhw, Window::exact_xyhw);
+            set_relative_floating(window, ws, outer);
         }
     } else {
         window.tags = vec![1];
@@ -313,10 +503,10 @@ fn insert_window(state: &mut State, window: &mut Window, layout: Layout) {
             state.actions.push_back(act);
             was_fullscreen = true;
         }
-        if matches!(layout, Layout::Monocle | Layout::MainAndDeck) {
+        if layout.is_monocle() || layout == Layout::MainAndDeck {
             // Extract the current windows on the same workspace.
             let mut to_reorder = helpers::vec_extract(&mut state.windows, for_active_workspace);
-            if layout == Layout::Monocle || to_reorder.is_empty() {
+            if layout.is_monocle() || to_reorder.is_empty() {
                 // When in monocle we want the new window to be fullscreen if a window was
                 // fullscreen.
                 if was_fullscreen {
@@ -374,20 +564,60 @@ fn insert_window(state: &mut State, window: &mut Window, layout: Layout) {
 fn set_relative_floating(window: &mut Window, ws: &Workspace, outer: Xyhw) {
     window.set_floating(true);
     window.normal = ws.xyhw;
-    let xyhw = window.requested.map_or_else(
-        || ws.center_halfed(),
-        |mut requested| {
-            requested.center_relative(outer, window.border);
-            if ws.xyhw.contains_xyhw(&requested) {
-                requested
-            } else {
-                ws.center_halfed()
-            }
-        },
-    );
+    let mut xyhw = window.requested.unwrap_or_else(|| ws.center_halfed());
+    xyhw.center_relative(outer, window.border);
+    // If it doesn't fit centered on `outer` try the center of the workspace.
+    if !ws.xyhw.contains_xyhw(&xyhw) {
+        xyhw.center_relative(ws.xyhw, window.border);
+    }
+    if !ws.xyhw.contains_xyhw(&xyhw) {
+        xyhw = ws.center_halfed();
+    }
     window.set_floating_exact(xyhw);
 }
 
+/// The key floating geometry is remembered by, if the window is one whose geometry is worth
+/// remembering.
+fn floating_geometry_key(state: &State, window: &Window) -> Option<String> {
+    if window.r#type != WindowType::Normal || window.transient.is_some() {
+        return None;
+    }
+    if is_scratchpad(state, window) {
+        return None;
+    }
+    window.res_class.clone().or_else(|| window.res_name.clone())
+}
+
+fn remember_floating_geometry(state: &mut State, handle: &WindowHandle) {
+    let window = match state.windows.iter().find(|w| &w.handle == handle) {
+        Some(window) if window.floating() && !window.is_fullscreen() => window,
+        _ => return,
+    };
+    if let Some(key) = floating_geometry_key(state, window) {
+        let xyhw = window.exact_xyhw();
+        state.floating_geometry.insert(key, xyhw);
+    }
+}
+
+/// Place a new floating window where the last window of its class was.
+fn restore_floating_geometry(state: &State, window: &mut Window) {
+    if !window.floating() {
+        return;
+    }
+    let xyhw = match floating_geometry_key(state, window)
+        .and_then(|key| state.floating_geometry.get(&key))
+    {
+        Some(xyhw) => *xyhw,
+        None => return,
+    };
+    // The screen layout might have changed since the geometry was remembered.
+    let (x, y) = xyhw.center();
+    if let Some(ws) = state.workspaces.iter().find(|ws| ws.contains_point(x, y)) {
+        window.normal = ws.xyhw;
+        window.set_floating_exact(xyhw);
+    }
+}
+
 fn is_scratchpad(state: &State, window: &Window) -> bool {
     state
         .active_scratchpads
@@ -428,7 +658,8 @@ fn find_terminal(state: &State, pid: Option<u32>) -> Option<&Window> {
 
 fn find_transient_parent(windows: &[Window], transient: Option<WindowHandle>) -> Option<&Window> {
     let mut transient = transient?;
-    loop {
+    // Windows can be made transient for each other, which leaves no parent at the top.
+    for _ in 0..windows.len() {
         transient = if let Some(found) = windows
             .iter()
             .find(|x| x.handle == transient)
@@ -436,9 +667,10 @@ fn find_transient_parent(windows: &[Window], transient: Option<WindowHandle>) ->
         {
             found
         } else {
-            return windows.iter().find(|x| x.handle == transient);
+            break;
         };
     }
+    windows.iter().find(|x| x.handle == transient)
 }
 
 // Get size and position of scratchpad from config and workspace size
@@ -479,6 +711,137 @@ mod tests {
     use crate::models::Screen;
     use crate::Manager;
 
+    #[test]
+    fn focused_window_should_get_the_focused_border_width() {
+        let mut manager = Manager::new_test(vec![]);
+        manager.state.border_width = 1;
+        manager.state.focused_border_width = Some(5);
+        manager.screen_create_handler(Screen::default());
+        for i in 1..=2 {
+            manager.window_created_handler(
+                Window::new(WindowHandle::MockHandle(i), None, None),
+                -1,
+                -1,
+            );
+        }
+        manager.state.focus_window(&WindowHandle::MockHandle(1));
+        manager.update_windows();
+        let borders: Vec<i32> = manager.state.windows.iter().map(|w| w.border).collect();
+        assert_eq!(borders, vec![5, 1]);
+    }
+
+    #[test]
+    fn smart_borders_should_leave_a_lone_window_without_border() {
+        let mut manager = Manager::new_test(vec![]);
+        manager.state.border_width = 2;
+        manager.state.smart_borders = true;
+        manager.screen_create_handler(Screen::default());
+        manager.window_created_handler(
+            Window::new(WindowHandle::MockHandle(1), None, None),
+            -1,
+            -1,
+        );
+        manager.update_windows();
+        assert_eq!(manager.state.windows[0].border, 0);
+
+        manager.window_created_handler(
+            Window::new(WindowHandle::MockHandle(2), None, None),
+            -1,
+            -1,
+        );
+        manager.update_windows();
+        let borders: Vec<i32> = manager.state.windows.iter().map(|w| w.border).collect();
+        assert_eq!(borders, vec![2, 2]);
+    }
+
+    #[test]
+    fn windows_under_a_fullscreen_one_should_only_be_displayed_when_changed() {
+        let mut manager = Manager::new_test(vec![]);
+        manager.screen_create_handler(Screen::default());
+        for id in 1..=2 {
+            manager.window_created_handler(
+                Window::new(WindowHandle::MockHandle(id), None, None),
+                -1,
+                -1,
+            );
+        }
+        let displayed = |manager: &mut Manager<_, _>| -> Vec<WindowHandle> {
+            manager.update_windows();
+            manager
+                .state
+                .windows_to_display()
+                .iter()
+                .map(|w| w.handle)
+                .collect()
+        };
+        let both = vec![WindowHandle::MockHandle(1), WindowHandle::MockHandle(2)];
+        assert_eq!(displayed(&mut manager), both);
+
+        manager.state.windows[0].set_states(vec![WindowState::Fullscreen]);
+        assert_eq!(displayed(&mut manager), both);
+        assert_eq!(displayed(&mut manager), vec![]);
+        manager.state.windows[1].border = 5;
+        assert_eq!(displayed(&mut manager), vec![WindowHandle::MockHandle(2)]);
+
+        manager.state.windows[0].set_states(vec![]);
+        assert_eq!(displayed(&mut manager), both);
+        assert_eq!(displayed(&mut manager), both);
+    }
+
+    #[test]
+    fn closing_the_last_window_of_a_tag_should_follow_the_empty_tag_behavior() {
+        let close_last_window = |behavior: EmptyTagBehavior| -> Option<TagId> {
+            let mut manager = Manager::new_test(vec!["1".into(), "2".into(), "3".into()]);
+            manager.state.empty_tag_behavior = behavior;
+            manager.screen_create_handler(Screen::default());
+            manager.window_created_handler(
+                Window::new(WindowHandle::MockHandle(1), None, None),
+                -1,
+                -1,
+            );
+            manager.state.goto_tag_handler(3);
+            manager.state.goto_tag_handler(2);
+            manager.window_created_handler(
+                Window::new(WindowHandle::MockHandle(2), None, None),
+                -1,
+                -1,
+            );
+            manager.window_destroyed_handler(&WindowHandle::MockHandle(2));
+            manager.state.focus_manager.tag(0)
+        };
+        assert_eq!(close_last_window(EmptyTagBehavior::Stay), Some(2));
+        assert_eq!(close_last_window(EmptyTagBehavior::LastNonEmpty), Some(1));
+        assert_eq!(close_last_window(EmptyTagBehavior::Previous), Some(3));
+    }
+
+    #[test]
+    fn new_windows_should_go_to_the_workspace_of_the_placement() {
+        let placed_on = |placement: NewWindowPlacement| -> Vec<TagId> {
+            let mut manager = Manager::new_test(vec!["1".into(), "2".into()]);
+            manager.state.new_window_placement = placement;
+            let left = crate::config::Workspace {
+                width: 800,
+                height: 600,
+                ..Default::default()
+            };
+            let right = crate::config::Workspace {
+                x: 800,
+                ..left.clone()
+            };
+            manager.screen_create_handler(Screen::from(&left));
+            manager.screen_create_handler(Screen::from(&right));
+            let first = manager.state.workspaces[0].clone();
+            manager.state.focus_workspace(&first);
+            let window = Window::new(WindowHandle::MockHandle(1), None, None);
+            manager.window_created_handler(window, 1000, 300);
+            manager.state.windows[0].tags.clone()
+        };
+        assert_eq!(placed_on(NewWindowPlacement::Pointer), vec![2]);
+        assert_eq!(placed_on(NewWindowPlacement::Focused), vec![1]);
+        // `ClickTo` focus, so the focused workspace.
+        assert_eq!(placed_on(NewWindowPlacement::Terminal), vec![1]);
+    }
+
     #[test]
     fn insert_behavior_bottom_add_window_at_the_end_of_the_stack() {
         let mut manager = Manager::new_test(vec![]);
@@ -590,4 +953,174 @@ mod tests {
 
         assert_eq!(actual, expected);
     }
+
+    #[test]
+    fn windows_asking_to_be_above_or_below_should_be_stacked_so() {
+        let mut manager = Manager::new_test(vec![]);
+        manager.screen_create_handler(Screen::default());
+        for i in 1..=3 {
+            manager.window_created_handler(
+                Window::new(WindowHandle::MockHandle(i), None, None),
+                -1,
+                -1,
+            );
+        }
+        let ask = |manager: &mut Manager<_, _>, id, state| {
+            let mut change = WindowChange::new(WindowHandle::MockHandle(id));
+            change.states = Some(vec![state]);
+            manager.window_changed_handler(change);
+        };
+        ask(&mut manager, 1, WindowState::Below);
+        ask(&mut manager, 3, WindowState::Above);
+        let order: Vec<WindowHandle> = manager.state.windows.iter().map(|w| w.handle).collect();
+        assert_eq!(
+            order,
+            vec![
+                WindowHandle::MockHandle(3),
+                WindowHandle::MockHandle(2),
+                WindowHandle::MockHandle(1)
+            ]
+        );
+    }
+
+    #[test]
+    fn floating_geometry_is_remembered_by_class() {
+        let mut manager = Manager::new_test(vec![]);
+        manager.screen_create_handler(Screen::default());
+        let geometry: Xyhw = XyhwBuilder {
+            h: 200,
+            w: 300,
+            x: 40,
+            y: 50,
+            ..XyhwBuilder::default()
+        }
+        .into();
+
+        let mut first = Window::new(WindowHandle::MockHandle(1), None, None);
+        first.res_class = Some("Gimp".to_string());
+        first.set_floating(true);
+        manager.window_created_handler(first, -1, -1);
+        let window = &mut manager.state.windows[0];
+        window.set_floating_exact(geometry);
+        manager.window_destroyed_handler(&WindowHandle::MockHandle(1));
+
+        let mut second = Window::new(WindowHandle::MockHandle(2), None, None);
+        second.res_class = Some("Gimp".to_string());
+        second.set_floating(true);
+        manager.window_created_handler(second, -1, -1);
+        assert_eq!(manager.state.windows[0].exact_xyhw(), geometry);
+
+        let mut other = Window::new(WindowHandle::MockHandle(3), None, None);
+        other.res_class = Some("Firefox".to_string());
+        other.set_floating(true);
+        manager.window_created_handler(other, -1, -1);
+        let other = manager
+            .state
+            .windows
+            .iter()
+            .find(|w| w.handle == WindowHandle::MockHandle(3))
+            .unwrap();
+        assert_ne!(other.exact_xyhw(), geometry);
+    }
+
+    fn assert_centered(window: &Window, outer: Xyhw) {
+        // Allow for rounding of odd sizes and the border kept outside of the centered area.
+        let (x, y) = window.exact_xyhw().center();
+        let (outer_x, outer_y) = outer.center();
+        assert!((x - outer_x).abs() <= 1 + window.border());
+        assert!((y - outer_y).abs() <= 1 + window.border());
+    }
+
+    #[test]
+    fn transient_windows_are_centered_on_their_parent_and_kept_above() {
+        let mut manager = Manager::new_test(vec![]);
+        manager.screen_create_handler(Screen::default());
+        manager.window_created_handler(
+            Window::new(WindowHandle::MockHandle(1), None, None),
+            -1,
+            -1,
+        );
+        manager.window_created_handler(
+            Window::new(WindowHandle::MockHandle(2), None, None),
+            -1,
+            -1,
+        );
+        manager.update_windows();
+        let parent = manager.state.windows[1].exact_xyhw();
+
+        let requested: Xyhw = XyhwBuilder {
+            h: 100,
+            w: 200,
+            ..XyhwBuilder::default()
+        }
+        .into();
+        let mut dialog = Window::new(WindowHandle::MockHandle(3), None, None);
+        dialog.transient = Some(WindowHandle::MockHandle(2));
+        dialog.requested = Some(requested);
+        manager.window_created_handler(dialog, -1, -1);
+
+        let dialog = &manager.state.windows[0];
+        assert_eq!(dialog.handle, WindowHandle::MockHandle(3));
+        assert!(dialog.floating());
+        assert_centered(dialog, parent);
+    }
+
+    #[test]
+    fn windows_transient_for_each_other_should_be_handled_without_hanging() {
+        let mut manager = Manager::new_test(vec![]);
+        manager.screen_create_handler(Screen::default());
+        for handle in 1..=2 {
+            let window = Window::new(WindowHandle::MockHandle(handle), None, None);
+            manager.window_created_handler(window, -1, -1);
+        }
+        for (handle, parent) in [(1, 2), (2, 1)] {
+            let mut change = WindowChange::new(WindowHandle::MockHandle(handle));
+            change.transient = Some(Some(WindowHandle::MockHandle(parent)));
+            manager.window_changed_handler(change);
+        }
+
+        let mut change = WindowChange::new(WindowHandle::MockHandle(1));
+        change.states = Some(vec![]);
+        manager.window_changed_handler(change);
+        assert!(manager.window_destroyed_handler(&WindowHandle::MockHandle(1)));
+    }
+
+    #[test]
+    fn closing_a_window_should_unfocus_it_if_its_parent_cannot_take_the_focus() {
+        let mut manager = Manager::new_test(vec![]);
+        manager.screen_create_handler(Screen::default());
+        let mut dock = Window::new(WindowHandle::MockHandle(1), None, None);
+        dock.r#type = WindowType::Dock;
+        manager.window_created_handler(dock, -1, -1);
+        let mut dialog = Window::new(WindowHandle::MockHandle(2), None, None);
+        dialog.transient = Some(WindowHandle::MockHandle(1));
+        manager.window_created_handler(dialog, -1, -1);
+        manager.state.focus_window(&WindowHandle::MockHandle(2));
+
+        manager.window_destroyed_handler(&WindowHandle::MockHandle(2));
+        assert_eq!(
+            manager.state.focus_manager.window_history.front(),
+            Some(&None)
+        );
+    }
+
+    #[test]
+    fn transient_windows_without_known_parent_are_centered_on_the_workspace() {
+        let mut manager = Manager::new_test(vec![]);
+        manager.screen_create_handler(Screen::default());
+        let requested: Xyhw = XyhwBuilder {
+            h: 100,
+            w: 200,
+            ..XyhwBuilder::default()
+        }
+        .into();
+        let mut dialog = Window::new(WindowHandle::MockHandle(1), None, None);
+        dialog.transient = Some(WindowHandle::MockHandle(42));
+        dialog.requested = Some(requested);
+        manager.window_created_handler(dialog, -1, -1);
+
+        let dialog = &manager.state.windows[0];
+        assert!(dialog.floating());
+        assert_centered(dialog, manager.state.workspaces[0].xyhw);
+    }
 }
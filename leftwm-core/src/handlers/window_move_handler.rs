@@ -1,6 +1,9 @@
 use super::{Manager, Window, WindowHandle, Workspace};
 use crate::config::Config;
+use crate::display_action::DisplayAction;
 use crate::display_servers::DisplayServer;
+use crate::models::{BBox, Screen, Xyhw};
+use crate::state::State;
 
 impl<C: Config, SERVER: DisplayServer> Manager<C, SERVER> {
     pub fn window_move_handler(
@@ -18,17 +21,129 @@ impl<C: Config, SERVER: DisplayServer> Manager<C, SERVER> {
             Some(w) => w.margin_multiplier(),
             None => 1.0,
         };
+        let snap_distance = self.state.snap_distance;
+        let others: Vec<Xyhw> = self
+            .state
+            .windows
+            .iter()
+            .filter(|w| w.handle != *handle && w.visible())
+            .map(outer_xyhw)
+            .collect();
         match self.state.windows.iter_mut().find(|w| w.handle == *handle) {
             Some(w) => {
                 process_window(w, offset_x, offset_y);
                 w.apply_margin_multiplier(margin_multiplier);
-                if snap_to_workspaces(w, &self.state.workspaces) {
-                    self.state.sort_windows();
-                }
-                true
             }
-            None => false,
+            None => return false,
+        }
+        // A tile over another one is dropped into its slot rather than snapped.
+        if self.state.dragging_tile && self.state.update_drag_swap_target(handle) {
+            return true;
+        }
+        if let Some(w) = self.state.windows.iter_mut().find(|w| w.handle == *handle) {
+            if snap_to_workspaces(w, &self.state.workspaces) {
+                self.state.sort_windows();
+            } else {
+                snap_to_edges(
+                    w,
+                    &self.state.screens,
+                    &self.state.workspaces,
+                    &others,
+                    snap_distance,
+                );
+            }
         }
+        true
+    }
+}
+
+impl State {
+    /// Outline the tiled window under the center of a tile being dragged, which it swaps places
+    /// with when dropped. Returns whether there is such a window.
+    fn update_drag_swap_target(&mut self, handle: &WindowHandle) -> bool {
+        let center = self
+            .windows
+            .iter()
+            .find(|w| w.handle == *handle)
+            .map(|w| w.calculated_xyhw().center());
+        let target = center.and_then(|(x, y)| {
+            self.windows.iter().find(|w| {
+                w.handle != *handle
+                    && w.visible()
+                    && !w.floating()
+                    && !w.is_unmanaged()
+                    && w.calculated_xyhw().contains_point(x, y)
+            })
+        });
+        let target_handle = target.map(|w| w.handle);
+        if target_handle != self.drag_swap_target {
+            let area = target.map(outer_xyhw);
+            self.drag_swap_target = target_handle;
+            self.actions.push_back(DisplayAction::HighlightArea(area));
+        }
+        target_handle.is_some()
+    }
+
+    /// Drop a dragged tile into the slot of the window it was dragged onto, if any. A window
+    /// dropped elsewhere on another workspace moves there, see `drop_on_workspace`.
+    pub(crate) fn finish_tile_drag(&mut self, handle: WindowHandle) {
+        let was_tile = std::mem::take(&mut self.dragging_tile);
+        if let Some(target) = self.drag_swap_target.take() {
+            self.actions.push_back(DisplayAction::HighlightArea(None));
+            let dragged_index = self.windows.iter().position(|w| w.handle == handle);
+            let target_index = self.windows.iter().position(|w| w.handle == target);
+            if let (Some(dragged_index), Some(target_index)) = (dragged_index, target_index) {
+                let tags = self.windows[target_index].tags.clone();
+                let dragged = &mut self.windows[dragged_index];
+                dragged.set_floating(false);
+                dragged.tags = tags;
+                self.windows.swap(dragged_index, target_index);
+            }
+        } else {
+            self.drop_on_workspace(handle, was_tile);
+        }
+    }
+
+    /// Give a window dropped on a workspace not showing its tags the tags shown there, keeping
+    /// it where it was dropped. A dragged tile is tiled there again, beside the tile nearest to
+    /// the drop position.
+    fn drop_on_workspace(&mut self, handle: WindowHandle, retile: bool) -> Option<()> {
+        let index = self.windows.iter().position(|w| w.handle == handle)?;
+        let window = &self.windows[index];
+        let exact = window.exact_xyhw();
+        let (x, y) = window.calculated_xyhw().center();
+        let workspace = self.workspaces.iter().find(|ws| ws.contains_point(x, y))?;
+        let tag = *workspace.tags.first()?;
+        if window.tags.iter().any(|t| workspace.has_tag(t)) {
+            return None;
+        }
+        let window = &mut self.windows[index];
+        window.tags.clone_from(&workspace.tags);
+        window.normal = workspace.xyhw;
+        window.set_floating_exact(exact);
+        if !retile {
+            return Some(());
+        }
+        window.set_floating(false);
+        let nearest = self
+            .windows
+            .iter()
+            .enumerate()
+            .filter(|(i, w)| *i != index && w.has_tag(&tag) && !w.floating() && !w.is_unmanaged())
+            .map(|(i, w)| (i, w.calculated_xyhw().center()))
+            .min_by_key(|(_, (tile_x, tile_y))| (x - tile_x).abs() + (y - tile_y).abs());
+        if let Some((target, (tile_x, tile_y))) = nearest {
+            // Past the tile along the axis it is furthest from it, the window goes after it.
+            let after = if (x - tile_x).abs() >= (y - tile_y).abs() {
+                x > tile_x
+            } else {
+                y > tile_y
+            };
+            let window = self.windows.remove(index);
+            let target = if target > index { target - 1 } else { target };
+            self.windows.insert(target + usize::from(after), window);
+        }
+        Some(())
     }
 }
 
@@ -86,3 +201,287 @@ fn should_snap(window: &mut Window, workspace: &Workspace) -> bool {
     }
     false
 }
+
+/// Snap a floating window against the edges of the screen under it and the borders of other
+/// visible windows. Both axes are handled separately, so a window near a corner snaps into it.
+///
+/// Returns `true` if the window was moved.
+pub(crate) fn snap_to_edges(
+    window: &mut Window,
+    screens: &[Screen],
+    workspaces: &[Workspace],
+    others: &[Xyhw],
+    distance: i32,
+) -> bool {
+    if distance <= 0 || !window.floating() {
+        return false;
+    }
+    let loc = outer_xyhw(window);
+    let (center_x, center_y) = loc.center();
+    let deltas = screen_bbox_under(screens, workspaces, center_x, center_y)
+        .map(|bbox| snap_deltas(&loc, bbox, others, distance));
+    match deltas {
+        Some((delta_x, delta_y)) if delta_x != 0 || delta_y != 0 => {
+            let mut offset = window.get_floating_offsets().unwrap_or_default();
+            offset.set_x(offset.x() + delta_x);
+            offset.set_y(offset.y() + delta_y);
+            window.set_floating_offsets(Some(offset));
+            true
+        }
+        _ => false,
+    }
+}
+
+fn snap_deltas(loc: &Xyhw, bbox: BBox, others: &[Xyhw], distance: i32) -> (i32, i32) {
+    let mut lines_x = vec![bbox.x, bbox.x + bbox.width];
+    let mut lines_y = vec![bbox.y, bbox.y + bbox.height];
+    for other in others {
+        // Only borders the window could actually touch are of interest.
+        if spans_overlap(loc.y(), loc.h(), other.y(), other.h(), distance) {
+            lines_x.push(other.x());
+            lines_x.push(other.x() + other.w());
+        }
+        if spans_overlap(loc.x(), loc.w(), other.x(), other.w(), distance) {
+            lines_y.push(other.y());
+            lines_y.push(other.y() + other.h());
+        }
+    }
+
+    (
+        snap_delta(loc.x(), loc.w(), &lines_x, distance),
+        snap_delta(loc.y(), loc.h(), &lines_y, distance),
+    )
+}
+
+/// The `BBox` of the screen holding the workspace under a point. Falls back to the screen under
+/// the point if no workspace contains it.
+fn screen_bbox_under(screens: &[Screen], workspaces: &[Workspace], x: i32, y: i32) -> Option<BBox> {
+    let workspace = workspaces.iter().find(|ws| ws.contains_point(x, y));
+    let screen = match workspace {
+        Some(ws) => screens
+            .iter()
+            .find(|s| s.contains_point(ws.xyhw.x(), ws.xyhw.y())),
+        None => None,
+    };
+    screen
+        .or_else(|| screens.iter().find(|s| s.contains_point(x, y)))
+        .map(|s| s.bbox)
+}
+
+/// The area a window covers on screen, borders included.
+fn outer_xyhw(window: &Window) -> Xyhw {
+    let mut loc = window.calculated_xyhw();
+    loc.set_w(loc.w() + window.border() * 2);
+    loc.set_h(loc.h() + window.border() * 2);
+    loc
+}
+
+const fn spans_overlap(
+    start: i32,
+    size: i32,
+    other_start: i32,
+    other_size: i32,
+    slack: i32,
+) -> bool {
+    start < other_start + other_size + slack && other_start < start + size + slack
+}
+
+/// Smallest movement that puts either side of the span `start..start + size` on one of the
+/// lines, or 0 if no line is within `distance`.
+fn snap_delta(start: i32, size: i32, lines: &[i32], distance: i32) -> i32 {
+    let end = start + size;
+    lines
+        .iter()
+        .flat_map(|&line| [line - start, line - end])
+        .filter(|delta| delta.abs() < distance)
+        .min_by_key(|delta| delta.abs())
+        .unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::TestConfig;
+    use crate::display_servers::MockDisplayServer;
+    use crate::models::{Mode, XyhwBuilder};
+    use crate::DisplayEvent;
+
+    fn floating_window(x: i32, y: i32, w: i32, h: i32) -> Window {
+        let mut window = Window::new(WindowHandle::MockHandle(1), None, None);
+        window.set_floating(true);
+        window.set_floating_exact(
+            XyhwBuilder {
+                x,
+                y,
+                h,
+                w,
+                ..XyhwBuilder::default()
+            }
+            .into(),
+        );
+        window
+    }
+
+    #[test]
+    fn floating_window_snaps_into_screen_corner() {
+        let screens = vec![Screen::default()];
+        let mut window = floating_window(795 - 300, 4, 300, 200);
+        assert!(snap_to_edges(&mut window, &screens, &[], &[], 10));
+        assert_eq!(window.x(), 500);
+        assert_eq!(window.y(), 0);
+    }
+
+    #[test]
+    fn floating_window_snaps_beside_other_window() {
+        let screens = vec![Screen::default()];
+        let other: Xyhw = XyhwBuilder {
+            x: 100,
+            y: 100,
+            h: 200,
+            w: 200,
+            ..XyhwBuilder::default()
+        }
+        .into();
+        let mut window = floating_window(306, 150, 200, 100);
+        assert!(snap_to_edges(&mut window, &screens, &[], &[other], 10));
+        assert_eq!(window.x(), 300);
+        assert_eq!(window.y(), 150);
+    }
+
+    #[test]
+    fn far_away_window_is_not_snapped() {
+        let screens = vec![Screen::default()];
+        let mut window = floating_window(200, 200, 200, 100);
+        assert!(!snap_to_edges(&mut window, &screens, &[], &[], 10));
+        assert_eq!(window.x(), 200);
+    }
+
+    #[test]
+    fn snapping_uses_the_screen_under_the_window() {
+        let screens = vec![
+            Screen::default(),
+            Screen::new(BBox {
+                x: 800,
+                y: 0,
+                width: 800,
+                height: 600,
+            }),
+        ];
+        let mut window = floating_window(805, 300, 200, 100);
+        assert!(snap_to_edges(&mut window, &screens, &[], &[], 10));
+        assert_eq!(window.x(), 800);
+    }
+
+    #[test]
+    fn tile_dragged_onto_another_swaps_with_it() {
+        let mut manager = Manager::new_test(vec!["1".to_string()]);
+        manager.screen_create_handler(Screen::default());
+        for i in 1..=2 {
+            manager.window_created_handler(
+                Window::new(WindowHandle::MockHandle(i), None, None),
+                -1,
+                -1,
+            );
+        }
+        manager.update_windows();
+        let dragged = WindowHandle::MockHandle(1);
+        manager.state.mode = Mode::ReadyToMove(dragged);
+
+        manager.display_event_handler(DisplayEvent::MoveWindow(dragged, 400, 0));
+        assert_eq!(
+            manager.state.drag_swap_target,
+            Some(WindowHandle::MockHandle(2))
+        );
+        assert!(manager.state.actions.iter().any(|action| matches!(
+            action,
+            DisplayAction::HighlightArea(Some(area)) if area.x() == 400
+        )));
+
+        manager.display_event_handler(DisplayEvent::ChangeToNormalMode);
+        assert_eq!(manager.state.windows[1].handle, dragged);
+        assert!(!manager.state.windows[1].floating());
+        assert!(manager
+            .state
+            .actions
+            .iter()
+            .any(|action| matches!(action, DisplayAction::HighlightArea(None))));
+    }
+
+    fn two_screen_manager() -> Manager<TestConfig, MockDisplayServer> {
+        let mut manager = Manager::new_test(vec!["1".to_string(), "2".to_string()]);
+        manager.screen_create_handler(Screen::default());
+        manager.screen_create_handler(Screen::new(BBox {
+            x: 800,
+            y: 0,
+            width: 800,
+            height: 600,
+        }));
+        manager
+            .state
+            .focus_workspace(&manager.state.workspaces[0].clone());
+        manager
+    }
+
+    #[test]
+    fn tile_dropped_on_another_workspace_is_tiled_there() {
+        let mut manager = two_screen_manager();
+        for i in 1..=2 {
+            manager.window_created_handler(
+                Window::new(WindowHandle::MockHandle(i), None, None),
+                -1,
+                -1,
+            );
+        }
+        manager.update_windows();
+        // Drop the main window of the first workspace on the empty second one.
+        let dragged = WindowHandle::MockHandle(1);
+        manager.state.mode = Mode::ReadyToMove(dragged);
+        manager.display_event_handler(DisplayEvent::MoveWindow(dragged, 1000, 50));
+        manager.update_windows();
+        manager.display_event_handler(DisplayEvent::ChangeToNormalMode);
+        manager.update_windows();
+
+        let window = manager
+            .state
+            .windows
+            .iter()
+            .find(|w| w.handle == dragged)
+            .unwrap();
+        assert_eq!(window.tags, vec![2]);
+        assert!(!window.floating());
+        assert_eq!((window.x(), window.width()), (800, 800));
+    }
+
+    #[test]
+    fn floating_window_dropped_on_another_workspace_keeps_its_place() {
+        let mut manager = two_screen_manager();
+        manager.window_created_handler(
+            Window::new(WindowHandle::MockHandle(1), None, None),
+            -1,
+            -1,
+        );
+        manager.update_windows();
+        let window = &mut manager.state.windows[0];
+        window.set_floating(true);
+        window.set_floating_exact(
+            XyhwBuilder {
+                x: 100,
+                y: 100,
+                h: 200,
+                w: 200,
+                ..XyhwBuilder::default()
+            }
+            .into(),
+        );
+        let dragged = WindowHandle::MockHandle(1);
+        manager.state.mode = Mode::ReadyToMove(dragged);
+        manager.display_event_handler(DisplayEvent::MoveWindow(dragged, 900, 0));
+        manager.display_event_handler(DisplayEvent::ChangeToNormalMode);
+        manager.update_windows();
+
+        let window = &manager.state.windows[0];
+        assert_eq!(window.tags, vec![2]);
+        assert!(window.floating());
+        assert_eq!((window.x(), window.y()), (1000, 100));
+    }
+}
@@ -0,0 +1,52 @@
+use crate::models::Tag;
+use crate::models::Window;
+use crate::models::Workspace;
+use crate::models::XyhwBuilder;
+
+/// Binary space partitioning layout, where each new window splits the region of the focused one,
+/// alternately side by side and above each other. The regions are kept in the `bsp` of the tag.
+/// ```text
+/// +-----------+-----------+
+/// |           |           |
+/// |           |     2     |
+/// |           |           |
+/// |     1     +-----+-----+
+/// |           |     |     |
+/// |           |  3  |  4  |
+/// |           |     |     |
+/// +-----------+-----+-----+
+/// ```
+pub fn update(workspace: &Workspace, tag: &Tag, windows: &mut [&mut Window]) {
+    let column_count = match windows.len() {
+        1 => 1,
+        _ => 2,
+    };
+    let x = workspace.x_limited(column_count);
+    let width = workspace.width_limited(column_count);
+    let area = XyhwBuilder {
+        x,
+        y: workspace.y(),
+        h: workspace.height(),
+        w: width,
+        ..XyhwBuilder::default()
+    }
+    .into();
+    let areas = tag.bsp.areas(area);
+
+    for window in windows.iter_mut() {
+        // Windows the tree doesn't know yet get the whole area until the next update.
+        let region = areas
+            .iter()
+            .find(|(handle, _)| *handle == window.handle)
+            .map_or(area, |(_, region)| *region);
+        let region_x = if tag.flipped_horizontal {
+            2 * x + width - region.x() - region.w()
+        } else {
+            region.x()
+        };
+        window.set_height(region.h());
+        window.set_width(region.w());
+        window.set_x(region_x);
+        window.set_y(region.y());
+    }
+}
@@ -76,10 +76,19 @@ pub fn update(workspace: &Workspace, tag: &Tag, windows: &mut [&mut Window]) {
         _ => ((workspace_width as f32 / 100.0) * tag.main_width_percentage()).floor() as i32,
     };
 
-    let secondary_width = match window_count {
+    // The space beside the primary column is split between the left and the right column.
+    let left_width = match window_count {
         1 => 0,
         2 => workspace_width - primary_width,
-        _ => ((workspace_width - primary_width) as f32 / 2.0).floor() as i32,
+        _ => ((workspace_width - primary_width) as f32 / 100.0 * tag.stack_split_percentage())
+            .floor() as i32,
+    };
+    let right_width = workspace_width - primary_width - left_width;
+
+    let (secondary_width, stack_width) = if tag.flipped_horizontal {
+        (right_width, left_width)
+    } else {
+        (left_width, right_width)
     };
 
     let (primary_x, secondary_x, stack_x) = match window_count {
@@ -96,14 +105,14 @@ pub fn update(workspace: &Workspace, tag: &Tag, windows: &mut [&mut Window]) {
             (px, sx, 0)
         }
         _ => {
-            let px = workspace_x + secondary_width;
+            let px = workspace_x + left_width;
             let (sx, stx);
             if tag.flipped_horizontal {
-                sx = workspace_x + primary_width + secondary_width;
+                sx = workspace_x + left_width + primary_width;
                 stx = workspace_x;
             } else {
                 sx = workspace_x;
-                stx = workspace_x + primary_width + secondary_width;
+                stx = workspace_x + left_width + primary_width;
             }
             (px, sx, stx)
         }
@@ -139,7 +148,7 @@ pub fn update(workspace: &Workspace, tag: &Tag, windows: &mut [&mut Window]) {
 
         for w in iter {
             w.set_height(height);
-            w.set_width(secondary_width);
+            w.set_width(stack_width);
             w.set_x(stack_x);
             w.set_y(workspace.y() + y);
             y += height;
@@ -170,11 +170,14 @@ pub fn update(workspace: &Workspace, tag: &Tag, windows: &mut [&mut Window]) {
         _ => ((workspace_width as f32 / 100.0) * tag.main_width_percentage()).floor() as i32,
     };
 
+    // The space beside the primary column is split between the left and the right column.
     let secondary_width = match window_count {
         1 => 0_i32,
         2 => workspace_width - primary_width,
-        _ => ((workspace_width - primary_width) as f32 / 2.0).floor() as i32,
+        _ => ((workspace_width - primary_width) as f32 / 100.0 * tag.stack_split_percentage())
+            .floor() as i32,
     };
+    let right_width = workspace_width - primary_width - secondary_width;
 
     let primary_x = match window_count {
         1 => workspace_x,
@@ -237,7 +240,7 @@ pub fn update(workspace: &Workspace, tag: &Tag, windows: &mut [&mut Window]) {
         workspace_x + secondary_width + primary_width,
         workspace.y(),
         workspace.height(),
-        secondary_width,
+        right_width,
     );
 }
 
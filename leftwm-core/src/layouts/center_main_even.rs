@@ -0,0 +1,77 @@
+use super::main_and_vert_stack::column;
+use crate::models::Tag;
+use crate::models::Window;
+use crate::models::Workspace;
+
+/// Layout which gives the tag's main windows the center column, and halves the other windows
+/// between a column on either side. The main column keeps the main width of the tag, and the
+/// side columns share the rest equally.
+///
+/// Meant for ultra-wide monitors.
+///
+/// 2 windows
+/// ```text
+/// +-----------+-----------+
+/// |           |           |
+/// |           |           |
+/// |     1     |     2     |
+/// |           |           |
+/// |           |           |
+/// +-----------+-----------+
+/// ```
+/// 5 windows
+/// ```text
+/// +-----+-----------+-----+
+/// |     |           |     |
+/// |  4  |           |  2  |
+/// |     |           |     |
+/// +-----+     1     +-----+
+/// |     |           |     |
+/// |  5  |           |  3  |
+/// |     |           |     |
+/// +-----+-----------+-----+
+/// ```
+pub fn update(workspace: &Workspace, tag: &Tag, windows: &mut [&mut Window]) {
+    let window_count = windows.len();
+
+    if window_count == 0 {
+        return;
+    }
+
+    let main_count = tag.main_count().min(window_count);
+    let stack_count = window_count - main_count;
+    let column_count = match stack_count {
+        0 => 1,
+        1 => 2,
+        _ => 3,
+    };
+    let workspace_width = workspace.width_limited(column_count);
+    let workspace_x = workspace.x_limited(column_count);
+
+    let main_width = match stack_count {
+        0 => workspace_width,
+        _ => (workspace_width as f32 / 100.0 * tag.main_width_percentage()).floor() as i32,
+    };
+    // With a single stacked window there is no column left of the main one.
+    let left_width = match stack_count {
+        0 | 1 => 0,
+        _ => (workspace_width - main_width) / 2,
+    };
+    let right_width = workspace_width - main_width - left_width;
+
+    let (main, stack) = windows.split_at_mut(main_count);
+    let (right, left) = stack.split_at_mut(stack_count - stack_count / 2);
+    let main_x = if tag.flipped_horizontal {
+        workspace_x + right_width
+    } else {
+        workspace_x + left_width
+    };
+    let (right_x, left_x) = if tag.flipped_horizontal {
+        (workspace_x, main_x + main_width)
+    } else {
+        (main_x + main_width, workspace_x)
+    };
+    column(workspace, tag, main, main_x, main_width);
+    column(workspace, tag, right, right_x, right_width);
+    column(workspace, tag, left, left_x, left_width);
+}
@@ -0,0 +1,132 @@
+use super::external;
+use crate::models::Tag;
+use crate::models::Window;
+use crate::models::Workspace;
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+
+/// A layout defined in the config, as the regions the windows take for each number of windows.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Default)]
+pub struct CustomLayout {
+    pub name: String,
+    /// The regions of each arrangement, one per window. The arrangement with as many regions as
+    /// there are windows is used, or else the biggest one with fewer, the windows left over
+    /// sharing its last region. With more regions than windows the first ones are used.
+    #[serde(default)]
+    pub arrangements: Vec<Vec<Region>>,
+    /// A program computing the layout instead, see `external`. The regions are used when it
+    /// fails.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub command: Option<String>,
+}
+
+/// An area of the workspace, in percent of its size.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+pub struct Region {
+    pub x: f32,
+    pub y: f32,
+    pub w: f32,
+    pub h: f32,
+}
+
+/// The name of a layout defined in the config.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LayoutName(Arc<str>);
+
+impl LayoutName {
+    #[must_use]
+    pub fn new(name: &str) -> Self {
+        Self(Arc::from(name))
+    }
+
+    #[must_use]
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+impl CustomLayout {
+    /// The arrangement for a number of windows, the whole workspace if there is none.
+    fn arrangement(&self, window_count: usize) -> Vec<Region> {
+        let usable = || {
+            self.arrangements
+                .iter()
+                .filter(|regions| !regions.is_empty())
+        };
+        usable()
+            .filter(|regions| regions.len() <= window_count)
+            .max_by_key(|regions| regions.len())
+            .or_else(|| usable().min_by_key(|regions| regions.len()))
+            .cloned()
+            .unwrap_or_else(|| {
+                vec![Region {
+                    x: 0.0,
+                    y: 0.0,
+                    w: 100.0,
+                    h: 100.0,
+                }]
+            })
+    }
+}
+
+/// Layout placing the windows in the regions the config defines, the windows left over being
+/// stacked in the last region.
+pub fn update(
+    workspace: &Workspace,
+    tag: &Tag,
+    layout: &CustomLayout,
+    windows: &mut [&mut Window],
+) {
+    let window_count = windows.len();
+    if window_count == 0 {
+        return;
+    }
+    if let Some(command) = &layout.command {
+        let request = external::Request {
+            window_count,
+            x: workspace.x_limited(1),
+            y: workspace.y(),
+            w: workspace.width_limited(1),
+            h: workspace.height(),
+            main_width_percentage: tag.main_width_percentage,
+            main_count: tag.main_count,
+        };
+        if let Some(geometries) = external::geometries(command, &request) {
+            for (w, (x, y, width, height)) in windows.iter_mut().zip(geometries) {
+                w.set_x(x);
+                w.set_y(y);
+                w.set_width(width);
+                w.set_height(height);
+            }
+            return;
+        }
+    }
+    let arrangement = layout.arrangement(window_count);
+    let last = arrangement.len() - 1;
+    let sharing_last = window_count.saturating_sub(last).max(1);
+    let width = workspace.width_limited(1) as f32;
+    let height = workspace.height() as f32;
+    let x = workspace.x_limited(1) as f32;
+    let y = workspace.y() as f32;
+    for (index, w) in windows.iter_mut().enumerate() {
+        let mut area = arrangement[index.min(last)];
+        if index >= last {
+            area.h /= sharing_last as f32;
+            area.y += area.h * (index - last) as f32;
+        }
+        if tag.flipped_horizontal {
+            area.x = 100.0 - area.x - area.w;
+        }
+        if tag.flipped_vertical {
+            area.y = 100.0 - area.y - area.h;
+        }
+        let left = (x + width * area.x / 100.0).round() as i32;
+        let top = (y + height * area.y / 100.0).round() as i32;
+        let right = (x + width * (area.x + area.w) / 100.0).round() as i32;
+        let bottom = (y + height * (area.y + area.h) / 100.0).round() as i32;
+        w.set_x(left);
+        w.set_y(top);
+        w.set_width(right - left);
+        w.set_height(bottom - top);
+    }
+}
@@ -0,0 +1,282 @@
+//! Layouts computed by an external program, in the way of river's layout generators.
+//!
+//! The program is started once with `sh -c` and kept running. For each layout pass leftwm writes
+//! a line to its stdin:
+//!
+//! ```text
+//! <window count> <x> <y> <width> <height> <main width percentage> <main count>
+//! ```
+//!
+//! and reads back one `<x> <y> <width> <height>` line per window, in absolute pixels.
+//!
+//! A generator which fails is started again after a while, waiting longer each time, and is
+//! given up on until the next reload after failing a few times in a row. The windows it lays out
+//! fall back to the regions meanwhile.
+use crate::utils::child_process::session_env;
+use std::collections::HashMap;
+use std::io::{BufRead, BufReader, Write};
+use std::process::{Child, ChildStdin, Command, Stdio};
+use std::sync::mpsc::{self, Receiver};
+use std::sync::{Mutex, PoisonError};
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// How long a generator may take to answer a request before leftwm falls back to the regions.
+const TIMEOUT: Duration = Duration::from_millis(200);
+
+/// How long after its first failure a generator is started again, doubling with each failure.
+const BACKOFF: Duration = Duration::from_secs(1);
+
+/// How many times in a row a generator may fail before it is given up on until the next reload.
+const MAX_FAILURES: u32 = 5;
+
+static GENERATORS: Mutex<Option<Generators>> = Mutex::new(None);
+
+/// The running generators, and the failing ones, by command.
+#[derive(Default)]
+struct Generators {
+    running: HashMap<String, Generator>,
+    /// How many times in a row the generators failed, and when they may be started again, `None`
+    /// once they are given up on.
+    failed: HashMap<String, (u32, Option<Instant>)>,
+}
+
+impl Generators {
+    fn ask(&mut self, command: &str, request: &Request) -> Option<Vec<(i32, i32, i32, i32)>> {
+        match self.failed.get(command) {
+            Some((_, None)) => return None,
+            Some((_, Some(retry_at))) if Instant::now() < *retry_at => return None,
+            _ => {}
+        }
+        if !self.running.contains_key(command) {
+            let Some(generator) = Generator::spawn(command) else {
+                self.fail(command);
+                return None;
+            };
+            self.running.insert(command.to_owned(), generator);
+        }
+        let answer = self.running.get_mut(command)?.ask(request);
+        if answer.is_some() {
+            self.failed.remove(command);
+        } else {
+            self.running.remove(command);
+            self.fail(command);
+        }
+        answer
+    }
+
+    fn fail(&mut self, command: &str) {
+        let failures = self
+            .failed
+            .get(command)
+            .map_or(0, |(failures, _)| *failures)
+            + 1;
+        let retry_at = if failures < MAX_FAILURES {
+            let wait = BACKOFF * 2_u32.pow(failures - 1);
+            log::warn!(
+                "The layout generator {command} failed, it will be started again in {}s",
+                wait.as_secs()
+            );
+            Some(Instant::now() + wait)
+        } else {
+            log::error!("The layout generator {command} failed {failures} times, giving up on it until the next reload");
+            None
+        };
+        self.failed.insert(command.to_owned(), (failures, retry_at));
+    }
+
+    /// Stop the generators not run by any of `commands`, and give the failed ones another try.
+    fn retain<'a>(&mut self, commands: impl IntoIterator<Item = &'a str>) {
+        let commands: Vec<&str> = commands.into_iter().collect();
+        // Dropping a generator kills and reaps it.
+        self.running
+            .retain(|command, _| commands.contains(&command.as_str()));
+        self.failed.clear();
+    }
+}
+
+/// The area a generator lays the windows out in, and the ratios of the tag.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Request {
+    pub window_count: usize,
+    pub x: i32,
+    pub y: i32,
+    pub w: i32,
+    pub h: i32,
+    pub main_width_percentage: f32,
+    pub main_count: usize,
+}
+
+struct Generator {
+    child: Child,
+    stdin: ChildStdin,
+    lines: Receiver<String>,
+}
+
+impl Generator {
+    fn spawn(command: &str) -> Option<Self> {
+        let mut child = Command::new("sh")
+            .arg("-c")
+            .arg(command)
+            .envs(session_env())
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .spawn()
+            .map_err(|err| log::error!("Couldn't start the layout generator {command}: {err}"))
+            .ok()?;
+        let stdin = child.stdin.take()?;
+        let stdout = child.stdout.take()?;
+        let (sender, lines) = mpsc::channel();
+        thread::spawn(move || {
+            for line in BufReader::new(stdout).lines().map_while(Result::ok) {
+                if sender.send(line).is_err() {
+                    break;
+                }
+            }
+        });
+        Some(Self {
+            child,
+            stdin,
+            lines,
+        })
+    }
+
+    fn ask(&mut self, request: &Request) -> Option<Vec<(i32, i32, i32, i32)>> {
+        // Drop whatever a previous answer left over.
+        while self.lines.try_recv().is_ok() {}
+        writeln!(
+            self.stdin,
+            "{} {} {} {} {} {} {}",
+            request.window_count,
+            request.x,
+            request.y,
+            request.w,
+            request.h,
+            request.main_width_percentage,
+            request.main_count
+        )
+        .ok()?;
+        self.stdin.flush().ok()?;
+        // The whole answer shares one deadline, so a slow generator can't hold up the event loop
+        // for longer with more windows.
+        let deadline = Instant::now() + TIMEOUT;
+        (0..request.window_count)
+            .map(|_| {
+                let left = deadline.saturating_duration_since(Instant::now());
+                parse_geometry(&self.lines.recv_timeout(left).ok()?)
+            })
+            .collect()
+    }
+}
+
+impl Drop for Generator {
+    fn drop(&mut self) {
+        let _ = self.child.kill();
+        let _ = self.child.wait();
+    }
+}
+
+fn parse_geometry(line: &str) -> Option<(i32, i32, i32, i32)> {
+    let mut values = line.split_whitespace().map(str::parse);
+    let geometry = (
+        values.next()?.ok()?,
+        values.next()?.ok()?,
+        values.next()?.ok()?,
+        values.next()?.ok()?,
+    );
+    values.next().is_none().then_some(geometry)
+}
+
+/// The geometries the generator run by `command` gives the windows, `None` if it can't be
+/// started, doesn't answer in time or is waiting to be started again after failing.
+pub fn geometries(command: &str, request: &Request) -> Option<Vec<(i32, i32, i32, i32)>> {
+    let mut generators = GENERATORS.lock().unwrap_or_else(PoisonError::into_inner);
+    generators
+        .get_or_insert_with(Generators::default)
+        .ask(command, request)
+}
+
+/// Stop the generators not run by any of `commands`, as after a reload dropping their layouts,
+/// and give those which failed another try.
+pub fn retain<'a>(commands: impl IntoIterator<Item = &'a str>) {
+    let mut generators = GENERATORS.lock().unwrap_or_else(PoisonError::into_inner);
+    if let Some(generators) = generators.as_mut() {
+        generators.retain(commands);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const REQUEST: Request = Request {
+        window_count: 2,
+        x: 0,
+        y: 0,
+        w: 400,
+        h: 300,
+        main_width_percentage: 50.0,
+        main_count: 1,
+    };
+
+    #[test]
+    fn generators_should_answer_a_geometry_per_window() {
+        let command = "while read n x y w h m c; do \
+            echo \"$x $y $((w / 2)) $h\"; echo \"$((w / 2)) $y $((w / 2)) $h\"; done";
+        assert_eq!(
+            geometries(command, &REQUEST),
+            Some(vec![(0, 0, 200, 300), (200, 0, 200, 300)])
+        );
+        assert_eq!(
+            geometries(command, &Request { h: 600, ..REQUEST }),
+            Some(vec![(0, 0, 200, 600), (200, 0, 200, 600)])
+        );
+    }
+
+    #[test]
+    fn generators_answering_badly_should_give_nothing() {
+        assert_eq!(geometries("read line; echo nonsense", &REQUEST), None);
+        assert_eq!(geometries("exit 1", &REQUEST), None);
+    }
+
+    #[test]
+    fn failed_generators_should_be_started_again_only_after_a_while() {
+        let mut generators = Generators::default();
+        let command = "read line; exit 1";
+        assert_eq!(generators.ask(command, &REQUEST), None);
+        let start = Instant::now();
+        assert_eq!(generators.ask(command, &REQUEST), None);
+        assert!(start.elapsed() < TIMEOUT);
+        assert!(!generators.running.contains_key(command));
+        assert_eq!(generators.failed[command].0, 1);
+
+        generators
+            .failed
+            .insert(command.to_owned(), (MAX_FAILURES, None));
+        generators.retain([command]);
+        assert!(generators.failed.is_empty());
+    }
+
+    #[test]
+    fn slow_generators_should_be_given_one_timeout_per_request() {
+        let command = "while read n x y w h m c; do \
+            sleep 0.15; echo \"$x $y $w $h\"; sleep 0.15; echo \"$x $y $w $h\"; done";
+        let start = Instant::now();
+        assert_eq!(geometries(command, &REQUEST), None);
+        assert!(start.elapsed() < TIMEOUT * 2);
+    }
+
+    #[test]
+    fn generators_no_longer_configured_should_be_stopped() {
+        let mut generators = Generators::default();
+        for command in ["kept", "dropped"] {
+            let generator = Generator::spawn("cat").unwrap();
+            generators.running.insert(command.to_owned(), generator);
+        }
+        let pid = generators.running["dropped"].child.id();
+        generators.retain(["kept"]);
+        assert!(generators.running.contains_key("kept"));
+        assert!(!generators.running.contains_key("dropped"));
+        assert!(!std::path::Path::new(&format!("/proc/{pid}")).exists());
+    }
+}
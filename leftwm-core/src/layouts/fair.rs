@@ -0,0 +1,55 @@
+use crate::models::Tag;
+use crate::models::Window;
+use crate::models::Workspace;
+
+/// Layout which gives every window an equal area, whatever their number. The windows are dealt
+/// out to as many columns as there would be in a `Grid`, each column being as wide as its share
+/// of the windows and divided evenly among them.
+/// Example arrangement (5 windows):
+/// ```text
+/// +---+------+------+
+/// |   |      |      |
+/// |   +------+------+
+/// |   |      |      |
+/// +---+------+------+
+/// ```
+pub fn update(workspace: &Workspace, tag: &Tag, windows: &mut [&mut Window]) {
+    let window_count = windows.len();
+    if window_count == 0 {
+        return;
+    }
+    let num_cols = (window_count as f32).sqrt().ceil() as usize;
+    let x = workspace.x_limited(num_cols);
+    let width = workspace.width_limited(num_cols);
+    let height = workspace.height();
+
+    // The columns on the right take the windows left over.
+    let fewer = window_count / num_cols;
+    let more_from = num_cols - window_count % num_cols;
+    let mut windows = windows.iter_mut();
+    let mut before = 0;
+    for col in 0..num_cols {
+        let count = if col < more_from { fewer } else { fewer + 1 };
+        let (start, end) = if tag.flipped_horizontal {
+            (window_count - before - count, window_count - before)
+        } else {
+            (before, before + count)
+        };
+        let left = x + edge(width, start, window_count);
+        let right = x + edge(width, end, window_count);
+        for (row, win) in windows.by_ref().take(count).enumerate() {
+            let top = workspace.y() + edge(height, row, count);
+            let bottom = workspace.y() + edge(height, row + 1, count);
+            win.set_x(left);
+            win.set_y(top);
+            win.set_width(right - left);
+            win.set_height(bottom - top);
+        }
+        before += count;
+    }
+}
+
+/// Where the `index`th of `count` equal parts of `size` starts.
+fn edge(size: i32, index: usize, count: usize) -> i32 {
+    (i64::from(size) * index as i64 / count as i64) as i32
+}
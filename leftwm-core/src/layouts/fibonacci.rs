@@ -2,7 +2,8 @@ use crate::models::Tag;
 use crate::models::Window;
 use crate::models::Workspace;
 
-/// Fibonacci layout, which divides the workspace in subsequent halves and assignes them to the windows
+/// Fibonacci layout, which divides the workspace in subsequent halves and assignes them to the windows.
+/// The first split follows the main width of the tag.
 /// ```text
 /// +-----------+-----------+
 /// |           |           |
@@ -30,15 +31,20 @@ pub fn update(workspace: &Workspace, tag: &Tag, windows: &mut [&mut Window]) {
             continue;
         }
 
-        let half_width = (width as f32 / 2.0).floor() as i32;
+        let split_width = if i == 0 {
+            (width as f32 / 100.0 * tag.main_width_percentage()).floor() as i32
+        } else {
+            (width as f32 / 2.0).floor() as i32
+        };
+        let alt_width = width - split_width;
         let half_height = (height as f32 / 2.0).floor() as i32;
         let (main_x, alt_x);
         if tag.flipped_horizontal {
-            main_x = x + half_width;
+            main_x = x + alt_width;
             alt_x = x;
         } else {
             main_x = x;
-            alt_x = x + half_width;
+            alt_x = x + split_width;
         }
         let (new_y, alt_y);
         if tag.flipped_vertical {
@@ -51,16 +57,16 @@ pub fn update(workspace: &Workspace, tag: &Tag, windows: &mut [&mut Window]) {
         match window_count - i {
             1 => setter(windows[i], height, width, x, y),
             2 => {
-                setter(windows[i], height, half_width, main_x, y);
-                setter(windows[i + 1], height, half_width, alt_x, y);
+                setter(windows[i], height, split_width, main_x, y);
+                setter(windows[i + 1], height, alt_width, alt_x, y);
             }
             _ => {
-                setter(windows[i], height, half_width, main_x, y);
-                setter(windows[i + 1], half_height, half_width, alt_x, alt_y);
+                setter(windows[i], height, split_width, main_x, y);
+                setter(windows[i + 1], half_height, alt_width, alt_x, alt_y);
 
                 x = alt_x;
                 y = new_y;
-                width = half_width;
+                width = alt_width;
                 height = half_height;
             }
         }
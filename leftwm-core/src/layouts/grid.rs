@@ -0,0 +1,54 @@
+use crate::models::Tag;
+use crate::models::Window;
+use crate::models::Workspace;
+
+/// Layout which arranges the windows in rows of equal height, with as many columns as rows or one
+/// more. The windows of a last row which isn't full share its width.
+/// Example arrangement (5 windows):
+/// ```text
+/// +---+---+---+
+/// |   |   |   |
+/// +---+-+-+---+
+/// |     |     |
+/// +-----+-----+
+/// ```
+pub fn update(workspace: &Workspace, tag: &Tag, windows: &mut [&mut Window]) {
+    let window_count = windows.len();
+    if window_count == 0 {
+        return;
+    }
+    let num_cols = (window_count as f32).sqrt().ceil() as usize;
+    let num_rows = window_count.div_ceil(num_cols);
+    let x = workspace.x_limited(num_cols);
+    let width = workspace.width_limited(num_cols);
+    let height = workspace.height();
+
+    for (row, row_windows) in windows.chunks_mut(num_cols).enumerate() {
+        let pos_y = if tag.flipped_vertical {
+            num_rows - row - 1
+        } else {
+            row
+        };
+        let top = workspace.y() + edge(height, pos_y, num_rows);
+        let bottom = workspace.y() + edge(height, pos_y + 1, num_rows);
+        let count = row_windows.len();
+        for (col, win) in row_windows.iter_mut().enumerate() {
+            let pos_x = if tag.flipped_horizontal {
+                count - col - 1
+            } else {
+                col
+            };
+            let left = x + edge(width, pos_x, count);
+            let right = x + edge(width, pos_x + 1, count);
+            win.set_x(left);
+            win.set_y(top);
+            win.set_width(right - left);
+            win.set_height(bottom - top);
+        }
+    }
+}
+
+/// Where the `index`th of `count` equal parts of `size` starts.
+fn edge(size: i32, index: usize, count: usize) -> i32 {
+    (i64::from(size) * index as i64 / count as i64) as i32
+}
@@ -3,7 +3,17 @@ use crate::models::Window;
 use crate::models::Workspace;
 // use crate::models::WindowState;
 
-/// Layout which gives only one window with the full desktop realestate. A monocle mode.
+/// Layout which gives the main window the left column and stacks all the other windows in a
+/// deck in the right column, showing only the front one. Focusing a window of the deck brings it
+/// to the front, as in `Monocle`.
+///
+/// ```text
+/// +-----------+-----------+
+/// |           |           |
+/// |     1     |   2 3 4   |
+/// |           |           |
+/// +-----------+-----------+
+/// ```
 pub fn update(workspace: &Workspace, tag: &Tag, windows: &mut [&mut Window]) {
     let window_count = windows.len();
 
@@ -2,59 +2,60 @@ use crate::models::Tag;
 use crate::models::Window;
 use crate::models::Workspace;
 
-/// Layout which splits the workspace into two rows, gives one window all of the upper row,
-/// and divides the lower row among all the other windows.
+/// Layout which splits the workspace into two rows, gives the tag's main windows the upper row,
+/// and divides the lower row among all the other windows. The main windows share the whole
+/// workspace when there are no others.
 pub fn update(workspace: &Workspace, tag: &Tag, windows: &mut [&mut Window]) {
     let window_count = windows.len();
     if window_count == 0 {
         return;
     }
 
-    let column_count = match window_count {
-        1 => 1,
-        _ => 2,
-    };
+    let main_count = tag.main_count().min(window_count);
+    let stacked = window_count > main_count;
+    let column_count = if stacked { 2 } else { 1 };
     let workspace_width = workspace.width_limited(column_count);
     let workspace_x = workspace.x_limited(column_count);
 
-    let height = match window_count {
-        1 => workspace.height() as i32,
-        _ => (workspace.height() as f32 / 100.0 * tag.main_width_percentage()).floor() as i32,
+    let height = if stacked {
+        (workspace.height() as f32 / 100.0 * tag.main_width_percentage()).floor() as i32
+    } else {
+        workspace.height()
     };
 
     let mut main_y = workspace.y();
     let mut stack_y = workspace.y() + height;
-    if tag.flipped_vertical {
-        main_y = match window_count {
-            1 => main_y,
-            _ => main_y + height,
-        };
-        stack_y = match window_count {
-            1 => 0,
-            _ => stack_y - height,
-        };
+    if tag.flipped_vertical && stacked {
+        main_y += height;
+        stack_y -= height;
     }
 
-    //build the main window.
-    let mut iter = windows.iter_mut();
-    {
-        if let Some(first) = iter.next() {
-            first.set_width(workspace_width);
-            first.set_height(height);
-            first.set_x(workspace_x);
-            first.set_y(main_y);
-        }
-    }
+    let (main, stack) = windows.split_at_mut(main_count);
+    row(tag, workspace_x, workspace_width, main, main_y, height);
+    row(
+        tag,
+        workspace_x,
+        workspace_width,
+        stack,
+        stack_y,
+        workspace.height() - height,
+    );
+}
 
-    //stack all the others
-    let width_f = workspace_width as f32 / (window_count - 1) as f32;
-    let width = width_f.floor() as i32;
-    let mut x = 0;
-    for w in iter {
-        w.set_height(workspace.height() - height);
-        w.set_width(width);
-        w.set_x(workspace_x + x);
-        w.set_y(stack_y);
-        x += width;
+/// Divide the width available to the layout among the windows by their weights, in a row at `y`.
+fn row(tag: &Tag, x: i32, width: i32, windows: &mut [&mut Window], y: i32, height: i32) {
+    let weights: Vec<f32> = windows
+        .iter()
+        .map(|w| tag.window_weight(&w.handle))
+        .collect();
+    let total: f32 = weights.iter().sum();
+    let mut offset = 0;
+    for (w, weight) in windows.iter_mut().zip(weights) {
+        let window_width = (width as f32 * weight / total).floor() as i32;
+        w.set_height(height);
+        w.set_width(window_width);
+        w.set_x(x + offset);
+        w.set_y(y);
+        offset += window_width;
     }
 }
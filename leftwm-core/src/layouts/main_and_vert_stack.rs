@@ -2,8 +2,9 @@ use crate::models::Tag;
 use crate::models::Window;
 use crate::models::Workspace;
 
-/// Layout which splits the workspace into two columns, gives one window all of the left column,
-/// and divides the right column among all the other windows.
+/// Layout which splits the workspace into two columns, gives the tag's main windows the left
+/// column, and divides the right column among all the other windows. The main windows share the
+/// whole workspace when there are no others.
 pub fn update(workspace: &Workspace, tag: &Tag, windows: &mut [&mut Window]) {
     let window_count = windows.len();
 
@@ -11,51 +12,54 @@ pub fn update(workspace: &Workspace, tag: &Tag, windows: &mut [&mut Window]) {
         return;
     }
 
-    let column_count = match window_count {
-        1 => 1,
-        _ => 2,
-    };
+    let main_count = tag.main_count().min(window_count);
+    let stacked = window_count > main_count;
+    let column_count = if stacked { 2 } else { 1 };
     let workspace_width = workspace.width_limited(column_count);
     let workspace_x = workspace.x_limited(column_count);
 
-    let primary_width = match window_count {
-        1 => workspace_width as i32,
-        _ => (workspace_width as f32 / 100.0 * tag.main_width_percentage()).floor() as i32,
+    let primary_width = if stacked {
+        (workspace_width as f32 / 100.0 * tag.main_width_percentage()).floor() as i32
+    } else {
+        workspace_width
     };
 
-    let mut main_x = workspace_x;
-    let stack_x = if tag.flipped_horizontal {
-        main_x = match window_count {
-            1 => main_x,
-            _ => main_x + workspace_width - primary_width,
-        };
-        match window_count {
-            1 => 0,
-            _ => workspace_x,
-        }
+    let (main_x, stack_x) = if tag.flipped_horizontal && stacked {
+        (workspace_x + workspace_width - primary_width, workspace_x)
     } else {
-        workspace_x + primary_width
+        (workspace_x, workspace_x + primary_width)
     };
 
-    //build the main window.
-    let mut iter = windows.iter_mut();
-    {
-        if let Some(first) = iter.next() {
-            first.set_height(workspace.height());
-            first.set_width(primary_width);
-            first.set_x(main_x);
-            first.set_y(workspace.y());
-        }
-    }
+    let (main, stack) = windows.split_at_mut(main_count);
+    column(workspace, tag, main, main_x, primary_width);
+    column(
+        workspace,
+        tag,
+        stack,
+        stack_x,
+        workspace_width - primary_width,
+    );
+}
 
-    //stack all the others
-    let height_f = workspace.height() as f32 / (window_count - 1) as f32;
-    let height = height_f.floor() as i32;
+/// Divide the height of the workspace among the windows by their weights, in a column at `x`.
+pub(super) fn column(
+    workspace: &Workspace,
+    tag: &Tag,
+    windows: &mut [&mut Window],
+    x: i32,
+    width: i32,
+) {
+    let weights: Vec<f32> = windows
+        .iter()
+        .map(|w| tag.window_weight(&w.handle))
+        .collect();
+    let total: f32 = weights.iter().sum();
     let mut y = 0;
-    for w in iter {
+    for (w, weight) in windows.iter_mut().zip(weights) {
+        let height = (workspace.height() as f32 * weight / total).floor() as i32;
         w.set_height(height);
-        w.set_width(workspace_width - primary_width);
-        w.set_x(stack_x);
+        w.set_width(width);
+        w.set_x(x);
         w.set_y(workspace.y() + y);
         y += height;
     }
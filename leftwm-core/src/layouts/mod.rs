@@ -5,12 +5,18 @@ use serde::{Deserialize, Serialize};
 use std::str::FromStr;
 use thiserror::Error;
 
+mod bsp;
 mod center_main;
 mod center_main_balanced;
+mod center_main_even;
 mod center_main_fluid;
+mod custom;
 mod even_horizontal;
 mod even_vertical;
+pub(crate) mod external;
+mod fair;
 mod fibonacci;
+mod grid;
 mod grid_horizontal;
 mod left_main;
 mod main_and_deck;
@@ -18,8 +24,14 @@ mod main_and_horizontal_stack;
 mod main_and_vert_stack;
 mod monocle;
 mod right_main_and_vert_stack;
+mod tabbed;
+mod three_column;
 
-#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+pub use custom::{CustomLayout, LayoutName, Region};
+pub use tabbed::{tab_bar_height, TAB_BAR_HEIGHT};
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+#[serde(try_from = "String", into = "String")]
 pub enum Layout {
     MainAndVertStack,
     MainAndHorizontalStack,
@@ -31,10 +43,18 @@ pub enum Layout {
     LeftMain,
     CenterMain,
     CenterMainBalanced,
+    CenterMainEven,
     CenterMainFluid,
     Monocle,
     RightWiderLeftStack,
     LeftWiderRightStack,
+    Bsp,
+    Grid,
+    ThreeColumn,
+    Tabbed,
+    Fair,
+    /// A layout defined in the config by its name.
+    Custom(LayoutName),
 }
 
 pub const LAYOUTS: &[Layout] = &[
@@ -48,10 +68,16 @@ pub const LAYOUTS: &[Layout] = &[
     Layout::LeftMain,
     Layout::CenterMain,
     Layout::CenterMainBalanced,
+    Layout::CenterMainEven,
     Layout::CenterMainFluid,
     Layout::Monocle,
     Layout::RightWiderLeftStack,
     Layout::LeftWiderRightStack,
+    Layout::Bsp,
+    Layout::Grid,
+    Layout::ThreeColumn,
+    Layout::Tabbed,
+    Layout::Fair,
 ];
 
 impl Default for Layout {
@@ -62,7 +88,13 @@ impl Default for Layout {
 
 // This is tedious, but simple and effective.
 impl Layout {
-    pub fn update_windows(&self, workspace: &Workspace, windows: &mut [&mut Window], tag: &Tag) {
+    pub fn update_windows(
+        &self,
+        workspace: &Workspace,
+        windows: &mut [&mut Window],
+        tag: &Tag,
+        custom_layouts: &[CustomLayout],
+    ) {
         match self {
             Self::MainAndVertStack | Self::LeftWiderRightStack => {
                 main_and_vert_stack::update(workspace, tag, windows);
@@ -78,18 +110,63 @@ impl Layout {
             Self::LeftMain => left_main::update(workspace, tag, windows),
             Self::CenterMain => center_main::update(workspace, tag, windows),
             Self::CenterMainBalanced => center_main_balanced::update(workspace, tag, windows),
+            Self::CenterMainEven => center_main_even::update(workspace, tag, windows),
             Self::CenterMainFluid => center_main_fluid::update(workspace, tag, windows),
             Self::Monocle => monocle::update(workspace, windows),
             Self::RightWiderLeftStack => {
                 right_main_and_vert_stack::update(workspace, tag, windows);
             }
+            Self::Bsp => bsp::update(workspace, tag, windows),
+            Self::Grid => grid::update(workspace, tag, windows),
+            Self::ThreeColumn => three_column::update(workspace, tag, windows),
+            Self::Tabbed => tabbed::update(workspace, windows),
+            Self::Fair => fair::update(workspace, tag, windows),
+            Self::Custom(name) => match custom_layouts.iter().find(|l| l.name == name.as_str()) {
+                Some(layout) => custom::update(workspace, tag, layout, windows),
+                None => main_and_vert_stack::update(workspace, tag, windows),
+            },
+        }
+    }
+
+    /// Whether only the front window is shown, the windows being rotated to bring the focused
+    /// one to the front.
+    #[must_use]
+    pub const fn is_monocle(&self) -> bool {
+        matches!(self, Self::Monocle | Self::Tabbed)
+    }
+
+    /// The name the layout goes by in the config and commands.
+    #[must_use]
+    pub fn name(&self) -> &str {
+        match self {
+            Self::MainAndVertStack => "MainAndVertStack",
+            Self::MainAndHorizontalStack => "MainAndHorizontalStack",
+            Self::MainAndDeck => "MainAndDeck",
+            Self::GridHorizontal => "GridHorizontal",
+            Self::EvenHorizontal => "EvenHorizontal",
+            Self::EvenVertical => "EvenVertical",
+            Self::Fibonacci => "Fibonacci",
+            Self::LeftMain => "LeftMain",
+            Self::CenterMain => "CenterMain",
+            Self::CenterMainBalanced => "CenterMainBalanced",
+            Self::CenterMainEven => "CenterMainEven",
+            Self::CenterMainFluid => "CenterMainFluid",
+            Self::Monocle => "Monocle",
+            Self::RightWiderLeftStack => "RightWiderLeftStack",
+            Self::LeftWiderRightStack => "LeftWiderRightStack",
+            Self::Bsp => "Bsp",
+            Self::Grid => "Grid",
+            Self::ThreeColumn => "ThreeColumn",
+            Self::Tabbed => "Tabbed",
+            Self::Fair => "Fair",
+            Self::Custom(name) => name.as_str(),
         }
     }
 
-    pub const fn main_width(&self) -> u8 {
+    pub const fn main_width(&self) -> f32 {
         match self {
-            Self::RightWiderLeftStack | Self::LeftWiderRightStack => 75,
-            _ => 50,
+            Self::RightWiderLeftStack | Self::LeftWiderRightStack => 75.0,
+            _ => 50.0,
         }
     }
 
@@ -97,7 +174,7 @@ impl Layout {
     pub fn rotations(&self) -> Vec<(bool, bool)> {
         match self {
             //Layouts that can be flipped both ways
-            Self::Fibonacci | Self::GridHorizontal => {
+            Self::Fibonacci | Self::GridHorizontal | Self::Grid => {
                 [(false, false), (true, false), (true, true), (false, true)].to_vec()
             }
             //Layouts that can be flipped vertically
@@ -119,28 +196,90 @@ impl FromStr for Layout {
         match s {
             "MainAndVertStack" => Ok(Self::MainAndVertStack),
             "MainAndHorizontalStack" => Ok(Self::MainAndHorizontalStack),
-            "MainAndDeck" => Ok(Self::MainAndDeck),
+            "MainAndDeck" | "Deck" => Ok(Self::MainAndDeck),
             "GridHorizontal" => Ok(Self::GridHorizontal),
             "EvenHorizontal" => Ok(Self::EvenHorizontal),
             "EvenVertical" => Ok(Self::EvenVertical),
-            "Fibonacci" => Ok(Self::Fibonacci),
+            "Fibonacci" | "Dwindle" => Ok(Self::Fibonacci),
             "LeftMain" => Ok(Self::LeftMain),
             "CenterMain" => Ok(Self::CenterMain),
             "CenterMainBalanced" => Ok(Self::CenterMainBalanced),
+            "CenterMainEven" => Ok(Self::CenterMainEven),
             "CenterMainFluid" => Ok(Self::CenterMainFluid),
             "Monocle" => Ok(Self::Monocle),
             "RightWiderLeftStack" => Ok(Self::RightWiderLeftStack),
             "LeftWiderRightStack" => Ok(Self::LeftWiderRightStack),
+            "Bsp" => Ok(Self::Bsp),
+            "Grid" => Ok(Self::Grid),
+            "ThreeColumn" => Ok(Self::ThreeColumn),
+            "Tabbed" => Ok(Self::Tabbed),
+            "Fair" => Ok(Self::Fair),
+            // Any other name is one of the layouts of the config.
+            _ if !s.is_empty() && !s.contains(char::is_whitespace) => {
+                Ok(Self::Custom(LayoutName::new(s)))
+            }
             _ => Err(ParseLayoutError(s.to_string())),
         }
     }
 }
 
+impl TryFrom<String> for Layout {
+    type Error = ParseLayoutError;
+
+    fn try_from(s: String) -> Result<Self, Self::Error> {
+        Self::from_str(&s)
+    }
+}
+
+impl From<Layout> for String {
+    fn from(layout: Layout) -> Self {
+        layout.name().to_owned()
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
     use crate::models::{BBox, Margins, WindowHandle};
 
+    /// Lay `count` windows without borders or margins out with the layout of `tag`, on a
+    /// workspace of `width` by `height` without margins.
+    fn arrange(
+        tag: &Tag,
+        (width, height): (i32, i32),
+        count: u32,
+        custom_layouts: &[CustomLayout],
+    ) -> Vec<Window> {
+        let bbox = BBox {
+            width,
+            height,
+            x: 0,
+            y: 0,
+        };
+        let mut ws = Workspace::new(None, bbox, tag.layout.clone(), None);
+        ws.margin = Margins::new(0);
+        ws.update_avoided_areas();
+        let mut windows: Vec<Window> = (1..=count)
+            .map(|i| {
+                let mut w = Window::new(WindowHandle::MockHandle(i as i32), None, None);
+                w.border = 0;
+                w.margin = Margins::new(0);
+                w
+            })
+            .collect();
+        let mut refs: Vec<&mut Window> = windows.iter_mut().collect();
+        tag.layout
+            .update_windows(&ws, &mut refs, tag, custom_layouts);
+        windows
+    }
+
+    fn areas(windows: &[Window]) -> Vec<(i32, i32, i32, i32)> {
+        windows
+            .iter()
+            .map(|w| (w.x(), w.y(), w.width(), w.height()))
+            .collect()
+    }
+
     #[test]
     fn should_fullscreen_a_single_window() {
         //size defaults to 600x800
@@ -171,9 +310,22 @@ mod tests {
         assert!(w.width() == 800, "window was not size to the correct width");
     }
 
+    #[test]
+    fn main_windows_should_share_the_main_column() {
+        let mut tag = Tag::new(1, "1", Layout::MainAndVertStack);
+        tag.set_main_count(2);
+        assert_eq!(
+            areas(&arrange(&tag, (800, 600), 3, &[])),
+            vec![(0, 0, 400, 300), (0, 300, 400, 300), (400, 0, 400, 600)]
+        );
+
+        // With no windows left for the stack, the main windows take the whole width.
+        assert_eq!(arrange(&tag, (800, 600), 2, &[])[0].width(), 800);
+    }
+
     #[test]
     fn test_from_str() {
-        let layout_strs: [&str; 14] = [
+        let layout_strs: [&str; 20] = [
             "MainAndVertStack",
             "MainAndHorizontalStack",
             "MainAndDeck",
@@ -184,10 +336,16 @@ mod tests {
             "LeftMain",
             "CenterMain",
             "CenterMainBalanced",
+            "CenterMainEven",
             "CenterMainFluid",
             "Monocle",
             "RightWiderLeftStack",
             "LeftWiderRightStack",
+            "Bsp",
+            "Grid",
+            "ThreeColumn",
+            "Tabbed",
+            "Fair",
         ];
 
         assert_eq!(layout_strs.len(), LAYOUTS.len());
@@ -199,4 +357,121 @@ mod tests {
             );
         }
     }
+
+    #[test]
+    fn main_and_deck_should_show_only_the_front_of_the_deck() {
+        let tag = Tag::new(1, "1", Layout::MainAndDeck);
+        let windows = arrange(&tag, (800, 600), 4, &[]);
+        let visible: Vec<bool> = windows.iter().map(Window::visible).collect();
+        assert_eq!(visible, vec![true, true, false, false]);
+        let deck: Vec<(i32, i32)> = windows[1..].iter().map(|w| (w.x(), w.width())).collect();
+        assert_eq!(deck, vec![(400, 400); 3]);
+        assert_eq!(Layout::from_str("Deck").unwrap(), Layout::MainAndDeck);
+    }
+
+    #[test]
+    fn fair_should_give_every_window_the_same_area() {
+        let tag = Tag::new(1, "1", Layout::Fair);
+        let windows = arrange(&tag, (900, 600), 5, &[]);
+        assert_eq!(
+            areas(&windows),
+            vec![
+                (0, 0, 180, 600),
+                (180, 0, 360, 300),
+                (180, 300, 360, 300),
+                (540, 0, 360, 300),
+                (540, 300, 360, 300),
+            ]
+        );
+        assert!(windows.iter().all(|w| w.width() * w.height() == 108_000));
+    }
+
+    #[test]
+    fn custom_layouts_should_place_the_windows_in_their_regions() {
+        let region = |x, y, w, h| Region { x, y, w, h };
+        let sidebar = CustomLayout {
+            name: "Sidebar".to_string(),
+            arrangements: vec![
+                vec![region(0.0, 0.0, 100.0, 100.0)],
+                vec![
+                    region(0.0, 0.0, 70.0, 100.0),
+                    region(70.0, 0.0, 30.0, 100.0),
+                ],
+            ],
+            command: None,
+        };
+        let layout = Layout::from_str("Sidebar").unwrap();
+        assert_eq!(layout, Layout::Custom(LayoutName::new("Sidebar")));
+        let tag = Tag::new(1, "1", layout);
+        // The third window shares the last region of the biggest arrangement.
+        assert_eq!(
+            areas(&arrange(&tag, (1000, 600), 3, &[sidebar])),
+            vec![(0, 0, 700, 600), (700, 0, 300, 300), (700, 300, 300, 300)]
+        );
+    }
+
+    #[test]
+    fn layouts_should_serialize_by_name() {
+        let layouts = vec![
+            Layout::Fibonacci,
+            Layout::Custom(LayoutName::new("Sidebar")),
+        ];
+        let json = serde_json::to_string(&layouts).unwrap();
+        assert_eq!(json, r#"["Fibonacci","Sidebar"]"#);
+        let parsed: Vec<Layout> = serde_json::from_str(r#"["Dwindle","Sidebar"]"#).unwrap();
+        assert_eq!(parsed, layouts);
+    }
+
+    #[test]
+    fn grid_should_stretch_the_windows_of_the_last_row() {
+        let tag = Tag::new(1, "1", Layout::Grid);
+        assert_eq!(
+            areas(&arrange(&tag, (800, 600), 5, &[])),
+            vec![
+                (0, 0, 266, 300),
+                (266, 0, 267, 300),
+                (533, 0, 267, 300),
+                (0, 300, 400, 300),
+                (400, 300, 400, 300),
+            ]
+        );
+    }
+
+    #[test]
+    fn center_main_even_should_halve_the_stack_between_the_sides() {
+        let tag = Tag::new(1, "1", Layout::CenterMainEven);
+        assert_eq!(
+            areas(&arrange(&tag, (800, 600), 5, &[])),
+            vec![
+                (200, 0, 400, 600),
+                (600, 0, 200, 300),
+                (600, 300, 200, 300),
+                (0, 0, 200, 300),
+                (0, 300, 200, 300),
+            ]
+        );
+    }
+
+    #[test]
+    fn three_column_should_deal_the_stack_out_to_the_sides() {
+        let mut tag = Tag::new(1, "1", Layout::ThreeColumn);
+        tag.set_stack_split(25.0);
+        assert_eq!(
+            areas(&arrange(&tag, (800, 600), 5, &[])),
+            vec![
+                (100, 0, 400, 600),
+                (500, 0, 300, 300),
+                (0, 0, 100, 300),
+                (500, 300, 300, 300),
+                (0, 300, 100, 300),
+            ]
+        );
+    }
+
+    #[test]
+    fn dwindle_should_be_the_fibonacci_layout() {
+        assert_eq!(Layout::from_str("Dwindle").unwrap(), Layout::Fibonacci);
+        let layout: Layout = serde_json::from_str("\"Dwindle\"").unwrap();
+        assert_eq!(layout, Layout::Fibonacci);
+    }
 }
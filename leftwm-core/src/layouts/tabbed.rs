@@ -0,0 +1,37 @@
+use crate::models::Window;
+use crate::models::Workspace;
+
+/// Height in pixels of the strip of tabs drawn above the windows of the `Tabbed` layout.
+pub const TAB_BAR_HEIGHT: i32 = 20;
+
+/// Height of the strip of tabs, made taller for text bigger than fits `TAB_BAR_HEIGHT`.
+#[must_use]
+pub fn tab_bar_height(min_font_size: Option<u32>) -> i32 {
+    min_font_size.map_or(TAB_BAR_HEIGHT, |size| TAB_BAR_HEIGHT.max(size as i32 + 8))
+}
+
+/// Layout which, like `Monocle`, shows only the front window, below a strip with a tab for each
+/// window of the tag.
+///
+/// ```text
+/// +-------+-------+-------+
+/// |   1   |   2   |   3   |
+/// +-------+-------+-------+
+/// |                       |
+/// |           1           |
+/// |                       |
+/// +-----------------------+
+/// ```
+pub fn update(workspace: &Workspace, windows: &mut [&mut Window]) {
+    let height = (workspace.height() - workspace.tab_bar_height).max(0);
+    let y = workspace.y() + workspace.height() - height;
+    let width = workspace.width_limited(1);
+    let x = workspace.x_limited(1);
+    for (i, w) in windows.iter_mut().enumerate() {
+        w.set_height(height);
+        w.set_width(width);
+        w.set_x(x);
+        w.set_y(y);
+        w.set_visible(i == 0);
+    }
+}
@@ -32,11 +32,12 @@ use utils::xkeysym_lookup::Button;
 use utils::xkeysym_lookup::ModMask;
 use utils::xkeysym_lookup::XKeysym;
 
-pub use command::Command;
+pub use command::{Command, ParseTagCycleError};
 pub use config::{Config, Keybind};
 pub use display_action::DisplayAction;
 pub use display_event::DisplayEvent;
 pub use display_servers::xlib_display_server::XWrap;
+pub use display_servers::Backend;
 pub use display_servers::DisplayServer;
 pub use display_servers::XlibDisplayServer;
 pub use models::Manager;
@@ -46,4 +47,6 @@ pub use models::Workspace;
 pub use state::State;
 pub use utils::child_process;
 pub use utils::command_pipe::CommandPipe;
+pub use utils::command_socket::CommandSocket;
+pub use utils::i3_ipc::I3Socket;
 pub use utils::state_socket::StateSocket;
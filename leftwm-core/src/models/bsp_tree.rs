@@ -0,0 +1,273 @@
+use crate::models::{WindowHandle, Xyhw, XyhwBuilder};
+use serde::{Deserialize, Serialize};
+
+/// The regions of the `Bsp` layout, as a binary tree with a window in each leaf.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Default)]
+pub struct BspTree {
+    root: Option<BspNode>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+enum BspNode {
+    Leaf(WindowHandle),
+    Split {
+        /// Whether the children are side by side rather than above each other.
+        vertical: bool,
+        first: Box<BspNode>,
+        second: Box<BspNode>,
+    },
+}
+
+impl BspTree {
+    /// Make the tree hold exactly the given windows. Windows new to the tree split the region of
+    /// the most recently focused window in it, so `focus_order` starts with the focused window.
+    pub fn sync(&mut self, windows: &[WindowHandle], focus_order: &[WindowHandle]) {
+        for handle in self.handles() {
+            if !windows.contains(&handle) {
+                self.remove(handle);
+            }
+        }
+        for &handle in windows {
+            if !self.contains(handle) {
+                let target = focus_order.iter().copied().find(|&h| self.contains(h));
+                self.insert(handle, target);
+            }
+        }
+    }
+
+    /// The windows in the tree, in order.
+    #[must_use]
+    pub fn handles(&self) -> Vec<WindowHandle> {
+        let mut handles = vec![];
+        if let Some(root) = &self.root {
+            root.collect(&mut handles);
+        }
+        handles
+    }
+
+    #[must_use]
+    pub fn contains(&self, handle: WindowHandle) -> bool {
+        self.handles().contains(&handle)
+    }
+
+    /// Split the region of `target`, or of the last window if it isn't in the tree, between it
+    /// and `handle`. Splits alternate between vertical and horizontal with their depth.
+    pub fn insert(&mut self, handle: WindowHandle, target: Option<WindowHandle>) {
+        let target = target
+            .filter(|&t| self.contains(t))
+            .or_else(|| self.handles().last().copied());
+        match (&mut self.root, target) {
+            (Some(root), Some(target)) => {
+                root.split(target, handle, true);
+            }
+            _ => self.root = Some(BspNode::Leaf(handle)),
+        }
+    }
+
+    /// Remove a window, giving its region to its sibling.
+    pub fn remove(&mut self, handle: WindowHandle) {
+        self.root = self.root.take().and_then(|root| root.without(handle));
+    }
+
+    /// Toggle the direction of every split in the subtree around a window.
+    pub fn rotate(&mut self, handle: WindowHandle) {
+        if let Some(subtree) = self.subtree_of(handle) {
+            subtree.for_each_split(&mut |vertical, _, _| *vertical = !*vertical);
+        }
+    }
+
+    /// Swap the children of every split in the subtree around a window.
+    pub fn flip(&mut self, handle: WindowHandle) {
+        if let Some(subtree) = self.subtree_of(handle) {
+            subtree.for_each_split(&mut |_, first, second| std::mem::swap(first, second));
+        }
+    }
+
+    /// The area of each window, dividing `area` in halves along the tree.
+    #[must_use]
+    pub fn areas(&self, area: Xyhw) -> Vec<(WindowHandle, Xyhw)> {
+        let mut areas = vec![];
+        if let Some(root) = &self.root {
+            root.divide(area, &mut areas);
+        }
+        areas
+    }
+
+    /// The split holding a window, or the window's leaf when it has the whole tree.
+    fn subtree_of(&mut self, handle: WindowHandle) -> Option<&mut BspNode> {
+        let root = self.root.as_mut()?;
+        if matches!(root, BspNode::Leaf(h) if *h == handle) {
+            return Some(root);
+        }
+        root.parent_of(handle)
+    }
+}
+
+impl BspNode {
+    fn collect(&self, handles: &mut Vec<WindowHandle>) {
+        match self {
+            Self::Leaf(handle) => handles.push(*handle),
+            Self::Split { first, second, .. } => {
+                first.collect(handles);
+                second.collect(handles);
+            }
+        }
+    }
+
+    /// Replace the leaf of `target` with a split holding it and `handle`. Returns whether the
+    /// leaf was found.
+    fn split(&mut self, target: WindowHandle, handle: WindowHandle, vertical: bool) -> bool {
+        match self {
+            Self::Leaf(leaf) if *leaf == target => {
+                *self = Self::Split {
+                    vertical,
+                    first: Box::new(Self::Leaf(target)),
+                    second: Box::new(Self::Leaf(handle)),
+                };
+                true
+            }
+            Self::Leaf(_) => false,
+            Self::Split { first, second, .. } => {
+                first.split(target, handle, !vertical) || second.split(target, handle, !vertical)
+            }
+        }
+    }
+
+    fn without(self, handle: WindowHandle) -> Option<Self> {
+        match self {
+            Self::Leaf(leaf) if leaf == handle => None,
+            Self::Leaf(_) => Some(self),
+            Self::Split {
+                vertical,
+                first,
+                second,
+            } => match (first.without(handle), second.without(handle)) {
+                (Some(first), Some(second)) => Some(Self::Split {
+                    vertical,
+                    first: Box::new(first),
+                    second: Box::new(second),
+                }),
+                (remaining, None) | (None, remaining) => remaining,
+            },
+        }
+    }
+
+    fn parent_of(&mut self, handle: WindowHandle) -> Option<&mut Self> {
+        let is_parent = match self {
+            Self::Leaf(_) => return None,
+            Self::Split { first, second, .. } => [first, second]
+                .iter()
+                .any(|child| matches!(***child, Self::Leaf(h) if h == handle)),
+        };
+        if is_parent {
+            return Some(self);
+        }
+        match self {
+            Self::Leaf(_) => None,
+            Self::Split { first, second, .. } => match first.parent_of(handle) {
+                Some(parent) => Some(parent),
+                None => second.parent_of(handle),
+            },
+        }
+    }
+
+    fn for_each_split(&mut self, f: &mut impl FnMut(&mut bool, &mut Box<Self>, &mut Box<Self>)) {
+        if let Self::Split {
+            vertical,
+            first,
+            second,
+        } = self
+        {
+            f(vertical, first, second);
+            first.for_each_split(f);
+            second.for_each_split(f);
+        }
+    }
+
+    fn divide(&self, area: Xyhw, areas: &mut Vec<(WindowHandle, Xyhw)>) {
+        match self {
+            Self::Leaf(handle) => areas.push((*handle, area)),
+            Self::Split {
+                vertical,
+                first,
+                second,
+            } => {
+                let (first_area, second_area) = if *vertical {
+                    let w = area.w() / 2;
+                    (
+                        build(area.x(), area.y(), w, area.h()),
+                        build(area.x() + w, area.y(), area.w() - w, area.h()),
+                    )
+                } else {
+                    let h = area.h() / 2;
+                    (
+                        build(area.x(), area.y(), area.w(), h),
+                        build(area.x(), area.y() + h, area.w(), area.h() - h),
+                    )
+                };
+                first.divide(first_area, areas);
+                second.divide(second_area, areas);
+            }
+        }
+    }
+}
+
+fn build(x: i32, y: i32, w: i32, h: i32) -> Xyhw {
+    XyhwBuilder {
+        x,
+        y,
+        h,
+        w,
+        ..XyhwBuilder::default()
+    }
+    .into()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn handle(id: i32) -> WindowHandle {
+        WindowHandle::MockHandle(id)
+    }
+
+    fn geometry(tree: &BspTree) -> Vec<(i32, i32, i32, i32)> {
+        tree.areas(build(0, 0, 800, 600))
+            .iter()
+            .map(|(_, a)| (a.x(), a.y(), a.w(), a.h()))
+            .collect()
+    }
+
+    #[test]
+    fn new_windows_split_the_focused_region_alternately() {
+        let mut tree = BspTree::default();
+        tree.sync(&[handle(1)], &[]);
+        tree.sync(&[handle(1), handle(2)], &[handle(1)]);
+        tree.sync(&[handle(1), handle(2), handle(3)], &[handle(2)]);
+        assert_eq!(
+            geometry(&tree),
+            vec![(0, 0, 400, 600), (400, 0, 400, 300), (400, 300, 400, 300)]
+        );
+    }
+
+    #[test]
+    fn removed_windows_leave_their_region_to_the_sibling() {
+        let mut tree = BspTree::default();
+        tree.sync(&[handle(1), handle(2), handle(3)], &[]);
+        tree.sync(&[handle(1), handle(3)], &[]);
+        assert_eq!(geometry(&tree), vec![(0, 0, 400, 600), (400, 0, 400, 600)]);
+    }
+
+    #[test]
+    fn subtrees_can_be_rotated_and_flipped() {
+        let mut tree = BspTree::default();
+        tree.sync(&[handle(1), handle(2), handle(3)], &[]);
+        tree.rotate(handle(3));
+        assert_eq!(
+            geometry(&tree),
+            vec![(0, 0, 400, 600), (400, 0, 200, 600), (600, 0, 200, 600)]
+        );
+        tree.flip(handle(3));
+        assert_eq!(tree.handles(), vec![handle(1), handle(3), handle(2)]);
+    }
+}
@@ -0,0 +1,129 @@
+use crate::models::Xyhw;
+use serde::{Deserialize, Serialize};
+use std::str::FromStr;
+use thiserror::Error;
+
+/// A direction on screen, for commands finding windows by their geometry.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+pub enum Direction {
+    Left,
+    Right,
+    Up,
+    Down,
+}
+
+impl Direction {
+    /// How far `to` lies from `from` in this direction, or `None` if it doesn't lie in this
+    /// direction at all. Areas in line with `from` compare nearer than any area that isn't.
+    #[must_use]
+    pub fn distance(self, from: &Xyhw, to: &Xyhw) -> Option<(bool, i32)> {
+        let (from_x, from_y) = from.center();
+        let (to_x, to_y) = to.center();
+        let (ahead, aside, in_line) = match self {
+            Self::Left => (
+                from_x - to_x,
+                to_y - from_y,
+                overlap(from.y(), from.h(), to.y(), to.h()),
+            ),
+            Self::Right => (
+                to_x - from_x,
+                to_y - from_y,
+                overlap(from.y(), from.h(), to.y(), to.h()),
+            ),
+            Self::Up => (
+                from_y - to_y,
+                to_x - from_x,
+                overlap(from.x(), from.w(), to.x(), to.w()),
+            ),
+            Self::Down => (
+                to_y - from_y,
+                to_x - from_x,
+                overlap(from.x(), from.w(), to.x(), to.w()),
+            ),
+        };
+        if ahead <= 0 {
+            return None;
+        }
+        if in_line {
+            Some((false, ahead))
+        } else {
+            Some((true, ahead + aside.abs()))
+        }
+    }
+
+    /// The candidate nearest to `from` in this direction, if any lies in it.
+    pub fn nearest<T>(self, from: &Xyhw, candidates: impl Iterator<Item = (Xyhw, T)>) -> Option<T> {
+        candidates
+            .filter_map(|(to, item)| self.distance(from, &to).map(|distance| (distance, item)))
+            .min_by_key(|(distance, _)| *distance)
+            .map(|(_, item)| item)
+    }
+}
+
+const fn overlap(start: i32, size: i32, other_start: i32, other_size: i32) -> bool {
+    start < other_start + other_size && other_start < start + size
+}
+
+#[derive(Debug, Error)]
+#[error("Could not parse direction: {0}")]
+pub struct ParseDirectionError(String);
+
+impl FromStr for Direction {
+    type Err = ParseDirectionError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "Left" => Ok(Self::Left),
+            "Right" => Ok(Self::Right),
+            "Up" => Ok(Self::Up),
+            "Down" => Ok(Self::Down),
+            _ => Err(ParseDirectionError(s.to_string())),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::XyhwBuilder;
+
+    fn area(x: i32, y: i32, w: i32, h: i32) -> Xyhw {
+        XyhwBuilder {
+            x,
+            y,
+            h,
+            w,
+            ..XyhwBuilder::default()
+        }
+        .into()
+    }
+
+    #[test]
+    fn areas_behind_are_not_in_the_direction() {
+        let from = area(400, 0, 400, 600);
+        assert_eq!(
+            Direction::Right.distance(&from, &area(0, 0, 400, 600)),
+            None
+        );
+        assert!(Direction::Left
+            .distance(&from, &area(0, 0, 400, 600))
+            .is_some());
+    }
+
+    #[test]
+    fn areas_in_line_are_preferred_over_nearer_ones() {
+        // The main window of a three-column layout, with a floating window just above its row.
+        let from = area(300, 0, 200, 300);
+        let in_line = area(600, 200, 200, 300);
+        let nearer = area(510, 310, 50, 50);
+        let in_line = Direction::Right.distance(&from, &in_line);
+        let nearer = Direction::Right.distance(&from, &nearer);
+        assert!(in_line < nearer);
+    }
+
+    #[test]
+    fn should_be_able_to_parse_directions() {
+        assert_eq!(Direction::from_str("Up").unwrap(), Direction::Up);
+        assert!(Direction::from_str("Sideways").is_err());
+    }
+}
@@ -61,150 +61,266 @@ impl From<&[i32]> for DockArea {
 }
 
 impl DockArea {
+    /// Resolve the strut into the area it reserves on the screens whose edge it touches.
+    ///
+    /// Strut values are measured from the edges of the combined screen area, so a dock on a
+    /// monitor further down a vertical stack reports a strut crossing all the monitors above it.
+    /// Only the screens the strut ends on are reserved; if the dock spans several of them the
+    /// returned area covers all of them.
     #[must_use]
-    pub fn as_xyhw(
-        &self,
-        screens_height: i32,
-        screens_width: i32,
-        screen: &Screen,
-    ) -> Option<Xyhw> {
+    pub fn resolve(&self, screens: &[Screen], screens_area: (i32, i32)) -> Option<Xyhw> {
+        let (screens_height, screens_width) = screens_area;
         if self.top > 0 {
-            return Some(self.xyhw_from_top(screen.bbox.y));
+            return self.resolve_top(screens);
         }
         if self.bottom > 0 {
-            return Some(self.xyhw_from_bottom(screens_height, screen.bbox.y + screen.bbox.height));
+            return self.resolve_bottom(screens, screens_height);
         }
         if self.left > 0 {
-            return Some(self.xyhw_from_left(screen.bbox.x));
+            return self.resolve_left(screens);
         }
         if self.right > 0 {
-            return Some(self.xyhw_from_right(screens_width, screen.bbox.x + screen.bbox.width));
+            return self.resolve_right(screens, screens_width);
         }
         None
     }
 
-    fn xyhw_from_top(&self, screen_y: i32) -> Xyhw {
-        XyhwBuilder {
-            x: self.top_start_x,
-            y: screen_y,
-            h: self.top - screen_y,
-            w: self.top_end_x - self.top_start_x,
-            ..XyhwBuilder::default()
-        }
-        .into()
+    fn resolve_top(&self, screens: &[Screen]) -> Option<Xyhw> {
+        let edge = self.top;
+        let touched = touched_screens(screens, self.top_start_x, self.top_end_x, true, |s| {
+            s.bbox.y < edge && edge <= s.bbox.y + s.bbox.height
+        });
+        let (start, end) = span(&touched, self.top_start_x, self.top_end_x, true)?;
+        let y = touched.iter().map(|s| s.bbox.y).min()?;
+        Some(build(start, y, end - start, edge - y))
     }
 
-    fn xyhw_from_bottom(&self, screens_height: i32, screen_bottom: i32) -> Xyhw {
-        XyhwBuilder {
-            x: self.bottom_start_x,
-            y: screens_height - self.bottom,
-            h: self.bottom - (screens_height - screen_bottom),
-            w: self.bottom_end_x - self.bottom_start_x,
-            ..XyhwBuilder::default()
-        }
-        .into()
+    fn resolve_bottom(&self, screens: &[Screen], screens_height: i32) -> Option<Xyhw> {
+        let edge = screens_height - self.bottom;
+        let touched = touched_screens(screens, self.bottom_start_x, self.bottom_end_x, true, |s| {
+            s.bbox.y <= edge && edge < s.bbox.y + s.bbox.height
+        });
+        let (start, end) = span(&touched, self.bottom_start_x, self.bottom_end_x, true)?;
+        let bottom = touched.iter().map(|s| s.bbox.y + s.bbox.height).max()?;
+        Some(build(start, edge, end - start, bottom - edge))
     }
 
-    fn xyhw_from_left(&self, screen_x: i32) -> Xyhw {
-        XyhwBuilder {
-            x: screen_x,
-            y: self.left_start_y,
-            h: self.left_end_y - self.left_start_y,
-            w: self.left - screen_x,
-            ..XyhwBuilder::default()
-        }
-        .into()
+    fn resolve_left(&self, screens: &[Screen]) -> Option<Xyhw> {
+        let edge = self.left;
+        let touched = touched_screens(screens, self.left_start_y, self.left_end_y, false, |s| {
+            s.bbox.x < edge && edge <= s.bbox.x + s.bbox.width
+        });
+        let (start, end) = span(&touched, self.left_start_y, self.left_end_y, false)?;
+        let x = touched.iter().map(|s| s.bbox.x).min()?;
+        Some(build(x, start, edge - x, end - start))
     }
 
-    fn xyhw_from_right(&self, screens_width: i32, screen_right: i32) -> Xyhw {
-        XyhwBuilder {
-            x: screens_width - self.right,
-            y: self.right_start_y,
-            h: self.right_end_y - self.right_start_y,
-            w: self.right - (screens_width - screen_right),
-            ..XyhwBuilder::default()
-        }
-        .into()
+    fn resolve_right(&self, screens: &[Screen], screens_width: i32) -> Option<Xyhw> {
+        let edge = screens_width - self.right;
+        let touched = touched_screens(screens, self.right_start_y, self.right_end_y, false, |s| {
+            s.bbox.x <= edge && edge < s.bbox.x + s.bbox.width
+        });
+        let (start, end) = span(&touched, self.right_start_y, self.right_end_y, false)?;
+        let right = touched.iter().map(|s| s.bbox.x + s.bbox.width).max()?;
+        Some(build(edge, start, right - edge, end - start))
     }
 }
 
+/// The screens a strut ends on which overlap its `start..=end` range. The range runs along the
+/// x axis when `horizontal` is set, along the y axis otherwise.
+fn touched_screens(
+    screens: &[Screen],
+    start: i32,
+    end: i32,
+    horizontal: bool,
+    ends_on: impl Fn(&Screen) -> bool,
+) -> Vec<&Screen> {
+    screens
+        .iter()
+        .filter(|s| {
+            let (s_start, s_size) = axis(s, horizontal);
+            start < s_start + s_size && s_start <= end
+        })
+        .filter(|s| ends_on(s))
+        .collect()
+}
+
+/// Clip the `start..=end` range of a strut to the screens it touches.
+fn span(touched: &[&Screen], start: i32, end: i32, horizontal: bool) -> Option<(i32, i32)> {
+    let s_start = touched.iter().map(|s| axis(s, horizontal).0).min()?;
+    let s_end = touched
+        .iter()
+        .map(|s| {
+            let (s_start, s_size) = axis(s, horizontal);
+            s_start + s_size
+        })
+        .max()?;
+    Some((start.max(s_start), end.saturating_add(1).min(s_end)))
+}
+
+const fn axis(screen: &Screen, horizontal: bool) -> (i32, i32) {
+    if horizontal {
+        (screen.bbox.x, screen.bbox.width)
+    } else {
+        (screen.bbox.y, screen.bbox.height)
+    }
+}
+
+fn build(x: i32, y: i32, w: i32, h: i32) -> Xyhw {
+    XyhwBuilder {
+        x,
+        y,
+        h,
+        w,
+        ..XyhwBuilder::default()
+    }
+    .into()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::models::BBox;
+
+    fn screen(x: i32, y: i32, width: i32, height: i32) -> Screen {
+        Screen::new(BBox {
+            x,
+            y,
+            width,
+            height,
+        })
+    }
 
     #[test]
-    fn should_be_able_to_build_from_top() {
+    fn should_be_able_to_resolve_top() {
         let area = DockArea {
             top: 2,
             top_start_x: 10,
-            top_end_x: 200,
+            top_end_x: 199,
             ..DockArea::default()
         };
-        let expected: Xyhw = XyhwBuilder {
-            h: 2,
-            w: 190,
-            x: 10,
-            y: 0,
-            ..XyhwBuilder::default()
-        }
-        .into();
-        assert_eq!(area.xyhw_from_top(0), expected);
+        let screens = vec![screen(0, 0, 1000, 1000)];
+        assert_eq!(
+            area.resolve(&screens, (1000, 1000)),
+            Some(build(10, 0, 190, 2))
+        );
     }
 
     #[test]
-    fn should_be_able_to_build_from_bottom() {
+    fn should_be_able_to_resolve_bottom() {
         let area = DockArea {
             bottom: 2,
             bottom_start_x: 10,
-            bottom_end_x: 200,
+            bottom_end_x: 199,
             ..DockArea::default()
         };
-        let expected: Xyhw = XyhwBuilder {
-            h: 2,
-            w: 190,
-            x: 10,
-            y: 998,
-            ..XyhwBuilder::default()
-        }
-        .into();
-        assert_eq!(area.xyhw_from_bottom(1000, 1000), expected);
+        let screens = vec![screen(0, 0, 1000, 1000)];
+        assert_eq!(
+            area.resolve(&screens, (1000, 1000)),
+            Some(build(10, 998, 190, 2))
+        );
     }
 
     #[test]
-    fn should_be_able_to_build_from_left() {
+    fn should_be_able_to_resolve_left() {
         let area = DockArea {
             left: 2,
             left_start_y: 10,
-            left_end_y: 200,
+            left_end_y: 199,
             ..DockArea::default()
         };
-        let expected: Xyhw = XyhwBuilder {
-            h: 190,
-            w: 2,
-            x: 0,
-            y: 10,
-            ..XyhwBuilder::default()
-        }
-        .into();
-        assert_eq!(area.xyhw_from_left(0), expected);
+        let screens = vec![screen(0, 0, 2000, 2000)];
+        assert_eq!(
+            area.resolve(&screens, (2000, 2000)),
+            Some(build(0, 10, 2, 190))
+        );
     }
 
     #[test]
-    fn should_be_able_to_build_from_right() {
+    fn should_be_able_to_resolve_right() {
         let area = DockArea {
             right: 2,
             right_start_y: 10,
-            right_end_y: 200,
+            right_end_y: 199,
             ..DockArea::default()
         };
-        let expected: Xyhw = XyhwBuilder {
-            h: 190,
-            w: 2,
-            x: 1998,
-            y: 10,
-            ..XyhwBuilder::default()
-        }
-        .into();
-        assert_eq!(area.xyhw_from_right(2000, 2000), expected);
+        let screens = vec![screen(0, 0, 2000, 2000)];
+        assert_eq!(
+            area.resolve(&screens, (2000, 2000)),
+            Some(build(1998, 10, 2, 190))
+        );
+    }
+
+    #[test]
+    fn top_strut_on_lower_monitor_of_a_vertical_stack() {
+        // A 30px bar on top of the lower monitor reserves 1110px from the top of the root.
+        let area = DockArea {
+            top: 1110,
+            top_start_x: 0,
+            top_end_x: 1919,
+            ..DockArea::default()
+        };
+        let screens = vec![screen(0, 0, 1920, 1080), screen(0, 1080, 1920, 1080)];
+        assert_eq!(
+            area.resolve(&screens, (2160, 1920)),
+            Some(build(0, 1080, 1920, 30))
+        );
+    }
+
+    #[test]
+    fn bottom_strut_on_upper_monitor_of_a_vertical_stack() {
+        let area = DockArea {
+            bottom: 1110,
+            bottom_start_x: 0,
+            bottom_end_x: 1919,
+            ..DockArea::default()
+        };
+        let screens = vec![screen(0, 0, 1920, 1080), screen(0, 1080, 1920, 1080)];
+        assert_eq!(
+            area.resolve(&screens, (2160, 1920)),
+            Some(build(0, 1050, 1920, 30))
+        );
+    }
+
+    #[test]
+    fn strut_only_on_the_monitor_it_is_placed_on() {
+        let area = DockArea {
+            top: 30,
+            top_start_x: 1920,
+            top_end_x: 3839,
+            ..DockArea::default()
+        };
+        let screens = vec![screen(0, 0, 1920, 1080), screen(1920, 0, 1920, 1080)];
+        assert_eq!(
+            area.resolve(&screens, (1080, 3840)),
+            Some(build(1920, 0, 1920, 30))
+        );
+    }
+
+    #[test]
+    fn strut_spanning_multiple_monitors() {
+        let area = DockArea {
+            top: 30,
+            top_start_x: 0,
+            top_end_x: 3839,
+            ..DockArea::default()
+        };
+        let screens = vec![screen(0, 0, 1920, 1080), screen(1920, 0, 1920, 1080)];
+        assert_eq!(
+            area.resolve(&screens, (1080, 3840)),
+            Some(build(0, 0, 3840, 30))
+        );
+    }
+
+    #[test]
+    fn strut_without_matching_screen_is_not_resolved() {
+        let area = DockArea {
+            left: 30,
+            left_start_y: 5000,
+            left_end_y: 6000,
+            ..DockArea::default()
+        };
+        let screens = vec![screen(0, 0, 1920, 1080)];
+        assert_eq!(area.resolve(&screens, (1080, 1920)), None);
     }
 }
@@ -1,24 +1,61 @@
 use crate::layouts::Layout;
+use crate::models::{BBox, Tag, TagId, TagListing, Window, WindowHandle, WindowType};
 use crate::state::State;
 use serde::{Deserialize, Serialize};
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct Viewport {
+    /// The name the config gives the workspace.
+    #[serde(default)]
+    pub name: Option<String>,
     pub tags: Vec<String>,
     pub h: u32,
     pub w: u32,
     pub x: i32,
     pub y: i32,
     pub layout: Layout,
+    pub main_width_percentage: f32,
+    pub stack_split_percentage: f32,
+    /// Set when every workspace has its own tags.
+    #[serde(default)]
+    pub own_tags: Option<ViewportTags>,
+    /// The number of tiled windows on the tag shown.
+    #[serde(default)]
+    pub window_count: usize,
+    /// The number of the focused window among them, e.g. for a bar to show `3/5` in `Monocle`.
+    #[serde(default)]
+    pub focused_window: Option<usize>,
+    /// Set while the workspace is in game mode.
+    #[serde(default)]
+    pub game_mode: bool,
+}
+
+/// The labels of the tags of a workspace with its own set of them, which may be the same as the
+/// ones of other workspaces.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ViewportTags {
+    pub names: Vec<String>,
+    /// The index of each of the `names` among the tags of the workspace.
+    #[serde(default)]
+    pub indexes: Vec<usize>,
+    pub focused: bool,
+    pub working: Vec<String>,
+    pub urgent: Vec<String>,
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct ManagerState {
     pub window_title: Option<String>,
     pub desktop_names: Vec<String>,
+    /// The index of each of the `desktop_names` among the tags, which bars go to them by. The
+    /// names leave out the hidden tags and follow the `tag_order`.
+    #[serde(default)]
+    pub desktop_indexes: Vec<usize>,
     pub viewports: Vec<Viewport>,
     pub active_desktop: Vec<String>,
     pub working_tags: Vec<String>,
+    #[serde(default)]
+    pub urgent_tags: Vec<String>,
 }
 
 #[allow(clippy::struct_excessive_bools)]
@@ -30,6 +67,7 @@ pub struct TagsForWorkspace {
     pub visible: bool,
     pub focused: bool,
     pub busy: bool,
+    pub urgent: bool,
 }
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct DisplayWorkspace {
@@ -38,9 +76,299 @@ pub struct DisplayWorkspace {
     pub x: i32,
     pub y: i32,
     pub layout: Layout,
+    pub main_width_percentage: f32,
+    pub stack_split_percentage: f32,
     pub index: usize,
+    pub name: Option<String>,
     pub tags: Vec<TagsForWorkspace>,
+    pub window_count: usize,
+    pub focused_window: Option<usize>,
+    pub game_mode: bool,
+}
+/// The answer to a `WindowAt` query.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct WindowAt {
+    pub x: i32,
+    pub y: i32,
+    /// The managed window there, if any.
+    pub window: Option<WindowInfo>,
 }
+
+impl WindowAt {
+    /// The topmost visible managed window at the point.
+    #[must_use]
+    pub fn new(state: &State, x: i32, y: i32) -> Self {
+        // The windows are kept in stacking order, the topmost first.
+        let window = state
+            .windows
+            .iter()
+            .filter(|w| w.visible() && !w.is_unmanaged())
+            .find(|w| w.contains_point(x, y))
+            .map(|w| WindowInfo::new(state, w));
+        Self { x, y, window }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct WindowInfo {
+    pub handle: WindowHandle,
+    /// The id of the window on the X server, as screen recorders capture it by.
+    #[serde(default)]
+    pub window_id: Option<u64>,
+    pub class: Option<String>,
+    #[serde(default)]
+    pub instance: Option<String>,
+    pub name: Option<String>,
+    #[serde(default)]
+    pub x: i32,
+    #[serde(default)]
+    pub y: i32,
+    #[serde(default)]
+    pub w: i32,
+    #[serde(default)]
+    pub h: i32,
+    /// The labels of the tags of the window.
+    #[serde(default)]
+    pub tags: Vec<String>,
+    /// The index of the workspace showing the window, if it is shown.
+    #[serde(default)]
+    pub workspace: Option<usize>,
+    /// The name of the output the window is on, e.g. `DP-1`.
+    #[serde(default)]
+    pub output: Option<String>,
+    /// The position of the window in the stack of its first tag, the topmost window first.
+    #[serde(default)]
+    pub stack_index: usize,
+}
+
+impl WindowInfo {
+    #[must_use]
+    pub fn new(state: &State, window: &Window) -> Self {
+        let xyhw = window.calculated_xyhw();
+        let workspace = state
+            .workspaces
+            .iter()
+            .position(|ws| ws.is_displaying(window));
+        Self {
+            handle: window.handle,
+            window_id: window.handle.xlib_handle(),
+            class: window.res_class.clone(),
+            instance: window.res_name.clone(),
+            name: window.name.clone(),
+            x: xyhw.x(),
+            y: xyhw.y(),
+            w: xyhw.w(),
+            h: xyhw.h(),
+            tags: window
+                .tags
+                .iter()
+                .filter_map(|&tag_id| state.tags.get(tag_id))
+                .map(|tag| tag.label.clone())
+                .collect(),
+            workspace,
+            output: workspace.and_then(|i| state.workspaces[i].output.clone()),
+            stack_index: state
+                .windows
+                .iter()
+                .filter(|w| !w.is_unmanaged() && first_tag(w) == first_tag(window))
+                .position(|w| w.handle == window.handle)
+                .unwrap_or_default(),
+        }
+    }
+}
+
+/// The tag a window is listed under, windows without tags coming last.
+fn first_tag(window: &Window) -> TagId {
+    window.tags.iter().min().copied().unwrap_or(TagId::MAX)
+}
+
+/// An output and the workspace on it, named as xrandr names it.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct OutputInfo {
+    pub name: Option<String>,
+    pub workspace: usize,
+    pub x: i32,
+    pub y: i32,
+    pub w: i32,
+    pub h: i32,
+}
+
+/// The answer to a `ListWindows` query. The windows are ordered by their first tag, then by
+/// their `stack_index`, so the order only changes when the windows do.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct WindowList {
+    pub windows: Vec<WindowInfo>,
+    pub outputs: Vec<OutputInfo>,
+}
+
+impl From<&State> for WindowList {
+    fn from(state: &State) -> Self {
+        let windows = listed_windows(state)
+            .into_iter()
+            .map(|w| WindowInfo::new(state, w))
+            .collect();
+        let outputs = state
+            .workspaces
+            .iter()
+            .enumerate()
+            .map(|(workspace, ws)| OutputInfo {
+                name: ws.output.clone(),
+                workspace,
+                x: ws.xyhw.x(),
+                y: ws.xyhw.y(),
+                w: ws.xyhw.w(),
+                h: ws.xyhw.h(),
+            })
+            .collect();
+        Self { windows, outputs }
+    }
+}
+
+/// The managed windows, ordered by their first tag, then by their `stack_index`.
+fn listed_windows(state: &State) -> Vec<&Window> {
+    let mut windows: Vec<&Window> = state.windows.iter().filter(|w| !w.is_unmanaged()).collect();
+    // A stable sort, keeping the stacking order within each tag.
+    windows.sort_by_key(|w| first_tag(w));
+    windows
+}
+
+/// The answer to a `GetState` query, everything a script may want to decide by at once.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct FullState {
+    pub screens: Vec<ScreenInfo>,
+    pub workspaces: Vec<WorkspaceInfo>,
+    /// Every tag, the hidden ones last.
+    pub tags: Vec<TagInfo>,
+    /// Ordered as the `windows` of `ListWindows`.
+    pub windows: Vec<WindowDetails>,
+    /// The index of the focused workspace.
+    pub focused_workspace: Option<usize>,
+    pub focused_tag: Option<TagId>,
+    pub focused_window: Option<WindowHandle>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct ScreenInfo {
+    pub output: Option<String>,
+    pub bbox: BBox,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct WorkspaceInfo {
+    pub index: usize,
+    pub name: Option<String>,
+    pub output: Option<String>,
+    pub x: i32,
+    pub y: i32,
+    pub w: i32,
+    pub h: i32,
+    /// The ids of the tags shown.
+    pub tags: Vec<TagId>,
+    pub layout: Layout,
+    pub main_width_percentage: f32,
+    pub stack_split_percentage: f32,
+    pub game_mode: bool,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct TagInfo {
+    pub id: TagId,
+    pub label: String,
+    /// Set for the tags leftwm keeps for itself, like those of the scratchpads.
+    pub hidden: bool,
+    /// Set for the tags left out of the bars and tag cycling.
+    pub unlisted: bool,
+    pub layout: Layout,
+    /// The index of the workspace showing the tag, if any.
+    pub workspace: Option<usize>,
+    pub window_count: usize,
+    pub urgent: bool,
+}
+
+#[allow(clippy::struct_excessive_bools)]
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct WindowDetails {
+    #[serde(flatten)]
+    pub info: WindowInfo,
+    pub r#type: WindowType,
+    pub visible: bool,
+    pub floating: bool,
+    pub fullscreen: bool,
+    pub sticky: bool,
+    pub urgent: bool,
+}
+
+impl From<&State> for FullState {
+    fn from(state: &State) -> Self {
+        let screens = state
+            .screens
+            .iter()
+            .map(|screen| ScreenInfo {
+                output: screen.output.clone(),
+                bbox: screen.bbox,
+            })
+            .collect();
+        let workspaces = state
+            .workspaces
+            .iter()
+            .enumerate()
+            .map(|(index, ws)| WorkspaceInfo {
+                index,
+                name: ws.name.clone(),
+                output: ws.output.clone(),
+                x: ws.xyhw.x(),
+                y: ws.xyhw.y(),
+                w: ws.xyhw.w(),
+                h: ws.xyhw.h(),
+                tags: ws.tags.clone(),
+                layout: ws.layout.clone(),
+                main_width_percentage: ws.main_width_percentage,
+                stack_split_percentage: ws.stack_split_percentage,
+                game_mode: ws.game_mode,
+            })
+            .collect();
+        let tags = state
+            .tags
+            .all()
+            .iter()
+            .map(|tag| {
+                let windows = state.windows.iter().filter(|w| w.has_tag(&tag.id));
+                TagInfo {
+                    id: tag.id,
+                    label: tag.label.clone(),
+                    hidden: tag.hidden,
+                    unlisted: tag.listing == TagListing::Unlisted,
+                    layout: tag.layout.clone(),
+                    workspace: state.workspaces.iter().position(|ws| ws.has_tag(&tag.id)),
+                    window_count: windows.clone().count(),
+                    urgent: windows.clone().any(|w| w.urgent),
+                }
+            })
+            .collect();
+        let windows = listed_windows(state)
+            .into_iter()
+            .map(|w| WindowDetails {
+                info: WindowInfo::new(state, w),
+                r#type: w.r#type.clone(),
+                visible: w.visible(),
+                floating: w.floating(),
+                fullscreen: w.is_fullscreen(),
+                sticky: w.is_sticky(),
+                urgent: w.urgent,
+            })
+            .collect();
+        Self {
+            screens,
+            workspaces,
+            tags,
+            windows,
+            focused_workspace: state.focus_manager.workspace_history.front().copied(),
+            focused_tag: state.focus_manager.tag(0),
+            focused_window: state.focus_manager.window(&state.windows).map(|w| w.handle),
+        }
+    }
+}
+
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct DisplayState {
     pub window_title: String,
@@ -55,14 +383,15 @@ impl From<ManagerState> for DisplayState {
             .iter()
             .enumerate()
             .map(|(i, vp)| {
-                viewport_into_display_workspace(
-                    &m.desktop_names,
-                    &m.active_desktop,
-                    &visible,
-                    &m.working_tags,
-                    vp,
-                    i,
-                )
+                let tags = TagLists {
+                    names: &m.desktop_names,
+                    indexes: &m.desktop_indexes,
+                    visible: &visible,
+                    focused: &m.active_desktop,
+                    working: &m.working_tags,
+                    urgent: &m.urgent_tags,
+                };
+                viewport_into_display_workspace(&tags, vp, i)
             })
             .collect();
         Self {
@@ -72,24 +401,49 @@ impl From<ManagerState> for DisplayState {
     }
 }
 
+/// The tags a workspace can show, by their names, and those of them in each state.
+struct TagLists<'a> {
+    names: &'a [String],
+    indexes: &'a [usize],
+    visible: &'a [String],
+    focused: &'a [String],
+    working: &'a [String],
+    urgent: &'a [String],
+}
+
 fn viewport_into_display_workspace(
-    all_tags: &[String],
-    focused: &[String],
-    visible: &[String],
-    working_tags: &[String],
+    shared: &TagLists,
     viewport: &Viewport,
     ws_index: usize,
 ) -> DisplayWorkspace {
-    let tags: Vec<TagsForWorkspace> = all_tags
+    // A workspace with its own tags is the only one which can show or focus them.
+    let own_lists;
+    let lists = match &viewport.own_tags {
+        Some(own) => {
+            own_lists = TagLists {
+                names: &own.names,
+                indexes: &own.indexes,
+                visible: &viewport.tags,
+                focused: if own.focused { &viewport.tags } else { &[] },
+                working: &own.working,
+                urgent: &own.urgent,
+            };
+            &own_lists
+        }
+        None => shared,
+    };
+    let tags: Vec<TagsForWorkspace> = lists
+        .names
         .iter()
         .enumerate()
-        .map(|(index, t)| TagsForWorkspace {
+        .map(|(i, t)| TagsForWorkspace {
             name: t.clone(),
-            index,
+            index: lists.indexes.get(i).copied().unwrap_or(i),
             mine: viewport.tags.contains(t),
-            visible: visible.contains(t),
-            focused: focused.contains(t),
-            busy: working_tags.contains(t),
+            visible: lists.visible.contains(t),
+            focused: lists.focused.contains(t),
+            busy: lists.working.contains(t),
+            urgent: lists.urgent.contains(t),
         })
         .collect();
     DisplayWorkspace {
@@ -98,8 +452,14 @@ fn viewport_into_display_workspace(
         w: viewport.w,
         x: viewport.x,
         y: viewport.y,
-        layout: viewport.layout,
+        layout: viewport.layout.clone(),
+        main_width_percentage: viewport.main_width_percentage,
+        stack_split_percentage: viewport.stack_split_percentage,
         index: ws_index,
+        name: viewport.name.clone(),
+        window_count: viewport.window_count,
+        focused_window: viewport.focused_window,
+        game_mode: viewport.game_mode,
     }
 }
 
@@ -114,7 +474,18 @@ impl From<&State> for ManagerState {
             .filter(|tag| state.windows.iter().any(|w| w.has_tag(&tag.id)))
             .map(|t| t.label.clone())
             .collect();
+        let urgent_tags = state
+            .tags
+            .all()
+            .iter()
+            .filter(|tag| state.windows.iter().any(|w| w.urgent && w.has_tag(&tag.id)))
+            .map(|t| t.label.clone())
+            .collect();
+        let focused_workspace = state.focus_manager.workspace(&state.workspaces);
+        let focused_window = state.focus_manager.window(&state.windows);
         for ws in &state.workspaces {
+            let shown_tag = ws.tags.first().and_then(|&tag_id| state.tags.get(tag_id));
+            let tiled = ws.tiled_windows(&state.windows);
             let tag_labels = ws
                 .tags
                 .iter()
@@ -123,12 +494,46 @@ impl From<&State> for ManagerState {
                 .collect();
 
             viewports.push(Viewport {
+                name: ws.name.clone(),
                 tags: tag_labels,
                 x: ws.xyhw.x(),
                 y: ws.xyhw.y(),
                 h: ws.xyhw.h() as u32,
                 w: ws.xyhw.w() as u32,
-                layout: ws.layout,
+                layout: ws.layout.clone(),
+                main_width_percentage: ws.main_width_percentage,
+                stack_split_percentage: ws.stack_split_percentage,
+                own_tags: state.per_screen_tags.then(|| {
+                    let all_own = state.workspace_tags(ws);
+                    let own: Vec<&Tag> = state
+                        .listed_tags(&all_own)
+                        .iter()
+                        .filter_map(|&tag_id| state.tags.get(tag_id))
+                        .collect();
+                    let labels = |keep: &dyn Fn(&Tag) -> bool| {
+                        own.iter()
+                            .filter(|tag| keep(tag))
+                            .map(|tag| tag.label.clone())
+                            .collect()
+                    };
+                    ViewportTags {
+                        names: labels(&|_| true),
+                        indexes: own
+                            .iter()
+                            .filter_map(|tag| all_own.iter().position(|&id| id == tag.id))
+                            .collect(),
+                        focused: focused_workspace.is_some_and(|focused| focused.id == ws.id),
+                        working: labels(&|tag| state.windows.iter().any(|w| w.has_tag(&tag.id))),
+                        urgent: labels(&|tag| {
+                            state.windows.iter().any(|w| w.urgent && w.has_tag(&tag.id))
+                        }),
+                    }
+                }),
+                window_count: tiled.len(),
+                focused_window: shown_tag
+                    .zip(focused_window)
+                    .and_then(|(tag, window)| tag.window_position(&tiled, window.handle)),
+                game_mode: ws.game_mode,
             });
         }
         let active_desktop = match state.focus_manager.workspace(&state.workspaces) {
@@ -143,17 +548,20 @@ impl From<&State> for ManagerState {
             Some(win) => win.name.clone(),
             None => None,
         };
+        let normal: Vec<TagId> = state.tags.normal().iter().map(|tag| tag.id).collect();
+        let listed: Vec<&Tag> = state
+            .listed_tags(&normal)
+            .iter()
+            .filter_map(|&tag_id| state.tags.get(tag_id))
+            .collect();
         Self {
             window_title,
-            desktop_names: state
-                .tags
-                .normal()
-                .iter()
-                .map(|t| t.label.clone())
-                .collect(),
+            desktop_names: listed.iter().map(|tag| tag.label.clone()).collect(),
+            desktop_indexes: listed.iter().map(|tag| tag.id - 1).collect(),
             viewports,
             active_desktop,
             working_tags,
+            urgent_tags,
         }
     }
 }
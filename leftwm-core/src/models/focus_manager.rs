@@ -33,10 +33,18 @@ impl FocusBehaviour {
     }
 }
 
+const fn default_true() -> bool {
+    true
+}
+
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct FocusManager {
     pub behaviour: FocusBehaviour,
     pub focus_new_windows: bool,
+    #[serde(default = "default_true")]
+    pub sloppy_focus_empty_workspaces: bool,
+    #[serde(default)]
+    pub warp_cursor: bool,
     pub workspace_history: VecDeque<usize>,
     pub window_history: VecDeque<MaybeWindowHandle>,
     pub tag_history: VecDeque<TagId>,
@@ -48,6 +56,8 @@ impl FocusManager {
         Self {
             behaviour: config.focus_behaviour(),
             focus_new_windows: config.focus_new_windows(),
+            sloppy_focus_empty_workspaces: config.sloppy_focus_empty_workspaces(),
+            warp_cursor: config.warp_cursor(),
             workspace_history: Default::default(),
             window_history: Default::default(),
             tag_history: Default::default(),
@@ -3,6 +3,8 @@ use crate::{config::Config, layouts::Layout, Workspace};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
+/// Whether the layout, main width and stack split follow the tags, or stay with the workspaces
+/// whichever tag they show.
 #[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
 pub enum LayoutMode {
     Tag,
@@ -11,7 +13,7 @@ pub enum LayoutMode {
 
 impl Default for LayoutMode {
     fn default() -> Self {
-        Self::Workspace
+        Self::Tag
     }
 }
 
@@ -20,6 +22,10 @@ pub struct LayoutManager {
     pub mode: LayoutMode,
     pub layouts: Vec<Layout>,
     pub layouts_per_workspaces: HashMap<i32, Vec<Layout>>,
+    #[serde(default)]
+    pub default_layout: Option<Layout>,
+    #[serde(default)]
+    pub default_layouts_per_workspaces: HashMap<i32, Layout>,
 }
 
 impl LayoutManager {
@@ -35,25 +41,41 @@ impl LayoutManager {
                 )
             })
             .collect();
+        let default_layouts_per_workspaces = config
+            .workspaces()
+            .unwrap_or_default()
+            .iter()
+            .filter_map(|ws| Some((ws.id.unwrap_or_default(), ws.default_layout.clone()?)))
+            .collect();
 
         Self {
             mode: config.layout_mode(),
             layouts: config.layouts(),
             layouts_per_workspaces,
+            default_layout: config.default_layout(),
+            default_layouts_per_workspaces,
         }
     }
 
+    /// The layout tags start with: the default layout of the workspace, or else the first of its
+    /// own layouts, or else the global default layout, or else the first of the global layouts.
     pub fn new_layout(&self, workspace_id: Option<i32>) -> Layout {
-        *self
-            .layouts(workspace_id)
-            .first()
-            .unwrap_or(&Layout::default())
+        let own_default = workspace_id.and_then(|id| self.default_layouts_per_workspaces.get(&id));
+        let own_first = workspace_id
+            .and_then(|id| self.layouts_per_workspaces.get(&id))
+            .and_then(|layouts| layouts.first());
+        own_default
+            .or(own_first)
+            .or(self.default_layout.as_ref())
+            .or_else(|| self.layouts.first())
+            .cloned()
+            .unwrap_or_default()
     }
 
-    pub fn next_layout(&self, workspace: &Workspace) -> Layout {
-        let layouts = self.layouts(workspace.id);
+    pub fn next_layout(&self, workspace: &Workspace, tag: Option<&Tag>) -> Layout {
+        let layouts = self.layouts(workspace.id, tag);
 
-        let next = match layouts.iter().position(|&x| x == workspace.layout) {
+        let next = match layouts.iter().position(|x| *x == workspace.layout) {
             Some(index) if index == layouts.len() - 1 => layouts.first(),
             Some(index) => layouts.get(index + 1),
             None => None,
@@ -61,13 +83,14 @@ impl LayoutManager {
 
         // If no layout was found, return the first in the list, in case of a
         // SoftReload with a new list that does not include the current layout.
-        *next.unwrap_or_else(|| layouts.first().unwrap_or(&workspace.layout))
+        next.unwrap_or_else(|| layouts.first().unwrap_or(&workspace.layout))
+            .clone()
     }
 
-    pub fn previous_layout(&self, workspace: &Workspace) -> Layout {
-        let layouts = self.layouts(workspace.id);
+    pub fn previous_layout(&self, workspace: &Workspace, tag: Option<&Tag>) -> Layout {
+        let layouts = self.layouts(workspace.id, tag);
 
-        let next = match layouts.iter().position(|&x| x == workspace.layout) {
+        let next = match layouts.iter().position(|x| *x == workspace.layout) {
             Some(index) if index == 0 => layouts.last(),
             Some(index) => layouts.get(index - 1),
             None => None,
@@ -75,7 +98,8 @@ impl LayoutManager {
 
         // If no layout was found, return the first in the list, in case of a
         // SoftReload with a new list that does not include the current layout.
-        *next.unwrap_or_else(|| layouts.first().unwrap_or(&workspace.layout))
+        next.unwrap_or_else(|| layouts.first().unwrap_or(&workspace.layout))
+            .clone()
     }
 
     pub fn update_layouts(
@@ -87,18 +111,25 @@ impl LayoutManager {
             let tag = tags.iter_mut().find(|t| t.id == workspace.tags[0])?;
             match self.mode {
                 LayoutMode::Workspace => {
-                    tag.set_layout(workspace.layout, workspace.main_width_percentage);
+                    tag.set_layout(workspace.layout.clone(), workspace.main_width_percentage);
+                    tag.set_stack_split(workspace.stack_split_percentage);
                 }
                 LayoutMode::Tag => {
-                    workspace.layout = tag.layout;
+                    workspace.layout = tag.layout.clone();
                     workspace.main_width_percentage = tag.main_width_percentage;
+                    workspace.stack_split_percentage = tag.stack_split_percentage;
                 }
             }
         }
         Some(true)
     }
 
-    fn layouts(&self, workspace_id: Option<i32>) -> &Vec<Layout> {
+    /// The layouts cycled through on a tag: its own, or else those of the workspace, or else the
+    /// global ones.
+    fn layouts<'a>(&'a self, workspace_id: Option<i32>, tag: Option<&'a Tag>) -> &'a Vec<Layout> {
+        if let Some(tag) = tag.filter(|tag| !tag.layouts.is_empty()) {
+            return &tag.layouts;
+        }
         workspace_id
             .and_then(|id| self.layouts_per_workspaces.get(&id))
             .and_then(|layouts| {
@@ -140,12 +171,18 @@ mod tests {
                     id: Some(1),
                     ..Default::default()
                 },
+                crate::config::Workspace {
+                    id: Some(3),
+                    default_layout: Some(Layout::Fibonacci),
+                    ..Default::default()
+                },
                 crate::config::Workspace {
                     id: Some(2),
                     layouts: Some(vec![]),
                     ..Default::default()
                 },
             ]),
+            default_layout: Some(Layout::EvenVertical),
             ..Default::default()
         };
 
@@ -170,10 +207,48 @@ mod tests {
     fn layouts_should_fallback_to_the_global_list() {
         let layout_manager = layout_manager();
 
-        assert_eq!(layout_manager.layouts(Some(1)), &layout_manager.layouts); // layouts = None
-        assert_eq!(layout_manager.layouts(Some(2)), &layout_manager.layouts); // layouts = vec[]!
-        assert_eq!(layout_manager.layouts(Some(3)), &layout_manager.layouts); // Non existent id
-        assert_eq!(layout_manager.layouts(None), &layout_manager.layouts);
+        assert_eq!(
+            layout_manager.layouts(Some(1), None),
+            &layout_manager.layouts
+        ); // layouts = None
+        assert_eq!(
+            layout_manager.layouts(Some(2), None),
+            &layout_manager.layouts
+        ); // layouts = vec[]!
+        assert_eq!(
+            layout_manager.layouts(Some(3), None),
+            &layout_manager.layouts
+        ); // Non existent id
+        assert_eq!(layout_manager.layouts(None, None), &layout_manager.layouts);
+    }
+
+    #[test]
+    fn new_layouts_should_follow_the_defaults() {
+        let layout_manager = layout_manager();
+
+        assert_eq!(layout_manager.new_layout(Some(0)), Layout::CenterMain);
+        assert_eq!(layout_manager.new_layout(Some(1)), Layout::EvenVertical);
+        assert_eq!(layout_manager.new_layout(Some(3)), Layout::Fibonacci);
+        assert_eq!(layout_manager.new_layout(None), Layout::EvenVertical);
+    }
+
+    #[test]
+    fn layouts_of_tags_should_take_precedence() {
+        let layout_manager = layout_manager();
+        let mut tag = Tag::new(1, "video", Layout::Monocle);
+        tag.layouts = vec![Layout::Monocle, Layout::Grid];
+
+        assert_eq!(layout_manager.layouts(Some(0), Some(&tag)), &tag.layouts);
+        let workspace = workspace(0, Layout::Monocle);
+        assert_eq!(
+            layout_manager.next_layout(&workspace, Some(&tag)),
+            Layout::Grid
+        );
+        tag.layouts = vec![];
+        assert_eq!(
+            layout_manager.next_layout(&workspace, Some(&tag)),
+            Layout::CenterMain
+        );
     }
 
     #[test]
@@ -181,7 +256,10 @@ mod tests {
         let layout_manager = layout_manager();
         let workspace = workspace(0, Layout::CenterMainBalanced);
 
-        assert_eq!(layout_manager.next_layout(&workspace), Layout::MainAndDeck);
+        assert_eq!(
+            layout_manager.next_layout(&workspace, None),
+            Layout::MainAndDeck
+        );
     }
 
     #[test]
@@ -189,7 +267,10 @@ mod tests {
         let layout_manager = layout_manager();
         let workspace = workspace(0, Layout::MainAndDeck);
 
-        assert_eq!(layout_manager.next_layout(&workspace), Layout::CenterMain);
+        assert_eq!(
+            layout_manager.next_layout(&workspace, None),
+            Layout::CenterMain
+        );
     }
 
     #[test]
@@ -198,7 +279,7 @@ mod tests {
 
         let workspace = workspace(1, Layout::EvenVertical);
         assert_eq!(
-            layout_manager.next_layout(&workspace),
+            layout_manager.next_layout(&workspace, None),
             Layout::MainAndHorizontalStack
         );
     }
@@ -208,7 +289,10 @@ mod tests {
         let layout_manager = layout_manager();
         let workspace = workspace(0, Layout::Fibonacci);
 
-        assert_eq!(layout_manager.next_layout(&workspace), Layout::CenterMain);
+        assert_eq!(
+            layout_manager.next_layout(&workspace, None),
+            Layout::CenterMain
+        );
     }
 
     #[test]
@@ -217,7 +301,7 @@ mod tests {
         let workspace = workspace(0, Layout::CenterMainBalanced);
 
         assert_eq!(
-            layout_manager.previous_layout(&workspace),
+            layout_manager.previous_layout(&workspace, None),
             Layout::CenterMain
         );
     }
@@ -228,7 +312,7 @@ mod tests {
         let workspace = workspace(0, Layout::CenterMain);
 
         assert_eq!(
-            layout_manager.previous_layout(&workspace),
+            layout_manager.previous_layout(&workspace, None),
             Layout::MainAndDeck
         );
     }
@@ -238,7 +322,10 @@ mod tests {
         let layout_manager = layout_manager();
         let workspace = workspace(2, Layout::EvenVertical);
 
-        assert_eq!(layout_manager.previous_layout(&workspace), Layout::Monocle);
+        assert_eq!(
+            layout_manager.previous_layout(&workspace, None),
+            Layout::Monocle
+        );
     }
 
     #[test]
@@ -247,7 +334,7 @@ mod tests {
         let workspace = workspace(0, Layout::Fibonacci);
 
         assert_eq!(
-            layout_manager.previous_layout(&workspace),
+            layout_manager.previous_layout(&workspace, None),
             Layout::CenterMain
         );
     }
@@ -1,7 +1,8 @@
 use crate::config::Config;
 use crate::display_servers::DisplayServer;
+use crate::models::{Screen, WindowHandle};
 use crate::state::State;
-use crate::utils::child_process::Children;
+use crate::utils::child_process::{exec_shell, Children};
 use std::sync::{atomic::AtomicBool, Arc};
 
 /// Maintains current program state.
@@ -13,6 +14,11 @@ pub struct Manager<C, SERVER> {
     pub(crate) children: Children,
     pub(crate) reap_requested: Arc<AtomicBool>,
     pub(crate) reload_requested: bool,
+    pub(crate) exit_requested: bool,
+    /// Whether the worker was started again by a reload, rather than with the session.
+    pub(crate) restarted: bool,
+    /// The focused window and its title when the focus hooks last ran.
+    pub(crate) last_focus: Option<(WindowHandle, Option<String>)>,
     pub display_server: SERVER,
 }
 
@@ -29,6 +35,9 @@ where
             children: Default::default(),
             reap_requested: Default::default(),
             reload_requested: false,
+            exit_requested: false,
+            restarted: false,
+            last_focus: None,
         }
     }
 }
@@ -42,6 +51,17 @@ impl<C, SERVER> Manager<C, SERVER> {
     pub fn hard_reload(&mut self) {
         self.reload_requested = true;
     }
+
+    /// Stop the worker for good, running the exit hook and the `down` scripts first.
+    pub fn exit(&mut self) {
+        self.exit_requested = true;
+    }
+
+    /// Tell the worker it was started again by a reload, so that it runs the `on_reload`
+    /// commands once it's up.
+    pub fn restarted(&mut self) {
+        self.restarted = true;
+    }
 }
 
 impl<C: Config, SERVER: DisplayServer> Manager<C, SERVER> {
@@ -53,6 +73,67 @@ impl<C: Config, SERVER: DisplayServer> Manager<C, SERVER> {
         self.state.load_config(&self.config);
         true
     }
+
+    /// Read the configuration again and apply it, keeping the windows where they are. Workspaces
+    /// the configuration gives another geometry are laid out again in it.
+    pub fn reload_config_file(&mut self) -> bool {
+        if !self.config.reload() {
+            return false;
+        }
+        log::info!("Reloaded the config");
+        self.reload_config();
+        self.run_reload_cmds();
+        // Workspaces configured without an id are numbered in the order they are listed.
+        for (i, wsc) in self
+            .config
+            .workspaces()
+            .unwrap_or_default()
+            .iter()
+            .enumerate()
+        {
+            let mut screen = Screen::from(wsc);
+            screen.wsid = Some(wsc.id.unwrap_or(i as i32));
+            self.screen_update_handler(&screen);
+        }
+        true
+    }
+
+    /// Switch to the workspaces of a profile, the way the screens change when monitors are
+    /// plugged in or out.
+    pub fn load_profile(&mut self, name: &str) -> bool {
+        let profile = self.config.profiles().into_iter().find(|p| p.name == name);
+        if let Some(profile) = profile {
+            log::info!("Loading the profile {}", name);
+            let screens = self.display_server.workspace_screens(&profile.workspaces);
+            return self.screens_change_handler(screens);
+        }
+        log::warn!("There is no profile named {}", name);
+        false
+    }
+
+    /// Load the profile made for exactly these outputs being connected, in any order, if there
+    /// is one.
+    pub fn load_profile_of_outputs(&mut self, outputs: &[String]) -> bool {
+        let mut outputs = outputs.to_vec();
+        outputs.sort();
+        let name = self
+            .config
+            .profiles()
+            .into_iter()
+            .find(|profile| {
+                let mut wanted = profile.outputs.clone();
+                wanted.sort();
+                !wanted.is_empty() && wanted == outputs
+            })
+            .map(|profile| profile.name);
+        name.is_some_and(|name| self.load_profile(&name))
+    }
+
+    pub(crate) fn run_reload_cmds(&mut self) {
+        for cmd in self.config.on_reload_cmds() {
+            exec_shell(&cmd, &mut self.children);
+        }
+    }
 }
 
 #[cfg(test)]
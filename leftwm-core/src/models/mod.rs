@@ -1,4 +1,6 @@
 //! Objects (such as windows) used to develop `LeftWM`.
+mod bsp_tree;
+mod direction;
 mod dock_area;
 mod focus_manager;
 mod gutter;
@@ -8,6 +10,7 @@ mod margins;
 mod mode;
 mod screen;
 mod size;
+mod tab_bar;
 mod tag;
 mod window;
 mod window_change;
@@ -20,6 +23,8 @@ mod xyhw_change;
 pub mod dto;
 use crate::layouts;
 
+pub use bsp_tree::BspTree;
+pub use direction::{Direction, ParseDirectionError};
 pub use dock_area::DockArea;
 pub use focus_manager::FocusBehaviour;
 pub use focus_manager::FocusManager;
@@ -31,18 +36,23 @@ pub use manager::Manager;
 pub use margins::Margins;
 pub use mode::Mode;
 pub use screen::{BBox, Screen};
-pub use size::Size;
+pub use size::{ParseSizeError, Size};
+pub use tab_bar::{Tab, TabBar};
+pub use window::SizeIncrements;
+pub use window::UrgencySource;
 pub use window::Window;
 pub use window::WindowHandle;
 pub use window_change::WindowChange;
 pub use window_state::WindowState;
 pub use window_type::WindowType;
 pub use workspace::Workspace;
+pub use workspace::Zoom;
 pub use xyhw::Xyhw;
 pub use xyhw::XyhwBuilder;
 pub use xyhw_change::XyhwChange;
 
 pub use tag::Tag;
+pub use tag::TagListing;
 pub use tag::Tags;
 
 pub type TagId = usize;
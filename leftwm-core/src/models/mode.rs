@@ -7,6 +7,8 @@ pub enum Mode {
     ReadyToMove(WindowHandle),
     ResizingWindow(WindowHandle),
     MovingWindow(WindowHandle),
+    /// The next click picks the window under the cursor, in the overview.
+    PickingWindow,
     Normal,
 }
 
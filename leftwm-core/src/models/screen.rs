@@ -1,4 +1,4 @@
-use super::{DockArea, Size, WindowHandle};
+use super::{Size, WindowHandle};
 use crate::config::Workspace;
 use serde::{Deserialize, Serialize};
 use std::convert::From;
@@ -11,10 +11,16 @@ pub struct Screen {
     pub bbox: BBox,
     pub wsid: Option<i32>,
     pub max_window_width: Option<Size>,
+    /// The name of the output, e.g. `DP-1`, when the display server knows it.
+    #[serde(default)]
+    pub output: Option<String>,
+    /// The name the config gives the workspace of the screen.
+    #[serde(default)]
+    pub name: Option<String>,
 }
 
 /// Screen Bounding Box
-#[derive(Serialize, Deserialize, Debug, Clone, Copy)]
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
 pub struct BBox {
     pub x: i32,
     pub y: i32,
@@ -30,6 +36,8 @@ impl Screen {
             bbox,
             wsid: None,
             max_window_width: None,
+            output: None,
+            name: None,
         }
     }
 
@@ -41,22 +49,54 @@ impl Screen {
         (bbox.x <= x && x <= max_x) && (bbox.y <= y && y <= max_y)
     }
 
+    /// Merge the screens of mirrored outputs, which overlap, into one covering all of them.
+    /// Otherwise they would get a workspace each, fighting over the same windows.
     #[must_use]
-    pub const fn contains_dock_area(&self, dock_area: DockArea, screens_area: (i32, i32)) -> bool {
-        if dock_area.top > 0 {
-            return self.contains_point(dock_area.top_start_x, dock_area.top);
+    pub fn merge_mirrored(screens: Vec<Self>) -> Vec<Self> {
+        let mut merged: Vec<Self> = vec![];
+        for mut screen in screens {
+            let mut place = merged.len();
+            // Growing the screen can make it overlap one it didn't before.
+            while let Some(index) = merged.iter().position(|m| m.bbox.overlaps(&screen.bbox)) {
+                let earlier = merged.remove(index);
+                log::info!(
+                    "Treating the overlapping outputs {:?} and {:?} as mirrored",
+                    earlier.output,
+                    screen.output
+                );
+                screen = Self {
+                    bbox: earlier.bbox.union(&screen.bbox),
+                    ..earlier
+                };
+                place = place.min(index);
+            }
+            merged.insert(place.min(merged.len()), screen);
         }
-        if dock_area.bottom > 0 {
-            return self
-                .contains_point(dock_area.bottom_start_x, screens_area.0 - dock_area.bottom);
-        }
-        if dock_area.left > 0 {
-            return self.contains_point(dock_area.left, dock_area.left_start_y);
-        }
-        if dock_area.right > 0 {
-            return self.contains_point(screens_area.1 - dock_area.right, dock_area.right_start_y);
+        merged
+    }
+}
+
+impl BBox {
+    /// Whether the two boxes share some area, rather than just touching.
+    #[must_use]
+    pub const fn overlaps(&self, other: &Self) -> bool {
+        self.x < other.x + other.width
+            && other.x < self.x + self.width
+            && self.y < other.y + other.height
+            && other.y < self.y + self.height
+    }
+
+    /// The smallest box holding both boxes.
+    #[must_use]
+    pub fn union(&self, other: &Self) -> Self {
+        let x = self.x.min(other.x);
+        let y = self.y.min(other.y);
+        Self {
+            x,
+            y,
+            width: (self.x + self.width).max(other.x + other.width) - x,
+            height: (self.y + self.height).max(other.y + other.height) - y,
         }
-        false
     }
 }
 
@@ -72,6 +112,8 @@ impl From<&Workspace> for Screen {
             },
             wsid: wsc.id,
             max_window_width: wsc.max_window_width,
+            output: None,
+            name: wsc.name.clone(),
         }
     }
 }
@@ -88,6 +130,8 @@ impl From<&xlib::XWindowAttributes> for Screen {
             },
             wsid: None,
             max_window_width: None,
+            output: None,
+            name: None,
         }
     }
 }
@@ -104,6 +148,8 @@ impl From<&x11_dl::xinerama::XineramaScreenInfo> for Screen {
             },
             wsid: None,
             max_window_width: None,
+            output: None,
+            name: None,
         }
     }
 }
@@ -120,6 +166,55 @@ impl Default for Screen {
             },
             wsid: None,
             max_window_width: None,
+            output: None,
+            name: None,
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn screen(x: i32, y: i32, width: i32, height: i32) -> Screen {
+        Screen::new(BBox {
+            x,
+            y,
+            width,
+            height,
+        })
+    }
+
+    fn bboxes(screens: &[Screen]) -> Vec<(i32, i32, i32, i32)> {
+        screens
+            .iter()
+            .map(|s| (s.bbox.x, s.bbox.y, s.bbox.width, s.bbox.height))
+            .collect()
+    }
+
+    #[test]
+    fn mirrored_screens_should_be_merged() {
+        let screens = vec![
+            screen(0, 0, 1920, 1080),
+            screen(0, 0, 1280, 1024),
+            screen(1920, 0, 1920, 1080),
+        ];
+        assert_eq!(
+            bboxes(&Screen::merge_mirrored(screens)),
+            vec![(0, 0, 1920, 1080), (1920, 0, 1920, 1080)]
+        );
+    }
+
+    #[test]
+    fn screens_overlapping_once_grown_should_be_merged() {
+        let screens = vec![
+            screen(0, 0, 1000, 1000),
+            screen(1000, 0, 1000, 1000),
+            screen(500, 0, 1000, 1000),
+        ];
+        assert_eq!(
+            bboxes(&Screen::merge_mirrored(screens)),
+            vec![(0, 0, 2000, 1000)]
+        );
+    }
+}
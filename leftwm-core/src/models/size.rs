@@ -1,4 +1,6 @@
 use serde::{Deserialize, Serialize};
+use std::str::FromStr;
+use thiserror::Error;
 
 /// Helper enum to represent a size which can be
 /// an absolute pixel value or a relative percentage value
@@ -22,3 +24,33 @@ impl Size {
         }
     }
 }
+
+#[derive(Debug, Error)]
+#[error("Could not parse size: {0}")]
+pub struct ParseSizeError(String);
+
+impl FromStr for Size {
+    type Err = ParseSizeError;
+
+    /// Whole numbers are pixels, anything with a fraction is a ratio, as in the config.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if let Ok(pixels) = i32::from_str(s) {
+            return Ok(Self::Pixel(pixels));
+        }
+        f32::from_str(s)
+            .map(Self::Ratio)
+            .map_err(|_| ParseSizeError(s.to_string()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_be_able_to_parse_sizes() {
+        assert_eq!(Size::from_str("1200").unwrap(), Size::Pixel(1200));
+        assert_eq!(Size::from_str("0.5").unwrap(), Size::Ratio(0.5));
+        assert!(Size::from_str("wide").is_err());
+    }
+}
@@ -0,0 +1,114 @@
+use crate::models::{Tag, Window, WindowHandle, Workspace, Xyhw, XyhwBuilder};
+use serde::{Deserialize, Serialize};
+
+/// The strip of tabs drawn above the windows of a workspace in the `Tabbed` layout.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct TabBar {
+    pub area: Xyhw,
+    pub tabs: Vec<Tab>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Tab {
+    pub handle: WindowHandle,
+    pub title: String,
+    /// The window of the tab is the one shown.
+    pub active: bool,
+}
+
+impl TabBar {
+    /// The tabs of the tiled windows shown on `workspace`, laid out by its first tag `tag`, in
+    /// the order focus cycles through them, or `None` if there are no windows or one of them is
+    /// fullscreen.
+    #[must_use]
+    pub fn new(workspace: &Workspace, tag: &Tag, windows: &[Window]) -> Option<Self> {
+        if windows
+            .iter()
+            .any(|w| workspace.is_displaying(w) && w.is_fullscreen())
+        {
+            return None;
+        }
+        let handles = workspace.tiled_windows(windows);
+        let front = *handles.first()?;
+        let mut tabs: Vec<(usize, Tab)> = windows
+            .iter()
+            .filter(|w| handles.contains(&w.handle))
+            .map(|w| {
+                let title = w.name.clone().or_else(|| w.res_class.clone());
+                let tab = Tab {
+                    handle: w.handle,
+                    title: title.unwrap_or_default(),
+                    active: w.handle == front,
+                };
+                (tag.window_position(&handles, w.handle).unwrap_or(0), tab)
+            })
+            .collect();
+        tabs.sort_by_key(|(position, _)| *position);
+        let area = XyhwBuilder {
+            x: workspace.x_limited(1),
+            y: workspace.y(),
+            w: workspace.width_limited(1),
+            h: workspace.tab_bar_height.min(workspace.height()),
+            ..XyhwBuilder::default()
+        }
+        .into();
+        Some(Self {
+            area,
+            tabs: tabs.into_iter().map(|(_, tab)| tab).collect(),
+        })
+    }
+
+    /// The window of the tab at `x`, relative to the left of the bar. The tabs share the width
+    /// equally.
+    #[must_use]
+    pub fn tab_at(&self, x: i32) -> Option<WindowHandle> {
+        if self.tabs.is_empty() || x < 0 || x >= self.area.w() {
+            return None;
+        }
+        let index = x as usize * self.tabs.len() / self.area.w() as usize;
+        self.tabs.get(index).map(|tab| tab.handle)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::layouts::Layout;
+    use crate::models::BBox;
+
+    #[test]
+    fn tabs_follow_the_window_numbering() {
+        let mut workspace = Workspace::new(
+            None,
+            BBox {
+                width: 600,
+                height: 400,
+                x: 0,
+                y: 0,
+            },
+            Layout::Tabbed,
+            None,
+        );
+        workspace.tags = vec![1];
+        let mut tag = Tag::new(1, "1", Layout::Tabbed);
+        let mut windows: Vec<Window> = (1..=3)
+            .map(|i| {
+                let mut w = Window::new(WindowHandle::MockHandle(i), Some(i.to_string()), None);
+                w.tag(&1);
+                w
+            })
+            .collect();
+        tag.sync_first_window(&tag.tiled_windows(&windows));
+        // Focusing the previous window rotates it to the front.
+        windows.rotate_right(1);
+
+        let bar = TabBar::new(&workspace, &tag, &windows).unwrap();
+        let titles: Vec<(&str, bool)> = bar
+            .tabs
+            .iter()
+            .map(|tab| (tab.title.as_str(), tab.active))
+            .collect();
+        assert_eq!(titles, vec![("1", false), ("3", true), ("2", false)]);
+        assert_eq!(bar.tab_at(250), Some(WindowHandle::MockHandle(3)));
+    }
+}
@@ -1,8 +1,10 @@
 use serde::{Deserialize, Serialize};
 
-use crate::{layouts::Layout, Window, Workspace};
+use crate::config::Config;
+use crate::layouts::{CustomLayout, Layout};
+use crate::{Window, Workspace};
 
-use super::TagId;
+use super::{BspTree, Size, TagId, WindowHandle};
 
 /// Wrapper struct holding all the tags.
 /// This wrapper provides convenience methods to change the tag-list
@@ -77,8 +79,19 @@ impl Tags {
     // todo: add_new_at(position, label, layout)
     // -> shifting all one to the right and re-number them (vec.insert)
 
-    // todo: remove(id)
-    // -> shifting all right of the removed tag one to the left and re-number them
+    /// Remove a normal tag, shifting all right of it one to the left
+    /// and re-numbering them, so the IDs stay without gaps.
+    /// Returns the removed tag, or `None` if there is no normal tag with this ID.
+    pub fn remove(&mut self, id: TagId) -> Option<Tag> {
+        let index = id
+            .checked_sub(1)
+            .filter(|&index| index < self.normal.len())?;
+        let removed = self.normal.remove(index);
+        for tag in self.normal.iter_mut().skip(index) {
+            tag.id -= 1;
+        }
+        Some(removed)
+    }
 
     /// Create a new hidden tag with the provided label,
     /// and append it to the list of hidden tags.
@@ -138,15 +151,18 @@ impl Tags {
     /// Get a tag by its ID.
     /// This method returns normal, as well as hidden tags.
     pub fn get(&self, id: TagId) -> Option<&Tag> {
-        self.normal
-            .get(id - 1) // tag id starts at 1, but arrays at 0 :)
+        id.checked_sub(1) // tag id starts at 1, but arrays at 0 :)
+            .and_then(|index| self.normal.get(index))
             .or_else(|| self.hidden.iter().find(|&hidden_tag| hidden_tag.id == id))
     }
 
     /// Get a tag by its ID as mutable
     /// This method returns normal, as well as hidden tags.
     pub fn get_mut(&mut self, id: TagId) -> Option<&mut Tag> {
-        if let Some(normal) = self.normal.get_mut(id - 1) {
+        if let Some(normal) = id
+            .checked_sub(1)
+            .and_then(|index| self.normal.get_mut(index))
+        {
             return Some(normal);
         }
         return self
@@ -176,11 +192,23 @@ impl Default for Tags {
 /// Each Screen/Workspace will always display a certain Tag.
 /// A Tag can not be displayed on more than one Workspace at a time.
 ///
+/// Whether a tag is listed among the others.
+#[derive(Default, Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TagListing {
+    #[default]
+    Listed,
+    /// Configured as hidden, the tag is left out when cycling through the tags and from the
+    /// state given to bars, though it can still be gone to by number.
+    Unlisted,
+}
+
 /// Unlike in some other WMs (eg. `dwm`), in `LeftWM`
 /// the same set of tags and windows are shared among
 /// all Workspaces, this means there aren't multiple instances of
 /// the same Tag on different Screens.
-#[derive(Default, Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+/// With `per_screen_tags` each Workspace gets its own copy of the
+/// configured tags instead, which only it displays.
+#[derive(Default, Serialize, Deserialize, Debug, Clone, PartialEq)]
 pub struct Tag {
     /// Unique identifier for the tag,
     /// this is automatically assigned by `LeftWM`.
@@ -211,6 +239,18 @@ pub struct Tag {
     /// are unknown to other programs (eg. polybar)
     pub hidden: bool,
 
+    /// Whether the tag is left out when cycling
+    /// through the tags and from the state given
+    /// to bars, see `TagListing`.
+    #[serde(default)]
+    pub listing: TagListing,
+
+    /// Where the tag comes when cycling
+    /// through the tags and in the bars,
+    /// which sort the tags by it.
+    #[serde(default)]
+    pub order: usize,
+
     /// The layout in which the windows
     /// on this Tag are arranged
     pub layout: Layout,
@@ -219,11 +259,86 @@ pub struct Tag {
     /// which is designated for the "main"
     /// column of the layout, compared
     /// to the secondary column(s).
-    pub main_width_percentage: u8,
+    pub main_width_percentage: f32,
+
+    /// The percentage of the space left
+    /// beside the "main" column which is
+    /// designated for the first of the
+    /// secondary columns, in layouts with
+    /// one on each side.
+    #[serde(default = "default_stack_split")]
+    pub stack_split_percentage: f32,
+
+    /// The number of windows placed in
+    /// the "main" area of the layout,
+    /// in layouts which support more than one.
+    #[serde(default = "default_main_count")]
+    pub main_count: usize,
+
+    /// Overrides the `max_window_width` of
+    /// the workspace displaying this tag.
+    #[serde(default)]
+    pub max_window_width: Option<Size>,
+
+    /// The layout this tag starts with,
+    /// overriding the default layout
+    /// of the workspace displaying it.
+    #[serde(default)]
+    pub default_layout: Option<Layout>,
+
+    /// The layouts cycled through on
+    /// this tag, overriding those of the
+    /// workspace displaying it when set.
+    #[serde(default)]
+    pub layouts: Vec<Layout>,
+
+    /// Relative sizes of the windows sharing
+    /// a column or row of the layout,
+    /// `1.0` for windows not listed.
+    #[serde(default)]
+    pub window_weights: Vec<(WindowHandle, f32)>,
+
+    /// The workspace this tag belongs to,
+    /// when every workspace has its
+    /// own set of tags.
+    #[serde(default)]
+    pub workspace_id: Option<i32>,
+
+    /// The regions of the windows
+    /// in the `Bsp` layout.
+    #[serde(default)]
+    pub bsp: BspTree,
+
+    /// The window counted first when
+    /// numbering the windows in `Monocle`,
+    /// which rotates them as focus moves.
+    #[serde(default)]
+    pub first_window: Option<WindowHandle>,
 
     pub flipped_horizontal: bool,
     pub flipped_vertical: bool,
     pub layout_rotation: usize,
+
+    /// The layout used before the current one,
+    /// for `ToggleLastLayout`.
+    #[serde(default)]
+    pub last_layout: Option<Layout>,
+}
+
+/// Bounds of the main width percentage, leaving a usable amount of space to either side.
+pub(crate) const MIN_MAIN_WIDTH: f32 = 5.0;
+pub(crate) const MAX_MAIN_WIDTH: f32 = 95.0;
+
+pub(crate) const fn default_stack_split() -> f32 {
+    50.0
+}
+
+/// Bounds of the window weights, so no window can be shrunk out of sight.
+const MIN_WINDOW_WEIGHT: f32 = 0.1;
+const MAX_WINDOW_WEIGHT: f32 = 10.0;
+
+const fn default_main_count() -> usize {
+    1
 }
 
 impl Tag {
@@ -233,18 +348,112 @@ impl Tag {
             id,
             label: label.to_owned(),
             hidden: false,
-            layout,
+            listing: TagListing::Listed,
+            order: id,
             main_width_percentage: layout.main_width(),
+            layout,
+            stack_split_percentage: default_stack_split(),
+            main_count: default_main_count(),
+            max_window_width: None,
+            default_layout: None,
+            layouts: vec![],
+            window_weights: vec![],
+            workspace_id: None,
+            bsp: BspTree::default(),
+            first_window: None,
             flipped_horizontal: false,
             flipped_vertical: false,
             layout_rotation: 0,
+            last_layout: None,
         }
     }
 
-    pub fn update_windows(&self, windows: &mut [Window], workspace: &Workspace) {
+    /// Apply the settings the config gives the tag numbered `number` within its set of tags.
+    pub fn load_config(&mut self, config: &impl Config, number: TagId) {
+        self.max_window_width = config.tag_max_window_width(number);
+        self.listing = if config.tag_hidden(number) {
+            TagListing::Unlisted
+        } else {
+            TagListing::Listed
+        };
+        self.default_layout = config.tag_default_layout(number);
+        self.layouts = config.tag_layouts(number);
+        let order = config.tag_order();
+        self.order = order
+            .iter()
+            .position(|&n| n == number)
+            .unwrap_or(order.len() + number);
+    }
+
+    /// The layout the config starts this tag with, if it gives it one: its default layout, or
+    /// else the first of its layouts.
+    #[must_use]
+    pub fn own_layout(&self) -> Option<Layout> {
+        self.default_layout
+            .clone()
+            .or_else(|| self.layouts.first().cloned())
+    }
+
+    /// Switch to the layout the config starts this tag with, if it gives it one.
+    pub fn use_own_layout(&mut self) {
+        if let Some(layout) = self.own_layout() {
+            self.main_width_percentage = layout.main_width();
+            self.layout = layout;
+        }
+    }
+
+    /// The tiled windows of this tag, in order.
+    #[must_use]
+    pub fn tiled_windows(&self, windows: &[Window]) -> Vec<WindowHandle> {
+        windows
+            .iter()
+            .filter(|w| w.has_tag(&self.id) && !w.is_unmanaged() && !w.floating())
+            .map(|w| w.handle)
+            .collect()
+    }
+
+    /// Keep the window numbered first in `Monocle` among the tiled windows laid out with this
+    /// tag, `handles`, falling back to the one shown.
+    pub fn sync_first_window(&mut self, handles: &[WindowHandle]) {
+        if !self.first_window.is_some_and(|h| handles.contains(&h)) {
+            self.first_window = handles.first().copied();
+        }
+    }
+
+    /// The 1-based position of `handle` among the tiled windows laid out with this tag,
+    /// `handles`. `Monocle` rotates the windows as focus moves, so there they are counted from
+    /// the first window, in the order `FocusWindowDown` shows them, which keeps the number with
+    /// the window.
+    #[must_use]
+    pub fn window_position(&self, handles: &[WindowHandle], handle: WindowHandle) -> Option<usize> {
+        let position = handles.iter().position(|&h| h == handle)?;
+        match self.first_window {
+            Some(first) if self.layout.is_monocle() => {
+                let first = handles.iter().position(|&h| h == first).unwrap_or(0);
+                Some((first + handles.len() - position) % handles.len() + 1)
+            }
+            _ => Some(position + 1),
+        }
+    }
+
+    /// Bring the `Bsp` regions in line with the tiled windows laid out with this tag, `handles`,
+    /// splitting the region of the most recently focused window for new ones.
+    pub fn sync_bsp(&mut self, handles: &[WindowHandle], focus_order: &[WindowHandle]) {
+        self.bsp.sync(handles, focus_order);
+    }
+
+    /// Lay out the windows `workspace` shows, this tag being the first of those it shows.
+    pub fn update_windows(
+        &self,
+        windows: &mut [Window],
+        workspace: &Workspace,
+        custom_layouts: &[CustomLayout],
+    ) {
+        // The overview lays out every window in a grid, fullscreen and floating ones too.
+        let overview = workspace.overview.is_some();
         if let Some(window) = windows
             .iter_mut()
-            .find(|w| w.has_tag(&self.id) && w.is_fullscreen())
+            .find(|w| !overview && workspace.is_displaying(w) && w.is_fullscreen())
         {
             window.set_visible(true);
             window.normal = workspace.xyhw;
@@ -252,7 +461,7 @@ impl Tag {
             windows
                 .iter_mut()
                 .filter(|w| {
-                    w.has_tag(&self.id)
+                    workspace.is_displaying(w)
                         && w.transient.unwrap_or_else(|| 0.into()) == handle
                         && !w.is_unmanaged()
                 })
@@ -262,52 +471,150 @@ impl Tag {
         } else {
             // Don't bother updating the other windows when a window is fullscreen.
             // Mark all windows for this workspace as visible.
-            let mut all_mine: Vec<&mut Window> =
-                windows.iter_mut().filter(|w| w.has_tag(&self.id)).collect();
+            let mut all_mine: Vec<&mut Window> = windows
+                .iter_mut()
+                .filter(|w| workspace.is_displaying(w))
+                .collect();
             all_mine.iter_mut().for_each(|w| w.set_visible(true));
             // Update the location of all non-floating windows.
             let mut managed_nonfloat: Vec<&mut Window> = windows
                 .iter_mut()
-                .filter(|w| w.has_tag(&self.id) && !w.is_unmanaged() && !w.floating())
+                .filter(|w| {
+                    workspace.is_displaying(w) && !w.is_unmanaged() && (overview || !w.floating())
+                })
                 .collect();
-            self.layout
-                .update_windows(workspace, &mut managed_nonfloat, self);
+            // A width set on the tag takes precedence over the one of the workspace.
+            let limited;
+            let layout_workspace = match self.max_window_width {
+                Some(size) => {
+                    let mut tag_limited = workspace.clone();
+                    tag_limited.max_window_width = Some(size);
+                    limited = tag_limited;
+                    &limited
+                }
+                None => workspace,
+            };
+            let layout = if overview {
+                Layout::Grid
+            } else {
+                self.layout.clone()
+            };
+            layout.update_windows(
+                layout_workspace,
+                &mut managed_nonfloat,
+                self,
+                custom_layouts,
+            );
+            layout_workspace.apply_gaps(&mut managed_nonfloat);
             for w in &mut managed_nonfloat {
                 w.container_size = Some(workspace.xyhw);
             }
             // Update the location of all floating windows.
             windows
                 .iter_mut()
-                .filter(|w| w.has_tag(&self.id) && !w.is_unmanaged() && w.floating())
+                .filter(|w| {
+                    !overview && workspace.is_displaying(w) && !w.is_unmanaged() && w.floating()
+                })
                 .for_each(|w| w.normal = workspace.xyhw);
         }
     }
 
     /// Changes the main width percentage by the provided delta.
-    /// Result is sanitized, so the percentage can't go below 0 or above 100.
+    /// Result is sanitized, so the percentage can't go below 5 or above 95.
     ///
     /// ## Arguments
     /// * `delta` - increase/decrease main width percentage by this amount
-    pub fn change_main_width(&mut self, delta: i8) {
-        self.main_width_percentage = (self.main_width_percentage as i8 + delta)
-            .max(0) // not smaller than 0
-            .min(100) as u8; // not larger than 100
+    pub fn change_main_width(&mut self, delta: f32) {
+        self.set_main_width(self.main_width_percentage + delta);
     }
 
     /// Sets the main width percentage
     ///
     /// ## Arguments
     /// * `val` - the new with percentage
-    pub fn set_main_width(&mut self, val: u8) {
-        self.main_width_percentage = val.min(100); // not larger than 100
+    pub fn set_main_width(&mut self, val: f32) {
+        self.main_width_percentage = val.clamp(MIN_MAIN_WIDTH, MAX_MAIN_WIDTH);
+    }
+
+    #[must_use]
+    pub const fn main_width_percentage(&self) -> f32 {
+        self.main_width_percentage
+    }
+
+    /// Changes the stack split percentage by the provided delta.
+    /// Result is sanitized, so the percentage can't go below 0 or above 100.
+    ///
+    /// ## Arguments
+    /// * `delta` - increase/decrease stack split percentage by this amount
+    pub fn change_stack_split(&mut self, delta: f32) {
+        self.set_stack_split(self.stack_split_percentage + delta);
+    }
+
+    /// Sets the stack split percentage
+    ///
+    /// ## Arguments
+    /// * `val` - the new split percentage
+    pub fn set_stack_split(&mut self, val: f32) {
+        self.stack_split_percentage = val.clamp(0.0, 100.0);
+    }
+
+    #[must_use]
+    pub const fn stack_split_percentage(&self) -> f32 {
+        self.stack_split_percentage
+    }
+
+    /// Changes the number of main windows by the provided delta.
+    /// Result is sanitized, so there is always at least one main window.
+    ///
+    /// ## Arguments
+    /// * `delta` - add/remove this many main windows
+    pub fn change_main_count(&mut self, delta: isize) {
+        self.set_main_count(self.main_count().saturating_add_signed(delta));
     }
 
+    /// Sets the number of main windows
+    ///
+    /// ## Arguments
+    /// * `val` - the new number of main windows
+    pub fn set_main_count(&mut self, val: usize) {
+        self.main_count = val.max(1);
+    }
+
+    /// The number of main windows, at least one even for tags built without going through
+    /// `Tag::new`.
     #[must_use]
-    pub fn main_width_percentage(&self) -> f32 {
-        f32::from(self.main_width_percentage)
+    pub fn main_count(&self) -> usize {
+        self.main_count.max(1)
     }
 
-    pub fn set_layout(&mut self, layout: Layout, main_width_percentage: u8) {
+    /// Changes the weight of a window by the provided delta.
+    /// Result is sanitized, so the weight can't go below 0.1 or above 10.
+    ///
+    /// ## Arguments
+    /// * `handle` - the window to grow or shrink
+    /// * `delta` - increase/decrease the weight by this amount
+    pub fn change_window_weight(&mut self, handle: WindowHandle, delta: f32) {
+        let weight =
+            (self.window_weight(&handle) + delta).clamp(MIN_WINDOW_WEIGHT, MAX_WINDOW_WEIGHT);
+        match self.window_weights.iter_mut().find(|(h, _)| *h == handle) {
+            Some((_, w)) => *w = weight,
+            None => self.window_weights.push((handle, weight)),
+        }
+    }
+
+    /// The share of its column or row a window gets, relative to the others in it.
+    #[must_use]
+    pub fn window_weight(&self, handle: &WindowHandle) -> f32 {
+        self.window_weights
+            .iter()
+            .find(|(h, _)| h == handle)
+            .map_or(1.0, |(_, weight)| *weight)
+    }
+
+    pub fn set_layout(&mut self, layout: Layout, main_width_percentage: f32) {
+        if layout != self.layout {
+            self.last_layout = Some(self.layout.clone());
+        }
         self.layout = layout;
         self.set_main_width(main_width_percentage);
         self.layout_rotation = 0;
@@ -448,4 +755,12 @@ mod tests {
         let second_retrieve = tags.get_mut(2).unwrap();
         assert_eq!(second_retrieve.label, String::from("code"));
     }
+
+    #[test]
+    fn there_is_no_tag_zero() {
+        let mut tags = Tags::new();
+        tags.add_new("home", Layout::default());
+        assert!(tags.get(0).is_none());
+        assert!(tags.get_mut(0).is_none());
+    }
 }
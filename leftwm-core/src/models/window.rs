@@ -7,10 +7,15 @@ use crate::models::TagId;
 use crate::models::Xyhw;
 use crate::models::XyhwBuilder;
 use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
 use x11_dl::xlib;
 
 type MockHandle = i32;
 
+/// Seconds over which the urgency requests of a window are limited.
+const URGENCY_PERIOD_SECS: u64 = 60;
+
 #[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
 pub enum WindowHandle {
     MockHandle(MockHandle),
@@ -32,6 +37,44 @@ impl WindowHandle {
     }
 }
 
+/// The steps a window can be resized in, from `WM_NORMAL_HINTS`, such as the character cells of
+/// a terminal. Sizes are `base + n * increment`.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Default)]
+pub struct SizeIncrements {
+    pub base_w: i32,
+    pub base_h: i32,
+    pub inc_w: i32,
+    pub inc_h: i32,
+}
+
+impl SizeIncrements {
+    /// The pixels of `width` and `height` past the last whole increment.
+    #[must_use]
+    pub const fn leftover(&self, width: i32, height: i32) -> (i32, i32) {
+        (
+            leftover(width, self.base_w, self.inc_w),
+            leftover(height, self.base_h, self.inc_h),
+        )
+    }
+}
+
+const fn leftover(size: i32, base: i32, increment: i32) -> i32 {
+    if increment > 1 && size > base {
+        (size - base) % increment
+    } else {
+        0
+    }
+}
+
+/// How a window asks for attention.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UrgencySource {
+    /// The urgency hint of `WM_HINTS`.
+    Hint,
+    /// The `_NET_WM_STATE_DEMANDS_ATTENTION` state.
+    State,
+}
+
 /// Store Window information.
 // We allow this as we're not managing state directly. This could be refactored in the future.
 // TODO: Refactor floating
@@ -64,6 +107,30 @@ pub struct Window {
     // Two strings that are within a XClassHint, kept separate for simpler comparing.
     pub res_name: Option<String>,
     pub res_class: Option<String>,
+    #[serde(default)]
+    pub size_increments: Option<SizeIncrements>,
+    /// The window asked for attention and hasn't been focused since.
+    #[serde(default)]
+    pub urgent: bool,
+    /// The sources the window asks for attention through, see `request_urgency`.
+    #[serde(default)]
+    urgency_hint: bool,
+    #[serde(default)]
+    demands_attention: bool,
+    /// Drawn without a border, as asked by a window rule.
+    #[serde(default)]
+    pub borderless: bool,
+    /// On a workspace in game mode, which the display server marks the window for.
+    #[serde(default)]
+    pub game_mode: bool,
+    /// Set once the window asked for attention too often, it then stays urgent until focused.
+    #[serde(default)]
+    urgency_throttled: bool,
+    #[serde(skip)]
+    urgency_requests: VecDeque<Instant>,
+    /// When the window last became urgent, unknown for windows urgent before a restart.
+    #[serde(skip)]
+    urgent_since: Option<Instant>,
 }
 
 impl Window {
@@ -95,6 +162,15 @@ impl Window {
             strut: None,
             res_name: None,
             res_class: None,
+            size_increments: None,
+            urgent: false,
+            urgency_hint: false,
+            demands_attention: false,
+            borderless: false,
+            game_mode: false,
+            urgency_throttled: false,
+            urgency_requests: VecDeque::new(),
+            urgent_since: None,
         }
     }
 
@@ -184,10 +260,71 @@ impl Window {
         self.normal.set_h(height);
     }
 
+    /// Shrink the tile of the window to a whole number of its resize increments, centering it in
+    /// the pixels left over so they add to the gap around it.
+    pub fn snap_to_size_increments(&mut self) {
+        let (extra_w, extra_h) = match self.size_increments {
+            Some(increments) => increments.leftover(self.width(), self.height()),
+            None => return,
+        };
+        self.normal.set_w(self.normal.w() - extra_w);
+        self.normal.set_h(self.normal.h() - extra_h);
+        self.normal.set_x(self.normal.x() + extra_w / 2);
+        self.normal.set_y(self.normal.y() + extra_h / 2);
+    }
+
     pub fn set_states(&mut self, states: Vec<WindowState>) {
         self.states = states;
     }
 
+    /// Set or clear the urgency the window asks for through `source`. The window is urgent while
+    /// either source asks for it. No more than `limit` requests a minute are followed, past that
+    /// the window stays urgent until it is focused. A `limit` of 0 follows every request.
+    ///
+    /// Returns `true` if the urgency of the window changed.
+    pub fn request_urgency(
+        &mut self,
+        source: UrgencySource,
+        urgent: bool,
+        limit: usize,
+        now: Instant,
+    ) -> bool {
+        match source {
+            UrgencySource::Hint => self.urgency_hint = urgent,
+            UrgencySource::State => self.demands_attention = urgent,
+        }
+        let urgent = self.urgency_hint || self.demands_attention;
+        if urgent == self.urgent || self.urgency_throttled {
+            return false;
+        }
+        if urgent && limit > 0 {
+            self.urgency_requests.retain(|&time| {
+                now.saturating_duration_since(time) < Duration::from_secs(URGENCY_PERIOD_SECS)
+            });
+            self.urgency_requests.push_back(now);
+            self.urgency_throttled = self.urgency_requests.len() > limit;
+        }
+        self.urgent = urgent;
+        self.urgent_since = urgent.then_some(now);
+        true
+    }
+
+    /// When the window last became urgent, `None` if it isn't or that was before a restart.
+    #[must_use]
+    pub fn urgent_since(&self) -> Option<Instant> {
+        self.urgent_since
+    }
+
+    /// Clear the urgency of the window, e.g. because it was focused.
+    pub fn clear_urgency(&mut self) {
+        self.urgent = false;
+        self.urgency_hint = false;
+        self.demands_attention = false;
+        self.urgency_throttled = false;
+        self.urgency_requests.clear();
+        self.urgent_since = None;
+    }
+
     #[must_use]
     pub fn has_state(&self, state: &WindowState) -> bool {
         self.states.contains(state)
@@ -268,7 +405,7 @@ impl Window {
 
     #[must_use]
     pub fn border(&self) -> i32 {
-        if self.is_fullscreen() {
+        if self.is_fullscreen() || self.borderless {
             0
         } else {
             self.border
@@ -368,4 +505,70 @@ mod tests {
         subject.untag(&1);
         assert!(!subject.has_tag(&1), "was unable to untag the window");
     }
+
+    #[test]
+    fn snapping_to_size_increments_centers_the_window_in_the_leftover() {
+        let mut subject = Window::new(WindowHandle::MockHandle(1), None, None);
+        subject.normal = XyhwBuilder {
+            w: 400,
+            h: 300,
+            ..XyhwBuilder::default()
+        }
+        .into();
+        subject.size_increments = Some(SizeIncrements {
+            base_w: 4,
+            base_h: 4,
+            inc_w: 10,
+            inc_h: 20,
+        });
+        subject.snap_to_size_increments();
+        // 378x278 inside the margins and borders, 4 and 14 pixels past the last whole cells.
+        assert_eq!((subject.width(), subject.height()), (374, 264));
+        assert_eq!((subject.x(), subject.y()), (12, 17));
+    }
+
+    #[test]
+    fn urgency_requests_past_the_limit_keep_the_window_urgent() {
+        let mut subject = Window::new(WindowHandle::MockHandle(1), None, None);
+        let now = Instant::now();
+        assert!(subject.request_urgency(UrgencySource::Hint, true, 2, now));
+        assert!(
+            !subject.request_urgency(UrgencySource::Hint, true, 2, now),
+            "repeated request changed urgency"
+        );
+        assert!(subject.request_urgency(UrgencySource::Hint, false, 2, now));
+        assert!(subject.request_urgency(UrgencySource::Hint, true, 2, now));
+        assert!(subject.request_urgency(UrgencySource::Hint, false, 2, now));
+        // The third request in a minute throttles the window.
+        assert!(subject.request_urgency(UrgencySource::Hint, true, 2, now));
+        assert!(!subject.request_urgency(UrgencySource::Hint, false, 2, now));
+        assert!(subject.urgent);
+
+        subject.clear_urgency();
+        assert!(!subject.urgent);
+        assert!(subject.request_urgency(UrgencySource::Hint, true, 2, now));
+    }
+
+    #[test]
+    fn windows_should_stay_urgent_while_either_source_asks_for_it() {
+        let mut subject = Window::new(WindowHandle::MockHandle(1), None, None);
+        let now = Instant::now();
+        assert!(subject.request_urgency(UrgencySource::Hint, true, 0, now));
+        assert!(!subject.request_urgency(UrgencySource::State, true, 0, now));
+        assert!(!subject.request_urgency(UrgencySource::Hint, false, 0, now));
+        assert!(subject.urgent);
+        assert!(subject.request_urgency(UrgencySource::State, false, 0, now));
+        assert!(!subject.urgent);
+    }
+
+    #[test]
+    fn urgency_requests_are_only_limited_within_a_minute() {
+        let mut subject = Window::new(WindowHandle::MockHandle(1), None, None);
+        let start = Instant::now();
+        for minute in 0..5 {
+            let now = start + Duration::from_secs(URGENCY_PERIOD_SECS * minute);
+            assert!(subject.request_urgency(UrgencySource::Hint, true, 1, now));
+            assert!(subject.request_urgency(UrgencySource::Hint, false, 1, now));
+        }
+    }
 }
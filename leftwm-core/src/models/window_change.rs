@@ -1,4 +1,6 @@
 use super::MaybeWindowHandle;
+use super::SizeIncrements;
+use super::UrgencySource;
 use super::Window;
 use super::WindowHandle;
 use super::WindowState;
@@ -18,7 +20,9 @@ pub struct WindowChange {
     pub floating: Option<XyhwChange>,
     pub strut: Option<XyhwChange>,
     pub requested: Option<Xyhw>,
+    pub size_increments: Option<SizeIncrements>,
     pub states: Option<Vec<WindowState>>,
+    pub urgent: Option<(UrgencySource, bool)>,
 }
 
 impl WindowChange {
@@ -33,7 +37,9 @@ impl WindowChange {
             floating: None,
             strut: None,
             requested: None,
+            size_increments: None,
             states: None,
+            urgent: None,
         }
     }
 
@@ -73,6 +79,10 @@ impl WindowChange {
         if let Some(requested) = self.requested {
             window.requested = Some(requested);
         }
+        if let Some(increments) = self.size_increments {
+            changed = changed || window.size_increments != Some(increments);
+            window.size_increments = Some(increments);
+        }
         if let Some(r#type) = &self.r#type {
             let changed_type = &window.r#type != r#type;
             changed = changed || changed_type;
@@ -83,7 +93,14 @@ impl WindowChange {
             }
         }
         if let Some(states) = self.states {
-            changed = true;
+            // Attention is followed through the urgency of the window.
+            let relevant = |s: &&WindowState| **s != WindowState::DemandsAttention;
+            let old_states = window.states();
+            changed = changed
+                || !old_states
+                    .iter()
+                    .filter(relevant)
+                    .eq(states.iter().filter(relevant));
             window.set_states(states);
         }
         changed
@@ -1,6 +1,9 @@
 use crate::config::Config;
+use crate::layouts::{tab_bar_height, TAB_BAR_HEIGHT};
+use crate::models::tag::{default_stack_split, MAX_MAIN_WIDTH, MIN_MAIN_WIDTH};
 use crate::models::{
-    layouts::Layout, BBox, Gutter, Margins, Side, Size, TagId, Window, Xyhw, XyhwBuilder,
+    layouts::Layout, BBox, Gutter, Margins, Side, Size, TagId, Window, WindowHandle, WindowType,
+    Xyhw, XyhwBuilder,
 };
 use serde::{Deserialize, Serialize};
 use std::fmt;
@@ -11,16 +14,73 @@ pub struct Workspace {
     pub id: Option<i32>,
     /// Active layout
     pub layout: Layout,
-    pub main_width_percentage: u8,
+    pub main_width_percentage: f32,
+    #[serde(default = "default_stack_split")]
+    pub stack_split_percentage: f32,
     pub tags: Vec<TagId>,
+    /// The tags shown before the last tag change, which `FocusLastTag` goes back to.
+    #[serde(default)]
+    pub previous_tags: Vec<TagId>,
+    /// The tags shown before, the latest last, which `TagHistoryBack` goes back through.
+    #[serde(default)]
+    pub tag_history_back: Vec<Vec<TagId>>,
+    /// The tags `TagHistoryBack` left, which `TagHistoryForward` returns to.
+    #[serde(default)]
+    pub tag_history_forward: Vec<Vec<TagId>>,
+    /// The tags shown before the overview, while it lays out the windows of all the tags.
+    #[serde(default)]
+    pub overview: Option<Vec<TagId>>,
     pub margin: Margins,
     pub margin_multiplier: f32,
+    /// The gap between tiled windows, replacing their own margins when set.
+    #[serde(default)]
+    pub inner_gap: Option<u32>,
+    /// Whether a window tiled alone goes without gaps.
+    #[serde(default)]
+    pub smart_gaps: bool,
+    /// The margins of the windows, given back to them on workspaces without an `inner_gap`.
+    #[serde(default)]
+    pub window_margin: Option<Margins>,
+    /// The inner gap and margin `toggle_gaps` took away, given back by the next toggle.
+    #[serde(default)]
+    pub hidden_gaps: Option<(Option<u32>, Margins)>,
+    /// The gaps are taken away, the focus doesn't follow the mouse and the windows are marked
+    /// for compositors to unredirect.
+    #[serde(default)]
+    pub game_mode: bool,
+    /// Whether entering game mode took the gaps away, so leaving it gives back only those.
+    #[serde(default)]
+    game_mode_hid_gaps: bool,
     pub gutters: Vec<Gutter>,
     #[serde(skip)]
     pub avoid: Vec<Xyhw>,
     pub xyhw: Xyhw,
     xyhw_avoided: Xyhw,
     pub max_window_width: Option<Size>,
+    /// Set while the workspace is zoomed in on a single window.
+    #[serde(default)]
+    pub zoom: Option<Zoom>,
+    /// The name of the output of the screen the workspace was made for.
+    #[serde(default)]
+    pub output: Option<String>,
+    /// The name the config gives the workspace.
+    #[serde(default)]
+    pub name: Option<String>,
+    /// Height of the strip of tabs of the `Tabbed` layout.
+    #[serde(default = "default_tab_bar_height")]
+    pub tab_bar_height: i32,
+}
+
+const fn default_tab_bar_height() -> i32 {
+    TAB_BAR_HEIGHT
+}
+
+/// What a workspace looked like before it was zoomed in on a window.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct Zoom {
+    pub layout: Layout,
+    /// The order of the windows managed by the workspace.
+    pub order: Vec<WindowHandle>,
 }
 
 impl fmt::Debug for Workspace {
@@ -52,11 +112,22 @@ impl Workspace {
     ) -> Self {
         Self {
             id,
-            layout,
             main_width_percentage: layout.main_width(),
+            layout,
+            stack_split_percentage: default_stack_split(),
             tags: vec![],
+            previous_tags: vec![],
+            tag_history_back: vec![],
+            tag_history_forward: vec![],
+            overview: None,
             margin: Margins::new(10),
             margin_multiplier: 1.0,
+            inner_gap: None,
+            hidden_gaps: None,
+            game_mode: false,
+            game_mode_hid_gaps: false,
+            smart_gaps: false,
+            window_margin: None,
             gutters: vec![],
             avoid: vec![],
             xyhw: XyhwBuilder {
@@ -76,12 +147,41 @@ impl Workspace {
             }
             .into(),
             max_window_width,
+            zoom: None,
+            output: None,
+            name: None,
+            tab_bar_height: TAB_BAR_HEIGHT,
         }
     }
 
     pub fn load_config(&mut self, config: &impl Config) {
-        self.margin = config.workspace_margin().unwrap_or_else(|| Margins::new(0));
+        // Workspaces configured without an id are numbered in the order they are listed.
+        let own = config.workspaces().and_then(|workspaces| {
+            workspaces
+                .into_iter()
+                .enumerate()
+                .find(|(i, wsc)| Some(wsc.id.unwrap_or(*i as i32)) == self.id)
+                .map(|(_, wsc)| wsc)
+        });
+        let outer_gap = own
+            .as_ref()
+            .and_then(|wsc| wsc.outer_gap)
+            .or_else(|| config.outer_gap());
+        self.margin = match outer_gap {
+            Some(gap) => Margins::new(gap),
+            None => config.workspace_margin().unwrap_or_else(|| Margins::new(0)),
+        };
+        self.inner_gap = own
+            .as_ref()
+            .and_then(|wsc| wsc.inner_gap)
+            .or_else(|| config.inner_gap());
+        self.smart_gaps = config.smart_gaps();
+        self.hidden_gaps = None;
+        self.game_mode = false;
+        self.game_mode_hid_gaps = false;
+        self.window_margin = Some(config.margin());
         self.gutters = self.get_gutters_for_theme(config);
+        self.tab_bar_height = tab_bar_height(config.min_font_size());
     }
 
     pub fn get_gutters_for_theme(&mut self, config: &impl Config) -> Vec<Gutter> {
@@ -112,6 +212,17 @@ impl Workspace {
         self.xyhw.contains_point(x, y)
     }
 
+    /// Show other tags, remembering the ones shown until now as the previous tags and in the
+    /// history, which keeps `depth` of them and loses what was ahead.
+    pub fn show_tags(&mut self, tags: Vec<TagId>, depth: usize) {
+        let old = std::mem::replace(&mut self.tags, tags);
+        self.tag_history_back.push(old.clone());
+        let excess = self.tag_history_back.len().saturating_sub(depth);
+        self.tag_history_back.drain(..excess);
+        self.tag_history_forward.clear();
+        self.previous_tags = old;
+    }
+
     #[must_use]
     pub fn has_tag(&self, tag: &TagId) -> bool {
         self.tags.contains(tag)
@@ -128,6 +239,16 @@ impl Workspace {
         false
     }
 
+    /// The tiled windows shown, which the layout of the first tag shown arranges.
+    #[must_use]
+    pub fn tiled_windows(&self, windows: &[Window]) -> Vec<WindowHandle> {
+        windows
+            .iter()
+            .filter(|w| self.is_managed(w) && !w.floating())
+            .map(|w| w.handle)
+            .collect()
+    }
+
     /// Returns true if the workspace is to update the locations info of this window.
     #[must_use]
     pub fn is_managed(&self, window: &Window) -> bool {
@@ -198,6 +319,75 @@ impl Workspace {
         }
     }
 
+    /// Give the tiled windows margins making up the `inner_gap` between them, and none on the
+    /// sides along the edges of the workspace, which its margin already sets apart. With
+    /// `smart_gaps`, a window tiled alone takes the whole workspace instead.
+    pub fn apply_gaps(&self, windows: &mut [&mut Window]) {
+        if let [window] = windows {
+            if self.smart_gaps {
+                self.remove_gaps(window);
+                return;
+            }
+        }
+        if let Some(gap) = self.inner_gap {
+            let side = |at_edge: bool, share: u32| if at_edge { 0 } else { share };
+            for window in windows.iter_mut() {
+                let [top, right, bottom, left] = self.edges_along(&window.normal);
+                window.margin = Margins {
+                    top: side(top, gap - gap / 2),
+                    right: side(right, gap / 2),
+                    bottom: side(bottom, gap / 2),
+                    left: side(left, gap - gap / 2),
+                };
+            }
+        } else if let Some(margin) = self.window_margin {
+            windows
+                .iter_mut()
+                .filter(|w| w.r#type == WindowType::Normal)
+                .for_each(|w| w.margin = margin);
+        }
+    }
+
+    /// Grow a window over the margin of the workspace along the edges it lies on, the gutters
+    /// left for bars excepted, and take away its own margin.
+    fn remove_gaps(&self, window: &mut Window) {
+        let [top, right, bottom, left] = self.edges_along(&window.normal);
+        let margin = |along: bool, size: u32| {
+            if along {
+                (size as f32 * self.margin_multiplier) as i32
+            } else {
+                0
+            }
+        };
+        let (top, right) = (
+            margin(top, self.margin.top),
+            margin(right, self.margin.right),
+        );
+        let (bottom, left) = (
+            margin(bottom, self.margin.bottom),
+            margin(left, self.margin.left),
+        );
+        let area = &mut window.normal;
+        area.set_x(area.x() - left);
+        area.set_y(area.y() - top);
+        area.set_w(area.w() + left + right);
+        area.set_h(area.h() + top + bottom);
+        window.margin = Margins::new(0);
+    }
+
+    /// Whether an area lies along the top, right, bottom and left edges of the space the windows
+    /// are tiled in.
+    fn edges_along(&self, area: &Xyhw) -> [bool; 4] {
+        let (left, top) = (self.x(), self.y());
+        let (right, bottom) = (left + self.width(), top + self.height());
+        [
+            area.y() <= top,
+            area.x() + area.w() >= right,
+            area.y() + area.h() >= bottom,
+            area.x() <= left,
+        ]
+    }
+
     #[must_use]
     pub fn center_halfed(&self) -> Xyhw {
         self.xyhw_avoided.center_halfed()
@@ -211,6 +401,59 @@ impl Workspace {
         self.xyhw_avoided = xyhw;
     }
 
+    /// Widen the gaps between the windows and around them by `step` pixels.
+    pub fn grow_gaps(&mut self, step: u32) {
+        self.resize_gaps(|size| size.saturating_add(step));
+    }
+
+    /// Narrow the gaps between the windows and around them by `step` pixels.
+    pub fn shrink_gaps(&mut self, step: u32) {
+        self.resize_gaps(|size| size.saturating_sub(step));
+    }
+
+    /// Resize the inner gap, starting from the one the margins of the windows make up, and the
+    /// margin. Gaps taken away by `toggle_gaps` grow from nothing instead of coming back.
+    fn resize_gaps(&mut self, resize: impl Fn(u32) -> u32) {
+        self.hidden_gaps = None;
+        let inner_gap = self.inner_gap.unwrap_or_else(|| {
+            self.window_margin
+                .map_or(0, |margin| margin.top + margin.bottom)
+        });
+        self.inner_gap = Some(resize(inner_gap));
+        self.margin = Margins {
+            top: resize(self.margin.top),
+            right: resize(self.margin.right),
+            bottom: resize(self.margin.bottom),
+            left: resize(self.margin.left),
+        };
+    }
+
+    /// Take the gaps away, or give them back when they were taken.
+    pub fn toggle_gaps(&mut self) {
+        if let Some((inner_gap, margin)) = self.hidden_gaps.take() {
+            self.inner_gap = inner_gap;
+            self.margin = margin;
+        } else {
+            self.hidden_gaps = Some((self.inner_gap, self.margin));
+            self.inner_gap = Some(0);
+            self.margin = Margins::new(0);
+        }
+    }
+
+    /// Enter game mode, taking the gaps away, or leave it, giving back the gaps it took. Gaps
+    /// already hidden, or changed while in game mode, are left as they are.
+    pub fn toggle_game_mode(&mut self) {
+        self.game_mode = !self.game_mode;
+        if self.game_mode {
+            self.game_mode_hid_gaps = self.hidden_gaps.is_none();
+            if self.game_mode_hid_gaps {
+                self.toggle_gaps();
+            }
+        } else if std::mem::take(&mut self.game_mode_hid_gaps) && self.hidden_gaps.is_some() {
+            self.toggle_gaps();
+        }
+    }
+
     /// Set the tag model's margin multiplier.
     pub fn set_margin_multiplier(&mut self, margin_multiplier: f32) {
         self.margin_multiplier = margin_multiplier;
@@ -222,21 +465,13 @@ impl Workspace {
         self.margin_multiplier
     }
 
-    pub fn change_main_width(&mut self, delta: i8) {
-        //Check we are not gonna go negative
-        let mwp = &mut self.main_width_percentage;
-        if (*mwp as i8) < -delta {
-            *mwp = 0;
-            return;
-        }
-        if delta.is_negative() {
-            *mwp -= delta.unsigned_abs();
-            return;
-        }
-        *mwp += delta as u8;
-        if *mwp > 100 {
-            *mwp = 100;
-        }
+    pub fn change_main_width(&mut self, delta: f32) {
+        self.main_width_percentage =
+            (self.main_width_percentage + delta).clamp(MIN_MAIN_WIDTH, MAX_MAIN_WIDTH);
+    }
+
+    pub fn change_stack_split(&mut self, delta: f32) {
+        self.stack_split_percentage = (self.stack_split_percentage + delta).clamp(0.0, 100.0);
     }
 }
 
@@ -285,4 +520,160 @@ mod tests {
         w.tag(&TAG_ID);
         assert!(subject.is_displaying(&w), "workspace should include window");
     }
+
+    #[test]
+    fn gaps_should_set_windows_apart_and_from_the_edges() {
+        use crate::config::{self, TestConfig};
+        use crate::display_servers::MockDisplayServer;
+        use crate::models::{Manager, Screen};
+
+        let mut manager: Manager<TestConfig, MockDisplayServer> = Manager::new(TestConfig {
+            tags: vec!["1".to_string(), "2".to_string()],
+            inner_gap: Some(10),
+            outer_gap: Some(20),
+            workspaces: Some(vec![
+                config::Workspace::default(),
+                config::Workspace {
+                    inner_gap: Some(4),
+                    outer_gap: Some(0),
+                    ..config::Workspace::default()
+                },
+            ]),
+            ..TestConfig::default()
+        });
+        for x in [0, 1000] {
+            manager.screen_create_handler(Screen::new(BBox {
+                x,
+                y: 0,
+                width: 1000,
+                height: 600,
+            }));
+        }
+        manager
+            .state
+            .focus_workspace(&manager.state.workspaces[0].clone());
+        for i in 1..=2 {
+            manager.window_created_handler(
+                Window::new(WindowHandle::MockHandle(i), None, None),
+                -1,
+                -1,
+            );
+        }
+        manager.update_windows();
+        let areas: Vec<Xyhw> = manager
+            .state
+            .windows
+            .iter()
+            .map(Window::calculated_xyhw)
+            .collect();
+        let (first, second) = if areas[0].x() < areas[1].x() {
+            (areas[0], areas[1])
+        } else {
+            (areas[1], areas[0])
+        };
+        assert_eq!((first.x(), first.y(), first.h()), (20, 20, 560));
+        assert_eq!(second.x() - (first.x() + first.w()), 10);
+        assert_eq!(second.x() + second.w(), 980);
+
+        let second_workspace = &manager.state.workspaces[1];
+        assert_eq!(second_workspace.margin, Margins::new(0));
+        assert_eq!(second_workspace.inner_gap, Some(4));
+    }
+
+    #[test]
+    fn smart_gaps_should_leave_a_lone_window_without_gaps() {
+        use crate::config::TestConfig;
+        use crate::display_servers::MockDisplayServer;
+        use crate::models::{Manager, Screen};
+
+        let mut manager: Manager<TestConfig, MockDisplayServer> = Manager::new(TestConfig {
+            tags: vec!["1".to_string()],
+            inner_gap: Some(10),
+            outer_gap: Some(20),
+            smart_gaps: true,
+            ..TestConfig::default()
+        });
+        manager.screen_create_handler(Screen::new(BBox {
+            x: 0,
+            y: 0,
+            width: 1000,
+            height: 600,
+        }));
+        let create = |manager: &mut Manager<TestConfig, MockDisplayServer>, id| {
+            manager.window_created_handler(
+                Window::new(WindowHandle::MockHandle(id), None, None),
+                -1,
+                -1,
+            );
+            manager.update_windows();
+        };
+        create(&mut manager, 1);
+        let alone = manager.state.windows[0].calculated_xyhw();
+        assert_eq!(
+            (alone.x(), alone.y(), alone.w(), alone.h()),
+            (0, 0, 1000, 600)
+        );
+
+        create(&mut manager, 2);
+        for window in &manager.state.windows {
+            let area = window.calculated_xyhw();
+            assert_eq!((area.y(), area.h()), (20, 560));
+        }
+    }
+
+    #[test]
+    fn gaps_should_change_and_toggle_at_runtime() {
+        let bbox = BBox {
+            width: 600,
+            height: 800,
+            x: 0,
+            y: 0,
+        };
+        let mut ws = Workspace::new(None, bbox, Layout::default(), None);
+        ws.inner_gap = Some(10);
+        ws.margin = Margins::new(4);
+        ws.grow_gaps(2);
+        assert_eq!((ws.inner_gap, ws.margin), (Some(12), Margins::new(6)));
+        ws.shrink_gaps(8);
+        assert_eq!((ws.inner_gap, ws.margin), (Some(4), Margins::new(0)));
+
+        ws.toggle_gaps();
+        assert_eq!((ws.inner_gap, ws.margin), (Some(0), Margins::new(0)));
+        ws.toggle_gaps();
+        assert_eq!((ws.inner_gap, ws.margin), (Some(4), Margins::new(0)));
+
+        ws.window_margin = Some(Margins::new(5));
+        ws.inner_gap = None;
+        ws.grow_gaps(1);
+        assert_eq!(ws.inner_gap, Some(11));
+    }
+
+    #[test]
+    fn game_mode_should_give_back_only_the_gaps_it_took() {
+        let bbox = BBox {
+            width: 600,
+            height: 800,
+            x: 0,
+            y: 0,
+        };
+        let mut ws = Workspace::new(None, bbox, Layout::default(), None);
+        ws.inner_gap = Some(10);
+        ws.margin = Margins::new(4);
+        ws.toggle_game_mode();
+        assert_eq!((ws.inner_gap, ws.margin), (Some(0), Margins::new(0)));
+        ws.toggle_game_mode();
+        assert_eq!((ws.inner_gap, ws.margin), (Some(10), Margins::new(4)));
+
+        ws.toggle_gaps();
+        ws.toggle_game_mode();
+        ws.toggle_game_mode();
+        assert_eq!((ws.inner_gap, ws.margin), (Some(0), Margins::new(0)));
+        ws.toggle_gaps();
+        assert_eq!((ws.inner_gap, ws.margin), (Some(10), Margins::new(4)));
+
+        ws.toggle_game_mode();
+        ws.grow_gaps(2);
+        ws.toggle_game_mode();
+        assert_eq!((ws.inner_gap, ws.margin), (Some(2), Margins::new(2)));
+    }
 }
@@ -216,6 +216,15 @@ impl Xyhw {
         self.contains_point(other.x, other.y) && self.contains_point(other_max_x, other_max_y)
     }
 
+    /// Returns true if the two areas overlap. Touching edges do not count as overlap.
+    #[must_use]
+    pub const fn intersects(&self, other: &Self) -> bool {
+        self.x < other.x + other.w
+            && other.x < self.x + self.w
+            && self.y < other.y + other.h
+            && other.y < self.y + self.h
+    }
+
     #[must_use]
     pub const fn volume(&self) -> u64 {
         self.h as u64 * self.w as u64
@@ -479,4 +488,34 @@ mod tests {
         };
         assert!(!a.contains_xyhw(&b));
     }
+
+    #[test]
+    fn intersects_should_ignore_touching_edges() {
+        let a: Xyhw = XyhwBuilder {
+            h: 100,
+            w: 100,
+            x: 0,
+            y: 0,
+            ..XyhwBuilder::default()
+        }
+        .into();
+        let touching: Xyhw = XyhwBuilder {
+            h: 100,
+            w: 100,
+            x: 0,
+            y: 100,
+            ..XyhwBuilder::default()
+        }
+        .into();
+        let overlapping: Xyhw = XyhwBuilder {
+            h: 100,
+            w: 100,
+            x: 50,
+            y: 90,
+            ..XyhwBuilder::default()
+        }
+        .into();
+        assert!(!a.intersects(&touching));
+        assert!(a.intersects(&overlapping));
+    }
 }
@@ -1,18 +1,24 @@
 //! Save and restore manager state.
 
-use crate::config::{Config, InsertBehavior, ScratchPad};
-use crate::layouts::Layout;
+use crate::config::{Config, EmptyTagBehavior, InsertBehavior, NewWindowPlacement, ScratchPad};
+use crate::display_event::Timestamp;
+use crate::layouts::{external, CustomLayout, Layout};
 use crate::models::Screen;
 use crate::models::Size;
-use crate::models::Tags;
+use crate::models::TabBar;
 use crate::models::Window;
 use crate::models::Workspace;
+use crate::models::Xyhw;
 use crate::models::{FocusManager, LayoutManager};
 use crate::models::{Mode, WindowHandle};
+use crate::models::{Tag, TagId, TagListing, Tags};
 use crate::DisplayAction;
 use serde::{Deserialize, Serialize};
 use std::collections::{HashMap, VecDeque};
 
+// The flags are mostly independent settings mirrored from the config, and aren't a state
+// machine to fold into an enum.
+#[allow(clippy::struct_excessive_bools)]
 #[derive(Serialize, Deserialize, Debug)]
 pub struct State {
     pub screens: Vec<Screen>,
@@ -31,7 +37,71 @@ pub struct State {
     pub default_width: i32,
     pub default_height: i32,
     pub disable_tile_drag: bool,
+    /// Every workspace has its own set of tags.
+    #[serde(default)]
+    pub per_screen_tags: bool,
+    /// Going to a tag shown on another workspace brings it over to the focused one.
+    #[serde(default)]
+    pub greedy_view: bool,
+    /// How many tag changes each workspace remembers for `TagHistoryBack`.
+    #[serde(default)]
+    pub tag_history_depth: usize,
+    /// Tiled windows are shrunk to a whole number of their resize increments.
+    #[serde(default)]
+    pub honor_size_increments: bool,
+    /// Milliseconds windows slide for when switching tags.
+    #[serde(default)]
+    pub tag_slide_duration: u64,
+    /// Set to move windows at once rather than animate them.
+    #[serde(default)]
+    pub reduce_motion: bool,
+    #[serde(default)]
+    pub border_width: i32,
+    /// Border width of the focused window, when it differs from `border_width`.
+    #[serde(default)]
+    pub focused_border_width: Option<i32>,
+    /// No border on a window tiled alone.
+    #[serde(default)]
+    pub smart_borders: bool,
+    pub snap_distance: i32,
+    #[serde(default)]
+    pub urgency_limit: usize,
     pub insert_behavior: InsertBehavior,
+    #[serde(default)]
+    pub empty_tag_behavior: EmptyTagBehavior,
+    #[serde(default)]
+    pub new_window_placement: NewWindowPlacement,
+    /// Last floating geometry of windows, by `WM_CLASS`.
+    #[serde(default)]
+    pub floating_geometry: HashMap<String, Xyhw>,
+    /// Docks are hidden and workspaces extend over their struts.
+    #[serde(default)]
+    pub docks_hidden: bool,
+    /// Tag an unconfirmed `CloseAllOnTag` was issued on.
+    #[serde(skip)]
+    pub close_all_pending: Option<TagId>,
+    /// Whether the window being moved was tiled when the drag started.
+    #[serde(skip)]
+    pub dragging_tile: bool,
+    /// Tiled window the dragged tile swaps places with when dropped now.
+    #[serde(skip)]
+    pub drag_swap_target: Option<WindowHandle>,
+    /// Times to run the next command, built up by `CountPrefix`.
+    #[serde(skip)]
+    pub pending_count: Option<usize>,
+    /// The tab bars last sent to the display server.
+    #[serde(skip)]
+    pub tab_bars: Vec<TabBar>,
+    /// The layouts defined in the config.
+    #[serde(skip)]
+    pub custom_layouts: Vec<CustomLayout>,
+    /// The time of the last input event, which the focus changes it causes carry.
+    #[serde(skip)]
+    pub event_time: Timestamp,
+    /// Geometry, border and visibility last sent to the display server of the windows on tags
+    /// with a fullscreen window, which aren't sent again until they change.
+    #[serde(skip)]
+    pub covered_windows: Vec<(WindowHandle, Xyhw, i32, bool)>,
 }
 
 impl State {
@@ -39,7 +109,11 @@ impl State {
         let layout_manager = LayoutManager::new(config);
         let mut tags = Tags::new();
         config.create_list_of_tag_labels().iter().for_each(|label| {
-            tags.add_new(label.as_str(), layout_manager.new_layout(None));
+            let id = tags.add_new(label.as_str(), layout_manager.new_layout(None));
+            if let Some(tag) = tags.get_mut(id) {
+                tag.load_config(config, id);
+                tag.use_own_layout();
+            }
         });
         tags.add_new_hidden("NSP");
 
@@ -60,20 +134,44 @@ impl State {
             default_width: config.default_width(),
             default_height: config.default_height(),
             disable_tile_drag: config.disable_tile_drag(),
+            per_screen_tags: config.per_screen_tags(),
+            greedy_view: config.greedy_view(),
+            tag_history_depth: config.tag_history_depth(),
+            honor_size_increments: config.honor_size_increments(),
+            tag_slide_duration: config.tag_slide_duration(),
+            reduce_motion: config.reduce_motion(),
+            border_width: config.border_width(),
+            focused_border_width: config.focused_border_width(),
+            smart_borders: config.smart_borders(),
+            snap_distance: config.snap_distance(),
+            urgency_limit: config.urgency_limit(),
             insert_behavior: config.insert_behavior(),
+            empty_tag_behavior: config.empty_tag_behavior(),
+            new_window_placement: config.new_window_placement(),
+            floating_geometry: Default::default(),
+            docks_hidden: false,
+            close_all_pending: None,
+            dragging_tile: false,
+            drag_swap_target: None,
+            pending_count: None,
+            tab_bars: vec![],
+            custom_layouts: config.custom_layouts(),
+            event_time: 0,
+            covered_windows: vec![],
         }
     }
 
     //sorts the windows and puts them in order of importance
     //keeps the order for each importance level
     pub fn sort_windows(&mut self) {
-        use crate::models::WindowType;
-        //first dialogs and modals
+        use crate::models::{WindowState, WindowType};
+        //first dialogs, modals and transients so they stay above their parents
         let (level1, other): (Vec<&Window>, Vec<&Window>) = self.windows.iter().partition(|w| {
             w.r#type == WindowType::Dialog
                 || w.r#type == WindowType::Splash
                 || w.r#type == WindowType::Utility
                 || w.r#type == WindowType::Menu
+                || w.transient.is_some()
         });
 
         //next floating
@@ -96,10 +194,101 @@ impl State {
             .chain(other.iter())
             .map(|&w| w.clone())
             .collect();
+        // Windows asking to be kept above or below the others are, docks aside.
+        self.windows.sort_by_key(|w| {
+            if w.is_unmanaged() {
+                3
+            } else if w.has_state(&WindowState::Above) {
+                0
+            } else if w.has_state(&WindowState::Below) {
+                2
+            } else {
+                1
+            }
+        });
         let act = DisplayAction::SetWindowOrder(self.windows.clone());
         self.actions.push_back(act);
     }
 
+    /// The tags a workspace can display, in order. These are its own tags when every workspace
+    /// has its own set, all the normal tags otherwise.
+    pub fn workspace_tags(&self, workspace: &Workspace) -> Vec<TagId> {
+        self.tags
+            .normal()
+            .iter()
+            .filter(|tag| !self.per_screen_tags || tag.workspace_id == workspace.id)
+            .map(|tag| tag.id)
+            .collect()
+    }
+
+    /// The tags cycled through and given to bars among `tags`: those which aren't hidden, in the
+    /// configured order.
+    pub fn listed_tags(&self, tags: &[TagId]) -> Vec<TagId> {
+        let mut tags: Vec<&Tag> = tags
+            .iter()
+            .filter_map(|&id| self.tags.get(id))
+            .filter(|tag| tag.listing == TagListing::Listed)
+            .collect();
+        tags.sort_by_key(|tag| tag.order);
+        tags.iter().map(|tag| tag.id).collect()
+    }
+
+    /// The tag a command numbering tags from 1 refers to. With a set of tags per workspace the
+    /// number counts the tags of the focused workspace.
+    pub fn focused_workspace_tag(&self, number: usize) -> Option<TagId> {
+        if !self.per_screen_tags {
+            return Some(number);
+        }
+        let workspace = self.focus_manager.workspace(&self.workspaces)?;
+        self.workspace_tags(workspace)
+            .get(number.checked_sub(1)?)
+            .copied()
+    }
+
+    /// Whether the focused workspace is in game mode.
+    pub fn in_game_mode(&self) -> bool {
+        self.focus_manager
+            .workspace(&self.workspaces)
+            .is_some_and(|ws| ws.game_mode)
+    }
+
+    /// Whether the focus follows the mouse, which it doesn't while the focused workspace is in
+    /// game mode.
+    pub fn focus_follows_mouse(&self) -> bool {
+        self.focus_manager.behaviour.is_sloppy() && !self.in_game_mode()
+    }
+
+    /// The windows to send to the display server after an update. The windows of a tag shown
+    /// with a fullscreen window are still laid out on every update, but only sent when their
+    /// geometry, border or visibility changed, so that a game isn't sent a configure event on
+    /// every update. Once the window leaves fullscreen all the windows of the tag are sent again.
+    pub fn windows_to_display(&mut self) -> Vec<&Window> {
+        let windows = &self.windows;
+        let covered_tags: Vec<TagId> = self
+            .workspaces
+            .iter()
+            .filter(|ws| {
+                windows
+                    .iter()
+                    .any(|w| ws.is_displaying(w) && w.is_fullscreen())
+            })
+            .flat_map(|ws| ws.tags.clone())
+            .collect();
+        let covered: Vec<(WindowHandle, Xyhw, i32, bool)> = windows
+            .iter()
+            .filter(|w| w.tags.iter().any(|tag| covered_tags.contains(tag)))
+            .map(|w| (w.handle, w.calculated_xyhw(), w.border(), w.visible()))
+            .collect();
+        let sent = std::mem::replace(&mut self.covered_windows, covered);
+        windows
+            .iter()
+            .filter(|w| {
+                let displayed = (w.handle, w.calculated_xyhw(), w.border(), w.visible());
+                !(sent.contains(&displayed) && self.covered_windows.contains(&displayed))
+            })
+            .collect()
+    }
+
     pub fn move_to_top(&mut self, handle: &WindowHandle) -> Option<()> {
         let index = self.windows.iter().position(|w| &w.handle == handle)?;
         let window = self.windows.remove(index);
@@ -127,6 +316,45 @@ impl State {
     pub(crate) fn load_config(&mut self, config: &impl Config) {
         self.mousekey = config.mousekey();
         self.max_window_width = config.max_window_width();
+        // The tags are matched to the configured ones by label, as tags added or removed at
+        // runtime renumber them. Tags the config doesn't have are left as they are.
+        let labels = config.create_list_of_tag_labels();
+        let numbers: Vec<(TagId, TagId)> = self
+            .tags
+            .normal()
+            .iter()
+            .filter_map(|tag| {
+                let index = labels.iter().position(|label| *label == tag.label)?;
+                Some((tag.id, index + 1))
+            })
+            .collect();
+        for (id, number) in numbers {
+            if let Some(tag) = self.tags.get_mut(id) {
+                tag.load_config(config, number);
+            }
+        }
+        self.snap_distance = config.snap_distance();
+        self.empty_tag_behavior = config.empty_tag_behavior();
+        self.new_window_placement = config.new_window_placement();
+        self.greedy_view = config.greedy_view();
+        self.tag_history_depth = config.tag_history_depth();
+        self.honor_size_increments = config.honor_size_increments();
+        self.tag_slide_duration = config.tag_slide_duration();
+        self.reduce_motion = config.reduce_motion();
+        self.custom_layouts = config.custom_layouts();
+        external::retain(
+            self.custom_layouts
+                .iter()
+                .filter_map(|layout| layout.command.as_deref()),
+        );
+        self.border_width = config.border_width();
+        self.focused_border_width = config.focused_border_width();
+        self.smart_borders = config.smart_borders();
+        self.urgency_limit = config.urgency_limit();
+        self.focus_manager.behaviour = config.focus_behaviour();
+        self.focus_manager.focus_new_windows = config.focus_new_windows();
+        self.focus_manager.sloppy_focus_empty_workspaces = config.sloppy_focus_empty_workspaces();
+        self.focus_manager.warp_cursor = config.warp_cursor();
         for win in &mut self.windows {
             config.load_window(win);
         }
@@ -137,21 +365,84 @@ impl State {
 
     /// Apply saved state to a running manager.
     pub fn restore_state(&mut self, state: &Self) {
-        // Restore tags.
+        self.restore_saved_tags(state);
+        let are_tags_equal = self.tags.all().eq(&state.tags.all());
+        let startup_tags: Vec<TagId> = self.focus_manager.tag(0).into_iter().collect();
+        let adopted = self.restore_saved_windows(state, are_tags_equal, &startup_tags);
+        self.restore_saved_workspaces(state, are_tags_equal);
+
+        // Restore remembered floating geometry.
+        self.floating_geometry.clone_from(&state.floating_geometry);
+        self.docks_hidden = state.docks_hidden;
+
+        // Restore scratchpads.
+        for (scratchpad, id) in &state.active_scratchpads {
+            self.active_scratchpads.insert(scratchpad.clone(), *id);
+        }
+
+        // Restore focus.
+        self.focus_manager
+            .tags_last_window
+            .clone_from(&state.focus_manager.tags_last_window);
+        self.focus_manager
+            .tags_last_window
+            .retain(|&id, _| self.tags.get(id).is_some());
+        let tag_id = match state.focus_manager.tag(0) {
+            // If the tag still exists it should be displayed on a workspace.
+            Some(tag_id) if self.tags.get(tag_id).is_some() => tag_id,
+            // If the tag doesn't exist, tag 1 should be displayed on a workspace.
+            Some(_) => 1,
+            // If we don't have any tag history (We should), focus the tag on workspace 1.
+            None => match self.workspaces.first() {
+                Some(ws) => ws.tags[0],
+                // This should never happen.
+                None => 1,
+            },
+        };
+        // Show windows opened while reloading on the restored tag, where they were opened.
+        for window in self
+            .windows
+            .iter_mut()
+            .filter(|w| adopted.contains(&w.handle))
+        {
+            window.clear_tags();
+            window.tag(&tag_id);
+            let act = DisplayAction::SetWindowTags(window.handle, window.tags.clone());
+            self.actions.push_back(act);
+        }
+        self.focus_tag(&tag_id);
+        // The tags shown may have changed, or have come with their own layouts.
+        self.layout_manager
+            .update_layouts(&mut self.workspaces, self.tags.all_mut());
+    }
+
+    /// Restore the layouts and the settings of the tags which still exist.
+    fn restore_saved_tags(&mut self, state: &Self) {
         for old_tag in state.tags.all() {
             if let Some(tag) = self.tags.get_mut(old_tag.id) {
                 tag.hidden = old_tag.hidden;
-                tag.layout = old_tag.layout;
+                tag.layout = old_tag.layout.clone();
                 tag.layout_rotation = old_tag.layout_rotation;
                 tag.flipped_vertical = old_tag.flipped_vertical;
                 tag.flipped_horizontal = old_tag.flipped_horizontal;
                 tag.main_width_percentage = old_tag.main_width_percentage;
+                tag.stack_split_percentage = old_tag.stack_split_percentage;
+                tag.main_count = old_tag.main_count;
+                tag.last_layout.clone_from(&old_tag.last_layout);
+                tag.window_weights.clone_from(&old_tag.window_weights);
+                tag.bsp.clone_from(&old_tag.bsp);
             }
         }
+    }
 
-        let are_tags_equal = self.tags.all().eq(&state.tags.all());
-
-        // Restore windows.
+    /// Restore the windows still open, in their saved order, on the saved tags which still
+    /// exist. Returns the windows opened while reloading on the tag focused at startup.
+    fn restore_saved_windows(
+        &mut self,
+        state: &Self,
+        are_tags_equal: bool,
+        startup_tags: &[TagId],
+    ) -> Vec<WindowHandle> {
         let mut ordered = vec![];
         let mut had_strut = false;
         state.windows.iter().for_each(|old_window| {
@@ -195,17 +486,29 @@ impl State {
         if had_strut {
             self.update_static();
         }
+        // Whatever is left was created while reloading, on the tag focused at startup.
+        let adopted: Vec<WindowHandle> = self
+            .windows
+            .iter()
+            .filter(|w| !w.is_unmanaged() && !w.is_sticky())
+            .filter(|w| !startup_tags.is_empty() && w.tags == startup_tags)
+            .map(|w| w.handle)
+            .collect();
         self.windows.append(&mut ordered);
+        adopted
+    }
 
+    /// Restore the layouts of the workspaces and the tags they show, if those still exist.
+    fn restore_saved_workspaces(&mut self, state: &Self, are_tags_equal: bool) {
         // This is needed due to mutable/immutable borrows.
         let tags = &self.tags;
-
-        // Restore workspaces.
         for workspace in &mut self.workspaces {
             if let Some(old_workspace) = state.workspaces.iter().find(|w| w.id == workspace.id) {
-                workspace.layout = old_workspace.layout;
+                workspace.layout = old_workspace.layout.clone();
                 workspace.main_width_percentage = old_workspace.main_width_percentage;
+                workspace.stack_split_percentage = old_workspace.stack_split_percentage;
                 workspace.margin_multiplier = old_workspace.margin_multiplier;
+                workspace.zoom = old_workspace.zoom.clone();
                 if are_tags_equal {
                     workspace.tags = old_workspace.tags.clone();
                 } else {
@@ -222,29 +525,43 @@ impl State {
                 }
             }
         }
+    }
+}
 
-        // Restore scratchpads.
-        for (scratchpad, id) in &state.active_scratchpads {
-            self.active_scratchpads.insert(scratchpad.clone(), *id);
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Command, Manager};
+
+    #[test]
+    fn windows_created_while_reloading_should_show_on_the_restored_tag() {
+        let tags = vec!["1".to_string(), "2".to_string()];
+        let mut manager = Manager::new_test(tags.clone());
+        manager.screen_create_handler(Screen::default());
+        manager.command_handler(&Command::GoToTag {
+            tag: 2,
+            swap: false,
+        });
+        manager.window_created_handler(
+            Window::new(WindowHandle::MockHandle(1), None, None),
+            -1,
+            -1,
+        );
+        let saved: State =
+            serde_json::from_str(&serde_json::to_string(&manager.state).unwrap()).unwrap();
+
+        let mut manager = Manager::new_test(tags);
+        manager.screen_create_handler(Screen::default());
+        for i in 1..=2 {
+            manager.window_created_handler(
+                Window::new(WindowHandle::MockHandle(i), None, None),
+                -1,
+                -1,
+            );
         }
+        manager.state.restore_state(&saved);
 
-        // Restore focus.
-        self.focus_manager.tags_last_window = state.focus_manager.tags_last_window.clone();
-        self.focus_manager
-            .tags_last_window
-            .retain(|&id, _| tags.get(id).is_some());
-        let tag_id = match state.focus_manager.tag(0) {
-            // If the tag still exists it should be displayed on a workspace.
-            Some(tag_id) if self.tags.get(tag_id).is_some() => tag_id,
-            // If the tag doesn't exist, tag 1 should be displayed on a workspace.
-            Some(_) => 1,
-            // If we don't have any tag history (We should), focus the tag on workspace 1.
-            None => match self.workspaces.first() {
-                Some(ws) => ws.tags[0],
-                // This should never happen.
-                None => 1,
-            },
-        };
-        self.focus_tag(&tag_id);
+        assert_eq!(manager.state.focus_manager.tag(0), Some(2));
+        assert!(manager.state.windows.iter().all(|w| w.tags == [2]));
     }
 }
@@ -1,6 +1,7 @@
 //! Starts programs in autostart, runs global 'up' script, and boots theme. Provides function to
 //! boot other desktop files also.
 use crate::errors::Result;
+use crate::I3Socket;
 use std::collections::HashMap;
 use std::collections::HashSet;
 use std::fs;
@@ -66,6 +67,7 @@ impl Nanny {
     fn run_script(path: &Path) -> Result<Option<Child>> {
         if path.is_file() {
             Command::new(&path)
+                .envs(session_env())
                 .stdin(Stdio::null())
                 .stdout(Stdio::null())
                 .spawn()
@@ -101,6 +103,32 @@ impl Nanny {
         path.push("up");
         Self::run_script(&path)
     }
+
+    /// Runs the 'down' script in the config directory, if there is one.
+    ///
+    /// # Errors
+    ///
+    /// Will error if unable to open current config directory.
+    /// Could be caused by inadequate permissions.
+    pub fn run_global_down_script() -> Result<Option<Child>> {
+        let mut path = Self::get_config_dir()?;
+        path.push("down");
+        Self::run_script(&path)
+    }
+
+    /// Runs the 'down' script of the current theme, if there is one.
+    ///
+    /// # Errors
+    ///
+    /// Will error if unable to open current theme directory.
+    /// Could be caused by inadequate permissions.
+    pub fn shutdown_current_theme() -> Result<Option<Child>> {
+        let mut path = Self::get_config_dir()?;
+        path.push("themes");
+        path.push("current");
+        path.push("down");
+        Self::run_script(&path)
+    }
 }
 
 #[derive(Debug, thiserror::Error)]
@@ -150,6 +178,7 @@ fn boot_desktop_file(path: &Path) -> std::result::Result<Child, EntryBootError>
 
     Command::new("sh")
         .current_dir(wd)
+        .envs(session_env())
         .arg("-c")
         .arg(entry.exec.unwrap())
         .spawn()
@@ -297,12 +326,36 @@ pub fn register_child_hook(flag: Arc<AtomicBool>) {
         .map_err(|err| log::error!("Cannot register SIGCHLD signal handler: {:?}", err));
 }
 
+/// The variables the programs leftwm starts get on top of its own environment, the socket of
+/// the IPC of i3 for the tools written for it.
+pub(crate) fn session_env() -> Vec<(&'static str, PathBuf)> {
+    match I3Socket::socket_file() {
+        Ok(path) => vec![("I3SOCK", path)],
+        Err(err) => {
+            log::warn!("There is no runtime directory for the i3 socket: {}", err);
+            vec![]
+        }
+    }
+}
+
 /// Sends command to shell for execution
 /// Assumes STDIN/STDOUT unwanted.
 pub fn exec_shell(command: &str, children: &mut Children) -> Option<u32> {
+    exec_shell_with_env(command, &[], children)
+}
+
+/// Sends command to shell for execution, with extra environment variables.
+/// Assumes STDIN/STDOUT unwanted.
+pub fn exec_shell_with_env(
+    command: &str,
+    env: &[(&str, String)],
+    children: &mut Children,
+) -> Option<u32> {
     let child = Command::new("sh")
         .arg("-c")
         .arg(&command)
+        .envs(session_env())
+        .envs(env.iter().map(|(key, value)| (key, value)))
         .stdin(Stdio::null())
         .stdout(Stdio::null())
         .spawn()
@@ -1,7 +1,19 @@
 //! Creates a pipe to listen for external commands.
+//!
+//! Commands are written one per line, as the name of the command followed by its arguments,
+//! separated by spaces: `SendWindowToTag 3`, `SetLayout CenterMain`, `SetMarginMultiplier 1.5`.
+//! A line may also hold a command in JSON, as leftwm serializes them, for any arguments:
+//! `"ToggleGaps"` or `{"SendWindowToTag":{"window":null,"tag":3}}`.
+//!
+//! A client may start with `Version <n>` to make sure the running leftwm speaks its protocol; if
+//! it doesn't, the rest of what it writes is dropped.
+//! `ListCommands` writes a description of every command, as JSON, to `commands.json` beside
+//! the pipe. Queries like `WindowAt` answer in JSON files beside the pipe as well, see
+//! [`answer_file`].
 use crate::layouts::Layout;
-use crate::models::TagId;
+use crate::models::{Direction, Size, TagId};
 use crate::Command;
+use serde::Serialize;
 use std::env;
 use std::path::{Path, PathBuf};
 use std::str::FromStr;
@@ -56,12 +68,7 @@ impl CommandPipe {
     }
 
     pub fn pipe_name() -> PathBuf {
-        let display = env::var("DISPLAY")
-            .ok()
-            .and_then(|d| d.rsplit_once(':').map(|(_, r)| r.to_owned()))
-            .unwrap_or_else(|| "0".to_string());
-
-        PathBuf::from(format!("command-{}.pipe", display))
+        PathBuf::from(format!("command-{}.pipe", display_number()))
     }
 
     pub async fn read_command(&mut self) -> Option<Command> {
@@ -69,11 +76,400 @@ impl CommandPipe {
     }
 }
 
+/// The number of the display of `DISPLAY`, with its screen, which the files leftwm talks through are
+/// named after.
+pub(crate) fn display_number() -> String {
+    env::var("DISPLAY")
+        .ok()
+        .and_then(|d| d.rsplit_once(':').map(|(_, r)| r.to_owned()))
+        .unwrap_or_else(|| "0".to_string())
+}
+
+/// Version of the command pipe protocol. Bumped whenever existing commands change.
+pub const PROTOCOL_VERSION: u32 = 1;
+
+/// File the answer to `WindowAt` is written to.
+pub const WINDOW_AT_FILE: &str = "window_at.json";
+
+/// File the answer to `ListWindows` is written to.
+pub const WINDOWS_FILE: &str = "windows.json";
+
+/// The file beside the pipe a query command answers in, `None` for other commands.
+pub fn answer_file(command: &str) -> Option<&'static str> {
+    match command.split_whitespace().next()? {
+        "WindowAt" => Some(WINDOW_AT_FILE),
+        "ListWindows" => Some(WINDOWS_FILE),
+        _ => None,
+    }
+}
+
+/// Write the answer to a query, as JSON, to a file beside the pipe. The file is replaced at
+/// once, so it is never read half written.
+///
+/// # Errors
+///
+/// Will error if the runtime directory or the file can't be written.
+pub fn write_answer(file_name: &str, answer: &impl Serialize) -> std::io::Result<()> {
+    let path = xdg::BaseDirectories::with_prefix("leftwm")?.place_runtime_file(file_name)?;
+    let partial = path.with_extension("partial");
+    std::fs::write(&partial, serde_json::to_string(answer)?)?;
+    std::fs::rename(partial, path)
+}
+
+/// Description of a command understood by the command pipe.
+#[derive(Serialize, Debug, Clone, Copy, PartialEq)]
+pub struct CommandInfo {
+    pub name: &'static str,
+    /// The arguments taken, empty if there are none.
+    pub args: &'static str,
+    pub description: &'static str,
+}
+
+const fn info(name: &'static str, args: &'static str, description: &'static str) -> CommandInfo {
+    CommandInfo {
+        name,
+        args,
+        description,
+    }
+}
+
+/// Every command understood by the command pipe.
+pub const COMMANDS: &[CommandInfo] = &[
+    info("LoadTheme", "<path/to/theme.toml>", "Load a theme"),
+    info("UnloadTheme", "", "Unload the current theme"),
+    info("SoftReload", "", "Restart leftwm, keeping its state"),
+    info("Exit", "", "Run the exit hooks and quit leftwm"),
+    info(
+        "ToggleFullScreen",
+        "",
+        "Toggle fullscreen for the focused window",
+    ),
+    info(
+        "ToggleSticky",
+        "",
+        "Toggle showing the focused window on every tag",
+    ),
+    info(
+        "ToggleZoom",
+        "",
+        "Show the focused window alone, or restore the layout",
+    ),
+    info("ToggleDock", "", "Hide or show docks and panels"),
+    info(
+        "SwapScreens",
+        "",
+        "Swap the tags of the focused and the last focused workspace, keeping the focus on the focused one",
+    ),
+    info(
+        "MoveWindowToLastWorkspace",
+        "",
+        "Move the focused window to the last workspace",
+    ),
+    info(
+        "MoveWindowToNextWorkspace",
+        "",
+        "Move the focused window to the next workspace",
+    ),
+    info(
+        "MoveWindowToPreviousWorkspace",
+        "",
+        "Move the focused window to the previous workspace",
+    ),
+    info("FloatingToTile", "", "Tile the focused floating window"),
+    info("TileToFloating", "", "Float the focused tiled window"),
+    info(
+        "ToggleFloating",
+        "",
+        "Toggle floating for the focused window",
+    ),
+    info("MoveWindowUp", "", "Move the focused window up the stack"),
+    info(
+        "MoveWindowDown",
+        "",
+        "Move the focused window down the stack",
+    ),
+    info(
+        "MoveWindowTop",
+        "[true|false]",
+        "Move the focused window to the top of the stack, swapping if already there",
+    ),
+    info(
+        "MoveWindowInDirection",
+        "<Left|Right|Up|Down>",
+        "Swap the focused window with its neighbour in a direction",
+    ),
+    info(
+        "SwapWindowWithMaster",
+        "",
+        "Swap the focused window with the master window",
+    ),
+    info(
+        "RotateStackForward",
+        "",
+        "Move every tiled window one slot further through the layout",
+    ),
+    info(
+        "RotateStackBackward",
+        "",
+        "Move every tiled window one slot back through the layout",
+    ),
+    info(
+        "FocusWindowUp",
+        "",
+        "Focus the previous window in the stack",
+    ),
+    info("FocusWindowDown", "", "Focus the next window in the stack"),
+    info(
+        "FocusWindowTop",
+        "[true|false]",
+        "Focus the top of the stack, swapping back if already there",
+    ),
+    info(
+        "FocusWindowInDirection",
+        "<Left|Right|Up|Down>",
+        "Focus the nearest window in a direction",
+    ),
+    info(
+        "FocusWindow",
+        "<WindowClass> or <visible-window-index>",
+        "Focus a window by class or index",
+    ),
+    info(
+        "FocusUrgent",
+        "",
+        "Show the tag of the window which most recently asked for attention, and focus it",
+    ),
+    info(
+        "FocusNextTag",
+        "[NoWrap] [SkipEmpty]",
+        "Show the next tag, not going from the last to the first with NoWrap, and skipping the tags without windows with SkipEmpty",
+    ),
+    info(
+        "FocusPreviousTag",
+        "[NoWrap] [SkipEmpty]",
+        "Show the previous tag, not going from the first to the last with NoWrap, and skipping the tags without windows with SkipEmpty",
+    ),
+    info(
+        "GoToTag",
+        "<tag-label-or-index>",
+        "Show a tag, given by its label or number",
+    ),
+    info(
+        "FocusLastTag",
+        "",
+        "Show the tag the focused workspace showed before",
+    ),
+    info(
+        "TagHistoryBack",
+        "",
+        "Go back through the tags the focused workspace showed",
+    ),
+    info(
+        "TagHistoryForward",
+        "",
+        "Return to the tags TagHistoryBack left",
+    ),
+    info(
+        "ToggleOverview",
+        "",
+        "Lay out the windows of all the tags on the focused workspace, or go back from there",
+    ),
+    info(
+        "PickOverviewWindow",
+        "",
+        "Leave the overview for the focused window, showing its tag",
+    ),
+    info("FocusWorkspaceNext", "", "Focus the next workspace"),
+    info("FocusWorkspacePrevious", "", "Focus the previous workspace"),
+    info("NextLayout", "", "Switch to the next layout"),
+    info("PreviousLayout", "", "Switch to the previous layout"),
+    info(
+        "ToggleLastLayout",
+        "",
+        "Switch back to the layout used before the current one",
+    ),
+    info("SetLayout", "<LayoutName>", "Switch to a layout"),
+    info("RotateTag", "", "Rotate the layout of the focused tag"),
+    info("IncreaseMainWidth", "<percentage>", "Widen the main area"),
+    info("DecreaseMainWidth", "<percentage>", "Narrow the main area"),
+    info(
+        "IncreaseStackSplit",
+        "<percentage>",
+        "Move the stack split further",
+    ),
+    info(
+        "DecreaseStackSplit",
+        "<percentage>",
+        "Move the stack split back",
+    ),
+    info("IncreaseMainCount", "", "Add a window to the main area"),
+    info(
+        "DecreaseMainCount",
+        "",
+        "Take a window out of the main area",
+    ),
+    info(
+        "IncreaseWindowWeight",
+        "<weight>",
+        "Grow the focused window within its column",
+    ),
+    info(
+        "DecreaseWindowWeight",
+        "<weight>",
+        "Shrink the focused window within its column",
+    ),
+    info(
+        "RotateSubtree",
+        "",
+        "Turn the splits around the focused window in the Bsp layout",
+    ),
+    info(
+        "FlipSubtree",
+        "",
+        "Swap the halves around the focused window in the Bsp layout",
+    ),
+    info(
+        "SetMarginMultiplier",
+        "<multiplier>",
+        "Scale the margins of the focused workspace",
+    ),
+    info(
+        "IncreaseGaps",
+        "<pixels>",
+        "Widen the gaps of the focused workspace",
+    ),
+    info(
+        "DecreaseGaps",
+        "<pixels>",
+        "Narrow the gaps of the focused workspace",
+    ),
+    info(
+        "ToggleGaps",
+        "",
+        "Take the gaps of the focused workspace away, or give them back",
+    ),
+    info(
+        "ToggleGameMode",
+        "",
+        "Put the focused workspace in game mode, or take it out",
+    ),
+    info(
+        "SetMaxWindowWidth",
+        "[<pixels>|<ratio>]",
+        "Limit the window width on the focused tag, or stop limiting it",
+    ),
+    info(
+        "ToggleScratchPad",
+        "<ScratchpadName>",
+        "Show or hide a scratchpad",
+    ),
+    info(
+        "SendWorkspaceToTag",
+        "<workspace-index> <tag-index>",
+        "Show a tag on a workspace",
+    ),
+    info(
+        "MoveTagToWorkspace",
+        "<workspace-index>",
+        "Move the focused tag with its windows to a workspace",
+    ),
+    info(
+        "SendWindowToTag",
+        "<tag-index>",
+        "Move the focused window to a tag",
+    ),
+    info(
+        "ToggleWindowTag",
+        "<tag-label-or-index>",
+        "Add the focused window to a tag, given by its label or number, or take it off the tag",
+    ),
+    info(
+        "ToggleTagView",
+        "<tag-label-or-index>",
+        "Show a tag, given by its label or number, on the focused workspace beside the tags it shows, or stop showing it",
+    ),
+    info("CloseWindow", "", "Close the focused window"),
+    info(
+        "CloseAllOtherWindows",
+        "",
+        "Close all other windows on the focused workspace",
+    ),
+    info(
+        "CloseAllOnTag",
+        "",
+        "Close every window on the focused tag, once repeated to confirm",
+    ),
+    info(
+        "CountPrefix",
+        "<digit>",
+        "Add a digit to the number of times to run the next command",
+    ),
+    info(
+        "WindowAt",
+        "<x> <y>",
+        "Write the managed window at a point to window_at.json beside the pipe",
+    ),
+    info(
+        "ListWindows",
+        "",
+        "Write the managed windows, with their X window ids, and the outputs to windows.json beside the pipe",
+    ),
+    info(
+        "LoadProfile",
+        "<ProfileName>",
+        "Switch to the workspaces of a profile",
+    ),
+    info("AddTag", "<Label>", "Add a tag with this label after the others"),
+    info(
+        "RemoveTag",
+        "",
+        "Remove the focused tag, moving its windows to the tag focused before it",
+    ),
+    info(
+        "RenameTag",
+        "[<Tag>] <Label>",
+        "Label a tag, given by its number or label, or the focused tag",
+    ),
+    info(
+        "ListCommands",
+        "",
+        "Describe every command in commands.json beside the pipe",
+    ),
+    info(
+        "Version",
+        "<protocol-version>",
+        "Make sure leftwm speaks this protocol version",
+    ),
+];
+
+#[derive(Serialize)]
+struct Discovery {
+    version: u32,
+    commands: &'static [CommandInfo],
+}
+
 async fn read_from_pipe(pipe_file: &Path, tx: &mpsc::UnboundedSender<Command>) -> Option<()> {
     let file = fs::File::open(pipe_file).await.ok()?;
     let mut lines = BufReader::new(file).lines();
 
     while let Some(line) = lines.next_line().await.ok()? {
+        if let Some(version) = line.strip_prefix("Version ") {
+            if !supports_version(version) {
+                log::error!(
+                    "Command pipe protocol version {} requested, but only {} is supported",
+                    version,
+                    PROTOCOL_VERSION
+                );
+                return None;
+            }
+            continue;
+        }
+        if line == "ListCommands" {
+            if let Err(err) = write_command_list(pipe_file).await {
+                log::error!("Failed to write the list of commands: {}", err);
+            }
+            continue;
+        }
         let cmd = match parse_command(&line) {
             Ok(cmd) => cmd,
             Err(err) => {
@@ -87,13 +483,32 @@ async fn read_from_pipe(pipe_file: &Path, tx: &mpsc::UnboundedSender<Command>) -
     Some(())
 }
 
-fn parse_command(s: &str) -> Result<Command, Box<dyn std::error::Error>> {
+fn supports_version(raw: &str) -> bool {
+    raw.trim().parse() == Ok(PROTOCOL_VERSION)
+}
+
+async fn write_command_list(pipe_file: &Path) -> std::io::Result<()> {
+    let discovery = Discovery {
+        version: PROTOCOL_VERSION,
+        commands: COMMANDS,
+    };
+    let json = serde_json::to_string(&discovery)?;
+    fs::write(pipe_file.with_file_name("commands.json"), json).await
+}
+
+pub(crate) fn parse_command(s: &str) -> Result<Command, Box<dyn std::error::Error>> {
+    if s.starts_with('{') || s.starts_with('"') {
+        return parse_json_command(s);
+    }
     let (head, rest) = s.split_once(' ').unwrap_or((s, ""));
     match head {
         "SoftReload" => Ok(Command::SoftReload),
+        "Exit" => Ok(Command::Exit),
         "ToggleFullScreen" => Ok(Command::ToggleFullScreen),
         "ToggleSticky" => Ok(Command::ToggleSticky),
-        "SwapScreens" => Ok(Command::SwapScreens),
+        "ToggleZoom" => Ok(Command::ToggleZoom),
+        "ToggleDock" => Ok(Command::ToggleDock),
+        "SwapScreens" | "SwapTags" => Ok(Command::SwapScreens),
         "MoveWindowToLastWorkspace" => Ok(Command::MoveWindowToLastWorkspace),
         "MoveWindowToNextWorkspace" => Ok(Command::MoveWindowToNextWorkspace),
         "MoveWindowToPreviousWorkspace" => Ok(Command::MoveWindowToPreviousWorkspace),
@@ -106,24 +521,76 @@ fn parse_command(s: &str) -> Result<Command, Box<dyn std::error::Error>> {
         "FocusWindowUp" => Ok(Command::FocusWindowUp),
         "FocusWindowDown" => Ok(Command::FocusWindowDown),
         "FocusWindowTop" => build_focus_window_top(rest),
-        "FocusNextTag" => Ok(Command::FocusNextTag),
-        "FocusPreviousTag" => Ok(Command::FocusPreviousTag),
+        "FocusWindow" => build_focus_window(rest),
+        "FocusUrgent" => Ok(Command::FocusUrgent),
+        "FocusNextTag" => Command::parse_tag_cycle(rest)
+            .map(|(wrap, skip_empty)| Command::FocusNextTag { wrap, skip_empty })
+            .map_err(Into::into),
+        "FocusPreviousTag" => Command::parse_tag_cycle(rest)
+            .map(|(wrap, skip_empty)| Command::FocusPreviousTag { wrap, skip_empty })
+            .map_err(Into::into),
+        "GoToTag" => build_go_to_tag(rest),
+        "FocusLastTag" => Ok(Command::FocusLastTag),
+        "TagHistoryBack" => Ok(Command::TagHistoryBack),
+        "TagHistoryForward" => Ok(Command::TagHistoryForward),
+        "ToggleOverview" => Ok(Command::ToggleOverview),
+        "PickOverviewWindow" => Ok(Command::PickOverviewWindow(None)),
         "FocusWorkspaceNext" => Ok(Command::FocusWorkspaceNext),
         "FocusWorkspacePrevious" => Ok(Command::FocusWorkspacePrevious),
         "NextLayout" => Ok(Command::NextLayout),
         "PreviousLayout" => Ok(Command::PreviousLayout),
+        "ToggleLastLayout" => Ok(Command::ToggleLastLayout),
         "RotateTag" => Ok(Command::RotateTag),
         "CloseWindow" => Ok(Command::CloseWindow),
         "ToggleScratchPad" => build_toggle_scratchpad(rest),
         "SendWorkspaceToTag" => build_send_workspace_to_tag(rest),
+        "MoveTagToWorkspace" => build_move_tag_to_workspace(rest),
         "SendWindowToTag" => build_send_window_to_tag(rest),
+        "ToggleWindowTag" => build_toggle_window_tag(rest),
+        "ToggleTagView" => build_toggle_tag_view(rest),
         "SetLayout" => build_set_layout(rest),
+        "FocusWindowInDirection" => build_direction(rest).map(Command::FocusWindowInDirection),
+        "MoveWindowInDirection" => build_direction(rest).map(Command::MoveWindowInDirection),
+        "SwapWindowWithMaster" => Ok(Command::SwapWindowWithMaster),
+        "RotateStackForward" => Ok(Command::RotateStackForward),
+        "RotateStackBackward" => Ok(Command::RotateStackBackward),
         "SetMarginMultiplier" => build_set_margin_multiplier(rest),
+        "SetMaxWindowWidth" => build_set_max_window_width(rest),
+        "IncreaseGaps" => build_gap_step(rest).map(Command::IncreaseGaps),
+        "DecreaseGaps" => build_gap_step(rest).map(Command::DecreaseGaps),
+        "ToggleGaps" => Ok(Command::ToggleGaps),
+        "ToggleGameMode" => Ok(Command::ToggleGameMode),
+        "IncreaseMainWidth" => build_ratio_delta(rest).map(Command::IncreaseMainWidth),
+        "DecreaseMainWidth" => build_ratio_delta(rest).map(Command::DecreaseMainWidth),
+        "IncreaseStackSplit" => build_ratio_delta(rest).map(Command::IncreaseStackSplit),
+        "DecreaseStackSplit" => build_ratio_delta(rest).map(Command::DecreaseStackSplit),
+        "IncreaseMainCount" => Ok(Command::IncreaseMainCount),
+        "DecreaseMainCount" => Ok(Command::DecreaseMainCount),
+        "IncreaseWindowWeight" => build_weight_delta(rest).map(Command::IncreaseWindowWeight),
+        "DecreaseWindowWeight" => build_weight_delta(rest).map(Command::DecreaseWindowWeight),
+        "RotateSubtree" => Ok(Command::RotateSubtree),
+        "FlipSubtree" => Ok(Command::FlipSubtree),
         "CloseAllOtherWindows" => Ok(Command::CloseAllOtherWindows),
+        "CloseAllOnTag" => Ok(Command::CloseAllOnTag),
+        "CountPrefix" => build_count_prefix(rest),
+        "WindowAt" => build_window_at(rest),
+        "ListWindows" => Ok(Command::ListWindows),
+        "LoadProfile" => build_load_profile(rest),
+        "AddTag" => build_add_tag(rest),
+        "RemoveTag" => Ok(Command::RemoveTag),
+        "RenameTag" => build_rename_tag(rest),
         _ => Ok(Command::Other(s.into())),
     }
 }
 
+/// A command in JSON, which can't run programs as keybinds do.
+fn parse_json_command(raw: &str) -> Result<Command, Box<dyn std::error::Error>> {
+    match serde_json::from_str(raw)? {
+        Command::Execute(_) => Err("Execute isn't taken from the command pipe".into()),
+        command => Ok(command),
+    }
+}
+
 fn build_toggle_scratchpad(raw: &str) -> Result<Command, Box<dyn std::error::Error>> {
     let name = if raw.is_empty() {
         return Err("missing argument scratchpad's name".into());
@@ -133,6 +600,20 @@ fn build_toggle_scratchpad(raw: &str) -> Result<Command, Box<dyn std::error::Err
     Ok(Command::ToggleScratchPad(name.to_string()))
 }
 
+fn build_load_profile(raw: &str) -> Result<Command, Box<dyn std::error::Error>> {
+    if raw.is_empty() {
+        return Err("missing argument profile's name".into());
+    }
+    Ok(Command::LoadProfile(raw.to_string()))
+}
+
+fn build_add_tag(raw: &str) -> Result<Command, Box<dyn std::error::Error>> {
+    if raw.is_empty() {
+        return Err("missing argument tag's label".into());
+    }
+    Ok(Command::AddTag(raw.to_string()))
+}
+
 fn build_send_window_to_tag(raw: &str) -> Result<Command, Box<dyn std::error::Error>> {
     let tag_id = if raw.is_empty() {
         return Err("missing argument tag_id".into());
@@ -145,6 +626,40 @@ fn build_send_window_to_tag(raw: &str) -> Result<Command, Box<dyn std::error::Er
     })
 }
 
+fn build_toggle_window_tag(raw: &str) -> Result<Command, Box<dyn std::error::Error>> {
+    if raw.is_empty() {
+        return Err("missing argument tag's label or index".into());
+    }
+    Ok(match TagId::from_str(raw) {
+        Ok(tag) => Command::ToggleWindowTag(tag),
+        Err(_) => Command::ToggleWindowLabelledTag(raw.to_string()),
+    })
+}
+
+fn build_toggle_tag_view(raw: &str) -> Result<Command, Box<dyn std::error::Error>> {
+    if raw.is_empty() {
+        return Err("missing argument tag's label or index".into());
+    }
+    Ok(match TagId::from_str(raw) {
+        Ok(tag) => Command::ToggleTagView(tag),
+        Err(_) => Command::ToggleLabelledTagView(raw.to_string()),
+    })
+}
+
+fn build_rename_tag(raw: &str) -> Result<Command, Box<dyn std::error::Error>> {
+    if raw.is_empty() {
+        return Err("missing argument tag's label".into());
+    }
+    let (tag, label) = match raw.split_once(' ') {
+        Some((tag, label)) => (Some(tag.to_string()), label.trim_start()),
+        None => (None, raw),
+    };
+    Ok(Command::RenameTag {
+        tag,
+        label: label.to_string(),
+    })
+}
+
 fn build_send_workspace_to_tag(raw: &str) -> Result<Command, Box<dyn std::error::Error>> {
     if raw.is_empty() {
         return Err("missing argument workspace index".into());
@@ -158,6 +673,37 @@ fn build_send_workspace_to_tag(raw: &str) -> Result<Command, Box<dyn std::error:
     Ok(Command::SendWorkspaceToTag(ws_index, tag_index))
 }
 
+fn build_move_tag_to_workspace(raw: &str) -> Result<Command, Box<dyn std::error::Error>> {
+    if raw.is_empty() {
+        return Err("missing argument workspace index".into());
+    }
+    Ok(Command::MoveTagToWorkspace(raw.parse()?))
+}
+
+fn build_go_to_tag(raw: &str) -> Result<Command, Box<dyn std::error::Error>> {
+    if raw.is_empty() {
+        return Err("missing argument tag's label or index".into());
+    }
+    Ok(match TagId::from_str(raw) {
+        Ok(tag) => Command::GoToTag { tag, swap: false },
+        Err(_) => Command::GoToLabelledTag(raw.to_string()),
+    })
+}
+
+fn build_window_at(raw: &str) -> Result<Command, Box<dyn std::error::Error>> {
+    let mut parts = raw.split_whitespace();
+    let x: i32 = parts.next().ok_or("missing argument x")?.parse()?;
+    let y: i32 = parts.next().ok_or("missing argument y")?.parse()?;
+    Ok(Command::WindowAt(x, y))
+}
+
+fn build_focus_window(raw: &str) -> Result<Command, Box<dyn std::error::Error>> {
+    if raw.is_empty() {
+        return Err("missing window class or index".into());
+    }
+    Ok(Command::FocusWindow(raw.to_string()))
+}
+
 fn build_set_layout(raw: &str) -> Result<Command, Box<dyn std::error::Error>> {
     let layout_name = if raw.is_empty() {
         return Err("missing layout name".into());
@@ -167,6 +713,13 @@ fn build_set_layout(raw: &str) -> Result<Command, Box<dyn std::error::Error>> {
     Ok(Command::SetLayout(Layout::from_str(layout_name)?))
 }
 
+fn build_direction(raw: &str) -> Result<Direction, Box<dyn std::error::Error>> {
+    if raw.is_empty() {
+        return Err("missing direction".into());
+    }
+    Ok(Direction::from_str(raw)?)
+}
+
 fn build_set_margin_multiplier(raw: &str) -> Result<Command, Box<dyn std::error::Error>> {
     let margin_multiplier = if raw.is_empty() {
         return Err("missing argument multiplier".into());
@@ -176,6 +729,45 @@ fn build_set_margin_multiplier(raw: &str) -> Result<Command, Box<dyn std::error:
     Ok(Command::SetMarginMultiplier(margin_multiplier))
 }
 
+fn build_set_max_window_width(raw: &str) -> Result<Command, Box<dyn std::error::Error>> {
+    if raw.is_empty() {
+        return Ok(Command::SetMaxWindowWidth(None));
+    }
+    Ok(Command::SetMaxWindowWidth(Some(Size::from_str(raw)?)))
+}
+
+fn build_ratio_delta(raw: &str) -> Result<f32, Box<dyn std::error::Error>> {
+    if raw.is_empty() {
+        return Err("missing argument percentage".into());
+    }
+    Ok(f32::from_str(raw)?)
+}
+
+fn build_gap_step(raw: &str) -> Result<u32, Box<dyn std::error::Error>> {
+    if raw.is_empty() {
+        return Err("missing argument pixels".into());
+    }
+    Ok(u32::from_str(raw)?)
+}
+
+fn build_count_prefix(raw: &str) -> Result<Command, Box<dyn std::error::Error>> {
+    match usize::from_str(raw)? {
+        digit @ 0..=9 => Ok(Command::CountPrefix(digit)),
+        _ => Err("count prefix must be a single digit".into()),
+    }
+}
+
+fn build_weight_delta(raw: &str) -> Result<f32, Box<dyn std::error::Error>> {
+    if raw.is_empty() {
+        return Err("missing argument weight".into());
+    }
+    let delta = f32::from_str(raw)?;
+    if !delta.is_finite() {
+        return Err(format!("weight {raw} is not a finite number").into());
+    }
+    Ok(delta)
+}
+
 fn build_focus_window_top(raw: &str) -> Result<Command, Box<dyn std::error::Error>> {
     let swap = if raw.is_empty() {
         false
@@ -197,6 +789,7 @@ fn build_move_window_top(raw: &str) -> Result<Command, Box<dyn std::error::Error
 #[cfg(test)]
 mod test {
     use super::*;
+    use crate::models::WindowHandle;
     use crate::utils::helpers::test::temp_path;
     use tokio::io::AsyncWriteExt;
     use tokio::time;
@@ -245,6 +838,38 @@ mod test {
         }
     }
 
+    #[test]
+    fn only_the_current_protocol_version_is_supported() {
+        assert!(supports_version(" 1"));
+        assert!(!supports_version("2"));
+        assert!(!supports_version("one"));
+    }
+
+    #[test]
+    fn listed_commands_are_understood() {
+        // These are handled by the config, or by the pipe itself.
+        let passed_on = ["LoadTheme", "UnloadTheme", "ListCommands", "Version"];
+        for command in COMMANDS.iter().filter(|c| !passed_on.contains(&c.name)) {
+            let example = match command.name {
+                "SetLayout" => "Monocle",
+                "ToggleScratchPad" => "Terminal",
+                "LoadProfile" => "docked",
+                "AddTag" => "mail",
+                "RenameTag" => "1 mail",
+                "SendWorkspaceToTag" | "WindowAt" => "0 1",
+                "MoveTagToWorkspace" => "0",
+                "FocusWindowInDirection" | "MoveWindowInDirection" => "Left",
+                _ => "1",
+            };
+            let parsed = parse_command(command.name)
+                .or_else(|_| parse_command(&format!("{} {}", command.name, example)));
+            match parsed {
+                Ok(Command::Other(_)) | Err(_) => panic!("{} is not understood", command.name),
+                Ok(_) => {}
+            }
+        }
+    }
+
     #[tokio::test]
     async fn pipe_cleanup() {
         let pipe_file = temp_path().await.unwrap();
@@ -286,6 +911,116 @@ mod test {
         assert!(build_send_workspace_to_tag("").is_err());
     }
 
+    #[test]
+    fn build_rename_tag_with_and_without_a_tag() {
+        assert_eq!(
+            build_rename_tag("web").unwrap(),
+            Command::RenameTag {
+                tag: None,
+                label: "web".to_string()
+            }
+        );
+        assert_eq!(
+            build_rename_tag("2 my project").unwrap(),
+            Command::RenameTag {
+                tag: Some("2".to_string()),
+                label: "my project".to_string()
+            }
+        );
+        assert!(build_rename_tag("").is_err());
+    }
+
+    #[test]
+    fn commands_in_json_should_carry_their_arguments() {
+        assert_eq!(
+            parse_command("\"ToggleGaps\"").unwrap(),
+            Command::ToggleGaps
+        );
+        assert_eq!(
+            parse_command(r#"{"SendWindowToTag":{"window":{"XlibHandle":42},"tag":3}}"#).unwrap(),
+            Command::SendWindowToTag {
+                window: Some(WindowHandle::XlibHandle(42)),
+                tag: 3
+            }
+        );
+        assert_eq!(
+            parse_command(r#"{"SetMarginMultiplier":1.5}"#).unwrap(),
+            Command::SetMarginMultiplier(1.5)
+        );
+        assert!(parse_command(r#"{"Execute":"xterm"}"#).is_err());
+        assert!(parse_command(r#"{"SendWindowToTag":3}"#).is_err());
+    }
+
+    #[test]
+    fn build_focus_next_and_previous_tag_with_flags() {
+        assert_eq!(
+            parse_command("FocusNextTag").unwrap(),
+            Command::FocusNextTag {
+                wrap: true,
+                skip_empty: false
+            }
+        );
+        assert_eq!(
+            parse_command("FocusPreviousTag SkipEmpty NoWrap").unwrap(),
+            Command::FocusPreviousTag {
+                wrap: false,
+                skip_empty: true
+            }
+        );
+        assert!(parse_command("FocusNextTag Sometimes").is_err());
+    }
+
+    #[test]
+    fn build_go_to_tag_by_index_or_label() {
+        assert_eq!(
+            build_go_to_tag("2").unwrap(),
+            Command::GoToTag {
+                tag: 2,
+                swap: false
+            }
+        );
+        assert_eq!(
+            build_go_to_tag("web").unwrap(),
+            Command::GoToLabelledTag("web".to_string())
+        );
+    }
+
+    #[test]
+    fn build_toggle_tag_commands_by_index_or_label() {
+        assert_eq!(
+            build_toggle_window_tag("2").unwrap(),
+            Command::ToggleWindowTag(2)
+        );
+        assert_eq!(
+            build_toggle_window_tag("web").unwrap(),
+            Command::ToggleWindowLabelledTag("web".to_string())
+        );
+        assert_eq!(
+            build_toggle_tag_view("2").unwrap(),
+            Command::ToggleTagView(2)
+        );
+        assert_eq!(
+            build_toggle_tag_view("web").unwrap(),
+            Command::ToggleLabelledTagView("web".to_string())
+        );
+        assert!(build_toggle_tag_view("").is_err());
+    }
+
+    #[test]
+    fn build_window_at_with_and_without_parameters() {
+        assert_eq!(build_window_at("10 20").unwrap(), Command::WindowAt(10, 20));
+        assert!(build_window_at("10").is_err());
+        assert!(build_window_at("").is_err());
+    }
+
+    #[test]
+    fn build_weight_delta_should_only_take_finite_numbers() {
+        assert!((build_weight_delta("0.5").unwrap() - 0.5).abs() < f32::EPSILON);
+        for raw in ["", "inf", "-inf", "NaN"] {
+            assert!(build_weight_delta(raw).is_err(), "{raw} was taken");
+        }
+    }
+
     #[test]
     fn build_set_layout_without_parameter() {
         assert!(build_set_layout("").is_err());
@@ -0,0 +1,520 @@
+//! A Unix socket taking requests in JSON and answering them, one line each way.
+//!
+//! Where the command pipe only takes commands, the socket answers every request it reads with a
+//! [`Response`], in the order they were sent, and any number of clients may be connected at once.
+//! A request is a command, in JSON as leftwm serializes them or as the command pipe takes it, or
+//! a query:
+//!
+//! ```text
+//! {"Command":{"SendWindowToTag":{"window":null,"tag":3}}}
+//! {"Command":"ToggleGaps"}
+//! SendWindowToTag 3
+//! {"WindowAt":{"x":100,"y":200}}
+//! "ListWindows"
+//! "GetState"
+//! ```
+//!
+//! answered with `"Done"`, `{"WindowAt":{..}}`, `{"Windows":{..}}`, `{"State":{..}}` or
+//! `{"Error":"<reason>"}`, which commands are answered with too when they can't be carried out,
+//! say for want of a focused window.
+//!
+//! A client sending `"Subscribe"` is then sent an [`Event`] a line as things change, such as
+//! `{"WindowFocused":{"XlibHandle":4194313}}`, and nothing else.
+use crate::errors::Result;
+use crate::layouts::Layout;
+use crate::models::dto::{FullState, ScreenInfo, WindowAt, WindowList};
+use crate::models::{TagId, WindowHandle};
+use crate::utils::command_pipe;
+use crate::Command;
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex, PoisonError};
+use tokio::fs;
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use tokio::net::unix::OwnedWriteHalf;
+use tokio::net::{UnixListener, UnixStream};
+use tokio::sync::mpsc::error::TrySendError;
+use tokio::sync::{mpsc, oneshot};
+use tokio::task::JoinHandle;
+
+/// What a client asks of the command socket.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub enum Request {
+    /// Run a command, as a keybind would.
+    Command(Command),
+    /// The managed window at a point of the screens.
+    WindowAt { x: i32, y: i32 },
+    /// The managed windows and the outputs.
+    ListWindows,
+    /// The screens, workspaces, tags and windows, and which of them are focused.
+    GetState,
+    /// Be sent the events from now on.
+    Subscribe,
+}
+
+/// The answer to a [`Request`].
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub enum Response {
+    /// The command was run.
+    Done,
+    WindowAt(WindowAt),
+    Windows(WindowList),
+    State(FullState),
+    /// The request couldn't be read or carried out.
+    Error(String),
+}
+
+/// A change sent to the clients which subscribed.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub enum Event {
+    /// The screens were added, removed or resized, and are now these.
+    MonitorsChanged(Vec<ScreenInfo>),
+    WindowOpened(WindowHandle),
+    WindowClosed(WindowHandle),
+    /// A workspace shows other tags.
+    TagChanged {
+        workspace: usize,
+        tags: Vec<TagId>,
+    },
+    LayoutChanged {
+        workspace: usize,
+        layout: Layout,
+    },
+    WorkspaceFocused(usize),
+    /// Another window, or none, has the focus.
+    WindowFocused(Option<WindowHandle>),
+}
+
+impl Event {
+    /// The events which took the state from `previous` to `current`.
+    #[must_use]
+    pub fn between(previous: &FullState, current: &FullState) -> Vec<Self> {
+        let mut events = vec![];
+        if previous.screens != current.screens {
+            events.push(Self::MonitorsChanged(current.screens.clone()));
+        }
+        let handles = |state: &FullState| -> Vec<WindowHandle> {
+            state.windows.iter().map(|w| w.info.handle).collect()
+        };
+        let (before, after) = (handles(previous), handles(current));
+        events.extend(
+            before
+                .iter()
+                .filter(|h| !after.contains(h))
+                .map(|&h| Self::WindowClosed(h)),
+        );
+        events.extend(
+            after
+                .iter()
+                .filter(|h| !before.contains(h))
+                .map(|&h| Self::WindowOpened(h)),
+        );
+        for ws in &current.workspaces {
+            let old = previous.workspaces.get(ws.index);
+            if old.map(|old| &old.tags) != Some(&ws.tags) {
+                events.push(Self::TagChanged {
+                    workspace: ws.index,
+                    tags: ws.tags.clone(),
+                });
+            }
+            if old.map(|old| &old.layout) != Some(&ws.layout) {
+                events.push(Self::LayoutChanged {
+                    workspace: ws.index,
+                    layout: ws.layout.clone(),
+                });
+            }
+        }
+        if let Some(index) = current.focused_workspace {
+            if previous.focused_workspace != Some(index) {
+                events.push(Self::WorkspaceFocused(index));
+            }
+        }
+        if previous.focused_window != current.focused_window {
+            events.push(Self::WindowFocused(current.focused_window));
+        }
+        events
+    }
+}
+
+/// How many events a subscriber may leave unread before it is dropped, so a client which stops
+/// reading can't make leftwm hold on to every event from then on.
+const SUBSCRIBER_BACKLOG: usize = 256;
+
+type Subscribers = Arc<Mutex<Vec<mpsc::Sender<Event>>>>;
+
+/// A request waiting for the manager to answer it.
+#[derive(Debug)]
+pub struct PendingRequest {
+    pub request: Request,
+    reply: oneshot::Sender<Response>,
+}
+
+impl PendingRequest {
+    /// A request, and where its answer comes.
+    #[must_use]
+    pub fn new(request: Request) -> (Self, oneshot::Receiver<Response>) {
+        let (reply, answer) = oneshot::channel();
+        (Self { request, reply }, answer)
+    }
+
+    /// Send the answer back to the client, which may have gone away already.
+    pub fn reply(self, response: Response) {
+        self.reply.send(response).ok();
+    }
+}
+
+/// Reaches the manager as the clients of the command socket do, for the other ways of talking
+/// to leftwm built on it.
+#[derive(Debug, Clone)]
+pub struct Client {
+    requests: mpsc::UnboundedSender<PendingRequest>,
+    subscribers: Subscribers,
+}
+
+impl Client {
+    /// Have the manager answer the request.
+    pub async fn ask(&self, request: Request) -> Response {
+        let gone = || Response::Error("leftwm went away before answering".to_string());
+        let (pending, answer) = PendingRequest::new(request);
+        if self.requests.send(pending).is_err() {
+            return gone();
+        }
+        answer.await.unwrap_or_else(|_| gone())
+    }
+
+    /// Be sent the events from now on, until the receiver is dropped or falls too far behind.
+    #[must_use]
+    pub fn subscribe(&self) -> mpsc::Receiver<Event> {
+        let (tx, rx) = mpsc::channel(SUBSCRIBER_BACKLOG);
+        self.lock_subscribers().push(tx);
+        rx
+    }
+
+    fn lock_subscribers(&self) -> std::sync::MutexGuard<'_, Vec<mpsc::Sender<Event>>> {
+        self.subscribers
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+/// Listens on the command socket, handing the requests of every client over in turn.
+#[derive(Debug)]
+pub struct CommandSocket {
+    socket_file: PathBuf,
+    client: Client,
+    rx: mpsc::UnboundedReceiver<PendingRequest>,
+    listener: Option<JoinHandle<()>>,
+}
+
+impl Drop for CommandSocket {
+    fn drop(&mut self) {
+        assert!(
+            std::thread::panicking() || self.listener.is_none(),
+            "CommandSocket has to be shutdown explicitly before drop"
+        );
+    }
+}
+
+impl CommandSocket {
+    /// Bind to the socket and accept clients.
+    ///
+    /// # Errors
+    ///
+    /// Will error if the socket can't be bound, likely a filesystem issue such as inadequate
+    /// permissions.
+    pub async fn listen(socket_file: PathBuf) -> Result<Self> {
+        let listener = if let Ok(listener) = UnixListener::bind(&socket_file) {
+            listener
+        } else {
+            fs::remove_file(&socket_file).await?;
+            UnixListener::bind(&socket_file)?
+        };
+        let (requests, rx) = mpsc::unbounded_channel();
+        let client = Client {
+            requests,
+            subscribers: Subscribers::default(),
+        };
+        let accepted = client.clone();
+        let listener = tokio::spawn(async move {
+            loop {
+                match listener.accept().await {
+                    Ok((peer, _)) => {
+                        tokio::spawn(serve(peer, accepted.clone()));
+                    }
+                    Err(e) => log::error!("accept failed = {:?}", e),
+                }
+            }
+        });
+        Ok(Self {
+            socket_file,
+            client,
+            rx,
+            listener: Some(listener),
+        })
+    }
+
+    /// Stop accepting clients and remove the socket.
+    pub async fn shutdown(&mut self) {
+        if let Some(listener) = self.listener.take() {
+            listener.abort();
+            listener.await.ok();
+            self.rx.close();
+            self.client.lock_subscribers().clear();
+            fs::remove_file(self.socket_file.as_path()).await.ok();
+        }
+    }
+
+    #[must_use]
+    pub fn socket_name() -> PathBuf {
+        PathBuf::from(format!("command-{}.sock", command_pipe::display_number()))
+    }
+
+    pub async fn read_request(&mut self) -> Option<PendingRequest> {
+        self.rx.recv().await
+    }
+
+    #[must_use]
+    pub fn client(&self) -> Client {
+        self.client.clone()
+    }
+
+    /// Whether a client subscribed to the events.
+    #[must_use]
+    pub fn has_subscribers(&self) -> bool {
+        !self.client.lock_subscribers().is_empty()
+    }
+
+    /// Send the events to every subscribed client, forgetting those which went away or left
+    /// too many events unread.
+    pub fn publish(&self, events: &[Event]) {
+        if !events.is_empty() {
+            self.client.lock_subscribers().retain(|tx| {
+                events.iter().all(|event| match tx.try_send(event.clone()) {
+                    Ok(()) => true,
+                    Err(TrySendError::Full(_)) => {
+                        log::warn!("Dropping a subscriber which stopped reading the events");
+                        false
+                    }
+                    Err(TrySendError::Closed(_)) => false,
+                })
+            });
+        }
+    }
+}
+
+/// Read the requests of a client until it disconnects, answering each before the next is read.
+async fn serve(peer: UnixStream, client: Client) {
+    let (reader, mut writer) = peer.into_split();
+    let mut lines = BufReader::new(reader).lines();
+    while let Ok(Some(line)) = lines.next_line().await {
+        if line.trim().is_empty() {
+            continue;
+        }
+        let mut events = None;
+        let response = match parse_request(&line) {
+            Ok(request) => {
+                // Subscribed before the manager hears of it, so it sees the events which follow.
+                if request == Request::Subscribe {
+                    events = Some(client.subscribe());
+                }
+                client.ask(request).await
+            }
+            Err(err) => Response::Error(format!("Invalid request: {err}")),
+        };
+        if !write_line(&mut writer, &response).await {
+            break;
+        }
+        if let Some(mut events) = events {
+            while let Some(event) = events.recv().await {
+                if !write_line(&mut writer, &event).await {
+                    break;
+                }
+            }
+            break;
+        }
+    }
+}
+
+/// A request in JSON, or a command as the command pipe takes it.
+fn parse_request(line: &str) -> std::result::Result<Request, String> {
+    serde_json::from_str(line).or_else(|_| {
+        command_pipe::parse_command(line)
+            .map(Request::Command)
+            .map_err(|err| err.to_string())
+    })
+}
+
+/// Write the value as a line of JSON. Returns whether the client is still there.
+async fn write_line(writer: &mut OwnedWriteHalf, value: &impl Serialize) -> bool {
+    let mut json = serde_json::to_string(value).unwrap_or_default();
+    json.push('\n');
+    writer.write_all(json.as_bytes()).await.is_ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::Screen;
+    use crate::utils::helpers::test::temp_path;
+    use crate::{Manager, Window};
+    use tokio::io::Lines;
+    use tokio::net::unix::OwnedReadHalf;
+
+    async fn ask(
+        lines: &mut Lines<BufReader<OwnedReadHalf>>,
+        writer: &mut OwnedWriteHalf,
+        request: &str,
+    ) -> String {
+        writer
+            .write_all(format!("{request}\n").as_bytes())
+            .await
+            .unwrap();
+        lines.next_line().await.unwrap().unwrap()
+    }
+
+    #[tokio::test]
+    async fn requests_should_be_answered_in_order_for_each_client() {
+        let socket_file = temp_path().await.unwrap();
+        let mut socket = CommandSocket::listen(socket_file.clone()).await.unwrap();
+        let answer = tokio::spawn(async move {
+            for _ in 0..4 {
+                let pending = socket.read_request().await.unwrap();
+                let response = match pending.request {
+                    Request::Command(Command::ToggleGaps) => Response::Done,
+                    ref request => Response::Error(format!("{request:?}")),
+                };
+                pending.reply(response);
+            }
+            socket
+        });
+
+        let (reader, mut first) = UnixStream::connect(&socket_file)
+            .await
+            .unwrap()
+            .into_split();
+        let mut first_lines = BufReader::new(reader).lines();
+        let (reader, mut second) = UnixStream::connect(&socket_file)
+            .await
+            .unwrap()
+            .into_split();
+        let mut second_lines = BufReader::new(reader).lines();
+        assert_eq!(
+            ask(&mut first_lines, &mut first, r#"{"Command":"ToggleGaps"}"#).await,
+            r#""Done""#
+        );
+        assert_eq!(
+            ask(&mut second_lines, &mut second, r#""ListWindows""#).await,
+            r#"{"Error":"ListWindows"}"#
+        );
+        assert_eq!(
+            ask(&mut second_lines, &mut second, "ToggleGaps").await,
+            r#""Done""#
+        );
+        assert!(ask(&mut first_lines, &mut first, r#"{"Bogus":1}"#)
+            .await
+            .starts_with(r#"{"Error":"Invalid request"#));
+        assert_eq!(
+            ask(
+                &mut first_lines,
+                &mut first,
+                r#"{"WindowAt":{"x":1,"y":2}}"#
+            )
+            .await,
+            r#"{"Error":"WindowAt { x: 1, y: 2 }"}"#
+        );
+
+        let mut socket = answer.await.unwrap();
+        socket.shutdown().await;
+        assert!(!socket_file.exists());
+    }
+
+    #[tokio::test]
+    async fn subscribers_should_be_sent_the_events() {
+        let socket_file = temp_path().await.unwrap();
+        let mut socket = CommandSocket::listen(socket_file.clone()).await.unwrap();
+        assert!(!socket.has_subscribers());
+        let answer = tokio::spawn(async move {
+            let pending = socket.read_request().await.unwrap();
+            assert_eq!(pending.request, Request::Subscribe);
+            pending.reply(Response::Done);
+            socket
+        });
+
+        let (reader, mut writer) = UnixStream::connect(&socket_file)
+            .await
+            .unwrap()
+            .into_split();
+        let mut lines = BufReader::new(reader).lines();
+        assert_eq!(
+            ask(&mut lines, &mut writer, r#""Subscribe""#).await,
+            r#""Done""#
+        );
+        let mut socket = answer.await.unwrap();
+        assert!(socket.has_subscribers());
+        socket.publish(&[Event::WorkspaceFocused(1), Event::WindowFocused(None)]);
+        assert_eq!(
+            lines.next_line().await.unwrap().unwrap(),
+            r#"{"WorkspaceFocused":1}"#
+        );
+        assert_eq!(
+            lines.next_line().await.unwrap().unwrap(),
+            r#"{"WindowFocused":null}"#
+        );
+
+        drop((lines, writer));
+        tokio::task::yield_now().await;
+        socket.publish(&[Event::WorkspaceFocused(0)]);
+        socket.shutdown().await;
+    }
+
+    #[tokio::test]
+    async fn subscribers_which_stop_reading_should_be_dropped() {
+        let mut socket = CommandSocket::listen(temp_path().await.unwrap())
+            .await
+            .unwrap();
+        let mut events = socket.client().subscribe();
+        let backlog = vec![Event::WorkspaceFocused(0); SUBSCRIBER_BACKLOG];
+        socket.publish(&backlog);
+        assert!(socket.has_subscribers());
+        socket.publish(&[Event::WorkspaceFocused(1)]);
+        assert!(!socket.has_subscribers());
+        assert_eq!(events.recv().await, Some(Event::WorkspaceFocused(0)));
+        socket.shutdown().await;
+    }
+
+    #[test]
+    fn events_should_follow_the_changes_of_the_state() {
+        let mut manager = Manager::new_test(vec!["1".to_string(), "2".to_string()]);
+        manager.screen_create_handler(Screen::default());
+        let before = FullState::from(&manager.state);
+        manager.window_created_handler(
+            Window::new(WindowHandle::MockHandle(1), None, None),
+            -1,
+            -1,
+        );
+        let opened = FullState::from(&manager.state);
+        assert_eq!(
+            Event::between(&before, &opened),
+            vec![
+                Event::WindowOpened(WindowHandle::MockHandle(1)),
+                Event::WindowFocused(Some(WindowHandle::MockHandle(1))),
+            ]
+        );
+
+        manager.command_handler(&Command::GoToTag {
+            tag: 2,
+            swap: false,
+        });
+        manager.command_handler(&Command::SetLayout(Layout::Monocle));
+        let events = Event::between(&opened, &FullState::from(&manager.state));
+        assert!(events.contains(&Event::TagChanged {
+            workspace: 0,
+            tags: vec![2]
+        }));
+        assert!(events.contains(&Event::LayoutChanged {
+            workspace: 0,
+            layout: Layout::Monocle
+        }));
+        assert!(Event::between(&opened, &opened).is_empty());
+    }
+}
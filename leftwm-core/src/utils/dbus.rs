@@ -0,0 +1,151 @@
+//! The `org.leftwm.Manager` service on the session bus, for desktop tooling which speaks D-Bus
+//! rather than the command socket. Its methods answer as the requests of the socket do, in the
+//! same JSON, and its `Event` signal carries the events the subscribers of the socket are sent:
+//!
+//! ```text
+//! busctl --user call org.leftwm.Manager /org/leftwm/Manager org.leftwm.Manager Command s "GoToTag 2"
+//! dbus-monitor "type='signal',interface='org.leftwm.Manager'"
+//! ```
+//!
+//! Commands which can't be carried out fail with `org.freedesktop.DBus.Error.Failed`.
+use crate::utils::command_pipe;
+use crate::utils::command_socket::{Client, CommandSocket, Request, Response};
+use zbus::fdo;
+use zbus::object_server::SignalEmitter;
+
+/// The name the service owns on the session bus, which is also the name of its interface.
+pub const NAME: &str = "org.leftwm.Manager";
+/// The path of the manager object.
+pub const PATH: &str = "/org/leftwm/Manager";
+
+struct Manager {
+    client: Client,
+}
+
+impl Manager {
+    async fn ask(&self, request: Request) -> fdo::Result<Response> {
+        match self.client.ask(request).await {
+            Response::Error(reason) => Err(fdo::Error::Failed(reason)),
+            response => Ok(response),
+        }
+    }
+
+    /// The answer to a query, as JSON.
+    async fn query(&self, request: Request) -> fdo::Result<String> {
+        let json = match self.ask(request).await? {
+            Response::WindowAt(answer) => serde_json::to_string(&answer),
+            Response::Windows(answer) => serde_json::to_string(&answer),
+            Response::State(answer) => serde_json::to_string(&answer),
+            response => serde_json::to_string(&response),
+        };
+        json.map_err(|err| fdo::Error::Failed(err.to_string()))
+    }
+}
+
+#[zbus::interface(name = "org.leftwm.Manager")]
+impl Manager {
+    /// Run a command, in JSON or as the command pipe takes it.
+    async fn command(&self, command: &str) -> fdo::Result<()> {
+        let command = command_pipe::parse_command(command)
+            .map_err(|err| fdo::Error::InvalidArgs(err.to_string()))?;
+        self.ask(Request::Command(command)).await.map(|_| ())
+    }
+
+    /// The managed window at a point of the screens.
+    async fn window_at(&self, x: i32, y: i32) -> fdo::Result<String> {
+        self.query(Request::WindowAt { x, y }).await
+    }
+
+    /// The managed windows and the outputs.
+    async fn list_windows(&self) -> fdo::Result<String> {
+        self.query(Request::ListWindows).await
+    }
+
+    /// The screens, workspaces, tags and windows, and which of them are focused.
+    async fn get_state(&self) -> fdo::Result<String> {
+        self.query(Request::GetState).await
+    }
+
+    /// Something changed, as the JSON of an event of the command socket tells.
+    #[zbus(signal)]
+    async fn event(emitter: &SignalEmitter<'_>, event: &str) -> zbus::Result<()>;
+}
+
+/// Serve the manager on the session bus, answering through the command socket, until it shuts
+/// down.
+///
+/// # Errors
+///
+/// Will error without a session bus, or when the name is owned already.
+pub async fn serve(socket: &CommandSocket) -> zbus::Result<()> {
+    let connection = zbus::connection::Builder::session()?
+        .name(NAME)?
+        .serve_at(
+            PATH,
+            Manager {
+                client: socket.client(),
+            },
+        )?
+        .build()
+        .await?;
+    let manager = connection
+        .object_server()
+        .interface::<_, Manager>(PATH)
+        .await?;
+    let mut events = socket.client().subscribe();
+    // The connection goes away with the task, once the socket shuts down.
+    tokio::spawn(async move {
+        while let Some(event) = events.recv().await {
+            let json = serde_json::to_string(&event).unwrap_or_default();
+            if let Err(err) = Manager::event(manager.signal_emitter(), &json).await {
+                log::error!("Failed to signal an event on the session bus: {}", err);
+            }
+        }
+    });
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::helpers::test::temp_path;
+    use crate::Command;
+
+    #[tokio::test]
+    async fn methods_should_answer_as_the_command_socket_does() {
+        let mut socket = CommandSocket::listen(temp_path().await.unwrap())
+            .await
+            .unwrap();
+        let manager = Manager {
+            client: socket.client(),
+        };
+        let answer = tokio::spawn(async move {
+            for _ in 0..4 {
+                let pending = socket.read_request().await.unwrap();
+                let response = match pending.request {
+                    Request::Command(Command::ToggleGaps) => Response::Done,
+                    Request::GetState => Response::Error("no state".to_string()),
+                    _ => Response::Error("couldn't be carried out".to_string()),
+                };
+                pending.reply(response);
+            }
+            socket
+        });
+
+        assert!(manager.command("ToggleGaps").await.is_ok());
+        assert!(manager.command(r#""ToggleGaps""#).await.is_ok());
+        assert_eq!(
+            manager.command("CloseWindow").await,
+            Err(fdo::Error::Failed("couldn't be carried out".to_string()))
+        );
+        assert!(matches!(
+            manager.command(r#"{"Bogus":1}"#).await,
+            Err(fdo::Error::InvalidArgs(_))
+        ));
+        assert_eq!(
+            manager.get_state().await,
+            Err(fdo::Error::Failed("no state".to_string()))
+        );
+        answer.await.unwrap().shutdown().await;
+    }
+}
@@ -0,0 +1,99 @@
+//! Runs the `on_focus_change` and `on_title_change` hooks, which let screen readers and other
+//! tools follow the active window, e.g. by passing it on to D-Bus with `gdbus emit`.
+use crate::child_process::exec_shell_with_env;
+use crate::config::Config;
+use crate::display_servers::DisplayServer;
+use crate::models::{Manager, WindowHandle};
+
+/// What changed about the focused window since the hooks last ran.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FocusChange {
+    /// Another window, or none, has the focus.
+    Window,
+    /// The focused window has a new title.
+    Title,
+}
+
+/// Compare the focused window and its title to the ones seen last.
+#[must_use]
+pub fn focus_change(
+    last: &Option<(WindowHandle, Option<String>)>,
+    current: &Option<(WindowHandle, Option<String>)>,
+) -> Option<FocusChange> {
+    match (last, current) {
+        (None, None) => None,
+        (Some((last, last_title)), Some((current, title))) if last == current => {
+            (last_title != title).then_some(FocusChange::Title)
+        }
+        _ => Some(FocusChange::Window),
+    }
+}
+
+impl<C: Config, SERVER: DisplayServer> Manager<C, SERVER> {
+    /// Run the hook for a change of the focused window or of its title, passing the window in
+    /// `LEFTWM_WINDOW_ID`, `LEFTWM_WINDOW_CLASS` and `LEFTWM_WINDOW_TITLE`. They are empty when
+    /// no window has the focus.
+    pub fn run_focus_hooks(&mut self) {
+        let focused = self.state.focus_manager.window(&self.state.windows);
+        let current = focused.map(|w| (w.handle, w.name.clone()));
+        let command = match focus_change(&self.last_focus, &current) {
+            Some(FocusChange::Window) => self.config.on_focus_change_cmd(),
+            Some(FocusChange::Title) => self.config.on_title_change_cmd(),
+            None => None,
+        };
+        if let Some(command) = command {
+            let env = [
+                (
+                    "LEFTWM_WINDOW_ID",
+                    focused
+                        .and_then(|w| w.handle.xlib_handle())
+                        .map(|id| id.to_string())
+                        .unwrap_or_default(),
+                ),
+                (
+                    "LEFTWM_WINDOW_CLASS",
+                    focused
+                        .and_then(|w| w.res_class.clone())
+                        .unwrap_or_default(),
+                ),
+                (
+                    "LEFTWM_WINDOW_TITLE",
+                    focused.and_then(|w| w.name.clone()).unwrap_or_default(),
+                ),
+            ];
+            exec_shell_with_env(&command, &env, &mut self.children);
+        }
+        self.last_focus = current;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn seen(id: i32, title: &str) -> (WindowHandle, Option<String>) {
+        (WindowHandle::MockHandle(id), Some(title.to_string()))
+    }
+
+    #[test]
+    fn focus_changes_are_told_apart_from_title_changes() {
+        assert_eq!(focus_change(&None, &None), None);
+        assert_eq!(focus_change(&Some(seen(1, "a")), &Some(seen(1, "a"))), None);
+        assert_eq!(
+            focus_change(&Some(seen(1, "a")), &Some(seen(1, "b"))),
+            Some(FocusChange::Title)
+        );
+        assert_eq!(
+            focus_change(&Some(seen(1, "a")), &Some(seen(2, "a"))),
+            Some(FocusChange::Window)
+        );
+        assert_eq!(
+            focus_change(&Some(seen(1, "a")), &None),
+            Some(FocusChange::Window)
+        );
+        assert_eq!(
+            focus_change(&None, &Some(seen(1, "a"))),
+            Some(FocusChange::Window)
+        );
+    }
+}
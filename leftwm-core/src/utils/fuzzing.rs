@@ -0,0 +1,322 @@
+//! Drives a [`Manager`] with the mock display server through sequences of events and commands
+//! decoded from arbitrary bytes, checking the state stays consistent after each of them. Used
+//! by the `manager_events` target in `leftwm-core/fuzz`, run with `cargo fuzz run manager_events`.
+use crate::config::TestConfig;
+use crate::display_servers::MockDisplayServer;
+use crate::layouts::LAYOUTS;
+use crate::models::{
+    BBox, Direction, Screen, Size, UrgencySource, WindowChange, WindowHandle, WindowState,
+    WindowType,
+};
+use crate::{Command, DisplayAction, DisplayEvent, Manager, Window};
+
+/// Handles of the windows the events are about, kept few so that events hit the same windows.
+const WINDOWS: u8 = 12;
+/// Screens created, at most, so that the workspaces don't run out of tags to show.
+const SCREENS: usize = 3;
+
+const DIRECTIONS: [Direction; 4] = [
+    Direction::Left,
+    Direction::Right,
+    Direction::Up,
+    Direction::Down,
+];
+
+const TYPES: [WindowType; 5] = [
+    WindowType::Normal,
+    WindowType::Normal,
+    WindowType::Dialog,
+    WindowType::Splash,
+    WindowType::Dock,
+];
+
+const STATES: [WindowState; 4] = [
+    WindowState::Fullscreen,
+    WindowState::Sticky,
+    WindowState::Above,
+    WindowState::DemandsAttention,
+];
+
+/// Reads the bytes as the values of the events, as zeroes once they run out.
+struct Bytes<'a>(std::slice::Iter<'a, u8>);
+
+impl Bytes<'_> {
+    fn byte(&mut self) -> u8 {
+        self.0.next().copied().unwrap_or_default()
+    }
+
+    fn flag(&mut self) -> bool {
+        self.byte() % 2 == 1
+    }
+
+    fn pick<T: Clone>(&mut self, items: &[T]) -> T {
+        items[usize::from(self.byte()) % items.len()].clone()
+    }
+
+    fn handle(&mut self) -> WindowHandle {
+        WindowHandle::MockHandle(i32::from(self.byte() % WINDOWS) + 1)
+    }
+
+    /// A coordinate on, or just off, the screens.
+    fn coordinate(&mut self) -> i32 {
+        i32::from(self.byte()) * 12 - 100
+    }
+
+    fn ratio(&mut self) -> f32 {
+        f32::from(self.byte() % 40)
+    }
+}
+
+/// Run the events and commands the bytes decode to, panicking on the first inconsistency.
+pub fn run(data: &[u8]) {
+    let mut manager: Manager<TestConfig, MockDisplayServer> = Manager::new(TestConfig {
+        tags: (1..=9).map(|tag| tag.to_string()).collect(),
+        layouts: LAYOUTS.to_vec(),
+        ..TestConfig::default()
+    });
+    manager.screen_create_handler(Screen::default());
+    let mut bytes = Bytes(data.iter());
+    let mut time = 0;
+    while !bytes.0.as_slice().is_empty() {
+        time += u64::from(bytes.byte());
+        let needs_update = match event(&mut bytes, &manager, time) {
+            Some(DisplayEvent::SendCommand(command)) => manager.command_handler(&command),
+            Some(event) => manager.display_event_handler(event),
+            None => false,
+        };
+        if needs_update {
+            manager.update_windows();
+        }
+        check(&manager);
+        // The display server would carry these out, and the mock one can't.
+        manager.state.actions.clear();
+    }
+}
+
+fn event(
+    bytes: &mut Bytes,
+    manager: &Manager<TestConfig, MockDisplayServer>,
+    time: u64,
+) -> Option<DisplayEvent> {
+    let event = match bytes.byte() % 14 {
+        0 => {
+            if manager.state.screens.len() >= SCREENS {
+                return None;
+            }
+            let x = manager
+                .state
+                .screens
+                .iter()
+                .map(|s| s.bbox.x + s.bbox.width)
+                .max()
+                .unwrap_or_default();
+            DisplayEvent::ScreenCreate(Screen::new(BBox {
+                x,
+                y: 0,
+                width: i32::from(bytes.byte()) * 4 + 200,
+                height: i32::from(bytes.byte()) * 4 + 200,
+            }))
+        }
+        1 | 2 => {
+            let mut window = Window::new(bytes.handle(), None, None);
+            window.r#type = bytes.pick(&TYPES);
+            if bytes.flag() {
+                window.transient = Some(bytes.handle());
+            }
+            DisplayEvent::WindowCreate(window, bytes.coordinate(), bytes.coordinate())
+        }
+        3 => DisplayEvent::WindowDestroy(bytes.handle()),
+        4 => DisplayEvent::HandleWindowFocus(bytes.handle(), time),
+        5 => DisplayEvent::WindowTakeFocus(bytes.handle(), time),
+        6 => DisplayEvent::Movement(bytes.handle(), bytes.coordinate(), bytes.coordinate(), time),
+        7 => DisplayEvent::MoveFocusTo(bytes.coordinate(), bytes.coordinate()),
+        8 => DisplayEvent::VerifyFocusedAt(bytes.handle()),
+        9 => DisplayEvent::FocusTab(bytes.handle(), time),
+        10 => {
+            let mut change = WindowChange::new(bytes.handle());
+            match bytes.byte() % 4 {
+                0 => {
+                    change.states = Some(if bytes.flag() {
+                        vec![]
+                    } else {
+                        vec![bytes.pick(&STATES)]
+                    });
+                }
+                1 => change.r#type = Some(bytes.pick(&TYPES)),
+                2 => change.urgent = Some((UrgencySource::Hint, bytes.flag())),
+                _ => change.transient = Some(bytes.flag().then(|| bytes.handle())),
+            }
+            DisplayEvent::WindowChange(change)
+        }
+        11 => DisplayEvent::MoveWindow(bytes.handle(), bytes.coordinate(), bytes.coordinate()),
+        12 => DisplayEvent::ResizeWindow(bytes.handle(), bytes.coordinate(), bytes.coordinate()),
+        _ => DisplayEvent::SendCommand(command(bytes)),
+    };
+    Some(event)
+}
+
+/// A command which only changes the state, leaving out those running programs or writing files.
+fn command(bytes: &mut Bytes) -> Command {
+    let tag = usize::from(bytes.byte() % 11);
+    match bytes.byte() % 65 {
+        0 => Command::CloseWindow,
+        1 => Command::SwapScreens,
+        2 => Command::ToggleFullScreen,
+        3 => Command::ToggleSticky,
+        4 => Command::ToggleZoom,
+        5 => Command::ToggleDock,
+        6 => Command::GoToTag {
+            tag,
+            swap: bytes.flag(),
+        },
+        7 => Command::ReturnToLastTag,
+        8 => Command::FloatingToTile,
+        9 => Command::TileToFloating,
+        10 => Command::ToggleFloating,
+        11 => Command::MoveWindowUp,
+        12 => Command::MoveWindowDown,
+        13 => Command::MoveWindowTop { swap: bytes.flag() },
+        14 => Command::FocusNextTag {
+            wrap: bytes.flag(),
+            skip_empty: bytes.flag(),
+        },
+        15 => Command::FocusPreviousTag {
+            wrap: bytes.flag(),
+            skip_empty: bytes.flag(),
+        },
+        16 => Command::FocusWindowUp,
+        17 => Command::FocusWindowDown,
+        18 => Command::FocusWindowTop { swap: bytes.flag() },
+        19 => Command::FocusWindowInDirection(bytes.pick(&DIRECTIONS)),
+        20 => Command::MoveWindowInDirection(bytes.pick(&DIRECTIONS)),
+        21 => Command::SwapWindowWithMaster,
+        22 => Command::RotateStackForward,
+        23 => Command::RotateStackBackward,
+        24 => Command::FocusWorkspaceNext,
+        25 => Command::FocusWorkspacePrevious,
+        26 => Command::SendWindowToTag {
+            window: bytes.flag().then(|| bytes.handle()),
+            tag,
+        },
+        27 => Command::MoveWindowToLastWorkspace,
+        28 => Command::MoveWindowToNextWorkspace,
+        29 => Command::MoveWindowToPreviousWorkspace,
+        30 => Command::NextLayout,
+        31 => Command::PreviousLayout,
+        32 => Command::ToggleLastLayout,
+        33 => Command::SetLayout(bytes.pick(LAYOUTS)),
+        34 => Command::RotateTag,
+        35 => Command::IncreaseMainWidth(bytes.ratio()),
+        36 => Command::DecreaseMainWidth(bytes.ratio()),
+        37 => Command::IncreaseStackSplit(bytes.ratio()),
+        38 => Command::DecreaseStackSplit(bytes.ratio()),
+        39 => Command::IncreaseMainCount,
+        40 => Command::DecreaseMainCount,
+        41 => Command::IncreaseWindowWeight(bytes.ratio() / 10.0),
+        42 => Command::DecreaseWindowWeight(bytes.ratio() / 10.0),
+        43 => Command::RotateSubtree,
+        44 => Command::FlipSubtree,
+        45 => Command::SetMarginMultiplier(bytes.ratio() / 10.0),
+        46 => Command::SetMaxWindowWidth(bytes.flag().then(|| Size::Pixel(bytes.coordinate()))),
+        47 => Command::SendWorkspaceToTag(usize::from(bytes.byte() % 4), tag),
+        48 => Command::CloseAllOnTag,
+        49 => Command::IncreaseGaps(u32::from(bytes.byte() % 8)),
+        50 => Command::DecreaseGaps(u32::from(bytes.byte() % 8)),
+        51 => Command::ToggleGaps,
+        52 => Command::ToggleGameMode,
+        53 => Command::AddTag(tag.to_string()),
+        54 => Command::RemoveTag,
+        55 => Command::ToggleWindowTag(tag),
+        56 => Command::ToggleTagView(tag),
+        57 => Command::FocusLastTag,
+        58 => Command::MoveTagToWorkspace(usize::from(bytes.byte() % 4)),
+        59 => Command::FocusUrgent,
+        60 => Command::TagHistoryBack,
+        61 => Command::TagHistoryForward,
+        62 => Command::ToggleOverview,
+        63 => Command::PickOverviewWindow(bytes.flag().then(|| bytes.handle())),
+        _ => Command::CountPrefix(usize::from(bytes.byte() % 10)),
+    }
+}
+
+/// Panic if the state no longer holds together.
+fn check(manager: &Manager<TestConfig, MockDisplayServer>) {
+    let state = &manager.state;
+    for (i, window) in state.windows.iter().enumerate() {
+        assert!(
+            state.windows[..i].iter().all(|w| w.handle != window.handle),
+            "{:?} is managed twice",
+            window.handle
+        );
+        for tag in &window.tags {
+            assert!(
+                state.tags.get(*tag).is_some(),
+                "{:?} has the unknown tag {tag}",
+                window.handle
+            );
+        }
+    }
+    // With `Sloppy` focus the window under the cursor is focused once the display server finds it.
+    let refocusing = state
+        .actions
+        .iter()
+        .any(|action| matches!(action, DisplayAction::FocusWindowUnderCursor));
+    if let Some(Some(focused)) = state.focus_manager.window_history.front() {
+        assert!(
+            refocusing || state.windows.iter().any(|w| w.handle == *focused),
+            "the focused window {focused:?} isn't managed"
+        );
+    }
+    if let Some(&index) = state.focus_manager.workspace_history.front() {
+        assert!(
+            index < state.workspaces.len(),
+            "the focused workspace {index} doesn't exist"
+        );
+    }
+    for (i, ws) in state.workspaces.iter().enumerate() {
+        for tag in &ws.tags {
+            assert!(
+                state.tags.get(*tag).is_some(),
+                "workspace {i} shows the unknown tag {tag}"
+            );
+            assert!(
+                state.workspaces[..i]
+                    .iter()
+                    .all(|other| !other.has_tag(tag)),
+                "tag {tag} is shown on two workspaces"
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Bytes from a linear congruential generator, standing in for the fuzzer.
+    fn noise(seed: u64, len: usize) -> Vec<u8> {
+        let mut value = seed;
+        (0..len)
+            .map(|_| {
+                value = value
+                    .wrapping_mul(6_364_136_223_846_793_005)
+                    .wrapping_add(1_442_695_040_888_963_407);
+                (value >> 33) as u8
+            })
+            .collect()
+    }
+
+    #[test]
+    fn event_sequences_should_keep_the_state_consistent() {
+        for seed in 0..64 {
+            run(&noise(seed, 4096));
+        }
+    }
+
+    #[test]
+    fn running_out_of_bytes_should_be_fine() {
+        run(&[]);
+        run(&[1]);
+        run(&[13, 13]);
+    }
+}
@@ -0,0 +1,679 @@
+//! Enough of the IPC of i3 for the tools written for it, like `i3-msg`, i3status-rust and the
+//! bars showing i3 workspaces, to work with leftwm. The socket is given to the programs leftwm
+//! starts in `I3SOCK`, which is where these tools look for it.
+//!
+//! The tags of leftwm are the workspaces of i3, numbered by their ids, and its workspaces the
+//! outputs. `RUN_COMMAND` takes this part of the commands of i3, without criteria:
+//!
+//! ```text
+//! workspace <name> | workspace number <n> | workspace next | workspace prev
+//! workspace back_and_forth | move container to workspace number <n>
+//! focus <direction> | move <direction> | kill | fullscreen toggle | sticky toggle
+//! floating enable | floating disable | floating toggle | layout tabbed | layout stacking
+//! layout toggle | reload | restart | exit
+//! ```
+//!
+//! `GET_WORKSPACES`, `GET_OUTPUTS`, `GET_TREE` and `GET_VERSION` are answered, and `SUBSCRIBE`
+//! sends the `workspace`, `output` and `window` events.
+use crate::errors::Result;
+use crate::layouts::Layout;
+use crate::models::dto::{FullState, TagInfo, WindowDetails, WorkspaceInfo};
+use crate::models::{Direction, WindowHandle};
+use crate::utils::command_pipe;
+use crate::utils::command_socket::{Client, Event, Request, Response};
+use crate::Command;
+use serde_json::{json, Value};
+use std::io;
+use std::path::PathBuf;
+use std::sync::Arc;
+use tokio::fs;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::unix::{OwnedReadHalf, OwnedWriteHalf};
+use tokio::net::{UnixListener, UnixStream};
+use tokio::sync::{mpsc, Mutex};
+use tokio::task::JoinHandle;
+
+const MAGIC: &[u8; 6] = b"i3-ipc";
+/// Messages longer than this are taken for garbage.
+const MAX_PAYLOAD: usize = 1 << 20;
+
+const RUN_COMMAND: u32 = 0;
+const GET_WORKSPACES: u32 = 1;
+const SUBSCRIBE: u32 = 2;
+const GET_OUTPUTS: u32 = 3;
+const GET_TREE: u32 = 4;
+const GET_VERSION: u32 = 7;
+
+/// Events have the highest bit of their type set.
+const WORKSPACE_EVENT: u32 = 1 << 31;
+const OUTPUT_EVENT: u32 = WORKSPACE_EVENT | 1;
+const WINDOW_EVENT: u32 = WORKSPACE_EVENT | 3;
+
+/// Listens on the i3 socket, answering through the command socket.
+#[derive(Debug)]
+pub struct I3Socket {
+    socket_file: PathBuf,
+    listener: Option<JoinHandle<()>>,
+}
+
+impl Drop for I3Socket {
+    fn drop(&mut self) {
+        assert!(
+            std::thread::panicking() || self.listener.is_none(),
+            "I3Socket has to be shutdown explicitly before drop"
+        );
+    }
+}
+
+impl I3Socket {
+    /// Bind to the socket and accept clients, which reach the manager through `client`.
+    ///
+    /// # Errors
+    ///
+    /// Will error if the socket can't be bound, likely a filesystem issue such as inadequate
+    /// permissions.
+    pub async fn listen(socket_file: PathBuf, client: Client) -> Result<Self> {
+        let listener = if let Ok(listener) = UnixListener::bind(&socket_file) {
+            listener
+        } else {
+            fs::remove_file(&socket_file).await?;
+            UnixListener::bind(&socket_file)?
+        };
+        let listener = tokio::spawn(async move {
+            loop {
+                match listener.accept().await {
+                    Ok((peer, _)) => {
+                        tokio::spawn(serve(peer, client.clone()));
+                    }
+                    Err(e) => log::error!("accept failed = {:?}", e),
+                }
+            }
+        });
+        Ok(Self {
+            socket_file,
+            listener: Some(listener),
+        })
+    }
+
+    /// Stop accepting clients and remove the socket.
+    pub async fn shutdown(&mut self) {
+        if let Some(listener) = self.listener.take() {
+            listener.abort();
+            listener.await.ok();
+            fs::remove_file(self.socket_file.as_path()).await.ok();
+        }
+    }
+
+    #[must_use]
+    pub fn socket_name() -> PathBuf {
+        PathBuf::from(format!("i3-{}.sock", command_pipe::display_number()))
+    }
+
+    /// The socket in the runtime directory of leftwm, which is created if need be.
+    ///
+    /// # Errors
+    ///
+    /// Will error without a runtime directory, or if it can't be created.
+    pub fn socket_file() -> io::Result<PathBuf> {
+        xdg::BaseDirectories::with_prefix("leftwm")?.place_runtime_file(Self::socket_name())
+    }
+}
+
+/// Answer the messages of a client until it disconnects. Its events are written by a task of
+/// their own, between the answers.
+async fn serve(peer: UnixStream, client: Client) {
+    let (mut reader, writer) = peer.into_split();
+    let writer = Arc::new(Mutex::new(writer));
+    let mut kinds: Vec<String> = vec![];
+    let mut forwarder: Option<JoinHandle<()>> = None;
+    while let Ok((kind, payload)) = read_message(&mut reader).await {
+        let mut subscribed = None;
+        let reply = match kind {
+            RUN_COMMAND => run_commands(&client, &String::from_utf8_lossy(&payload)).await,
+            GET_WORKSPACES => json!(get_state(&client).await.map(|s| workspaces(&s))),
+            GET_OUTPUTS => json!(get_state(&client).await.map(|s| outputs(&s))),
+            GET_TREE => json!(get_state(&client).await.map(|s| tree(&s))),
+            GET_VERSION => json!({
+                "major": 4,
+                "minor": 0,
+                "patch": 0,
+                "human_readable": format!("leftwm {}", env!("CARGO_PKG_VERSION")),
+                "loaded_config_file_name": "",
+            }),
+            SUBSCRIBE => match serde_json::from_slice::<Vec<String>>(&payload) {
+                Ok(more) => {
+                    kinds.extend(more);
+                    subscribed = Some(client.subscribe());
+                    json!({ "success": true })
+                }
+                Err(_) => json!({ "success": false }),
+            },
+            _ => json!({ "success": false, "error": "unsupported message type" }),
+        };
+        if write_message(&writer, kind, &reply).await.is_err() {
+            break;
+        }
+        if let Some(events) = subscribed {
+            if let Some(forwarder) = forwarder.take() {
+                forwarder.abort();
+            }
+            let task = forward_events(events, kinds.clone(), client.clone(), writer.clone());
+            forwarder = Some(tokio::spawn(task));
+        }
+    }
+    if let Some(forwarder) = forwarder {
+        forwarder.abort();
+    }
+}
+
+async fn read_message(reader: &mut OwnedReadHalf) -> io::Result<(u32, Vec<u8>)> {
+    let mut header = [0; 14];
+    reader.read_exact(&mut header).await?;
+    let length = u32::from_ne_bytes([header[6], header[7], header[8], header[9]]);
+    let length = usize::try_from(length).unwrap_or(usize::MAX);
+    if &header[..6] != MAGIC || length > MAX_PAYLOAD {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            "not an i3 IPC message",
+        ));
+    }
+    let kind = u32::from_ne_bytes([header[10], header[11], header[12], header[13]]);
+    let mut payload = vec![0; length];
+    reader.read_exact(&mut payload).await?;
+    Ok((kind, payload))
+}
+
+async fn write_message(writer: &Mutex<OwnedWriteHalf>, kind: u32, value: &Value) -> io::Result<()> {
+    let payload = serde_json::to_vec(value)?;
+    let length = u32::try_from(payload.len()).map_err(|_| io::ErrorKind::InvalidData)?;
+    let mut message = Vec::with_capacity(14 + payload.len());
+    message.extend_from_slice(MAGIC);
+    message.extend_from_slice(&length.to_ne_bytes());
+    message.extend_from_slice(&kind.to_ne_bytes());
+    message.extend_from_slice(&payload);
+    writer.lock().await.write_all(&message).await
+}
+
+async fn get_state(client: &Client) -> Option<FullState> {
+    match client.ask(Request::GetState).await {
+        Response::State(state) => Some(state),
+        _ => None,
+    }
+}
+
+/// Run the commands one after the other, answering whether each of them worked.
+async fn run_commands(client: &Client, commands: &str) -> Value {
+    let mut results = vec![];
+    for command in commands.split([';', ',']).map(str::trim) {
+        if command.is_empty() {
+            continue;
+        }
+        let result = match parse_command(command) {
+            Ok(command) => match client.ask(Request::Command(command)).await {
+                Response::Error(reason) => json!({ "success": false, "error": reason }),
+                _ => json!({ "success": true }),
+            },
+            Err(reason) => json!({ "success": false, "parse_error": true, "error": reason }),
+        };
+        results.push(result);
+    }
+    Value::Array(results)
+}
+
+/// The leftwm command doing what the i3 command does.
+fn parse_command(command: &str) -> std::result::Result<Command, String> {
+    if command.starts_with('[') {
+        return Err("criteria aren't supported".to_string());
+    }
+    let words: Vec<&str> = command
+        .split_whitespace()
+        .filter(|word| !word.starts_with("--"))
+        .collect();
+    let parsed = match words.as_slice() {
+        ["workspace", "number", number, ..] => {
+            tag_number(number).map(|tag| Command::GoToTag { tag, swap: false })
+        }
+        ["workspace", "next" | "next_on_output"] => Some(Command::FocusNextTag {
+            wrap: true,
+            skip_empty: true,
+        }),
+        ["workspace", "prev" | "prev_on_output"] => Some(Command::FocusPreviousTag {
+            wrap: true,
+            skip_empty: true,
+        }),
+        ["workspace", "back_and_forth"] => Some(Command::ReturnToLastTag),
+        ["workspace", name @ ..] if !name.is_empty() => {
+            Some(Command::GoToLabelledTag(name.join(" ")))
+        }
+        ["move", rest @ ..] => parse_move(rest),
+        ["focus", direction] => direction_of(direction).map(Command::FocusWindowInDirection),
+        ["kill"] => Some(Command::CloseWindow),
+        ["fullscreen"] | ["fullscreen", "toggle"] => Some(Command::ToggleFullScreen),
+        ["sticky", "toggle"] => Some(Command::ToggleSticky),
+        ["floating", "toggle"] => Some(Command::ToggleFloating),
+        ["floating", "enable"] => Some(Command::TileToFloating),
+        ["floating", "disable"] => Some(Command::FloatingToTile),
+        ["layout", "tabbed"] => Some(Command::SetLayout(Layout::Tabbed)),
+        ["layout", "stacking"] => Some(Command::SetLayout(Layout::Monocle)),
+        ["layout", "toggle"] => Some(Command::NextLayout),
+        ["reload" | "restart"] => Some(Command::SoftReload),
+        ["exit"] => Some(Command::Exit),
+        _ => None,
+    };
+    parsed.ok_or_else(|| format!("unsupported command: {command}"))
+}
+
+fn parse_move(words: &[&str]) -> Option<Command> {
+    let (["container" | "window", "to", words @ ..]
+    | ["container" | "window" | "to", words @ ..]
+    | words) = words;
+    match words {
+        ["workspace", "number", number, ..] | ["workspace", number] => {
+            tag_number(number).map(|tag| Command::SendWindowToTag { window: None, tag })
+        }
+        [direction] => direction_of(direction).map(Command::MoveWindowInDirection),
+        _ => None,
+    }
+}
+
+/// The number a workspace name like `3: web` starts with.
+fn tag_number(name: &str) -> Option<usize> {
+    let digits = name
+        .find(|c: char| !c.is_ascii_digit())
+        .unwrap_or(name.len());
+    name[..digits].parse().ok()
+}
+
+fn direction_of(word: &str) -> Option<Direction> {
+    match word {
+        "left" => Some(Direction::Left),
+        "right" => Some(Direction::Right),
+        "up" => Some(Direction::Up),
+        "down" => Some(Direction::Down),
+        _ => None,
+    }
+}
+
+/// Node ids which don't meet the ids of the X windows, which start much higher.
+const ROOT_ID: u64 = 1;
+const OUTPUT_IDS: u64 = 100;
+const WORKSPACE_IDS: u64 = 1000;
+
+fn window_id(handle: WindowHandle) -> u64 {
+    match handle {
+        WindowHandle::XlibHandle(id) => id,
+        WindowHandle::MockHandle(id) => u64::try_from(id).unwrap_or_default(),
+    }
+}
+
+fn rect(x: i32, y: i32, width: i32, height: i32) -> Value {
+    json!({ "x": x, "y": y, "width": width, "height": height })
+}
+
+fn output_name(ws: &WorkspaceInfo) -> String {
+    ws.output
+        .clone()
+        .unwrap_or_else(|| format!("leftwm-{}", ws.index))
+}
+
+/// The tags i3 would have as workspaces, those shown or with windows, and the workspace of each,
+/// the first one for the tags not shown.
+fn i3_workspaces(state: &FullState) -> Vec<(&TagInfo, Option<&WorkspaceInfo>)> {
+    state
+        .tags
+        .iter()
+        .filter(|tag| !tag.hidden && (tag.workspace.is_some() || tag.window_count > 0))
+        .map(|tag| {
+            let ws = tag
+                .workspace
+                .or(Some(0))
+                .and_then(|i| state.workspaces.get(i));
+            (tag, ws)
+        })
+        .collect()
+}
+
+fn workspace_rect(tag: &TagInfo, ws: Option<&WorkspaceInfo>) -> Value {
+    match ws {
+        Some(ws) if tag.workspace.is_some() => rect(ws.x, ws.y, ws.w, ws.h),
+        _ => rect(0, 0, 0, 0),
+    }
+}
+
+fn workspaces(state: &FullState) -> Vec<Value> {
+    i3_workspaces(state)
+        .into_iter()
+        .map(|(tag, ws)| {
+            json!({
+                "id": WORKSPACE_IDS + tag.id as u64,
+                "num": tag.id,
+                "name": tag.label,
+                "visible": tag.workspace.is_some(),
+                "focused": state.focused_tag == Some(tag.id),
+                "urgent": tag.urgent,
+                "rect": workspace_rect(tag, ws),
+                "output": ws.map(output_name),
+            })
+        })
+        .collect()
+}
+
+fn outputs(state: &FullState) -> Vec<Value> {
+    state
+        .workspaces
+        .iter()
+        .map(|ws| {
+            let current = ws
+                .tags
+                .first()
+                .and_then(|id| state.tags.iter().find(|tag| tag.id == *id))
+                .map(|tag| tag.label.clone());
+            json!({
+                "name": output_name(ws),
+                "active": true,
+                "primary": ws.index == 0,
+                "current_workspace": current,
+                "rect": rect(ws.x, ws.y, ws.w, ws.h),
+            })
+        })
+        .collect()
+}
+
+fn window_node(state: &FullState, window: &WindowDetails) -> Value {
+    let info = &window.info;
+    json!({
+        "id": window_id(info.handle),
+        "type": if window.floating { "floating_con" } else { "con" },
+        "name": info.name,
+        "window": info.window_id,
+        "window_properties": {
+            "class": info.class,
+            "instance": info.instance,
+            "title": info.name,
+        },
+        "rect": rect(info.x, info.y, info.w, info.h),
+        "focused": state.focused_window == Some(info.handle),
+        "urgent": window.urgent,
+        "fullscreen_mode": u8::from(window.fullscreen),
+        "sticky": window.sticky,
+        "nodes": [],
+        "floating_nodes": [],
+    })
+}
+
+fn workspace_node(state: &FullState, tag: &TagInfo, ws: Option<&WorkspaceInfo>) -> Value {
+    let windows: Vec<&WindowDetails> = state
+        .windows
+        .iter()
+        .filter(|w| w.info.tags.first() == Some(&tag.label))
+        .collect();
+    let nodes = |floating: bool| -> Vec<Value> {
+        windows
+            .iter()
+            .filter(|w| w.floating == floating)
+            .map(|w| window_node(state, w))
+            .collect()
+    };
+    json!({
+        "id": WORKSPACE_IDS + tag.id as u64,
+        "type": "workspace",
+        "num": tag.id,
+        "name": tag.label,
+        "rect": workspace_rect(tag, ws),
+        "focused": false,
+        "urgent": tag.urgent,
+        "layout": "splith",
+        "output": ws.map(output_name),
+        "nodes": nodes(false),
+        "floating_nodes": nodes(true),
+    })
+}
+
+fn tree(state: &FullState) -> Value {
+    let workspaces = i3_workspaces(state);
+    let outputs: Vec<Value> = state
+        .workspaces
+        .iter()
+        .map(|ws| {
+            let content: Vec<Value> = workspaces
+                .iter()
+                .filter(|(_, shown_on)| shown_on.is_some_and(|shown_on| shown_on.index == ws.index))
+                .map(|(tag, shown_on)| workspace_node(state, tag, *shown_on))
+                .collect();
+            json!({
+                "id": OUTPUT_IDS + ws.index as u64,
+                "type": "output",
+                "name": output_name(ws),
+                "rect": rect(ws.x, ws.y, ws.w, ws.h),
+                "nodes": [{
+                    "id": OUTPUT_IDS + 50 + ws.index as u64,
+                    "type": "con",
+                    "name": "content",
+                    "rect": rect(ws.x, ws.y, ws.w, ws.h),
+                    "nodes": content,
+                    "floating_nodes": [],
+                }],
+                "floating_nodes": [],
+            })
+        })
+        .collect();
+    let (w, h) = state.screens.iter().fold((0, 0), |(w, h), screen| {
+        let bbox = screen.bbox;
+        (w.max(bbox.x + bbox.width), h.max(bbox.y + bbox.height))
+    });
+    json!({
+        "id": ROOT_ID,
+        "type": "root",
+        "name": "root",
+        "rect": rect(0, 0, w, h),
+        "nodes": outputs,
+        "floating_nodes": [],
+    })
+}
+
+/// The i3 event told of a change, if it has one, with the kind of events it is of.
+fn i3_event(state: Option<&FullState>, event: &Event) -> Option<(u32, Value)> {
+    let find = |handle: &WindowHandle| {
+        state.and_then(|s| {
+            s.windows
+                .iter()
+                .find(|w| w.info.handle == *handle)
+                .map(|w| window_node(s, w))
+        })
+    };
+    let container = |handle: &WindowHandle| {
+        find(handle).unwrap_or_else(
+            || json!({ "id": window_id(*handle), "type": "con", "window": handle.xlib_handle() }),
+        )
+    };
+    match event {
+        Event::TagChanged { .. } | Event::WorkspaceFocused(_) => {
+            let state = state?;
+            let current = i3_workspaces(state)
+                .into_iter()
+                .find(|(tag, _)| state.focused_tag == Some(tag.id))
+                .map(|(tag, ws)| workspace_node(state, tag, ws));
+            Some((
+                WORKSPACE_EVENT,
+                json!({ "change": "focus", "current": current, "old": null }),
+            ))
+        }
+        Event::MonitorsChanged(_) => Some((OUTPUT_EVENT, json!({ "change": "unspecified" }))),
+        Event::WindowOpened(handle) => Some((
+            WINDOW_EVENT,
+            json!({ "change": "new", "container": container(handle) }),
+        )),
+        Event::WindowClosed(handle) => Some((
+            WINDOW_EVENT,
+            json!({ "change": "close", "container": container(handle) }),
+        )),
+        Event::WindowFocused(Some(handle)) => Some((
+            WINDOW_EVENT,
+            json!({ "change": "focus", "container": container(handle) }),
+        )),
+        Event::WindowFocused(None) | Event::LayoutChanged { .. } => None,
+    }
+}
+
+/// Write the events of the kinds subscribed to, as i3 sends them, until the client goes away.
+async fn forward_events(
+    mut events: mpsc::Receiver<Event>,
+    kinds: Vec<String>,
+    client: Client,
+    writer: Arc<Mutex<OwnedWriteHalf>>,
+) {
+    let wanted = |kind: u32| {
+        let name = match kind {
+            WORKSPACE_EVENT => "workspace",
+            OUTPUT_EVENT => "output",
+            _ => "window",
+        };
+        kinds.iter().any(|k| k == name)
+    };
+    while let Some(event) = events.recv().await {
+        let mut batch = vec![event];
+        while let Ok(event) = events.try_recv() {
+            batch.push(event);
+        }
+        let state = get_state(&client).await;
+        let mut sent: Vec<(u32, Value)> = vec![];
+        for event in &batch {
+            if let Some(message) = i3_event(state.as_ref(), event) {
+                if wanted(message.0) && !sent.contains(&message) {
+                    sent.push(message);
+                }
+            }
+        }
+        for (kind, value) in &sent {
+            if write_message(&writer, *kind, value).await.is_err() {
+                return;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::Screen;
+    use crate::utils::command_socket::CommandSocket;
+    use crate::utils::helpers::test::temp_path;
+    use crate::{Manager, Window};
+
+    #[test]
+    fn i3_commands_should_map_to_leftwm_commands() {
+        assert_eq!(
+            parse_command("workspace number 3: web"),
+            Ok(Command::GoToTag {
+                tag: 3,
+                swap: false
+            })
+        );
+        assert_eq!(
+            parse_command("workspace --no-auto-back-and-forth web"),
+            Ok(Command::GoToLabelledTag("web".to_string()))
+        );
+        assert_eq!(
+            parse_command("move container to workspace number 2"),
+            Ok(Command::SendWindowToTag {
+                window: None,
+                tag: 2
+            })
+        );
+        assert_eq!(
+            parse_command("move left"),
+            Ok(Command::MoveWindowInDirection(Direction::Left))
+        );
+        assert_eq!(
+            parse_command("floating toggle"),
+            Ok(Command::ToggleFloating)
+        );
+        assert!(parse_command("[class=\"Firefox\"] kill").is_err());
+        assert!(parse_command("exec xterm").is_err());
+        assert!(parse_command("move container to workspace web").is_err());
+    }
+
+    #[test]
+    fn the_tags_should_be_the_workspaces_of_the_tree() {
+        let mut manager = Manager::new_test(vec!["1".to_string(), "2".to_string()]);
+        manager.screen_create_handler(Screen::default());
+        manager.window_created_handler(
+            Window::new(WindowHandle::MockHandle(7), None, None),
+            -1,
+            -1,
+        );
+        manager.update_windows();
+        let state = FullState::from(&manager.state);
+
+        let workspaces = workspaces(&state);
+        assert_eq!(workspaces.len(), 1);
+        assert_eq!(workspaces[0]["name"], "1");
+        assert_eq!(workspaces[0]["focused"], true);
+        assert_eq!(outputs(&state)[0]["current_workspace"], "1");
+        let tree = tree(&state);
+        let workspace = &tree["nodes"][0]["nodes"][0]["nodes"][0];
+        assert_eq!(workspace["type"], "workspace");
+        assert_eq!(workspace["nodes"][0]["id"], 7);
+        assert_eq!(workspace["nodes"][0]["focused"], true);
+    }
+
+    #[tokio::test]
+    async fn messages_should_be_answered_in_the_i3_framing() {
+        let mut manager = Manager::new_test(vec!["1".to_string()]);
+        manager.screen_create_handler(Screen::default());
+        manager.window_created_handler(
+            Window::new(WindowHandle::MockHandle(1), None, None),
+            -1,
+            -1,
+        );
+        manager.update_windows();
+        let mut socket = CommandSocket::listen(temp_path().await.unwrap())
+            .await
+            .unwrap();
+        let i3_file = temp_path().await.unwrap();
+        let mut i3_socket = I3Socket::listen(i3_file.clone(), socket.client())
+            .await
+            .unwrap();
+        // The manager answers the commands, as the event loop does.
+        let answer = async {
+            for _ in 0..2 {
+                let pending = socket.read_request().await.unwrap();
+                let (response, _) = manager.request_handler(&pending.request);
+                pending.reply(response);
+            }
+        };
+        let ask = async {
+            let (mut reader, writer) = UnixStream::connect(&i3_file).await.unwrap().into_split();
+            let writer = Mutex::new(writer);
+            write_message(&writer, GET_VERSION, &json!(""))
+                .await
+                .unwrap();
+            let (kind, payload) = read_message(&mut reader).await.unwrap();
+            let version: Value = serde_json::from_slice(&payload).unwrap();
+            assert_eq!((kind, &version["major"]), (GET_VERSION, &json!(4)));
+
+            // Raw commands, as `i3-msg` sends them.
+            let commands = b"fullscreen toggle; kill; exec xterm";
+            let mut message = MAGIC.to_vec();
+            message.extend_from_slice(&u32::try_from(commands.len()).unwrap().to_ne_bytes());
+            message.extend_from_slice(&RUN_COMMAND.to_ne_bytes());
+            message.extend_from_slice(commands);
+            writer.lock().await.write_all(&message).await.unwrap();
+            let (kind, payload) = read_message(&mut reader).await.unwrap();
+            let results: Value = serde_json::from_slice(&payload).unwrap();
+            assert_eq!(kind, RUN_COMMAND);
+            let worked: Vec<&Value> = results
+                .as_array()
+                .unwrap()
+                .iter()
+                .map(|result| &result["success"])
+                .collect();
+            assert_eq!(worked, [&json!(true), &json!(true), &json!(false)]);
+        };
+        tokio::join!(answer, ask);
+
+        socket.shutdown().await;
+        i3_socket.shutdown().await;
+        assert!(!i3_file.exists());
+    }
+}
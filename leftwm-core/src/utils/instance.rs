@@ -0,0 +1,214 @@
+//! Keeps to a single leftwm per display. The running one owns the `WM_S<screen>` selection, as
+//! ICCCM has window managers do. A leftwm which crashed leaves its pipe and sockets behind, which
+//! are removed before starting another so that `leftwm-command` and `leftwm-state` don't wait on
+//! them.
+use crate::{CommandPipe, CommandSocket, I3Socket};
+use std::ffi::CString;
+use std::fs::{self, File, OpenOptions};
+use std::io;
+use std::os::raw::c_int;
+use std::os::unix::fs::{FileTypeExt, OpenOptionsExt};
+use std::os::unix::io::AsRawFd;
+use std::os::unix::net::UnixStream;
+use std::path::{Path, PathBuf};
+use std::ptr;
+use std::thread;
+use std::time::{Duration, Instant};
+use x11_dl::xlib;
+
+/// The `WM_S<screen>` selection of the default screen of a display.
+pub(crate) fn wm_selection(xlib: &xlib::Xlib, display: *mut xlib::Display) -> xlib::Atom {
+    let screen = unsafe { (xlib.XDefaultScreen)(display) };
+    let name = CString::new(format!("WM_S{screen}")).unwrap_or_default();
+    unsafe { (xlib.XInternAtom)(display, name.as_ptr(), xlib::False) }
+}
+
+/// Make `window` the owner of the selection, as ICCCM asks: at the time of the server rather
+/// than `CurrentTime`, and checking that the server gave it. Returns the time it was taken at,
+/// `None` if it wasn't.
+pub(crate) unsafe fn own_wm_selection(
+    xlib: &xlib::Xlib,
+    display: *mut xlib::Display,
+    selection: xlib::Atom,
+    window: xlib::Window,
+) -> Option<xlib::Time> {
+    let time = server_time(xlib, display, window);
+    (xlib.XSetSelectionOwner)(display, selection, window, time);
+    ((xlib.XGetSelectionOwner)(display, selection) == window).then_some(time)
+}
+
+/// The time of the server, read from the `PropertyNotify` an empty append to a property of
+/// `window` causes.
+unsafe fn server_time(
+    xlib: &xlib::Xlib,
+    display: *mut xlib::Display,
+    window: xlib::Window,
+) -> xlib::Time {
+    (xlib.XSelectInput)(display, window, xlib::PropertyChangeMask);
+    (xlib.XChangeProperty)(
+        display,
+        window,
+        xlib::XA_WM_NAME,
+        xlib::XA_STRING,
+        8,
+        xlib::PropModeAppend,
+        ptr::null(),
+        0,
+    );
+    let mut event: xlib::XEvent = std::mem::zeroed();
+    (xlib.XWindowEvent)(
+        display,
+        window,
+        xlib::PropertyChangeMask,
+        ptr::addr_of_mut!(event),
+    );
+    (xlib.XSelectInput)(display, window, xlib::NoEventMask);
+    event.property.time
+}
+
+/// Whether a window manager, leftwm or another one, owns the selection of the display of
+/// `DISPLAY`.
+#[must_use]
+pub fn running() -> bool {
+    with_display(|xlib, display| unsafe {
+        (xlib.XGetSelectionOwner)(display, wm_selection(xlib, display)) != 0
+    })
+    .unwrap_or_default()
+}
+
+/// Take the selection from the running window manager, which then exits, waiting up to
+/// `timeout` for it to. Returns whether it did, or there was none.
+#[must_use]
+pub fn replace_running(timeout: Duration) -> bool {
+    with_display(|xlib, display| unsafe {
+        let selection = wm_selection(xlib, display);
+        let owner = (xlib.XGetSelectionOwner)(display, selection);
+        if owner == 0 {
+            return true;
+        }
+        (xlib.XSelectInput)(display, owner, xlib::StructureNotifyMask);
+        let root = (xlib.XDefaultRootWindow)(display);
+        let window = (xlib.XCreateSimpleWindow)(display, root, -1, -1, 1, 1, 0, 0, 0);
+        if own_wm_selection(xlib, display, selection, window).is_none() {
+            return false;
+        }
+        (xlib.XSync)(display, xlib::False);
+        let start = Instant::now();
+        let mut event: xlib::XEvent = std::mem::zeroed();
+        let event = ptr::addr_of_mut!(event);
+        while start.elapsed() < timeout {
+            if (xlib.XCheckTypedWindowEvent)(display, owner, xlib::DestroyNotify, event) != 0 {
+                return true;
+            }
+            thread::sleep(Duration::from_millis(20));
+        }
+        false
+    })
+    .unwrap_or_default()
+}
+
+/// Remove the command pipe and socket of the display and the state socket when no leftwm uses
+/// them.
+pub fn remove_stale_files() {
+    if let Some(pipe) = command_pipe() {
+        if !command_pipe_is_read(&pipe) {
+            log::info!("Removing the stale {}", pipe.display());
+            fs::remove_file(pipe).ok();
+        }
+    }
+    let dirs = xdg::BaseDirectories::with_prefix("leftwm").ok();
+    for name in [
+        PathBuf::from("current_state.sock"),
+        CommandSocket::socket_name(),
+        I3Socket::socket_name(),
+    ] {
+        if let Some(socket) = dirs.as_ref().and_then(|dirs| dirs.find_runtime_file(name)) {
+            if UnixStream::connect(&socket).is_err() {
+                log::info!("Removing the stale {}", socket.display());
+                fs::remove_file(socket).ok();
+            }
+        }
+    }
+}
+
+/// The command pipe of the display of `DISPLAY`, if it exists.
+#[must_use]
+pub fn command_pipe() -> Option<PathBuf> {
+    xdg::BaseDirectories::with_prefix("leftwm")
+        .ok()?
+        .find_runtime_file(CommandPipe::pipe_name())
+}
+
+/// Open a command pipe to write commands to. Fails at once rather than block when no leftwm
+/// reads it.
+///
+/// # Errors
+///
+/// Errors if the pipe can't be opened, with `ENXIO` when nothing reads it.
+pub fn open_command_pipe(path: &Path) -> io::Result<File> {
+    let file = OpenOptions::new()
+        .append(true)
+        .custom_flags(nix::fcntl::OFlag::O_NONBLOCK.bits())
+        .open(path)?;
+    // Writes wait for leftwm to read again, as they did before.
+    nix::fcntl::fcntl(
+        file.as_raw_fd(),
+        nix::fcntl::FcntlArg::F_SETFL(nix::fcntl::OFlag::O_APPEND),
+    )
+    .map_err(|err| io::Error::from_raw_os_error(err as i32))?;
+    Ok(file)
+}
+
+/// Whether a process reads the pipe, a regular file counting as read.
+#[must_use]
+pub fn command_pipe_is_read(path: &Path) -> bool {
+    let is_fifo = fs::metadata(path).is_ok_and(|meta| meta.file_type().is_fifo());
+    !is_fifo || open_command_pipe(path).is_ok()
+}
+
+/// Run `f` with a connection to the display of `DISPLAY`, `None` if there is none. X errors are
+/// ignored, as windows may go away while it runs.
+fn with_display<T>(f: impl FnOnce(&xlib::Xlib, *mut xlib::Display) -> T) -> Option<T> {
+    extern "C" fn ignore_errors(_: *mut xlib::Display, _: *mut xlib::XErrorEvent) -> c_int {
+        0
+    }
+    let xlib = xlib::Xlib::open().ok()?;
+    let display = unsafe { (xlib.XOpenDisplay)(ptr::null()) };
+    if display.is_null() {
+        return None;
+    }
+    let result = unsafe {
+        let previous = (xlib.XSetErrorHandler)(Some(ignore_errors));
+        let result = f(&xlib, display);
+        (xlib.XSetErrorHandler)(previous);
+        result
+    };
+    unsafe { (xlib.XCloseDisplay)(display) };
+    Some(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::helpers::test::temp_path;
+
+    #[tokio::test]
+    async fn pipes_without_a_reader_should_be_stale() {
+        let pipe = temp_path().await.unwrap();
+        fs::remove_file(&pipe).unwrap();
+        nix::unistd::mkfifo(&pipe, nix::sys::stat::Mode::S_IRWXU).unwrap();
+        assert!(!command_pipe_is_read(&pipe));
+        assert_eq!(
+            open_command_pipe(&pipe).unwrap_err().raw_os_error(),
+            Some(nix::libc::ENXIO)
+        );
+
+        let _reader = OpenOptions::new()
+            .read(true)
+            .custom_flags(nix::fcntl::OFlag::O_NONBLOCK.bits())
+            .open(&pipe)
+            .unwrap();
+        assert!(command_pipe_is_read(&pipe));
+        fs::remove_file(&pipe).unwrap();
+    }
+}
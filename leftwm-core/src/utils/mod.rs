@@ -1,7 +1,15 @@
 //! Various shared functions that `LeftWM` uses.
 pub mod child_process;
 pub mod command_pipe;
+pub mod command_socket;
+#[cfg(feature = "dbus")]
+pub mod dbus;
+pub mod focus_hooks;
+#[cfg(any(test, feature = "fuzzing"))]
+pub mod fuzzing;
 pub mod helpers;
+pub mod i3_ipc;
+pub mod instance;
 pub mod state_socket;
 pub mod window_updater;
 pub mod xkeysym_lookup;
@@ -1,6 +1,8 @@
 use crate::config::Config;
 use crate::display_servers::DisplayServer;
-use crate::models::{Manager, Tag};
+use crate::layouts::Layout;
+use crate::models::{Manager, TabBar, TagId, Window, WindowHandle, WindowType};
+use crate::DisplayAction;
 
 impl<C: Config, SERVER: DisplayServer> Manager<C, SERVER> {
     /*
@@ -8,25 +10,35 @@ impl<C: Config, SERVER: DisplayServer> Manager<C, SERVER> {
      * based on the new state of the WM
      */
     pub fn update_windows(&mut self) {
-        // set all tagged windows as visible
-        self.state
-            .windows
-            .iter_mut()
-            .for_each(|w| w.set_visible(w.tags.is_empty()));
+        // set all untagged windows as visible, docks only if they aren't hidden
+        let docks_hidden = self.state.docks_hidden;
+        self.state.windows.iter_mut().for_each(|w| {
+            let hidden_dock = docks_hidden && w.r#type == WindowType::Dock;
+            w.set_visible(w.tags.is_empty() && !hidden_dock);
+        });
+
+        self.sync_layouts();
+        self.update_borders();
 
         for ws in &self.state.workspaces {
             let windows = &mut self.state.windows;
             let all_tags = &self.state.tags;
-            let tags: Vec<&Tag> = ws
-                .tags
-                .iter()
-                .filter_map(|tag_id| all_tags.get(*tag_id))
-                .collect();
-            for tag in &tags {
-                tag.update_windows(windows, ws);
+            if let Some(tag) = ws.tags.first().and_then(|tag_id| all_tags.get(*tag_id)) {
+                tag.update_windows(windows, ws, &self.state.custom_layouts);
             }
         }
 
+        self.mark_game_mode_windows();
+        self.update_tab_bars();
+
+        if self.state.honor_size_increments {
+            self.state
+                .windows
+                .iter_mut()
+                .filter(|w| w.visible() && !w.floating() && !w.is_fullscreen())
+                .for_each(Window::snap_to_size_increments);
+        }
+
         self.state
             .windows
             .iter()
@@ -35,4 +47,114 @@ impl<C: Config, SERVER: DisplayServer> Manager<C, SERVER> {
                 println!("{:?}", w);
             });
     }
+
+    /// The regions of the bsp layout and the numbering of monocle follow the windows, which
+    /// needs the tags mutably. The first tag of a workspace lays out the windows of all the tags
+    /// it shows.
+    fn sync_layouts(&mut self) {
+        let focus_order: Vec<WindowHandle> = self
+            .state
+            .focus_manager
+            .window_history
+            .iter()
+            .flatten()
+            .copied()
+            .collect();
+        for ws in self
+            .state
+            .workspaces
+            .iter()
+            .filter(|ws| ws.overview.is_none())
+        {
+            let handles = ws.tiled_windows(&self.state.windows);
+            if let Some(tag) = ws.tags.first().and_then(|id| self.state.tags.get_mut(*id)) {
+                match tag.layout {
+                    Layout::Bsp => tag.sync_bsp(&handles, &focus_order),
+                    Layout::Monocle | Layout::Tabbed => tag.sync_first_window(&handles),
+                    _ => {}
+                }
+            }
+        }
+    }
+
+    /// Give the windows their borders, or the focused one its own width.
+    fn update_borders(&mut self) {
+        if self.state.focused_border_width.is_none() && !self.state.smart_borders {
+            return;
+        }
+        let focused = self
+            .state
+            .focus_manager
+            .window(&self.state.windows)
+            .map(|w| w.handle);
+        // With `smart_borders` a window tiled alone on a workspace has no border to color.
+        let alone: Vec<WindowHandle> = if self.state.smart_borders {
+            self.state
+                .workspaces
+                .iter()
+                .map(|ws| ws.tiled_windows(&self.state.windows))
+                .filter(|tiled| tiled.len() == 1)
+                .flatten()
+                .collect()
+        } else {
+            vec![]
+        };
+        let border_width = self.state.border_width;
+        let focused_width = self.state.focused_border_width.unwrap_or(border_width);
+        self.state
+            .windows
+            .iter_mut()
+            .filter(|w| w.r#type == WindowType::Normal)
+            .for_each(|w| {
+                w.border = if alone.contains(&w.handle) {
+                    0
+                } else if Some(w.handle) == focused {
+                    focused_width
+                } else {
+                    border_width
+                };
+            });
+    }
+
+    /// Mark the windows of the workspaces in game mode for the compositor.
+    fn mark_game_mode_windows(&mut self) {
+        let game_tags: Vec<TagId> = self
+            .state
+            .workspaces
+            .iter()
+            .filter(|ws| ws.game_mode)
+            .flat_map(|ws| ws.tags.clone())
+            .collect();
+        for window in &mut self.state.windows {
+            let game_mode = window.tags.iter().any(|tag| game_tags.contains(tag));
+            if window.game_mode != game_mode {
+                window.game_mode = game_mode;
+                let act = DisplayAction::SetGameMode(window.handle, game_mode);
+                self.state.actions.push_back(act);
+            }
+        }
+    }
+
+    /// Ask for the tab bars of the workspaces with the `Tabbed` layout to be drawn, if they
+    /// changed.
+    fn update_tab_bars(&mut self) {
+        let tab_bars: Vec<TabBar> = self
+            .state
+            .workspaces
+            .iter()
+            .filter_map(|ws| {
+                let tag = self.state.tags.get(*ws.tags.first()?)?;
+                if tag.layout != Layout::Tabbed || ws.overview.is_some() {
+                    return None;
+                }
+                TabBar::new(ws, tag, &self.state.windows)
+            })
+            .collect();
+        if tab_bars != self.state.tab_bars {
+            self.state.tab_bars.clone_from(&tab_bars);
+            self.state
+                .actions
+                .push_back(DisplayAction::UpdateTabBars(tab_bars));
+        }
+    }
 }
@@ -8,6 +8,7 @@ use std::io::Write;
 use std::os::unix::fs::PermissionsExt;
 use std::path::Path;
 use std::path::PathBuf;
+use std::process::Command;
 use xdg::BaseDirectories;
 
 #[tokio::main]
@@ -42,28 +43,72 @@ async fn main() -> Result<()> {
         git_version::git_version!(fallback = option_env!("GIT_HASH").unwrap_or("NONE"))
     );
     println!("\x1b[0;94m::\x1b[0m Loading configuration . . .");
-    match load_from_file(config_file, verbose) {
+    let config_ok = match load_from_file(config_file, verbose) {
         Ok(config) => {
             println!("\x1b[0;92m    -> Configuration loaded OK \x1b[0m");
             if verbose {
                 dbg!(&config);
             }
             config.check_mousekey(verbose);
-            config.check_workspace_ids(verbose);
-            config.check_keybinds(verbose);
+            let checks = [
+                config.check_workspace_ids(verbose),
+                config.check_layouts(verbose),
+                check_outputs(&config, verbose),
+                config.check_keybinds(verbose),
+            ];
+            checks.iter().all(|ok| *ok)
         }
         Err(e) => {
-            println!("Configuration failed. Reason: {:?}", e);
+            println!(
+                "\x1b[1;91mERROR: Configuration failed to load: {:#}\x1b[0m",
+                e
+            );
+            false
         }
-    }
+    };
     println!("\x1b[0;94m::\x1b[0m Checking environment . . .");
     check_elogind(verbose)?;
     println!("\x1b[0;94m::\x1b[0m Checking theme . . .");
     check_theme(verbose);
 
+    // Fail, so that login scripts can keep a broken config from starting leftwm.
+    if !config_ok {
+        bail!("The configuration has errors, see above.");
+    }
     Ok(())
 }
 
+/// Checks the outputs of the window rules against those `xrandr` reports, skipped when it can't
+/// be run, e.g. outside of X.
+fn check_outputs(config: &Config, verbose: bool) -> bool {
+    println!("\x1b[0;94m::\x1b[0m Checking outputs . . .");
+    match Command::new("xrandr").arg("--listmonitors").output() {
+        Ok(output) if output.status.success() => {
+            let listing = String::from_utf8_lossy(&output.stdout);
+            let ok = config.check_outputs(&parse_outputs(&listing), verbose);
+            if ok {
+                println!("\x1b[0;92m    -> Outputs OK \x1b[0m");
+            }
+            ok
+        }
+        _ => {
+            println!("\x1b[1;93mWARN: Couldn't query xrandr, the outputs are not checked.\x1b[0m");
+            true
+        }
+    }
+}
+
+/// The output names in `xrandr --listmonitors`, which come last on lines like
+/// ` 0: +*DP-1 1920/527x1080/296+0+0  DP-1`.
+fn parse_outputs(listing: &str) -> Vec<String> {
+    listing
+        .lines()
+        .skip(1)
+        .filter_map(|line| line.split_whitespace().last())
+        .map(str::to_owned)
+        .collect()
+}
+
 /// Loads configuration from either specified file (preferred) or default.
 /// # Errors
 ///
@@ -77,18 +122,18 @@ pub fn load_from_file(fspath: Option<&str>, verbose: bool) -> Result<Config> {
             PathBuf::from(fspath)
         }
 
-        None => BaseDirectories::with_prefix("leftwm")?.place_config_file("config.toml")?,
+        None => leftwm::config_file()?,
     };
     if verbose {
         dbg!(&config_filename);
     }
     if Path::new(&config_filename).exists() {
-        let contents = fs::read_to_string(config_filename)?;
         if verbose {
-            dbg!(&contents);
+            dbg!(fs::read_to_string(&config_filename)?);
         }
 
-        let config = toml::from_str(&contents)?;
+        let mut config: Config = leftwm::read_config_file(&config_filename)?;
+        config.interpolate();
         Ok(config)
     } else {
         let config = Config::default();
@@ -287,3 +332,132 @@ fn check_theme_toml(filepath: PathBuf, verbose: bool) -> Result<PathBuf> {
         bail!("No `theme.toml` found at path: {}", filepath.display());
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use leftwm_core::Command;
+
+    #[test]
+    fn outputs_should_be_parsed_from_the_monitor_listing() {
+        let listing = "Monitors: 2\n 0: +*DP-1 1920/527x1080/296+0+0  DP-1\n 1: +HDMI-1 1280/340x1024/270+1920+0  HDMI-1\n";
+        assert_eq!(parse_outputs(listing), vec!["DP-1", "HDMI-1"]);
+    }
+
+    #[test]
+    fn configs_should_load_in_the_format_of_their_extension() {
+        let dir = tempfile::tempdir().unwrap();
+        let config = Config::default();
+        let files = [
+            ("config.ron", ron::to_string(&config).unwrap()),
+            ("config.yaml", serde_yaml::to_string(&config).unwrap()),
+            ("config.toml", toml::to_string(&config).unwrap()),
+        ];
+        for (name, contents) in files {
+            let path = dir.path().join(name);
+            fs::write(&path, contents).unwrap();
+            let loaded = load_from_file(path.to_str(), false).unwrap();
+            assert_eq!(loaded.keybind.len(), config.keybind.len(), "{}", name);
+        }
+
+        let path = dir.path().join("broken.yml");
+        fs::write(&path, "modkey: [").unwrap();
+        let err = load_from_file(path.to_str(), false).unwrap_err();
+        assert!(format!("{:#}", err).contains("broken.yml"));
+    }
+
+    #[test]
+    fn included_files_should_be_overridden_by_later_ones() {
+        let dir = tempfile::tempdir().unwrap();
+        let write =
+            |name: &str, contents: &str| fs::write(dir.path().join(name), contents).unwrap();
+        write(
+            "keybinds.toml",
+            "modkey = \"Mod1\"\n[[keybind]]\ncommand = \"CloseWindow\"\nkey = \"q\"\n",
+        );
+        write(
+            "workspaces.yaml",
+            "modkey: Mod3\nworkspaces:\n  - {x: 0, y: 0, width: 800, height: 600}\n",
+        );
+        write(
+            "config.toml",
+            "include = [\"keybinds.toml\", \"workspaces.yaml\"]\ndisable_tile_drag = true\n",
+        );
+        let path = dir.path().join("config.toml");
+        let config = load_from_file(path.to_str(), false).unwrap();
+        assert_eq!(config.modkey, "Mod3");
+        assert!(config.disable_tile_drag);
+        assert_eq!(config.keybind.len(), 1);
+        assert_eq!(config.workspaces.map(|wss| wss.len()), Some(1));
+
+        write("keybinds.toml", "include = [\"config.toml\"]\n");
+        let err = load_from_file(path.to_str(), false).unwrap_err();
+        assert!(format!("{:#}", err).contains("includes itself"));
+    }
+
+    #[test]
+    fn values_of_the_machine_should_be_interpolated() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("config.toml");
+        let config = r#"
+            tags = ["$(echo code)", "cost: $$5", "$UNSET_IN_TESTS-${UNSET_IN_TESTS}end"]
+            [[window_rules]]
+            window_class = "st"
+            output_name = "$(printf 'DP-%s' $((1 + 1)))"
+            [[keybind]]
+            command = "Execute"
+            value = "notify-send $(date)"
+            key = "d"
+        "#;
+        fs::write(&path, config).unwrap();
+        let config = load_from_file(path.to_str(), false).unwrap();
+        let labels: Vec<&str> = config
+            .tags
+            .iter()
+            .flatten()
+            .map(|tag| tag.label())
+            .collect();
+        assert_eq!(labels, vec!["code", "cost: $5", "-end"]);
+        let output = config.window_rules.unwrap()[0].output_name.clone();
+        assert_eq!(output.as_deref(), Some("DP-2"));
+        assert_eq!(config.keybind[0].value, "notify-send $(date)");
+    }
+
+    #[test]
+    fn keybinds_should_take_tags_by_label() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("config.toml");
+        let config = r#"
+            tags = ["web", "code", "chat"]
+            [[keybind]]
+            command = "GotoTag"
+            value = "code"
+            key = "c"
+            [[keybind]]
+            command = "MoveToTag"
+            value = "3"
+            key = "3"
+            [[keybind]]
+            command = "GotoTag"
+            value = "mail"
+            key = "m"
+        "#;
+        fs::write(&path, config).unwrap();
+        let config = load_from_file(path.to_str(), false).unwrap();
+        let commands: Vec<_> = config
+            .keybind
+            .iter()
+            .map(|keybind| {
+                keybind
+                    .try_convert_to_core_keybind(&config)
+                    .map(|k| k.command)
+            })
+            .collect();
+        assert!(matches!(commands[0], Ok(Command::GoToTag { tag: 2, .. })));
+        assert!(matches!(
+            commands[1],
+            Ok(Command::SendWindowToTag { tag: 3, .. })
+        ));
+        assert!(commands[2].is_err());
+    }
+}
@@ -1,10 +1,21 @@
-use anyhow::{Context, Result};
+use anyhow::{bail, Context, Result};
 use clap::{App, Arg};
-use leftwm_core::CommandPipe;
-use std::fs::OpenOptions;
+use leftwm_core::utils::command_pipe::{answer_file, COMMANDS, PROTOCOL_VERSION};
+use leftwm_core::utils::command_socket::Response;
+use leftwm_core::utils::instance;
+use leftwm_core::{CommandPipe, CommandSocket};
+use std::fs;
 use std::io::prelude::*;
+use std::io::BufReader;
+use std::os::unix::net::UnixStream;
+use std::path::Path;
+use std::thread;
+use std::time::Duration;
 use xdg::BaseDirectories;
 
+/// How long leftwm may take to answer a command on its socket.
+const ANSWER_TIMEOUT: Duration = Duration::from_secs(5);
+
 #[tokio::main]
 async fn main() -> Result<()> {
     let matches = App::new("LeftWM Command")
@@ -25,69 +36,44 @@ async fn main() -> Result<()> {
         )
         .get_matches();
 
-    let file_name = CommandPipe::pipe_name();
-    let file_path = BaseDirectories::with_prefix("leftwm")?
-        .find_runtime_file(&file_name)
-        .with_context(|| format!("ERROR: Couldn't find {}", file_name.display()))?;
-    let mut file = OpenOptions::new()
-        .append(true)
-        .open(file_path)
-        .with_context(|| format!("ERROR: Couldn't open {}", file_name.display()))?;
-    if let Some(commands) = matches.values_of("command") {
-        for command in commands {
-            if let Err(e) = writeln!(file, "{}", command) {
-                eprintln!(" ERROR: Couldn't write to commands.pipe: {}", e);
+    let dirs = BaseDirectories::with_prefix("leftwm")?;
+    let commands: Vec<&str> = matches.values_of("command").into_iter().flatten().collect();
+    // A leftwm without the command socket is only sent the commands, without answers.
+    let socket = dirs
+        .find_runtime_file(CommandSocket::socket_name())
+        .and_then(|path| UnixStream::connect(path).ok());
+    match socket {
+        Some(socket) => {
+            let failed = send_to_socket(socket, &commands)?;
+            if failed > 0 {
+                std::process::exit(1);
             }
         }
+        None => send_to_pipe(&dirs, &commands)?,
     }
 
     let command_list = matches.occurrences_of("list") == 1;
 
     if command_list {
+        println!("\n        Available Commands:\n");
+        println!("        Commands without arguments:\n");
+        for command in COMMANDS.iter().filter(|c| c.args.is_empty()) {
+            println!("        {}", command.name);
+        }
         println!(
             "
-        Available Commands:
-
-        Commands without arguments:
-
-        UnloadTheme
-        SoftReload
-        ToggleFullScreen
-        ToggleSticky
-        SwapScreens
-        MoveWindowToLastWorkspace
-        MoveWindowToNextWorkspace
-        MoveWindowToPreviousWorkspace
-        FloatingToTile
-        TileToFloating
-        ToggleFloating
-        MoveWindowUp
-        MoveWindowDown
-        MoveWindowTop
-        FocusWindowUp
-        FocusWindowDown
-        FocusWindowTop
-        FocusNextTag
-        FocusPreviousTag
-        FocusWorkspaceNext
-        FocusWorkspacePrevious
-        NextLayout
-        PreviousLayout
-        RotateTag
-        CloseWindow
-
         Commands with arguments:
             Use quotations for the command and arguments, like this:
             leftwm-command \"<command> <args>\"
-
-        LoadTheme              Args: <Path_to/theme.toml>
-        ToggleScratchPad       Args: <ScratchpadName>
-        SendWorkspaceToTag     Args: <workspaxe_index> <tag_index> (int)
-        SendWindowToTag        Args: <tag_index> (int)
-        SetLayout              Args: <LayoutName>
-        SetMarginMultiplier    Args: <multiplier-value> (float)
-        FocusWindow            Args: <WindowClass> or <visible-window-index> (int)
-
+            Commands can also be given in JSON, with any of their arguments:
+            leftwm-command '{{\"SendWindowToTag\":{{\"window\":null,\"tag\":3}}}}'
+"
+        );
+        for command in COMMANDS.iter().filter(|c| !c.args.is_empty()) {
+            println!("        {:<30} Args: {}", command.name, command.args);
+        }
+        println!(
+            "
         For more information please visit:
         https://github.com/leftwm/leftwm/wiki/External-Commands
          "
@@ -95,3 +81,84 @@ async fn main() -> Result<()> {
     }
     Ok(())
 }
+
+/// Send the commands one at a time, printing the answers to queries and the errors. Returns how
+/// many of the commands failed.
+fn send_to_socket(socket: UnixStream, commands: &[&str]) -> Result<usize> {
+    socket.set_read_timeout(Some(ANSWER_TIMEOUT))?;
+    let mut writer = socket.try_clone()?;
+    let mut reader = BufReader::new(socket);
+    let mut failed = 0;
+    for command in commands {
+        writeln!(writer, "{}", command).context("ERROR: Couldn't write to the command socket")?;
+        let mut line = String::new();
+        reader
+            .read_line(&mut line)
+            .with_context(|| format!("ERROR: leftwm didn't answer {}", command))?;
+        let answer = match serde_json::from_str(&line) {
+            Ok(Response::Done) => None,
+            Ok(Response::WindowAt(answer)) => Some(serde_json::to_string(&answer)?),
+            Ok(Response::Windows(answer)) => Some(serde_json::to_string(&answer)?),
+            Ok(Response::State(answer)) => Some(serde_json::to_string(&answer)?),
+            Ok(Response::Error(reason)) => {
+                eprintln!("ERROR: {}: {}", command, reason);
+                failed += 1;
+                None
+            }
+            Err(_) => bail!("ERROR: leftwm didn't answer {}", command),
+        };
+        if let Some(answer) = answer {
+            println!("{}", answer);
+        }
+    }
+    Ok(failed)
+}
+
+/// Write the commands to the command pipe, which doesn't tell whether they worked.
+fn send_to_pipe(dirs: &BaseDirectories, commands: &[&str]) -> Result<()> {
+    let file_name = CommandPipe::pipe_name();
+    let file_path = dirs
+        .find_runtime_file(&file_name)
+        .with_context(|| format!("ERROR: Couldn't find {}", file_name.display()))?;
+    let mut file = instance::open_command_pipe(&file_path).with_context(|| {
+        format!(
+            "ERROR: Couldn't open {}, is leftwm running?",
+            file_name.display()
+        )
+    })?;
+    if commands.is_empty() {
+        return Ok(());
+    }
+    if let Err(e) = writeln!(file, "Version {}", PROTOCOL_VERSION) {
+        eprintln!(" ERROR: Couldn't write to commands.pipe: {}", e);
+    }
+    for command in commands {
+        // Queries answer in a file, which is removed first so an old answer isn't printed.
+        let answer = match answer_file(command) {
+            Some(name) => Some(dirs.place_runtime_file(name)?),
+            None => None,
+        };
+        if let Some(answer) = &answer {
+            fs::remove_file(answer).ok();
+        }
+        if let Err(e) = writeln!(file, "{}", command) {
+            eprintln!(" ERROR: Couldn't write to commands.pipe: {}", e);
+        }
+        if let Some(answer) = &answer {
+            print_answer(answer)?;
+        }
+    }
+    Ok(())
+}
+
+/// Wait a second at most for leftwm to answer a query, and print the answer.
+fn print_answer(path: &Path) -> Result<()> {
+    for _ in 0..100 {
+        if let Ok(answer) = fs::read_to_string(path) {
+            println!("{}", answer);
+            return Ok(());
+        }
+        thread::sleep(Duration::from_millis(10));
+    }
+    bail!("leftwm didn't answer {}", path.display())
+}
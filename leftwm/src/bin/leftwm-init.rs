@@ -0,0 +1,156 @@
+//! Writes a starter `config.toml` for this machine, with a workspace per monitor reported by
+//! `xrandr` and keybinds for the terminal and launcher found in `PATH`.
+use anyhow::{bail, Context, Result};
+use clap::{App, Arg};
+use leftwm::{is_program_in_path, BaseCommand, Config};
+use leftwm_core::config::Workspace;
+use std::fs;
+use std::process::Command;
+use xdg::BaseDirectories;
+
+/// Launchers to bind to `modkey + p`, most preferred first.
+const LAUNCHERS: &[(&str, &str)] = &[
+    ("rofi", "rofi -show drun"),
+    ("dmenu_run", "dmenu_run"),
+    ("bemenu-run", "bemenu-run"),
+];
+
+fn main() -> Result<()> {
+    let matches = App::new("LeftWM Init")
+        .author("Lex Childs <lex.childs@gmail.com>")
+        .version(env!("CARGO_PKG_VERSION"))
+        .about("writes a starter configuration for the detected monitors and programs")
+        .arg(
+            Arg::with_name("force")
+                .short("f")
+                .long("force")
+                .help("Overwrite an existing config.toml"),
+        )
+        .arg(
+            Arg::with_name("print")
+                .short("p")
+                .long("print")
+                .help("Print the configuration instead of writing it"),
+        )
+        .get_matches();
+
+    let config = starter_config(&detect_workspaces());
+    let toml = to_toml(&config)?;
+    if matches.is_present("print") {
+        println!("{}", toml);
+        return Ok(());
+    }
+
+    // The TOML one is read before a config in another format, which --force leaves in place.
+    let existing = leftwm::config_file()?;
+    if existing.exists() && !matches.is_present("force") {
+        bail!(
+            "{} already exists, use --force to overwrite it",
+            existing.display()
+        );
+    }
+    let path =
+        BaseDirectories::with_prefix("leftwm")?.place_config_file(leftwm::CONFIG_FILES[0])?;
+    fs::write(&path, toml).with_context(|| format!("Couldn't write {}", path.display()))?;
+    println!("Wrote {}", path.display());
+    println!("Check it with `leftwm check`.");
+    Ok(())
+}
+
+/// The default config, with the given workspaces and the launcher found in `PATH`. The default
+/// config already picks the terminal.
+fn starter_config(workspaces: &[Workspace]) -> Config {
+    let mut config = Config {
+        workspaces: Some(workspaces.to_vec()),
+        ..Config::default()
+    };
+    let launcher = LAUNCHERS
+        .iter()
+        .find(|(program, _)| is_program_in_path(program))
+        .map(|(_, command)| *command);
+    if let Some(launcher) = launcher {
+        for keybind in &mut config.keybind {
+            if keybind.command == BaseCommand::Execute && keybind.value == "dmenu_run" {
+                keybind.value = launcher.to_owned();
+            }
+        }
+    }
+    config
+}
+
+/// Serialize through a `toml::Value`, which puts plain values before the tables, as the
+/// workspaces otherwise end up in front of values following them in `Config`.
+fn to_toml(config: &Config) -> Result<String> {
+    Ok(toml::to_string(&toml::Value::try_from(config)?)?)
+}
+
+/// A workspace for each monitor `xrandr` reports. Empty if it can't be run, leaving leftwm to
+/// use the screens it finds at startup.
+fn detect_workspaces() -> Vec<Workspace> {
+    match Command::new("xrandr").arg("--listmonitors").output() {
+        Ok(output) if output.status.success() => {
+            parse_monitors(&String::from_utf8_lossy(&output.stdout))
+        }
+        _ => {
+            eprintln!("Couldn't query xrandr, the screens will be detected at startup.");
+            vec![]
+        }
+    }
+}
+
+/// Parse the output of `xrandr --listmonitors`, whose lines look like
+/// ` 0: +*DP-1 1920/527x1080/296+0+0  DP-1`.
+fn parse_monitors(listing: &str) -> Vec<Workspace> {
+    listing
+        .lines()
+        .filter_map(|line| line.split_whitespace().nth(2))
+        .filter_map(parse_geometry)
+        .collect()
+}
+
+/// Parse a `width/mmxheight/mm+x+y` geometry.
+fn parse_geometry(geometry: &str) -> Option<Workspace> {
+    let (size, position) = geometry.split_once('+')?;
+    let (width, height) = size.split_once('x')?;
+    let (x, y) = position.split_once('+')?;
+    let physical = |value: &str| value.split('/').next()?.parse().ok();
+    Some(Workspace {
+        width: physical(width)?,
+        height: physical(height)?,
+        x: x.parse().ok()?,
+        y: y.parse().ok()?,
+        ..Workspace::default()
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_xrandr_monitors() {
+        let listing = "Monitors: 2
+ 0: +*DP-1 2560/597x1440/336+0+0  DP-1
+ 1: +HDMI-1 1920/527x1080/296+2560+360  HDMI-1
+";
+        let workspaces = parse_monitors(listing);
+        assert_eq!(workspaces.len(), 2);
+        assert_eq!(
+            (
+                workspaces[1].x,
+                workspaces[1].y,
+                workspaces[1].width,
+                workspaces[1].height
+            ),
+            (2560, 360, 1920, 1080)
+        );
+    }
+
+    #[test]
+    fn starter_config_is_valid() {
+        let config = starter_config(&parse_monitors(" 0: +*DP-1 1920/527x1080/296+0+0  DP-1"));
+        let toml = to_toml(&config).unwrap();
+        let parsed: Config = toml::from_str(&toml).unwrap();
+        assert_eq!(parsed.workspaces.map(|ws| ws.len()), Some(1));
+    }
+}
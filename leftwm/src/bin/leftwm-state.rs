@@ -1,5 +1,5 @@
 use clap::{value_t, App, Arg};
-use leftwm_core::errors::Result;
+use leftwm_core::errors::{LeftError, Result};
 use leftwm_core::models::dto::{DisplayState, ManagerState};
 use std::ffi::OsStr;
 use std::path::Path;
@@ -48,10 +48,11 @@ async fn main() -> Result<()> {
                 .help("Print new lines in the output"),
         )
         .arg(
-            Arg::with_name("quit")
+            Arg::with_name("once")
                 .short("q")
-                .long("quit")
-                .help("Prints the state once and quits"),
+                .long("once")
+                .visible_alias("quit")
+                .help("Prints the current state once and exits, for scripts and prompts"),
         )
         .get_matches();
 
@@ -65,7 +66,7 @@ async fn main() -> Result<()> {
     };
 
     let mut stream_reader = stream_reader().await?;
-    let once = matches.occurrences_of("quit") == 1;
+    let once = matches.is_present("once");
     let newline = matches.occurrences_of("newline") == 1;
 
     if let Some(template_file) = template_file {
@@ -79,9 +80,9 @@ async fn main() -> Result<()> {
             .parse(&template_str)
             .expect("Unable to parse template");
         while let Some(line) = stream_reader.next_line().await? {
-            let _droppable = template_handler(&template, newline, ws_num, &line);
+            let result = template_handler(&template, newline, ws_num, &line);
             if once {
-                break;
+                return result;
             }
         }
     } else if let Some(string_literal) = string_literal {
@@ -91,20 +92,24 @@ async fn main() -> Result<()> {
             .parse(string_literal)
             .expect("Unable to parse template");
         while let Some(line) = stream_reader.next_line().await? {
-            let _droppable = template_handler(&template, newline, ws_num, &line);
+            let result = template_handler(&template, newline, ws_num, &line);
             if once {
-                break;
+                return result;
             }
         }
     } else {
         while let Some(line) = stream_reader.next_line().await? {
-            let _droppable2 = raw_handler(&line);
+            let result = raw_handler(&line);
             if once {
-                break;
+                return result;
             }
         }
     }
 
+    // leftwm sends its state as soon as it accepts, so nothing means it went away.
+    if once {
+        return Err(LeftError::StreamError);
+    }
     Ok(())
 }
 
@@ -1,30 +1,74 @@
-use leftwm_core::{Manager, XlibDisplayServer};
+use clap::{App, Arg};
+use leftwm_core::{Backend, DisplayServer, Manager, XlibDisplayServer};
 use slog::{o, Drain};
 use std::panic;
+use std::str::FromStr;
 
 fn main() {
     //let _log_guard = setup_logfile();
     let _log_guard = setup_logging();
     log::info!("leftwm-worker booted!");
 
-    let completed = panic::catch_unwind(|| {
-        let rt = tokio::runtime::Runtime::new().expect("ERROR: couldn't init Tokio runtime");
-        let _rt_guard = rt.enter();
-
-        let config = leftwm::load();
-
-        let manager = Manager::<leftwm::Config, XlibDisplayServer>::new(config);
-        manager.register_child_hook();
+    let matches = App::new("LeftWM Worker")
+        .arg(
+            Arg::with_name("backend")
+                .long("backend")
+                .takes_value(true)
+                .help("The display server to run on, detected from the session if not given"),
+        )
+        .arg(
+            Arg::with_name("reloaded")
+                .long("reloaded")
+                .hidden(true)
+                .help("Started again by leftwm after a reload, rather than with the session"),
+        )
+        .get_matches();
+    let reloaded = matches.is_present("reloaded");
+    let backend = match matches.value_of("backend").map(Backend::from_str) {
+        Some(Ok(backend)) => backend,
+        Some(Err(err)) => {
+            log::error!("{}", err);
+            std::process::exit(leftwm::WORKER_EXIT_STATUS);
+        }
+        None => Backend::detect(),
+    };
+    log::info!("Running on {:?}", backend);
 
-        rt.block_on(manager.event_loop());
-    });
+    let completed = match backend {
+        Backend::X11 => panic::catch_unwind(|| run::<XlibDisplayServer>(reloaded)),
+        Backend::Wayland => {
+            // Exit for good rather than have leftwm restart the worker over and over.
+            log::error!("There is no Wayland backend yet, start leftwm from an X11 session");
+            std::process::exit(leftwm::WORKER_EXIT_STATUS);
+        }
+    };
 
     match completed {
-        Ok(_) => log::info!("Completed"),
+        Ok(true) => {
+            log::info!("Exiting");
+            std::process::exit(leftwm::WORKER_EXIT_STATUS);
+        }
+        Ok(false) => log::info!("Completed"),
         Err(err) => log::error!("Completed with error: {:?}", err),
     }
 }
 
+/// Run the window manager on a display server, returning whether it was asked to exit.
+fn run<SERVER: DisplayServer>(reloaded: bool) -> bool {
+    let rt = tokio::runtime::Runtime::new().expect("ERROR: couldn't init Tokio runtime");
+    let _rt_guard = rt.enter();
+
+    let config = leftwm::load();
+
+    let mut manager = Manager::<leftwm::Config, SERVER>::new(config);
+    manager.register_child_hook();
+    if reloaded {
+        manager.restarted();
+    }
+
+    rt.block_on(manager.event_loop())
+}
+
 // Very basic logging used when developing.
 // outputs to /tmp/leftwm/leftwm-XXXXXXXXXXXX.log
 #[allow(dead_code)]
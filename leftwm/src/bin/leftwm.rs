@@ -1,17 +1,23 @@
 //! Starts leftwm programs.
 //!
-//! If no arguments are passed, starts `leftwm-worker`. If arguments are passed, starts
-//! `leftwm-{check, command, state, theme}` as specified, and passes along any extra arguments.
+//! If no arguments are passed, starts `leftwm-worker`, on the display server given by `--backend`
+//! or detected from the session, unless a window manager already runs there and `--replace`
+//! isn't passed. If other arguments are passed, starts
+//! `leftwm-{check, command, init, state, theme}` as specified, and passes along any extra arguments.
 
-use clap::{crate_version, App, AppSettings, SubCommand};
+use clap::{crate_version, App, AppSettings, Arg, SubCommand};
 use leftwm_core::child_process::{self, Nanny};
+use leftwm_core::utils::instance;
+use leftwm_core::Backend;
 use std::collections::BTreeMap;
 use std::env;
 use std::process::{exit, Command};
+use std::str::FromStr;
 use std::sync::{
     atomic::{AtomicBool, Ordering},
     Arc,
 };
+use std::time::Duration;
 
 fn main() {
     let mut subcommands = BTreeMap::new();
@@ -19,15 +25,33 @@ fn main() {
     // This is a complete list of accepted subcommands. To add a new one, add a new `insert()` here.
     subcommands.insert("check", "Check syntax of the configuration file");
     subcommands.insert("command", "Send external commands to LeftWM");
+    subcommands.insert("init", "Write a starter configuration for this machine");
     subcommands.insert("state", "Print the current state of LeftWM");
     subcommands.insert("theme", "Manage LeftWM themes");
 
     let subcommand_names: Vec<&str> = subcommands.keys().copied().collect();
 
-    let args: Vec<String> = env::args().collect();
+    let mut args: Vec<String> = env::args().collect();
+
+    // `--replace` goes along with starting leftwm, the subcommands get their arguments untouched.
+    let replace = match args.iter().position(|arg| arg == "--replace") {
+        Some(index) if !subcommand_names.contains(&args[1].as_str()) => {
+            args.remove(index);
+            true
+        }
+        _ => false,
+    };
+
+    let worker_args = match backend_args(&args) {
+        Ok(worker_args) => worker_args,
+        Err(err) => {
+            eprintln!("{}", err);
+            exit(1);
+        }
+    };
 
     // If called with arguments, attempt to execute a subcommand.
-    if args.len() > 1 {
+    if args.len() > 1 && worker_args.is_none() {
         match execute_subcommand(&args, &subcommand_names) {
             // Subcommand executed. Exit success.
             Some(true) => exit(0),
@@ -42,7 +66,20 @@ fn main() {
         unreachable!();
     }
 
-    // If _not_ invoked with a subcommand, start leftwm.
+    // If _not_ invoked with a subcommand, start leftwm, unless it or another window manager
+    // already runs on the display.
+    if instance::running() {
+        if !replace {
+            eprintln!("A window manager already runs on this display, use --replace to replace it");
+            exit(1);
+        }
+        if !instance::replace_running(Duration::from_secs(5)) {
+            eprintln!("The running window manager didn't exit, not starting leftwm");
+            exit(1);
+        }
+    }
+    instance::remove_stale_files();
+
     if let Ok(current_exe) = std::env::current_exe() {
         // Boot everything WM agnostic or LeftWM related in ~/.config/autostart
         env::set_var("XDG_CURRENT_DESKTOP", "LeftWM");
@@ -54,19 +91,27 @@ fn main() {
         // Fix for Java apps so they repaint correctly
         env::set_var("_JAVA_AWT_WM_NONREPARENTING", "1");
 
+        // Started here so they are run only once, and reaped along with the autostart programs.
+        for cmd in &leftwm::startup_commands() {
+            child_process::exec_shell(cmd, &mut children);
+        }
+
         let worker_path = current_exe.with_file_name("leftwm-worker");
 
+        let mut reloaded = false;
         loop {
             let mut worker = Command::new(&worker_path)
+                .args(worker_args.iter().flatten())
+                .args(reloaded.then_some("--reloaded"))
                 .spawn()
                 .expect("failed to start leftwm");
+            reloaded = true;
 
             // Wait until worker exits.
-            while worker
-                .try_wait()
-                .expect("failed to wait on worker")
-                .is_none()
-            {
+            let status = loop {
+                if let Some(status) = worker.try_wait().expect("failed to wait on worker") {
+                    break status;
+                }
                 // Not worker, then it might be autostart programs.
                 children.reap();
                 // Wait for SIGCHLD signal flag to be set.
@@ -74,6 +119,11 @@ fn main() {
                     nix::unistd::pause();
                 }
                 // Either worker or autostart program exited.
+            };
+
+            // The worker only exits with this status when asked to, everything else restarts it.
+            if status.code() == Some(leftwm::WORKER_EXIT_STATUS) {
+                exit(0);
             }
 
             // TODO: either add more details or find a better workaround.
@@ -89,6 +139,23 @@ fn main() {
     }
 }
 
+/// The arguments to pass on to `leftwm-worker` when leftwm is started with just a `--backend`,
+/// checked here so a wrong one doesn't have the worker fail over and over.
+///
+/// # Errors
+///
+/// Errors if the backend isn't known or is missing.
+fn backend_args(args: &[String]) -> Result<Option<Vec<String>>, String> {
+    let backend = match args {
+        [_, flag, backend] if flag == "--backend" => backend.as_str(),
+        [_, flag] if flag.starts_with("--backend=") => &flag["--backend=".len()..],
+        [_, flag] if flag == "--backend" => return Err("--backend needs a value".to_string()),
+        _ => return Ok(None),
+    };
+    Backend::from_str(backend).map_err(|err| err.to_string())?;
+    Ok(Some(vec!["--backend".to_string(), backend.to_string()]))
+}
+
 /// Executes a subcommand.
 ///
 /// If a valid subcommand is supplied, executes that subcommand, passing `args` to the program.
@@ -172,7 +239,19 @@ fn handle_help_or_version_flags(args: &[String], subcommands: &BTreeMap<&str, &s
              it is installed.",
         )
         .version(&*version)
-        .settings(&[AppSettings::DisableHelpSubcommand, AppSettings::ColoredHelp]);
+        .settings(&[AppSettings::DisableHelpSubcommand, AppSettings::ColoredHelp])
+        .arg(
+            Arg::with_name("backend")
+                .long("backend")
+                .takes_value(true)
+                .possible_values(&["x11", "wayland"])
+                .help("Display server to run on, detected from the session by default"),
+        )
+        .arg(
+            Arg::with_name("replace")
+                .long("replace")
+                .help("Replace the window manager running on the display"),
+        );
     for (&subcommand, &description) in subcommands {
         app = app.subcommand(SubCommand::with_name(subcommand).about(description));
     }
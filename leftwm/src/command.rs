@@ -14,14 +14,21 @@ use serde::{Deserialize, Serialize};
 pub enum BaseCommand {
     Execute,
     CloseWindow,
+    #[serde(alias = "SwapScreens")]
     SwapTags,
     SoftReload,
     HardReload,
+    Exit,
     ToggleScratchPad,
     ToggleFullScreen,
     ToggleSticky,
+    ToggleZoom,
+    ToggleDock,
     GotoTag,
     ReturnToLastTag,
+    FocusLastTag,
+    TagHistoryBack,
+    TagHistoryForward,
     FloatingToTile,
     TileToFloating,
     ToggleFloating,
@@ -34,6 +41,11 @@ pub enum BaseCommand {
     FocusWindowUp,
     FocusWindowDown,
     FocusWindowTop,
+    FocusWindowInDirection,
+    MoveWindowInDirection,
+    SwapWindowWithMaster,
+    RotateStackForward,
+    RotateStackBackward,
     FocusWorkspaceNext,
     FocusWorkspacePrevious,
     MoveToTag,
@@ -43,13 +55,39 @@ pub enum BaseCommand {
     MouseMoveWindow,
     NextLayout,
     PreviousLayout,
+    ToggleLastLayout,
     SetLayout,
     RotateTag,
     IncreaseMainWidth,
     DecreaseMainWidth,
+    IncreaseStackSplit,
+    DecreaseStackSplit,
+    IncreaseMainCount,
+    DecreaseMainCount,
+    IncreaseWindowWeight,
+    DecreaseWindowWeight,
+    RotateSubtree,
+    FlipSubtree,
     SetMarginMultiplier,
+    IncreaseGaps,
+    DecreaseGaps,
+    ToggleGaps,
+    ToggleGameMode,
+    SetMaxWindowWidth,
     // Custom commands
     UnloadTheme,
     LoadTheme,
     CloseAllOtherWindows,
+    CloseAllOnTag,
+    CountPrefix,
+    LoadProfile,
+    AddTag,
+    RemoveTag,
+    RenameTag,
+    ToggleWindowTag,
+    ToggleTagView,
+    MoveTagToWorkspace,
+    FocusUrgent,
+    ToggleOverview,
+    PickOverviewWindow,
 }
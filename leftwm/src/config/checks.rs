@@ -1,6 +1,8 @@
-use super::Config;
+use super::{BaseCommand, Config};
+use leftwm_core::layouts::Layout;
 use leftwm_core::utils;
-use std::collections::HashSet;
+use std::collections::HashMap;
+use std::str::FromStr;
 
 impl Config {
     pub fn check_mousekey(&self, verbose: bool) {
@@ -21,8 +23,23 @@ impl Config {
         }
     }
 
-    /// Checks defined workspaces to ensure no ID collisions occur.
-    pub fn check_workspace_ids(&self, verbose: bool) {
+    /// Checks defined workspaces, and those of the profiles, to ensure no ID collisions occur.
+    /// Returns whether they don't.
+    #[must_use]
+    pub fn check_workspace_ids(&self, verbose: bool) -> bool {
+        let mut ok = true;
+        for profile in &self.profiles {
+            let ids = crate::get_workspace_ids(&profile.workspaces);
+            if ids.iter().any(Option::is_some)
+                && !(crate::all_ids_some(&ids) && crate::all_ids_unique(&ids))
+            {
+                println!(
+                    "The profile `{}` needs unique IDs for all of its workspaces, or for none.",
+                    profile.name
+                );
+                ok = false;
+            }
+        }
         if let Some(wss) = self.workspaces.as_ref() {
             if verbose {
                 println!("Checking config for valid workspace definitions.");
@@ -32,22 +49,106 @@ impl Config {
                 if crate::all_ids_some(&ids) {
                     if !crate::all_ids_unique(&ids) {
                         println!("Your config.toml contains duplicate workspace IDs. Please assign unique IDs to workspaces. The default config will be used instead.");
+                        return false;
                     }
                 } else {
                     println!("Your config.toml specifies an ID for some but not all workspaces. This can lead to ID collisions and is not allowed. The default config will be used instead.");
+                    return false;
                 }
             }
         }
+        ok
+    }
+
+    /// Checks that every layout named in the config is either built in or one of the
+    /// `custom_layouts`. Returns whether they all are.
+    #[must_use]
+    pub fn check_layouts(&self, verbose: bool) -> bool {
+        if verbose {
+            println!("Checking config for undefined layouts.");
+        }
+        let workspace_layouts = self.workspaces.iter().flatten().flat_map(|ws| {
+            ws.layouts
+                .clone()
+                .unwrap_or_default()
+                .into_iter()
+                .chain(ws.default_layout.clone())
+        });
+        let tag_layouts = self
+            .tags
+            .iter()
+            .flatten()
+            .flat_map(|tag| tag.layouts().into_iter().chain(tag.default_layout()));
+        let keybind_layouts = self
+            .keybind
+            .iter()
+            .filter(|keybind| keybind.command == BaseCommand::SetLayout)
+            .filter_map(|keybind| Layout::from_str(&keybind.value).ok());
+        let mut undefined: Vec<String> = self
+            .layouts
+            .iter()
+            .cloned()
+            .chain(self.default_layout.clone())
+            .chain(workspace_layouts)
+            .chain(tag_layouts)
+            .chain(keybind_layouts)
+            .filter_map(|layout| match layout {
+                Layout::Custom(name) => Some(name.as_str().to_owned()),
+                _ => None,
+            })
+            .filter(|name| !self.custom_layouts.iter().any(|l| l.name == *name))
+            .collect();
+        undefined.sort_unstable();
+        undefined.dedup();
+        for name in &undefined {
+            println!(
+                "\x1b[1;91mERROR: Layout `{}` is neither built in nor one of the custom_layouts\x1b[0m",
+                name
+            );
+        }
+        undefined.is_empty()
+    }
+
+    /// Checks that the outputs the `window_rules` spawn windows on are among the `connected`
+    /// ones. Returns whether they are.
+    #[must_use]
+    pub fn check_outputs(&self, connected: &[String], verbose: bool) -> bool {
+        if verbose {
+            println!(
+                "Checking config for unknown outputs, connected: {:?}",
+                connected
+            );
+        }
+        let mut unknown: Vec<&str> = self
+            .window_rules
+            .iter()
+            .flatten()
+            .filter_map(|rule| rule.output_name.as_deref())
+            .filter(|name| !connected.iter().any(|output| output == name))
+            .collect();
+        unknown.sort_unstable();
+        unknown.dedup();
+        for name in &unknown {
+            println!(
+                "\x1b[1;91mERROR: Output `{}` of a window rule is not connected, the connected ones are: {}\x1b[0m",
+                name,
+                connected.join(", ")
+            );
+        }
+        unknown.is_empty()
     }
 
     /// Check all keybinds to ensure that required values are provided
     /// Checks to see if value is provided (if required)
     /// Checks to see if keys are valid against Xkeysym
+    /// Checks that no two keybinds share a key combination, once `modkey` and `mousekey` are
+    /// resolved
     /// Ideally, we will pass this to the command handler with a dummy config
-    pub fn check_keybinds(&self, verbose: bool) {
+    #[must_use]
+    pub fn check_keybinds(&self, verbose: bool) -> bool {
         let mut returns = Vec::new();
         println!("\x1b[0;94m::\x1b[0m Checking keybinds . . .");
-        let mut bindings = HashSet::new();
+        let mut bindings = HashMap::new();
         for keybind in &self.keybind {
             if verbose {
                 println!("Keybind: {:?} {}", keybind, keybind.value.is_empty());
@@ -73,7 +174,19 @@ impl Config {
             }
 
             modkey.sort_unstable();
-            if let Some(conflict_key) = bindings.replace((modkey.clone(), &keybind.key)) {
+            let mut resolved: Vec<String> = modkey
+                .into_iter()
+                .flat_map(|m| match m.as_str() {
+                    "modkey" => vec![self.modkey.clone()],
+                    "mousekey" => self.mousekey.iter().flatten().collect(),
+                    _ => vec![m],
+                })
+                .collect();
+            resolved.sort_unstable();
+            resolved.dedup();
+            if let Some(conflict) =
+                bindings.insert((resolved, &keybind.key), keybind.command.clone())
+            {
                 returns.push((
                     None,
                     format!(
@@ -81,13 +194,14 @@ impl Config {
                     \n\x1b[1;91m    -> {:?}\
                     \n    -> {:?}\
                     \n\x1b[0mHelp: change one of the keybindings to something else.\n",
-                        modkey, keybind.key, conflict_key, keybind.command,
+                        modkey, keybind.key, conflict, keybind.command,
                     ),
                 ));
             }
         }
         if returns.is_empty() {
             println!("\x1b[0;92m    -> All keybinds OK\x1b[0m");
+            true
         } else {
             for error in returns {
                 match error.0 {
@@ -102,6 +216,7 @@ impl Config {
                     }
                 }
             }
+            false
         }
     }
 }
@@ -1,6 +1,6 @@
 use super::{
     default_terminal, exit_strategy, BaseCommand, Config, Default, FocusBehaviour, Keybind,
-    LayoutMode, ThemeSetting, LAYOUTS,
+    LayoutMode, TagConfig, ThemeSetting, LAYOUTS,
 };
 
 impl Default for Config {
@@ -193,28 +193,56 @@ impl Default for Config {
 
         let tags = vec!["1", "2", "3", "4", "5", "6", "7", "8", "9"]
             .iter()
-            .map(|s| (*s).to_string())
+            .map(|&s| TagConfig::from(s))
             .collect();
 
         Self {
             workspaces: Some(vec![]),
+            profiles: vec![],
             tags: Some(tags),
+            tag_order: vec![],
             layouts: LAYOUTS.to_vec(),
-            layout_mode: LayoutMode::Workspace,
+            default_layout: None,
+            custom_layouts: vec![],
+            layout_mode: LayoutMode::Tag,
             // TODO: add sane default for scratchpad config.
             // Currently default values are set in sane_dimension fn.
             scratchpad: Some(vec![]),
             window_rules: Some(vec![]),
             disable_current_tag_swap: false,
             disable_tile_drag: false,
+            per_screen_tags: false,
+            greedy_view: true,
+            tag_history_depth: 10,
+            honor_size_increments: false,
+            tag_slide_duration: 0,
+            reduce_motion: false,
+            snap_distance: 10,
+            inner_gap: None,
+            outer_gap: None,
+            gap_step: 2,
+            smart_gaps: false,
+            smart_borders: false,
+            urgency_limit: 5,
+            ratio_step: 5.0,
             focus_behaviour: FocusBehaviour::Sloppy, // default behaviour: mouse move auto-focuses window
             focus_new_windows: true, // default behaviour: focuses windows on creation
+            sloppy_focus_empty_workspaces: true, // default behaviour: hovering an empty workspace focuses it
+            click_passthrough: true, // default behaviour: the click focusing a window reaches it
+            warp_cursor: false,      // default behaviour: only Sloppy focus moves the cursor
             insert_behavior: leftwm_core::config::InsertBehavior::Bottom,
+            empty_tag_behavior: leftwm_core::config::EmptyTagBehavior::Stay,
+            new_window_placement: leftwm_core::config::NewWindowPlacement::Terminal,
             modkey: "Mod4".to_owned(),     //win key
             mousekey: Some("Mod4".into()), //win key
             keybind: commands,
             theme_setting: ThemeSetting::default(),
             max_window_width: None,
+            on_exit: None,
+            on_startup: vec![],
+            on_reload: vec![],
+            on_focus_change: None,
+            on_title_change: None,
             state: None,
         }
     }
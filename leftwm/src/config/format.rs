@@ -0,0 +1,155 @@
+//! The formats the config can be written in, told apart by the extension of its file, and the
+//! files a config includes.
+use anyhow::{bail, Context, Result};
+use serde::de::DeserializeOwned;
+use serde_json::{Map, Value};
+use std::ffi::OsStr;
+use std::fs;
+use std::path::{Path, PathBuf};
+use xdg::BaseDirectories;
+
+/// The names the config file is looked for under, in order. Without any of them the default
+/// config is written to the first.
+pub const CONFIG_FILES: [&str; 4] = ["config.toml", "config.ron", "config.yaml", "config.yml"];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    Toml,
+    Ron,
+    Yaml,
+}
+
+impl Format {
+    /// The format of a file by its extension, TOML for extensions of no other format.
+    #[must_use]
+    pub fn of(path: &Path) -> Self {
+        match path.extension().and_then(OsStr::to_str) {
+            Some("ron") => Self::Ron,
+            Some("yaml" | "yml") => Self::Yaml,
+            _ => Self::Toml,
+        }
+    }
+
+    /// # Errors
+    ///
+    /// Errors, with where in `contents`, if they aren't a `T` in this format.
+    pub fn parse<T: DeserializeOwned>(self, contents: &str) -> Result<T> {
+        Ok(match self {
+            Self::Toml => toml::from_str(contents)?,
+            Self::Ron => ron::from_str(contents)?,
+            Self::Yaml => serde_yaml::from_str(contents)?,
+        })
+    }
+}
+
+/// The config file in use, the first of the [`CONFIG_FILES`] which exists, or where the first
+/// would go when none does.
+///
+/// # Errors
+///
+/// Errors if the config directory can't be found or created.
+pub fn config_file() -> Result<PathBuf> {
+    let base = BaseDirectories::with_prefix("leftwm")?;
+    match CONFIG_FILES
+        .iter()
+        .find_map(|name| base.find_config_file(name))
+    {
+        Some(path) => Ok(path),
+        None => Ok(base.place_config_file(CONFIG_FILES[0])?),
+    }
+}
+
+/// Read a config file in the format of its extension, along with the files it `include`s.
+///
+/// The files listed in `include`, relative to the including one, are read in order, each
+/// overriding the ones before it, and the including file then overrides them all. Tables are
+/// merged key by key, while everything else, lists included, is replaced as a whole. Included
+/// files may include others in turn. RON files can neither include nor be included, as their
+/// enums don't survive the merge.
+///
+/// # Errors
+///
+/// Errors, naming the file, if it or one it includes can't be read or they don't make a `T`.
+pub fn read_config_file<T: DeserializeOwned>(path: &Path) -> Result<T> {
+    let contents = read(path)?;
+    if Format::of(path) == Format::Ron {
+        return parse(path, &contents);
+    }
+    let value: Value = parse(path, &contents)?;
+    if value.get("include").is_none() {
+        // Straight from the file, so that errors tell where in it they are.
+        return parse(path, &contents);
+    }
+    let merged = with_includes(path, value, &mut vec![canonical(path)])?;
+    serde_json::from_value(merged)
+        .with_context(|| format!("Couldn't parse {} with its includes", path.display()))
+}
+
+fn read(path: &Path) -> Result<String> {
+    fs::read_to_string(path).with_context(|| format!("Couldn't read {}", path.display()))
+}
+
+fn parse<T: DeserializeOwned>(path: &Path, contents: &str) -> Result<T> {
+    Format::of(path)
+        .parse(contents)
+        .with_context(|| format!("Couldn't parse {}", path.display()))
+}
+
+fn canonical(path: &Path) -> PathBuf {
+    fs::canonicalize(path).unwrap_or_else(|_| path.to_owned())
+}
+
+/// The `value` of the file at `path` merged over the files it includes. `including` holds the
+/// files including it, which it can't include back.
+fn with_includes(path: &Path, mut value: Value, including: &mut Vec<PathBuf>) -> Result<Value> {
+    let includes = match value
+        .as_object_mut()
+        .and_then(|table| table.remove("include"))
+    {
+        Some(includes) => serde_json::from_value::<Vec<PathBuf>>(includes)
+            .with_context(|| format!("`include` of {} must be a list of files", path.display()))?,
+        None => return Ok(value),
+    };
+    let dir = path.parent().unwrap_or_else(|| Path::new("."));
+    let mut merged = Value::Object(Map::new());
+    for include in includes {
+        let file = dir.join(include);
+        if Format::of(&file) == Format::Ron {
+            bail!(
+                "{} can't be included, RON files can't be merged",
+                file.display()
+            );
+        }
+        if including.contains(&canonical(&file)) {
+            bail!(
+                "{} includes itself, through {}",
+                file.display(),
+                path.display()
+            );
+        }
+        let included = parse(&file, &read(&file)?)?;
+        including.push(canonical(&file));
+        let included = with_includes(&file, included, including)?;
+        including.pop();
+        merge(&mut merged, included);
+    }
+    merge(&mut merged, value);
+    Ok(merged)
+}
+
+/// Merge `over` into `base`, table by table.
+fn merge(base: &mut Value, over: Value) {
+    match (base, over) {
+        (Value::Object(base), Value::Object(over)) => {
+            for (key, value) in over {
+                match base.get_mut(&key) {
+                    Some(existing) => merge(existing, value),
+                    None => {
+                        base.insert(key, value);
+                    }
+                }
+            }
+        }
+        (base, over) => *base = over,
+    }
+}
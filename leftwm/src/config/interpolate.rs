@@ -0,0 +1,134 @@
+//! Expansion of `~`, environment variables and commands in config values, so that one config
+//! can adapt to the machine it's on.
+use std::io::Read;
+use std::process::{Command, Stdio};
+use std::sync::mpsc;
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// How long the commands of one read of the config may take altogether, as they hold up the
+/// event loop on a reload. Those still running then are killed and expand to nothing.
+pub const TIMEOUT: Duration = Duration::from_secs(2);
+
+/// `value` with `~` at its start replaced by the home directory, `$VAR` and `${VAR}` by the
+/// environment variables, or nothing when they're unset, and `$(command)` by what the command
+/// prints, without the trailing newline, if it is done by `deadline`. `$$` is a `$`, and a `$`
+/// followed by anything else is left as it is.
+pub fn interpolate(value: &str, deadline: Instant) -> String {
+    let mut expanded = String::with_capacity(value.len());
+    let mut rest = value;
+    if let Some(path) = rest.strip_prefix('~') {
+        if path.is_empty() || path.starts_with('/') {
+            expanded.push_str(&std::env::var("HOME").unwrap_or_default());
+            rest = path;
+        }
+    }
+    while let Some(start) = rest.find('$') {
+        expanded.push_str(&rest[..start]);
+        let after = &rest[start + 1..];
+        let (replacement, remaining) = if let Some(after) = after.strip_prefix('$') {
+            ("$".to_owned(), after)
+        } else if let Some(command) = after.strip_prefix('(') {
+            match closing(command, '(', ')') {
+                Some(end) => (output(&command[..end], deadline), &command[end + 1..]),
+                None => ("$".to_owned(), after),
+            }
+        } else if let Some(name) = after.strip_prefix('{') {
+            match name.find('}') {
+                Some(end) => (variable(&name[..end]), &name[end + 1..]),
+                None => ("$".to_owned(), after),
+            }
+        } else {
+            let end = after
+                .find(|c: char| !c.is_ascii_alphanumeric() && c != '_')
+                .unwrap_or(after.len());
+            if end == 0 {
+                ("$".to_owned(), after)
+            } else {
+                (variable(&after[..end]), &after[end..])
+            }
+        };
+        expanded.push_str(&replacement);
+        rest = remaining;
+    }
+    expanded.push_str(rest);
+    expanded
+}
+
+/// The index of the `close` matching an `open` just before `text`, the ones in between nesting.
+fn closing(text: &str, open: char, close: char) -> Option<usize> {
+    let mut depth = 0;
+    for (i, c) in text.char_indices() {
+        if c == open {
+            depth += 1;
+        } else if c == close {
+            if depth == 0 {
+                return Some(i);
+            }
+            depth -= 1;
+        }
+    }
+    None
+}
+
+fn variable(name: &str) -> String {
+    std::env::var(name).unwrap_or_default()
+}
+
+fn output(command: &str, deadline: Instant) -> String {
+    let spawned = Command::new("sh")
+        .arg("-c")
+        .arg(command)
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .spawn();
+    let mut child = match spawned {
+        Ok(child) => child,
+        Err(err) => {
+            log::warn!("Couldn't run `{}` of the config: {}", command, err);
+            return String::new();
+        }
+    };
+    let (sender, printed) = mpsc::channel();
+    if let Some(mut stdout) = child.stdout.take() {
+        thread::spawn(move || {
+            let mut output = Vec::new();
+            stdout.read_to_end(&mut output).ok();
+            sender.send(output).ok();
+        });
+    }
+    let left = deadline.saturating_duration_since(Instant::now());
+    let output = printed.recv_timeout(left);
+    if output.is_err() {
+        log::warn!("`{}` of the config took too long, it was stopped", command);
+        child.kill().ok();
+    }
+    child.wait().ok();
+    output
+        .map(|output| {
+            String::from_utf8_lossy(&output)
+                .trim_end_matches('\n')
+                .to_owned()
+        })
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn commands_should_expand_to_their_output() {
+        let deadline = Instant::now() + TIMEOUT;
+        assert_eq!(interpolate("DP-$(echo 1)", deadline), "DP-1");
+        assert_eq!(interpolate("$$HOME", deadline), "$HOME");
+    }
+
+    #[test]
+    fn slow_commands_should_be_stopped_at_the_deadline() {
+        let start = Instant::now();
+        let deadline = start + Duration::from_millis(100);
+        assert_eq!(interpolate("a$(sleep 5; echo b)", deadline), "a");
+        assert!(start.elapsed() < Duration::from_secs(1));
+    }
+}
@@ -2,6 +2,7 @@ use super::BaseCommand;
 use crate::Config;
 use anyhow::{ensure, Context, Result};
 use leftwm_core::layouts::Layout;
+use leftwm_core::models::{Direction, Size};
 use serde::{Deserialize, Serialize};
 use std::str::FromStr;
 
@@ -31,16 +32,24 @@ impl Keybind {
             BaseCommand::SwapTags => leftwm_core::Command::SwapScreens,
             BaseCommand::SoftReload => leftwm_core::Command::SoftReload,
             BaseCommand::HardReload => leftwm_core::Command::HardReload,
+            BaseCommand::Exit => leftwm_core::Command::Exit,
             BaseCommand::ToggleScratchPad => {
                 leftwm_core::Command::ToggleScratchPad(ensure_non_empty!(self.value.clone()))
             }
             BaseCommand::ToggleFullScreen => leftwm_core::Command::ToggleFullScreen,
             BaseCommand::ToggleSticky => leftwm_core::Command::ToggleSticky,
+            BaseCommand::ToggleZoom => leftwm_core::Command::ToggleZoom,
+            BaseCommand::ToggleDock => leftwm_core::Command::ToggleDock,
             BaseCommand::GotoTag => leftwm_core::Command::GoToTag {
-                tag: usize::from_str(&self.value).context("invalid index value for GotoTag")?,
+                tag: config
+                    .tag_number(&self.value)
+                    .context("no tag with this number or label for GotoTag")?,
                 swap: !config.disable_current_tag_swap,
             },
             BaseCommand::ReturnToLastTag => leftwm_core::Command::ReturnToLastTag,
+            BaseCommand::FocusLastTag => leftwm_core::Command::FocusLastTag,
+            BaseCommand::TagHistoryBack => leftwm_core::Command::TagHistoryBack,
+            BaseCommand::TagHistoryForward => leftwm_core::Command::TagHistoryForward,
             BaseCommand::FloatingToTile => leftwm_core::Command::FloatingToTile,
             BaseCommand::TileToFloating => leftwm_core::Command::TileToFloating,
             BaseCommand::ToggleFloating => leftwm_core::Command::ToggleFloating,
@@ -54,8 +63,16 @@ impl Keybind {
                         .context("invalid boolean value for MoveWindowTop")?
                 },
             },
-            BaseCommand::FocusNextTag => leftwm_core::Command::FocusNextTag,
-            BaseCommand::FocusPreviousTag => leftwm_core::Command::FocusPreviousTag,
+            BaseCommand::FocusNextTag => {
+                let (wrap, skip_empty) = leftwm_core::Command::parse_tag_cycle(&self.value)
+                    .context("invalid flag for FocusNextTag")?;
+                leftwm_core::Command::FocusNextTag { wrap, skip_empty }
+            }
+            BaseCommand::FocusPreviousTag => {
+                let (wrap, skip_empty) = leftwm_core::Command::parse_tag_cycle(&self.value)
+                    .context("invalid flag for FocusPreviousTag")?;
+                leftwm_core::Command::FocusPreviousTag { wrap, skip_empty }
+            }
             BaseCommand::FocusWindow => leftwm_core::Command::FocusWindow(self.value.clone()),
             BaseCommand::FocusWindowUp => leftwm_core::Command::FocusWindowUp,
             BaseCommand::FocusWindowDown => leftwm_core::Command::FocusWindowDown,
@@ -67,13 +84,35 @@ impl Keybind {
                         .context("invalid boolean value for FocusWindowTop")?
                 },
             },
+            BaseCommand::FocusWindowInDirection => leftwm_core::Command::FocusWindowInDirection(
+                Direction::from_str(&self.value)
+                    .context("could not parse direction for command FocusWindowInDirection")?,
+            ),
+            BaseCommand::MoveWindowInDirection => leftwm_core::Command::MoveWindowInDirection(
+                Direction::from_str(&self.value)
+                    .context("could not parse direction for command MoveWindowInDirection")?,
+            ),
+            BaseCommand::SwapWindowWithMaster => leftwm_core::Command::SwapWindowWithMaster,
+            BaseCommand::RotateStackForward => leftwm_core::Command::RotateStackForward,
+            BaseCommand::RotateStackBackward => leftwm_core::Command::RotateStackBackward,
             BaseCommand::FocusWorkspaceNext => leftwm_core::Command::FocusWorkspaceNext,
             BaseCommand::FocusWorkspacePrevious => leftwm_core::Command::FocusWorkspacePrevious,
             BaseCommand::MoveToTag => leftwm_core::Command::SendWindowToTag {
                 window: None,
-                tag: usize::from_str(&self.value)
-                    .context("invalid index value for SendWindowToTag")?,
+                tag: config
+                    .tag_number(&self.value)
+                    .context("no tag with this number or label for SendWindowToTag")?,
             },
+            BaseCommand::ToggleWindowTag => leftwm_core::Command::ToggleWindowTag(
+                config
+                    .tag_number(&self.value)
+                    .context("no tag with this number or label for ToggleWindowTag")?,
+            ),
+            BaseCommand::ToggleTagView => leftwm_core::Command::ToggleTagView(
+                config
+                    .tag_number(&self.value)
+                    .context("no tag with this number or label for ToggleTagView")?,
+            ),
             BaseCommand::MoveToLastWorkspace => leftwm_core::Command::MoveWindowToLastWorkspace,
             BaseCommand::MoveWindowToNextWorkspace => {
                 leftwm_core::Command::MoveWindowToNextWorkspace
@@ -84,27 +123,101 @@ impl Keybind {
             BaseCommand::MouseMoveWindow => leftwm_core::Command::MouseMoveWindow,
             BaseCommand::NextLayout => leftwm_core::Command::NextLayout,
             BaseCommand::PreviousLayout => leftwm_core::Command::PreviousLayout,
+            BaseCommand::ToggleLastLayout => leftwm_core::Command::ToggleLastLayout,
             BaseCommand::SetLayout => leftwm_core::Command::SetLayout(
                 Layout::from_str(&self.value)
                     .context("could not parse layout for command SetLayout")?,
             ),
             BaseCommand::RotateTag => leftwm_core::Command::RotateTag,
             BaseCommand::IncreaseMainWidth => leftwm_core::Command::IncreaseMainWidth(
-                i8::from_str(&self.value).context("invalid width value for IncreaseMainWidth")?,
+                self.ratio_delta(config)
+                    .context("invalid width value for IncreaseMainWidth")?,
             ),
             BaseCommand::DecreaseMainWidth => leftwm_core::Command::DecreaseMainWidth(
-                i8::from_str(&self.value).context("invalid width value for DecreaseMainWidth")?,
+                self.ratio_delta(config)
+                    .context("invalid width value for DecreaseMainWidth")?,
+            ),
+            BaseCommand::IncreaseStackSplit => leftwm_core::Command::IncreaseStackSplit(
+                self.ratio_delta(config)
+                    .context("invalid split value for IncreaseStackSplit")?,
             ),
+            BaseCommand::DecreaseStackSplit => leftwm_core::Command::DecreaseStackSplit(
+                self.ratio_delta(config)
+                    .context("invalid split value for DecreaseStackSplit")?,
+            ),
+            BaseCommand::IncreaseMainCount => leftwm_core::Command::IncreaseMainCount,
+            BaseCommand::DecreaseMainCount => leftwm_core::Command::DecreaseMainCount,
+            BaseCommand::IncreaseWindowWeight => leftwm_core::Command::IncreaseWindowWeight(
+                self.weight_delta()
+                    .context("invalid weight for IncreaseWindowWeight")?,
+            ),
+            BaseCommand::DecreaseWindowWeight => leftwm_core::Command::DecreaseWindowWeight(
+                self.weight_delta()
+                    .context("invalid weight for DecreaseWindowWeight")?,
+            ),
+            BaseCommand::RotateSubtree => leftwm_core::Command::RotateSubtree,
+            BaseCommand::FlipSubtree => leftwm_core::Command::FlipSubtree,
             BaseCommand::SetMarginMultiplier => leftwm_core::Command::SetMarginMultiplier(
                 f32::from_str(&self.value)
                     .context("invalid margin multiplier for SetMarginMultiplier")?,
             ),
+            BaseCommand::SetMaxWindowWidth if self.value.is_empty() => {
+                leftwm_core::Command::SetMaxWindowWidth(None)
+            }
+            BaseCommand::SetMaxWindowWidth => leftwm_core::Command::SetMaxWindowWidth(Some(
+                Size::from_str(&self.value).context("invalid width for SetMaxWindowWidth")?,
+            )),
             BaseCommand::UnloadTheme => leftwm_core::Command::Other("UnloadTheme".into()),
             BaseCommand::LoadTheme => leftwm_core::Command::Other(format!(
                 "LoadTheme {}",
                 ensure_non_empty!(self.value.clone())
             )),
             BaseCommand::CloseAllOtherWindows => leftwm_core::Command::CloseAllOtherWindows,
+            BaseCommand::IncreaseGaps => leftwm_core::Command::IncreaseGaps(
+                self.gap_step(config)
+                    .context("invalid pixels for IncreaseGaps")?,
+            ),
+            BaseCommand::DecreaseGaps => leftwm_core::Command::DecreaseGaps(
+                self.gap_step(config)
+                    .context("invalid pixels for DecreaseGaps")?,
+            ),
+            BaseCommand::ToggleGaps => leftwm_core::Command::ToggleGaps,
+            BaseCommand::ToggleGameMode => leftwm_core::Command::ToggleGameMode,
+            BaseCommand::CloseAllOnTag => leftwm_core::Command::CloseAllOnTag,
+            BaseCommand::LoadProfile => {
+                let name = ensure_non_empty!(self.value.clone());
+                ensure!(
+                    config.profiles.iter().any(|profile| profile.name == name),
+                    "no profile named {} for LoadProfile",
+                    name
+                );
+                leftwm_core::Command::LoadProfile(name)
+            }
+            BaseCommand::AddTag => {
+                leftwm_core::Command::AddTag(ensure_non_empty!(self.value.clone()))
+            }
+            BaseCommand::RemoveTag => leftwm_core::Command::RemoveTag,
+            BaseCommand::RenameTag => {
+                let value = ensure_non_empty!(self.value.clone());
+                let (tag, label) = match value.split_once(' ') {
+                    Some((tag, label)) => (Some(tag.to_string()), label.trim_start().to_string()),
+                    None => (None, value),
+                };
+                leftwm_core::Command::RenameTag { tag, label }
+            }
+            BaseCommand::MoveTagToWorkspace => leftwm_core::Command::MoveTagToWorkspace(
+                usize::from_str(&self.value)
+                    .context("invalid workspace index for MoveTagToWorkspace")?,
+            ),
+            BaseCommand::FocusUrgent => leftwm_core::Command::FocusUrgent,
+            BaseCommand::ToggleOverview => leftwm_core::Command::ToggleOverview,
+            BaseCommand::PickOverviewWindow => leftwm_core::Command::PickOverviewWindow(None),
+            BaseCommand::CountPrefix => {
+                let digit =
+                    usize::from_str(&self.value).context("invalid digit for CountPrefix")?;
+                ensure!(digit <= 9, "CountPrefix takes a single digit");
+                leftwm_core::Command::CountPrefix(digit)
+            }
         };
 
         Ok(leftwm_core::Keybind {
@@ -118,6 +231,29 @@ impl Keybind {
             key: self.key.clone(),
         })
     }
+
+    /// The percentage to change a layout ratio by, defaulting to the configured step.
+    fn ratio_delta(&self, config: &Config) -> Result<f32> {
+        if self.value.is_empty() {
+            return Ok(config.ratio_step);
+        }
+        Ok(f32::from_str(&self.value)?)
+    }
+
+    /// The weight to change the focused window by, which must be a finite number.
+    fn weight_delta(&self) -> Result<f32> {
+        let delta = f32::from_str(&self.value)?;
+        ensure!(delta.is_finite(), "{delta} is not a finite number");
+        Ok(delta)
+    }
+
+    /// The pixels to change the gaps by, defaulting to the configured step.
+    fn gap_step(&self, config: &Config) -> Result<u32> {
+        if self.value.is_empty() {
+            return Ok(config.gap_step);
+        }
+        Ok(u32::from_str(&self.value)?)
+    }
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone, Hash, Eq, PartialEq)]
@@ -2,20 +2,30 @@
 
 mod checks;
 mod default;
+mod format;
+mod interpolate;
 mod keybind;
 
+use self::interpolate::interpolate;
 use self::keybind::Modifier;
+pub use format::{config_file, read_config_file, Format, CONFIG_FILES};
 
 use super::{BaseCommand, ThemeSetting};
 use crate::config::keybind::Keybind;
 use anyhow::Result;
 use leftwm_core::{
-    config::{InsertBehavior, ScratchPad, Workspace},
-    layouts::{Layout, LAYOUTS},
-    models::{FocusBehaviour, Gutter, LayoutMode, Margins, Size, Window},
+    config::{
+        EmptyTagBehavior, InsertBehavior, NewWindowPlacement, Profile, ScratchPad, SpawnTarget,
+        Workspace,
+    },
+    layouts::{CustomLayout, Layout, LAYOUTS},
+    models::{
+        FocusBehaviour, Gutter, LayoutMode, Margins, Size, TagId, Window, WindowState, WindowType,
+    },
     state::State,
     DisplayServer, Manager,
 };
+use regex::Regex;
 use serde::{Deserialize, Serialize};
 use std::convert::TryInto;
 use std::default::Default;
@@ -24,49 +34,112 @@ use std::fs;
 use std::fs::File;
 use std::io::prelude::*;
 use std::path::{Path, PathBuf};
-use xdg::BaseDirectories;
+use std::str::FromStr;
+use std::time::Instant;
 
 /// Path to file where state will be dumper upon soft reload.
 const STATE_FILE: &str = "/tmp/leftwm.state";
 
-/// Selecting by `WM_CLASS` and/or window title, allow the user to define if a
-/// window should spawn on a specified tag and/or its floating state.
+/// Selecting by `WM_CLASS`, instance, window title and/or window type, allow the user to define
+/// if a window should spawn on a specified tag, floating, fullscreen, sticky or without a border.
 ///
 /// # Example
 ///
 /// In `config.toml`
 ///
 /// ```toml
-/// [[window_config_by_class]]
-/// wm_class = "krita"
+/// [[window_rules]]
+/// window_class = "krita"
 /// spawn_on_tag = 3
 /// spawn_floating = false
+///
+/// [[window_rules]]
+/// window_title = "Picture-in-Picture|.* - mpv"
+/// spawn_sticky = true
+/// no_border = true
+/// ```
+///
+/// windows whose `WM_CLASS` is "krita" will spawn on tag 3 (1-indexed) and not floating, and
+/// picture-in-picture windows and those of mpv will be sticky and have no border.
+///
+/// ```toml
+/// [[window_rules]]
+/// window_class = "Slack"
+/// tag = "9"
+/// output_name = "DP-1"
+/// follow = false
 /// ```
 ///
-/// windows whose `WM_CLASS` is "krita" will spawn on tag 3 (1-indexed) and not floating.
+/// Slack will spawn on the tag labeled "9" of the monitor `DP-1`, leaving the focus where it is.
+///
+/// ```toml
+/// [[window_rules]]
+/// window_class = "Pavucontrol"
+/// width = 800
+/// height = 600
+/// ```
+///
+/// pavucontrol will float at 800x600, centered on its workspace. As for scratchpads, whole
+/// numbers are pixels and fractions are ratios of the workspace.
+///
+/// The class, instance and title are matched as given or as regular expressions for the whole
+/// value. All those given must match for the rule to apply.
 #[derive(Serialize, Deserialize, Default, Debug, Clone)]
 pub struct WindowHook {
-    /// `WM_CLASS` in X11
-    pub window_class: Option<String>,
+    /// `WM_CLASS` in X11, either of its instance and class
+    pub window_class: Option<Pattern>,
+    /// The instance of `WM_CLASS` in X11
+    pub window_instance: Option<Pattern>,
     /// `_NET_WM_NAME` in X11
-    pub window_title: Option<String>,
+    pub window_title: Option<Pattern>,
+    /// `_NET_WM_WINDOW_TYPE` in X11
+    pub window_type: Option<WindowType>,
     pub spawn_on_tag: Option<usize>,
     pub spawn_floating: Option<bool>,
+    pub spawn_fullscreen: Option<bool>,
+    pub spawn_sticky: Option<bool>,
+    pub no_border: Option<bool>,
+    /// The label of the tag to spawn on, taking precedence over `spawn_on_tag`
+    pub tag: Option<String>,
+    /// The output, e.g. `DP-1`, of the workspace to spawn on
+    pub output_name: Option<String>,
+    /// Whether the focus moves to the window, showing its tag
+    pub follow: Option<bool>,
+    /// The place of the window in its workspace, which then floats, centered when left out
+    pub x: Option<Size>,
+    pub y: Option<Size>,
+    /// The size of the floating window, the one it asks for when left out
+    pub width: Option<Size>,
+    pub height: Option<Size>,
 }
 
 impl WindowHook {
     /// Score the similarity between a [`leftwm_core::models::Window`] and a [`WindowHook`].
     ///
     /// Multiple [`WindowHook`]s might match a `WM_CLASS` but we want the most
-    /// specific one to apply: matches by title are scored greater than by `WM_CLASS`.
+    /// specific one to apply: matches by title are scored greater than by `WM_CLASS`, instance or
+    /// window type. A hook with something which doesn't match scores 0.
     fn score_window(&self, window: &Window) -> u8 {
-        u8::from(
-            self.window_class.is_some()
-                & (self.window_class == window.res_name || self.window_class == window.res_class),
-        ) + 2 * u8::from(
-            self.window_title.is_some()
-                & ((self.window_title == window.name) | (self.window_title == window.legacy_name)),
-        )
+        let class = [&window.res_name, &window.res_class];
+        let instance = [&window.res_name];
+        let title = [&window.name, &window.legacy_name];
+        let criteria = [
+            (1, self.window_class.as_ref().map(|p| p.matches(&class))),
+            (
+                1,
+                self.window_instance.as_ref().map(|p| p.matches(&instance)),
+            ),
+            (2, self.window_title.as_ref().map(|p| p.matches(&title))),
+            (1, self.window_type.as_ref().map(|t| *t == window.r#type)),
+        ];
+        if criteria.iter().any(|(_, matched)| *matched == Some(false)) {
+            return 0;
+        }
+        criteria
+            .iter()
+            .filter(|(_, matched)| *matched == Some(true))
+            .map(|(score, _)| score)
+            .sum()
     }
 
     fn apply(&self, window: &mut Window) {
@@ -76,6 +149,151 @@ impl WindowHook {
         if let Some(should_float) = self.spawn_floating {
             window.set_floating(should_float);
         }
+        let mut states = window.states();
+        for (state, wanted) in [
+            (WindowState::Fullscreen, self.spawn_fullscreen),
+            (WindowState::Sticky, self.spawn_sticky),
+        ] {
+            match wanted {
+                Some(true) if !states.contains(&state) => states.push(state),
+                Some(false) => states.retain(|s| *s != state),
+                _ => {}
+            }
+        }
+        window.set_states(states);
+        if let Some(no_border) = self.no_border {
+            window.borderless = no_border;
+        }
+    }
+
+    fn spawn_target(&self) -> SpawnTarget {
+        SpawnTarget {
+            tag: self.tag.clone(),
+            output: self.output_name.clone(),
+            follow: self.follow,
+            x: self.x,
+            y: self.y,
+            width: self.width,
+            height: self.height,
+        }
+    }
+}
+
+/// A value the window rules match windows by, as given or as a regular expression for the whole
+/// value. The expression is compiled once, as the config is read.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(from = "String", into = "String")]
+pub struct Pattern {
+    pattern: String,
+    regex: Option<Regex>,
+}
+
+impl From<String> for Pattern {
+    fn from(pattern: String) -> Self {
+        let regex = Regex::new(&format!("^(?:{pattern})$"))
+            .map_err(|err| log::warn!("Invalid pattern {pattern:?} in the window rules: {err}"))
+            .ok();
+        Self { pattern, regex }
+    }
+}
+
+impl From<Pattern> for String {
+    fn from(pattern: Pattern) -> Self {
+        pattern.pattern
+    }
+}
+
+impl Pattern {
+    /// Whether one of the `values` is the pattern, or matches it as a regular expression.
+    fn matches(&self, values: &[&Option<String>]) -> bool {
+        values
+            .iter()
+            .filter_map(|value| value.as_deref())
+            .any(|value| {
+                value == self.pattern || self.regex.as_ref().is_some_and(|re| re.is_match(value))
+            })
+    }
+}
+
+/// A tag, given either by its label alone or as a table with settings of its own.
+///
+/// # Example
+///
+/// In `config.toml`
+///
+/// ```toml
+/// tags = ["1", { label = "2", max_window_width = 1200 }, "3", { label = "mail", hidden = true }]
+/// ```
+///
+/// Hidden tags are left out when cycling through the tags and from the state given to bars, but
+/// can still be gone to by number.
+///
+/// A tag can start with its own `default_layout` and cycle through its own `layouts`, overriding
+/// those of the workspace displaying it:
+///
+/// ```toml
+/// tags = ["1", "2", { label = "video", default_layout = "Monocle", layouts = ["Monocle", "Grid"] }]
+/// ```
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(untagged)]
+pub enum TagConfig {
+    Label(String),
+    Table {
+        label: String,
+        max_window_width: Option<Size>,
+        #[serde(default)]
+        hidden: bool,
+        default_layout: Option<Layout>,
+        layouts: Option<Vec<Layout>>,
+    },
+}
+
+impl TagConfig {
+    #[must_use]
+    pub fn label(&self) -> &str {
+        match self {
+            Self::Label(label) | Self::Table { label, .. } => label,
+        }
+    }
+
+    #[must_use]
+    pub const fn hidden(&self) -> bool {
+        match self {
+            Self::Label(_) => false,
+            Self::Table { hidden, .. } => *hidden,
+        }
+    }
+
+    #[must_use]
+    pub const fn max_window_width(&self) -> Option<Size> {
+        match self {
+            Self::Label(_) => None,
+            Self::Table {
+                max_window_width, ..
+            } => *max_window_width,
+        }
+    }
+
+    #[must_use]
+    pub fn default_layout(&self) -> Option<Layout> {
+        match self {
+            Self::Label(_) => None,
+            Self::Table { default_layout, .. } => default_layout.clone(),
+        }
+    }
+
+    #[must_use]
+    pub fn layouts(&self) -> Vec<Layout> {
+        match self {
+            Self::Label(_) => vec![],
+            Self::Table { layouts, .. } => layouts.clone().unwrap_or_default(),
+        }
+    }
+}
+
+impl From<&str> for TagConfig {
+    fn from(label: &str) -> Self {
+        Self::Label(label.to_owned())
     }
 }
 
@@ -86,18 +304,73 @@ pub struct Config {
     pub modkey: String,
     pub mousekey: Option<Modifier>,
     pub workspaces: Option<Vec<Workspace>>,
-    pub tags: Option<Vec<String>>,
+    /// Named sets of workspaces, e.g. for docked and undocked, which `LoadProfile` switches to.
+    pub profiles: Vec<Profile>,
+    pub tags: Option<Vec<TagConfig>>,
+    /// Labels of the tags in the order to cycle through them and show them in the bars, the
+    /// tags left out following in the order of `tags`.
+    pub tag_order: Vec<String>,
     pub max_window_width: Option<Size>,
     pub layouts: Vec<Layout>,
+    /// The layout tags start with, instead of the first of the `layouts`.
+    pub default_layout: Option<Layout>,
+    /// Layouts made of regions, which `layouts` and the workspaces can list by their names.
+    pub custom_layouts: Vec<CustomLayout>,
     pub layout_mode: LayoutMode,
     pub insert_behavior: InsertBehavior,
+    /// Where the focused workspace goes once the last window of its tag closes.
+    pub empty_tag_behavior: EmptyTagBehavior,
+    /// The workspace new windows are put on: the one with the pointer, the focused one, or the
+    /// one of the terminal they were started from.
+    pub new_window_placement: NewWindowPlacement,
     pub scratchpad: Option<Vec<ScratchPad>>,
     pub window_rules: Option<Vec<WindowHook>>,
     //of you are on tag "1" and you goto tag "1" this takes you to the previous tag
     pub disable_current_tag_swap: bool,
     pub disable_tile_drag: bool,
+    /// Every workspace has its own set of tags, as in dwm, instead of sharing them.
+    pub per_screen_tags: bool,
+    /// Going to a tag shown on another workspace swaps it over, instead of focusing that workspace.
+    pub greedy_view: bool,
+    /// Tag changes each workspace remembers for `TagHistoryBack` and `TagHistoryForward`.
+    pub tag_history_depth: usize,
+    /// Tiled windows are shrunk to whole character cells of terminals and other clients with
+    /// resize increments, leaving the rest as gap.
+    pub honor_size_increments: bool,
+    /// Milliseconds windows slide for when switching tags, 0 to switch without sliding.
+    pub tag_slide_duration: u64,
+    /// Turns off every animation, such as `tag_slide_duration`.
+    pub reduce_motion: bool,
+    /// Percentage used by the layout ratio commands when no value is given.
+    pub ratio_step: f32,
+    pub snap_distance: i32,
+    /// Gap between tiled windows, replacing the `margin` of the theme.
+    pub inner_gap: Option<u32>,
+    /// Gap between the windows and the screen edges, replacing the `workspace_margin` of the theme.
+    pub outer_gap: Option<u32>,
+    /// Pixels `IncreaseGaps` and `DecreaseGaps` change the gaps by when given no value.
+    pub gap_step: u32,
+    /// No gaps around a window tiled alone, until a second one is.
+    pub smart_gaps: bool,
+    /// No border around a window tiled alone, until a second one is.
+    pub smart_borders: bool,
+    /// Urgency requests a window may make per minute, 0 for no limit.
+    pub urgency_limit: usize,
     pub focus_behaviour: FocusBehaviour,
     pub focus_new_windows: bool,
+    pub sloppy_focus_empty_workspaces: bool,
+    pub click_passthrough: bool,
+    pub warp_cursor: bool,
+    /// Shell command run on exit, before the `down` scripts.
+    pub on_exit: Option<String>,
+    /// Shell commands run once as the session starts, by leftwm itself.
+    pub on_startup: Vec<String>,
+    /// Shell commands run each time leftwm is back after a reload, and when the config is reloaded.
+    pub on_reload: Vec<String>,
+    /// Shell command run when the focused window changes, with it in `LEFTWM_WINDOW_*`.
+    pub on_focus_change: Option<String>,
+    /// Shell command run when the title of the focused window changes.
+    pub on_title_change: Option<String>,
     pub keybind: Vec<Keybind>,
     pub state: Option<PathBuf>,
 
@@ -105,6 +378,76 @@ pub struct Config {
     pub theme_setting: ThemeSetting,
 }
 
+impl Config {
+    fn tag_configs(&self) -> Vec<TagConfig> {
+        if let Some(tags) = &self.tags {
+            return tags.clone();
+        }
+        Self::default()
+            .tags
+            .expect("we created it in the Default impl; qed")
+    }
+
+    /// The number of a tag given by its number or its label, numbers winning over labels which
+    /// are numbers too.
+    #[must_use]
+    pub fn tag_number(&self, tag: &str) -> Option<usize> {
+        usize::from_str(tag).ok().or_else(|| {
+            self.tag_configs()
+                .iter()
+                .position(|config| config.label() == tag)
+                .map(|index| index + 1)
+        })
+    }
+
+    /// Expand `~`, `$VAR`, `${VAR}` and `$(command)` in the values naming things of the machine:
+    /// the tags and workspaces, the tags and outputs of the window rules, the values of the
+    /// keybinds and the state file. The commands leftwm runs, which the shell running them
+    /// expands, are left as they are.
+    pub fn interpolate(&mut self) {
+        // The commands of the whole config share the one deadline.
+        let deadline = Instant::now() + interpolate::TIMEOUT;
+        let expand = |value: &mut String| *value = interpolate(value, deadline);
+        for tag in self.tags.iter_mut().flatten() {
+            match tag {
+                TagConfig::Label(label) | TagConfig::Table { label, .. } => expand(label),
+            }
+        }
+        self.tag_order.iter_mut().for_each(expand);
+        let profile_workspaces = self.profiles.iter_mut().flat_map(|p| &mut p.workspaces);
+        for ws in self
+            .workspaces
+            .iter_mut()
+            .flatten()
+            .chain(profile_workspaces)
+        {
+            ws.name.iter_mut().for_each(expand);
+        }
+        for rule in self.window_rules.iter_mut().flatten() {
+            rule.tag.iter_mut().for_each(expand);
+            rule.output_name.iter_mut().for_each(expand);
+        }
+        for keybind in &mut self.keybind {
+            if keybind.command != BaseCommand::Execute {
+                expand(&mut keybind.value);
+            }
+        }
+        if let Some(state) = &mut self.state {
+            *state = PathBuf::from(interpolate(&state.to_string_lossy(), deadline));
+        }
+    }
+}
+
+/// The `on_startup` commands, read without expanding the config so that its `$(command)`s only
+/// run in the worker.
+#[must_use]
+pub fn startup_commands() -> Vec<String> {
+    config_file()
+        .and_then(|file| read_config_file::<Config>(&file))
+        .map(|config| config.on_startup)
+        .unwrap_or_default()
+}
+
 #[must_use]
 pub fn load() -> Config {
     load_from_file()
@@ -120,20 +463,21 @@ pub fn load() -> Config {
 /// # Errors
 ///
 /// Function will throw an error if `BaseDirectories` doesn't exist, if user doesn't have
-/// permissions to place config.toml, if config.toml cannot be read (access writes, malformed file,
-/// etc.).
+/// permissions to place config.toml, if the config file cannot be read (access writes, malformed
+/// file, etc.).
 /// Function can also error from inability to save config.toml (if it is the first time running
 /// `LeftWM`).
 fn load_from_file() -> Result<Config> {
-    let path = BaseDirectories::with_prefix("leftwm")?;
-    let config_filename = path.place_config_file("config.toml")?;
+    let config_filename = config_file()?;
     if Path::new(&config_filename).exists() {
-        let contents = fs::read_to_string(config_filename)?;
-        let config = toml::from_str(&contents)?;
+        let mut config: Config = read_config_file(&config_filename)?;
+        config.interpolate();
         if check_workspace_ids(&config) {
             Ok(config)
         } else {
-            log::warn!("Invalid workspace ID configuration in config.toml. Falling back to default config.");
+            log::warn!(
+                "Invalid workspace ID configuration in the config. Falling back to default config."
+            );
             Ok(Config::default())
         }
     } else {
@@ -270,18 +614,20 @@ impl leftwm_core::Config for Config {
     }
 
     fn create_list_of_tag_labels(&self) -> Vec<String> {
-        if let Some(tags) = &self.tags {
-            return tags.clone();
-        }
-        Self::default()
-            .tags
-            .expect("we created it in the Default impl; qed")
+        self.tag_configs()
+            .iter()
+            .map(|tag| tag.label().to_owned())
+            .collect()
     }
 
     fn workspaces(&self) -> Option<Vec<Workspace>> {
         self.workspaces.clone()
     }
 
+    fn profiles(&self) -> Vec<Profile> {
+        self.profiles.clone()
+    }
+
     fn focus_behaviour(&self) -> FocusBehaviour {
         self.focus_behaviour
     }
@@ -305,6 +651,14 @@ impl leftwm_core::Config for Config {
         self.layouts.clone()
     }
 
+    fn default_layout(&self) -> Option<Layout> {
+        self.default_layout.clone()
+    }
+
+    fn custom_layouts(&self) -> Vec<CustomLayout> {
+        self.custom_layouts.clone()
+    }
+
     fn layout_mode(&self) -> LayoutMode {
         self.layout_mode
     }
@@ -313,35 +667,54 @@ impl leftwm_core::Config for Config {
         self.insert_behavior
     }
 
+    fn empty_tag_behavior(&self) -> EmptyTagBehavior {
+        self.empty_tag_behavior
+    }
+
+    fn new_window_placement(&self) -> NewWindowPlacement {
+        self.new_window_placement
+    }
+
     fn focus_new_windows(&self) -> bool {
         self.focus_new_windows
     }
 
+    fn sloppy_focus_empty_workspaces(&self) -> bool {
+        self.sloppy_focus_empty_workspaces
+    }
+
+    fn click_passthrough(&self) -> bool {
+        self.click_passthrough
+    }
+
+    fn warp_cursor(&self) -> bool {
+        self.warp_cursor
+    }
+
     fn command_handler<SERVER: DisplayServer>(
         command: &str,
         manager: &mut Manager<Self, SERVER>,
-    ) -> bool {
-        if let Some((command, value)) = command.split_once(' ') {
-            match command {
-                "LoadTheme" => {
-                    if let Some(absolute) = absolute_path(value.trim()) {
-                        manager.config.theme_setting.load(absolute);
-                    } else {
-                        log::warn!("Path submitted does not exist.");
-                    }
-                    return manager.reload_config();
-                }
-                "UnloadTheme" => {
-                    manager.config.theme_setting = ThemeSetting::default();
-                    return manager.reload_config();
-                }
-                _ => {
-                    log::warn!("Command not recognized: {}", command);
-                    return false;
+    ) -> Option<bool> {
+        let (command, value) = command.split_once(' ').unwrap_or((command, ""));
+        match command {
+            "LoadTheme" => {
+                if let Some(absolute) = absolute_path(value.trim()) {
+                    manager.config.theme_setting.load(absolute);
+                } else {
+                    log::warn!("Path submitted does not exist.");
+                    return None;
                 }
+                Some(manager.reload_config())
+            }
+            "UnloadTheme" => {
+                manager.config.theme_setting = ThemeSetting::default();
+                Some(manager.reload_config())
+            }
+            _ => {
+                log::warn!("Command not recognized: {}", command);
+                None
             }
         }
-        false
     }
 
     fn border_width(&self) -> i32 {
@@ -371,6 +744,18 @@ impl leftwm_core::Config for Config {
             })
     }
 
+    fn inner_gap(&self) -> Option<u32> {
+        self.inner_gap
+    }
+
+    fn outer_gap(&self) -> Option<u32> {
+        self.outer_gap
+    }
+
+    fn smart_gaps(&self) -> bool {
+        self.smart_gaps
+    }
+
     fn gutter(&self) -> Option<Vec<Gutter>> {
         self.theme_setting.gutter.clone()
     }
@@ -399,10 +784,46 @@ impl leftwm_core::Config for Config {
         self.theme_setting.focused_border_color.clone()
     }
 
+    fn urgent_border_color(&self) -> String {
+        let theme = &self.theme_setting;
+        theme
+            .urgent_border_color
+            .clone()
+            .unwrap_or_else(|| theme.default_border_color.clone())
+    }
+
+    fn focused_border_width(&self) -> Option<i32> {
+        self.theme_setting.focused_border_width
+    }
+
+    fn smart_borders(&self) -> bool {
+        self.smart_borders
+    }
+
+    fn min_font_size(&self) -> Option<u32> {
+        self.theme_setting.min_font_size
+    }
+
     fn on_new_window_cmd(&self) -> Option<String> {
         self.theme_setting.on_new_window_cmd.clone()
     }
 
+    fn on_exit_cmd(&self) -> Option<String> {
+        self.on_exit.clone()
+    }
+
+    fn on_reload_cmds(&self) -> Vec<String> {
+        self.on_reload.clone()
+    }
+
+    fn on_focus_change_cmd(&self) -> Option<String> {
+        self.on_focus_change.clone()
+    }
+
+    fn on_title_change_cmd(&self) -> Option<String> {
+        self.on_title_change.clone()
+    }
+
     fn get_list_of_gutters(&self) -> Vec<Gutter> {
         self.theme_setting.gutter.clone().unwrap_or_default()
     }
@@ -411,10 +832,76 @@ impl leftwm_core::Config for Config {
         self.max_window_width
     }
 
+    fn tag_max_window_width(&self, tag_id: TagId) -> Option<Size> {
+        // Tag ids start at 1, in the order the tags are configured.
+        let index = tag_id.checked_sub(1)?;
+        self.tag_configs().get(index)?.max_window_width()
+    }
+
+    fn tag_hidden(&self, tag_id: TagId) -> bool {
+        tag_id
+            .checked_sub(1)
+            .and_then(|index| self.tag_configs().get(index).map(TagConfig::hidden))
+            .unwrap_or_default()
+    }
+
+    fn tag_default_layout(&self, tag_id: TagId) -> Option<Layout> {
+        let index = tag_id.checked_sub(1)?;
+        self.tag_configs().get(index)?.default_layout()
+    }
+
+    fn tag_layouts(&self, tag_id: TagId) -> Vec<Layout> {
+        tag_id
+            .checked_sub(1)
+            .and_then(|index| self.tag_configs().get(index).map(TagConfig::layouts))
+            .unwrap_or_default()
+    }
+
+    fn tag_order(&self) -> Vec<TagId> {
+        let tags = self.tag_configs();
+        self.tag_order
+            .iter()
+            .filter_map(|label| tags.iter().position(|tag| tag.label() == label))
+            .map(|index| index + 1)
+            .collect()
+    }
+
     fn disable_tile_drag(&self) -> bool {
         self.disable_tile_drag
     }
 
+    fn per_screen_tags(&self) -> bool {
+        self.per_screen_tags
+    }
+
+    fn greedy_view(&self) -> bool {
+        self.greedy_view
+    }
+
+    fn tag_history_depth(&self) -> usize {
+        self.tag_history_depth
+    }
+
+    fn honor_size_increments(&self) -> bool {
+        self.honor_size_increments
+    }
+
+    fn tag_slide_duration(&self) -> u64 {
+        self.tag_slide_duration
+    }
+
+    fn reduce_motion(&self) -> bool {
+        self.reduce_motion
+    }
+
+    fn snap_distance(&self) -> i32 {
+        self.snap_distance
+    }
+
+    fn urgency_limit(&self) -> usize {
+        self.urgency_limit
+    }
+
     fn save_state(&self, state: &State) {
         let path = self.state_file();
         let state_file = match File::create(&path) {
@@ -446,8 +933,26 @@ impl leftwm_core::Config for Config {
         }
     }
 
+    fn reload(&mut self) -> bool {
+        match load_from_file() {
+            Ok(mut config) => {
+                // The theme is loaded separately, by its `up` script.
+                config.theme_setting = std::mem::take(&mut self.theme_setting);
+                *self = config;
+                true
+            }
+            Err(err) => {
+                log::error!(
+                    "Cannot reload the config, keeping the current one: {:?}",
+                    err
+                );
+                false
+            }
+        }
+    }
+
     /// Pick the best matching [`WindowHook`], if any, and apply its config.
-    fn setup_predefined_window(&self, window: &mut Window) -> bool {
+    fn setup_predefined_window(&self, window: &mut Window) -> Option<SpawnTarget> {
         if let Some(window_rules) = &self.window_rules {
             let best_match = window_rules
                 .iter()
@@ -459,19 +964,20 @@ impl leftwm_core::Config for Config {
             if let Some((hook, _)) = best_match {
                 hook.apply(window);
                 log::debug!(
-                    "Window [[ TITLE={:?}, {:?}; WM_CLASS={:?}, {:?} ]] spawned in tag={:?} with floating={:?}",
+                    "Window [[ TITLE={:?}, {:?}; WM_CLASS={:?}, {:?} ]] spawned in tag={:?} with floating={:?}, states={:?}",
                     window.name,
                     window.legacy_name,
                     window.res_name,
                     window.res_class,
                     hook.spawn_on_tag,
                     hook.spawn_floating,
+                    window.states(),
                 );
-                return true;
+                return Some(hook.spawn_target());
             }
-            return false;
+            return None;
         }
-        false
+        None
     }
 }
 
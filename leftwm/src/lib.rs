@@ -5,3 +5,6 @@ mod theme_setting;
 pub use command::*;
 pub use config::*;
 pub use theme_setting::*;
+
+/// Status `leftwm-worker` exits with after the `Exit` command, telling `leftwm` not to restart it.
+pub const WORKER_EXIT_STATUS: i32 = 64;
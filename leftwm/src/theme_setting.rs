@@ -16,6 +16,12 @@ pub struct ThemeSetting {
     pub default_border_color: String,
     pub floating_border_color: String,
     pub focused_border_color: String,
+    /// Border color of the windows asking for attention, `default_border_color` if unset.
+    pub urgent_border_color: Option<String>,
+    /// Border width of the focused window, `border_width` if unset.
+    pub focused_border_width: Option<i32>,
+    /// Smallest pixel size of the text of the tabs of the `Tabbed` layout.
+    pub min_font_size: Option<u32>,
     #[serde(rename = "on_new_window")]
     pub on_new_window_cmd: Option<String>,
 }
@@ -45,6 +51,9 @@ impl Default for ThemeSetting {
             default_border_color: "#000000".to_owned(),
             floating_border_color: "#000000".to_owned(),
             focused_border_color: "#FF0000".to_owned(),
+            urgent_border_color: None,
+            focused_border_width: None,
+            min_font_size: None,
             on_new_window_cmd: None,
         }
     }
@@ -104,6 +113,9 @@ workspace_margin = 5
 default_border_color = '#222222'
 floating_border_color = '#005500'
 focused_border_color = '#FFB53A'
+urgent_border_color = '#FF5555'
+focused_border_width = 4
+min_font_size = 18
 on_new_window = 'echo Hello World'
 
 [[gutter]]
@@ -129,6 +141,9 @@ value = 0
                 default_border_color: "#222222".to_string(),
                 floating_border_color: "#005500".to_string(),
                 focused_border_color: "#FFB53A".to_string(),
+                urgent_border_color: Some("#FF5555".to_string()),
+                focused_border_width: Some(4),
+                min_font_size: Some(18),
                 on_new_window_cmd: Some("echo Hello World".to_string()),
             }
         );